@@ -0,0 +1,60 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use nflz::FileInfo;
+use regex::Regex;
+use std::time::Instant;
+
+/// Compares the hand-written parser for the default `(<digits>)` pattern with the equivalent
+/// regex. Run with `cargo run --release --example scan_benchmark`.
+fn main() {
+    const FILE_COUNT: usize = 500_000;
+    let filenames = (1..=FILE_COUNT)
+        .map(|i| format!("Vacation Paris 2022 (with friends) ({}).jpg", i))
+        .collect::<Vec<_>>();
+
+    let now = Instant::now();
+    let parsed = filenames
+        .iter()
+        .filter(|filename| FileInfo::new(filename).is_ok())
+        .count();
+    let duration_parser = now.elapsed();
+    assert_eq!(parsed, FILE_COUNT);
+
+    let regex = Regex::new(r"\(([0-9]+)\)").unwrap();
+    let now = Instant::now();
+    let parsed = filenames
+        .iter()
+        .filter(|filename| FileInfo::new_with_regex(filename, &regex).is_ok())
+        .count();
+    let duration_regex = now.elapsed();
+    assert_eq!(parsed, FILE_COUNT);
+
+    println!("{} filenames", FILE_COUNT);
+    println!("hand-written parser: {:?}", duration_parser);
+    println!("regex:               {:?}", duration_regex);
+    println!(
+        "speedup:             {:.1}x",
+        duration_regex.as_secs_f64() / duration_parser.as_secs_f64()
+    );
+}
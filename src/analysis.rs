@@ -0,0 +1,258 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the read-only analysis of the numbered files of a directory. See
+//! [`ArchiveAnalysis`].
+
+use crate::file_info::FileInfo;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+/// Health check of the numbered files of a directory, such as an existing archive.
+///
+/// Shows how the numbers are padded, which numbers are missing, and which ones are used
+/// more than once. See [`crate::NFLZAssistant::analysis`].
+///
+/// ```rust
+/// use nflz::NFLZAssistant;
+///
+/// let analysis = NFLZAssistant::new("./test-resources").unwrap().analysis();
+/// assert_eq!(Some(1), analysis.min_number());
+/// assert!(analysis.duplicates().is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ArchiveAnalysis {
+    /// Number of numbered files.
+    file_count: usize,
+    /// Number of files per amount of digits in the number group.
+    width_histogram: BTreeMap<usize, usize>,
+    /// Smallest number.
+    min_number: Option<u64>,
+    /// Highest number.
+    max_number: Option<u64>,
+    /// Missing numbers between the smallest and the highest number.
+    gaps: Vec<RangeInclusive<u64>>,
+    /// Numbers that are used by multiple files, with the paths of these files.
+    duplicates: BTreeMap<u64, Vec<PathBuf>>,
+}
+
+impl ArchiveAnalysis {
+    /// Analyzes the files. `item` returns the logical item of a file: files of the same item
+    /// with the same number are not duplicates, e.g., sidecar files.
+    pub(crate) fn new<'a, I: IntoIterator<Item = &'a FileInfo>>(
+        files: I,
+        item: impl Fn(&FileInfo) -> &str,
+    ) -> Self {
+        let mut analysis = Self::default();
+        let mut by_number = BTreeMap::<u64, Vec<&FileInfo>>::new();
+        for file in files {
+            analysis.file_count += 1;
+            *analysis
+                .width_histogram
+                .entry(file.number_group_str().len())
+                .or_default() += 1;
+            by_number
+                .entry(file.number_group_value())
+                .or_default()
+                .push(file);
+        }
+        analysis.min_number = by_number.keys().next().copied();
+        analysis.max_number = by_number.keys().next_back().copied();
+
+        let numbers = by_number.keys().copied().collect::<Vec<_>>();
+        analysis.gaps = numbers
+            .windows(2)
+            .filter(|pair| pair[1] - pair[0] > 1)
+            .map(|pair| pair[0] + 1..=pair[1] - 1)
+            .collect();
+
+        analysis.duplicates = by_number
+            .into_iter()
+            .filter(|(_, files)| {
+                files
+                    .iter()
+                    .map(|file| item(file))
+                    .collect::<BTreeSet<_>>()
+                    .len()
+                    > 1
+            })
+            .map(|(number, files)| {
+                let paths = files.iter().map(|file| file.path().to_path_buf()).collect();
+                (number, paths)
+            })
+            .collect();
+        analysis
+    }
+
+    /// Returns the number of numbered files.
+    pub const fn file_count(&self) -> usize {
+        self.file_count
+    }
+
+    /// Returns the number of files per amount of digits in the number group, e.g.,
+    /// `{1: 9, 3: 111}` if some files were not padded.
+    pub const fn width_histogram(&self) -> &BTreeMap<usize, usize> {
+        &self.width_histogram
+    }
+
+    /// Returns the smallest number. `None` if there are no numbered files.
+    pub const fn min_number(&self) -> Option<u64> {
+        self.min_number
+    }
+
+    /// Returns the highest number. `None` if there are no numbered files.
+    pub const fn max_number(&self) -> Option<u64> {
+        self.max_number
+    }
+
+    /// Returns the missing numbers between the smallest and the highest number, in
+    /// ascending order.
+    pub fn gaps(&self) -> &[RangeInclusive<u64>] {
+        &self.gaps
+    }
+
+    /// Returns the numbers that are used by multiple files, such as `img (3).jpg` and
+    /// `img (03).jpg`, with the paths of these files.
+    pub const fn duplicates(&self) -> &BTreeMap<u64, Vec<PathBuf>> {
+        &self.duplicates
+    }
+}
+
+/// Renders the analysis as a multi-line summary, for example:
+///
+/// ```text
+/// 6 numbered files, numbers 1 to 9
+/// Padding widths:
+///   1 digits: 4 files
+///   2 digits: 1 files
+///   3 digits: 1 files
+/// Gaps: 3-4, 6-8
+/// Duplicates:
+///   2: a (2).jpg, a (02).jpg
+/// ```
+///
+/// There is no newline behind the last line.
+impl Display for ArchiveAnalysis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (Some(min), Some(max)) = (self.min_number, self.max_number) else {
+            return write!(f, "0 numbered files");
+        };
+        writeln!(
+            f,
+            "{} numbered files, numbers {} to {}",
+            self.file_count, min, max
+        )?;
+        writeln!(f, "Padding widths:")?;
+        for (width, count) in &self.width_histogram {
+            writeln!(f, "  {} digits: {} files", width, count)?;
+        }
+        let gaps = self
+            .gaps
+            .iter()
+            .map(|gap| {
+                if gap.start() == gap.end() {
+                    gap.start().to_string()
+                } else {
+                    format!("{}-{}", gap.start(), gap.end())
+                }
+            })
+            .collect::<Vec<_>>();
+        if gaps.is_empty() {
+            writeln!(f, "Gaps: none")?;
+        } else {
+            writeln!(f, "Gaps: {}", gaps.join(", "))?;
+        }
+        if self.duplicates.is_empty() {
+            return write!(f, "Duplicates: none");
+        }
+        write!(f, "Duplicates:")?;
+        for (number, paths) in &self.duplicates {
+            let filenames = paths
+                .iter()
+                .map(|path| path.file_name().unwrap_or_default().to_string_lossy())
+                .collect::<Vec<_>>();
+            write!(f, "\n  {}: {}", number, filenames.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analysis() {
+        let files = [
+            "a (1).jpg",
+            "a (2).jpg",
+            "a (02).jpg",
+            "a (5).jpg",
+            "a (5).xmp",
+            "a (009).jpg",
+        ]
+        .map(|filename| FileInfo::new(filename).unwrap());
+        let analysis = ArchiveAnalysis::new(&files, FileInfo::original_filename);
+        assert_eq!(6, analysis.file_count());
+        assert_eq!(
+            [(1, 4), (2, 1), (3, 1)]
+                .into_iter()
+                .collect::<BTreeMap<_, _>>(),
+            *analysis.width_histogram()
+        );
+        assert_eq!(Some(1), analysis.min_number());
+        assert_eq!(Some(9), analysis.max_number());
+        assert_eq!(&[3..=4, 6..=8], analysis.gaps());
+        assert_eq!(
+            vec![&2, &5],
+            analysis.duplicates().keys().collect::<Vec<_>>()
+        );
+
+        // sidecars are not duplicates
+        let analysis = ArchiveAnalysis::new(&files, |file| {
+            file.original_filename().split('.').next().unwrap()
+        });
+        assert_eq!(vec![&2], analysis.duplicates().keys().collect::<Vec<_>>());
+        assert_eq!(
+            "6 numbered files, numbers 1 to 9
+Padding widths:
+  1 digits: 4 files
+  2 digits: 1 files
+  3 digits: 1 files
+Gaps: 3-4, 6-8
+Duplicates:
+  2: a (2).jpg, a (02).jpg",
+            analysis.to_string()
+        );
+        assert_eq!("0 numbered files", ArchiveAnalysis::default().to_string());
+
+        assert_eq!(
+            ArchiveAnalysis::default(),
+            ArchiveAnalysis::new(std::iter::empty(), FileInfo::original_filename)
+        );
+    }
+}
@@ -0,0 +1,949 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the configuration of a [`NFLZAssistant`]. See [`NFLZAssistantBuilder`].
+
+use crate::config::DirectoryConfig;
+use crate::content::ContentCategory;
+use crate::error::NFLZError;
+use crate::event::{Event, EventListener};
+use crate::file_info::FileInfo;
+#[cfg(feature = "regex")]
+use crate::file_info::Regex;
+use crate::math::Radix;
+use crate::nflz::NFLZAssistant;
+use crate::open_files::OpenFilePolicy;
+use crate::options::Options;
+#[cfg(feature = "regex")]
+use crate::pattern::BuiltinPattern;
+use crate::prefix::PrefixEquivalence;
+use crate::roman::RomanNumerals;
+use crate::scheme::NumberingScheme;
+use crate::sort::RenumberOrder;
+use crate::transform::{
+    FilenameTransform, FnTransform, ReplaceIllegalCharacters, TrimTrailingDotsAndSpaces,
+};
+use crate::unicode::UnicodeNormalization;
+use std::ffi::OsString;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Builder for a [`NFLZAssistant`]. Use this instead of [`NFLZAssistant::new`] if you need
+/// more than the default behaviour.
+///
+/// # Example
+/// ```rust
+/// use nflz::{LowercaseExtension, NFLZAssistant, ReplaceSeparator};
+///
+/// let assistant = NFLZAssistant::builder("./test-resources")
+///     .transform(ReplaceSeparator::new(' ', '_'))
+///     .transform(LowercaseExtension)
+///     .build()
+///     .unwrap();
+/// assert_eq!(
+///     assistant.files_to_rename()[0].new_filename(),
+///     Some("paris_(001).jpg")
+/// );
+/// ```
+#[derive(Debug)]
+pub struct NFLZAssistantBuilder {
+    /// Directory to search for files.
+    pub(crate) working_dir: PathBuf,
+    /// Ordered pipeline of transforms that is applied on every new filename after padding.
+    pub(crate) transforms: Vec<Box<dyn FilenameTransform>>,
+    /// Scheme that finds the number group(s). `None` means the default `(<digits>)` pattern
+    /// in the numeral system of [`Self::radix`].
+    pub(crate) scheme: Option<Box<dyn NumberingScheme>>,
+    /// Amount of files that will be added later. See [`Self::reserve_additional`].
+    pub(crate) reserve_additional: u64,
+    /// Minimum amount of digits of all number groups. See [`Self::fixed_width`].
+    pub(crate) fixed_width: u64,
+    /// Amount of files the sequence will eventually have. `0` means unknown. See
+    /// [`Self::expect_count`].
+    pub(crate) expected_count: u64,
+    /// First number if the files are renumbered. See [`Self::start_at`] and
+    /// [`Self::continue_from`].
+    pub(crate) renumber_start: Option<RenumberStart>,
+    /// Increment between two numbers if the files are renumbered. See [`Self::renumber_step`].
+    pub(crate) renumber_step: NonZeroU64,
+    /// Order in that the files get their new numbers. See [`Self::renumber_by`].
+    pub(crate) renumber_order: RenumberOrder,
+    /// Whether each file extension forms its own sequence. See
+    /// [`Self::sequence_per_extension`].
+    pub(crate) sequence_per_extension: bool,
+    /// Whether files with the same stem are one logical item. See [`Self::group_sidecars`].
+    pub(crate) group_sidecars: bool,
+    /// Whether every sequence of digits is padded. See [`Self::pad_all_number_groups`].
+    pub(crate) pad_all_number_groups: bool,
+    /// Whether the original filenames are recorded in an extended attribute. See
+    /// [`Self::record_original_filenames`].
+    #[cfg(feature = "xattr")]
+    pub(crate) record_original_filenames: bool,
+    /// Base of the numbers inside the number groups. See [`Self::hexadecimal`].
+    pub(crate) radix: Radix,
+    /// Amount of numbers per subdirectory. See [`Self::split_into_subdirectories`].
+    pub(crate) split_chunk_size: u64,
+    /// Whether trailing dots and spaces are removed from the new filenames.
+    pub(crate) trim_trailing_dots_and_spaces: bool,
+    /// Replacement for illegal characters in the new filenames. See
+    /// [`Self::replace_illegal_characters`].
+    pub(crate) illegal_character_replacement: Option<char>,
+    /// Whether hidden files are processed. See [`Self::include_hidden_files`].
+    pub(crate) include_hidden_files: bool,
+    /// What happens to files that are opened by other processes. See
+    /// [`Self::open_file_policy`].
+    pub(crate) open_file_policy: OpenFilePolicy,
+    /// Whether the `.nflzignore` file is read. See [`Self::respect_ignore_file`].
+    pub(crate) respect_ignore_file: bool,
+    /// Whether the `.gitignore` files are read. See [`Self::respect_gitignore`].
+    pub(crate) respect_gitignore: bool,
+    /// Content categories of the files to rename. Empty means all files. See
+    /// [`Self::content_categories`].
+    pub(crate) content_categories: Vec<ContentCategory>,
+    /// Numbers of the files that are left untouched. See [`Self::ignore_numbers`].
+    pub(crate) ignored_numbers: Vec<RangeInclusive<u64>>,
+    /// Range of the new numbers of the files that are renamed. See [`Self::only_numbers`].
+    pub(crate) number_range: Option<RangeInclusive<u64>>,
+    /// Maximum number of files to rename. See [`Self::limit`].
+    pub(crate) limit: Option<usize>,
+    /// Normalization form to compare filenames. See [`Self::unicode_normalization`].
+    pub(crate) unicode_normalization: Option<UnicodeNormalization>,
+    /// Which differences between prefixes are tolerated. See
+    /// [`Self::case_insensitive_prefixes`], [`Self::whitespace_tolerant_prefixes`], and
+    /// [`Self::unify_prefixes`].
+    pub(crate) prefix_equivalence: PrefixEquivalence,
+    /// Wildcard patterns of the files whose references are updated. See
+    /// [`Self::update_references_in`].
+    pub(crate) reference_patterns: Vec<String>,
+    /// Whether the filenames inside checksum manifests are updated. See
+    /// [`Self::update_checksum_manifests`].
+    pub(crate) update_manifests: bool,
+    /// Whether the symlinks to renamed files are retargeted. See
+    /// [`Self::retarget_symlinks`].
+    pub(crate) retarget_symlinks: bool,
+    /// Path of the checkpoint file and the number of renames per chunk. See
+    /// [`Self::checkpoint`].
+    pub(crate) checkpoint: Option<(PathBuf, NonZeroUsize)>,
+    /// Whether the directories are synced after the renaming. See
+    /// [`Self::sync_directories`].
+    pub(crate) sync_directories: bool,
+    /// Minimum interval between the start of two renames. See [`Self::throttle`].
+    pub(crate) throttle: Option<Duration>,
+    /// Maximum duration of a single rename. See [`Self::rename_timeout`].
+    pub(crate) rename_timeout: Option<Duration>,
+    /// Stops the renaming once it is set. See [`Self::interrupt_flag`].
+    pub(crate) interrupt_flag: Option<Arc<AtomicBool>>,
+    /// Filenames of the only entries to process instead of all entries of the working
+    /// directory. See [`Self::select_files`].
+    pub(crate) selected_files: Option<Vec<OsString>>,
+    /// Receives all events of the assistant.
+    pub(crate) event_listener: EventListener,
+}
+
+/// First number if the files are renumbered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum RenumberStart {
+    /// Starts at the given number. See [`NFLZAssistantBuilder::start_at`].
+    At(u64),
+    /// Starts after the highest number in the archive. See
+    /// [`NFLZAssistantBuilder::continue_from`].
+    After(PathBuf),
+}
+
+impl NFLZAssistantBuilder {
+    /// Creates a new builder with the default configuration.
+    ///
+    /// # Parameters
+    /// * `working_dir` Directory to search for files. See [`NFLZAssistant::new`].
+    pub fn new<P: AsRef<Path>>(working_dir: P) -> Self {
+        Self {
+            working_dir: PathBuf::from(working_dir.as_ref()),
+            transforms: Vec::new(),
+            scheme: None,
+            reserve_additional: 0,
+            fixed_width: 0,
+            expected_count: 0,
+            renumber_start: None,
+            renumber_step: NonZeroU64::MIN,
+            renumber_order: RenumberOrder::Number,
+            sequence_per_extension: false,
+            group_sidecars: false,
+            pad_all_number_groups: false,
+            #[cfg(feature = "xattr")]
+            record_original_filenames: false,
+            radix: Radix::Decimal,
+            split_chunk_size: 0,
+            trim_trailing_dots_and_spaces: false,
+            illegal_character_replacement: None,
+            include_hidden_files: false,
+            open_file_policy: OpenFilePolicy::Ignore,
+            respect_ignore_file: true,
+            respect_gitignore: false,
+            content_categories: Vec::new(),
+            ignored_numbers: Vec::new(),
+            number_range: None,
+            limit: None,
+            unicode_normalization: UnicodeNormalization::platform_default(),
+            prefix_equivalence: PrefixEquivalence::default(),
+            reference_patterns: Vec::new(),
+            update_manifests: false,
+            retarget_symlinks: false,
+            checkpoint: None,
+            sync_directories: false,
+            throttle: None,
+            rename_timeout: None,
+            interrupt_flag: None,
+            selected_files: None,
+            event_listener: EventListener::default(),
+        }
+    }
+
+    /// Appends a [`FilenameTransform`] to the transform pipeline. The transforms are applied
+    /// in the order they are added, after the leading zeroes were added to the number group.
+    pub fn transform<T: FilenameTransform + 'static>(mut self, transform: T) -> Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    /// Uses a [`NumberingScheme`] to find the number group(s) inside the filenames and to
+    /// build the new filenames instead of the default `(<digits>)` pattern. Replaces a scheme
+    /// set by `number_group_regex` or `pattern` (both require the `regex` feature).
+    pub fn numbering_scheme<S: NumberingScheme + 'static>(mut self, scheme: S) -> Self {
+        self.scheme = Some(Box::new(scheme));
+        self
+    }
+
+    /// Uses a custom regex to find the number group inside the filenames instead of the
+    /// default `(<digits>)` pattern. See [`FileInfo::new_with_regex`] for the requirements
+    /// of the regex. Without a custom regex, a faster hand-written parser is used.
+    #[cfg(feature = "regex")]
+    pub fn number_group_regex(self, regex: Regex) -> Self {
+        self.numbering_scheme(regex)
+    }
+
+    /// Uses a [`BuiltinPattern`] to find the number group(s) inside the filenames. Replaces a
+    /// scheme set by [`Self::numbering_scheme`] or [`Self::number_group_regex`]. The presets
+    /// for the files of cameras, such as [`BuiltinPattern::Dcf`], also keep the width that the
+    /// camera uses.
+    ///
+    /// # Example
+    /// ```rust
+    /// use nflz::{BuiltinPattern, NFLZAssistant};
+    /// # let dir = std::env::temp_dir().join("nflz-doctest-season-episode");
+    /// # let _ = std::fs::remove_dir_all(&dir);
+    /// # std::fs::create_dir_all(&dir).unwrap();
+    /// # for name in ["Show S1E3.mkv", "Show S1E10.mkv", "Show S10E1.mkv"] {
+    /// #     std::fs::write(dir.join(name), "").unwrap();
+    /// # }
+    ///
+    /// let assistant = NFLZAssistant::builder(&dir)
+    ///     .pattern(BuiltinPattern::SeasonEpisode)
+    ///     .build()
+    ///     .unwrap();
+    /// let new_filenames = assistant
+    ///     .files_to_rename()
+    ///     .iter()
+    ///     .map(|file| file.new_filename().unwrap())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     ["Show S01E03.mkv", "Show S01E10.mkv", "Show S10E01.mkv"],
+    ///     new_filenames.as_slice()
+    /// );
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn pattern(mut self, pattern: BuiltinPattern) -> Self {
+        self.scheme = pattern.scheme();
+        self
+    }
+
+    /// Appends a closure to the transform pipeline. See [`Self::transform`]. The closure
+    /// receives the [`FileInfo`] of the file and the new filename computed so far and returns
+    /// the new filename. Conflicts caused by the closure, such as two files with the same new
+    /// name, are detected by [`NFLZAssistant::check_can_rename_all`].
+    pub fn transform_fn<F: Fn(&FileInfo, &str) -> String + 'static>(self, f: F) -> Self {
+        self.transform(FnTransform(f))
+    }
+
+    /// Pads the numbers as if `n` more files with ascending numbers will be added later. For
+    /// example, if the highest number is `95` and `n` is `10`, all numbers get three digits
+    /// instead of two. Thus, an ongoing photo shoot doesn't need re-padding every time the
+    /// count crosses a power of ten. With two number groups, such as season and episode,
+    /// only the second one is affected.
+    ///
+    /// # Example
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// // highest number in the directory is 734
+    /// let assistant = NFLZAssistant::builder("./test-resources")
+    ///     .reserve_additional(300)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     assistant.files_to_rename()[0].new_filename(),
+    ///     Some("paris (0001).jpg")
+    /// );
+    /// ```
+    pub const fn reserve_additional(mut self, n: u64) -> Self {
+        self.reserve_additional = n;
+        self
+    }
+
+    /// Pads all numbers to at least `width` digits, independent of the highest number. Numbers
+    /// with more digits keep all of them. This is useful for tools that expect a fixed width,
+    /// such as `ffmpeg -i frame%04d.png`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let assistant = NFLZAssistant::builder("./test-resources")
+    ///     .fixed_width(5)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     assistant.files_to_rename()[0].new_filename(),
+    ///     Some("paris (00001).jpg")
+    /// );
+    /// ```
+    pub const fn fixed_width(mut self, width: u64) -> Self {
+        self.fixed_width = width;
+        self
+    }
+
+    /// Pads the numbers for an archive that will eventually contain `count` files, rather
+    /// than for the current highest number. Unlike [`Self::reserve_additional`], the width
+    /// doesn't depend on the files that are already there, so an archive that is still being
+    /// filled keeps the same width for every run. If the files are renumbered, the start and
+    /// the step are taken into account. Numbers with more digits keep all of them. With two
+    /// number groups, such as season and episode, only the second one is affected.
+    ///
+    /// # Example
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let assistant = NFLZAssistant::builder("./test-resources")
+    ///     .expect_count(25000)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     assistant.files_to_rename()[0].new_filename(),
+    ///     Some("paris (00001).jpg")
+    /// );
+    /// ```
+    pub const fn expect_count(mut self, count: u64) -> Self {
+        self.expected_count = count;
+        self
+    }
+
+    /// Renumbers the files in their order, starting at `start`, e.g., so that a newly
+    /// imported batch slots in after an existing archive. Overrides [`Self::continue_from`].
+    /// [`Self::build`] fails with [`NFLZError::RenumberingOverflow`] if the numbers of the
+    /// files exceed [`u64::MAX`].
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let assistant = NFLZAssistant::builder("./test-resources")
+    ///     .start_at(100)
+    ///     .build()
+    ///     .unwrap();
+    /// let file = assistant.files_to_rename()[0];
+    /// assert_eq!(file.new_filename(), Some("paris (100).jpg"));
+    /// ```
+    pub fn start_at(mut self, start: u64) -> Self {
+        self.renumber_start = Some(RenumberStart::At(start));
+        self
+    }
+
+    /// Renumbers the files in their order in increments of `step`, e.g., `10, 20, 30, ...`
+    /// for a step of 10, to leave room for inserting files later. Without
+    /// [`Self::start_at`], the first number is `step`. With [`Self::continue_from`], the
+    /// first number is the highest number of the archive plus `step`. A step of 1 doesn't
+    /// renumber the files on its own, which is the default.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    /// use std::num::NonZeroU64;
+    ///
+    /// let assistant = NFLZAssistant::builder("./test-resources")
+    ///     .renumber_step(NonZeroU64::new(10).unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// let files = assistant.files_to_rename();
+    /// assert_eq!(files[0].new_filename(), Some("paris (010).jpg"));
+    /// assert_eq!(files[1].new_filename(), Some("paris (020).jpg"));
+    /// ```
+    pub const fn renumber_step(mut self, step: NonZeroU64) -> Self {
+        self.renumber_step = step;
+        self
+    }
+
+    /// Renumbers the files in the given order instead of the order of their existing numbers,
+    /// e.g., by their modification time if the counters of the camera wrapped around. Without
+    /// [`Self::start_at`] or [`Self::continue_from`], the numbers start at the step of
+    /// [`Self::renumber_step`], i.e., 1 by default. [`RenumberOrder::Number`] doesn't
+    /// renumber the files on its own, which is the default.
+    pub const fn renumber_by(mut self, order: RenumberOrder) -> Self {
+        self.renumber_order = order;
+        self
+    }
+
+    /// Continues the numbering of an existing archive, e.g., for multi-volume sets such as
+    /// the files of a second SD card. Overrides [`Self::start_at`].
+    ///
+    /// The files are renumbered in their order, starting after the highest number of the
+    /// numbered files in `archive`. They are padded to at least the width of the widest
+    /// number group in the archive. The archive must use the same pattern as the working
+    /// directory and is only read. Fails in [`Self::build`] if the archive can't be read.
+    pub fn continue_from<P: AsRef<Path>>(mut self, archive: P) -> Self {
+        self.renumber_start = Some(RenumberStart::After(PathBuf::from(archive.as_ref())));
+        self
+    }
+
+    /// Treats the files of each file extension (case-insensitive) as an independent sequence
+    /// with its own prefix, suffix, and padding. For example, 1000 photos and 12 videos become
+    /// `IMG (0001).jpg` and `VID (01).mp4` instead of failing with
+    /// [`NFLZError::AmbiguousSuffixes`]. Renumbering options, such as [`Self::start_at`],
+    /// apply to each sequence. By default, all files form one sequence.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let builder = NFLZAssistant::builder("./test-resources").sequence_per_extension(true);
+    /// ```
+    pub const fn sequence_per_extension(mut self, per_extension: bool) -> Self {
+        self.sequence_per_extension = per_extension;
+        self
+    }
+
+    /// Treats files with the same stem, such as `img (3).jpg` and its sidecars `img (3).json`
+    /// and `img (3).xmp`, as one logical item:
+    /// - renumbering assigns one number per item, e.g., with [`Self::start_at`],
+    /// - the prefixes and suffixes are compared without the file extension,
+    /// - if renaming a file of an item fails, the already renamed files of the item are
+    ///   renamed back, as far as their old names are still free.
+    ///
+    /// By default, every file is an item of its own.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let builder = NFLZAssistant::builder("./test-resources").group_sidecars(true);
+    /// ```
+    pub const fn group_sidecars(mut self, group: bool) -> Self {
+        self.group_sidecars = group;
+        self
+    }
+
+    /// Treats prefixes that only differ in their case, such as `img (1).jpg` and
+    /// `IMG (2).jpg`, as the same sequence instead of failing with
+    /// [`NFLZError::AmbiguousPrefixes`]. Different cameras and operating systems frequently
+    /// disagree on the case. The files keep their prefix unless [`Self::unify_prefixes`] is
+    /// set. By default, the prefixes are compared case-sensitively.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let builder = NFLZAssistant::builder("./test-resources").case_insensitive_prefixes(true);
+    /// ```
+    pub const fn case_insensitive_prefixes(mut self, ignore_case: bool) -> Self {
+        self.prefix_equivalence.ignore_case = ignore_case;
+        self
+    }
+
+    /// Treats prefixes that only differ in leading, trailing, or repeated whitespace, such as
+    /// `img (1).jpg` and `img  (2).jpg`, as the same sequence instead of failing with
+    /// [`NFLZError::AmbiguousPrefixes`]. They look the same in most file managers. The files
+    /// keep their prefix unless [`Self::unify_prefixes`] is set. Can be combined with
+    /// [`Self::case_insensitive_prefixes`].
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let builder = NFLZAssistant::builder("./test-resources").whitespace_tolerant_prefixes(true);
+    /// ```
+    pub const fn whitespace_tolerant_prefixes(mut self, ignore_whitespace: bool) -> Self {
+        self.prefix_equivalence.ignore_whitespace = ignore_whitespace;
+        self
+    }
+
+    /// Renames the files whose prefix is only equivalent to the one of the other files, see
+    /// [`Self::case_insensitive_prefixes`] and [`Self::whitespace_tolerant_prefixes`], to the
+    /// most common spelling of the prefix in the sequence. For example, `IMG (3).jpg` becomes
+    /// `img (3).jpg` if most files start with `img (`. Has no effect with
+    /// [`Self::pad_all_number_groups`].
+    pub const fn unify_prefixes(mut self, unify: bool) -> Self {
+        self.prefix_equivalence.unify = unify;
+        self
+    }
+
+    /// Pads every sequence of digits in the filenames instead of a single number group, e.g.,
+    /// `S01E3 - part (2).mkv` becomes `S01E03 - part (02).mkv`.
+    ///
+    /// The width of each position, i.e., the first, second, ... sequence of digits, is
+    /// computed across all files. Every file with at least one sequence of digits is
+    /// processed; the last sequence is the number group that is used for sorting and
+    /// renumbering. The prefixes and suffixes are the texts before the first and behind the
+    /// last sequence of digits. Custom regexes and built-in patterns are ignored.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let builder = NFLZAssistant::builder("./test-resources").pad_all_number_groups(true);
+    /// ```
+    pub const fn pad_all_number_groups(mut self, pad_all: bool) -> Self {
+        self.pad_all_number_groups = pad_all;
+        self
+    }
+
+    /// Reads the number groups as hexadecimal counters, such as `dump (1f).bin`, that some
+    /// tools emit. They are parsed, ordered, and zero-padded as hexadecimal numbers, e.g.,
+    /// `dump (f).bin` becomes `dump (0f).bin` next to `dump (1f).bin`. All numbers of a
+    /// sequence get the letter case of the majority of them, as `0B` sorts before `0a`.
+    /// Renumbering and [`Self::ignore_numbers`] use the values of the numbers, i.e., `1f` is
+    /// `31`.
+    ///
+    /// Custom regexes must match the hexadecimal digits in their capture groups. Has no
+    /// effect with [`Self::pad_all_number_groups`]. Overrides [`Self::roman_numerals`]. By
+    /// default, the numbers are decimal.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let builder = NFLZAssistant::builder("./test-resources").hexadecimal(true);
+    /// ```
+    pub const fn hexadecimal(mut self, hexadecimal: bool) -> Self {
+        self.radix = if hexadecimal {
+            Radix::Hexadecimal
+        } else {
+            Radix::Decimal
+        };
+        self
+    }
+
+    /// Reads the number groups as roman numerals, such as `Chapter (IV).md`, which are common
+    /// in book and scan archives. The numerals are ordered by their values and either
+    /// converted to padded decimal numbers or padded as roman numerals to a consistent
+    /// width. See [`RomanNumerals`]. Each file keeps the letter case of its numeral.
+    ///
+    /// Custom regexes must match the numerals in their capture groups. Has no effect with
+    /// [`Self::pad_all_number_groups`]. Overrides [`Self::hexadecimal`].
+    ///
+    /// ```rust
+    /// use nflz::{NFLZAssistant, RomanNumerals};
+    ///
+    /// let builder =
+    ///     NFLZAssistant::builder("./test-resources").roman_numerals(RomanNumerals::ToDecimal);
+    /// ```
+    pub const fn roman_numerals(mut self, style: RomanNumerals) -> Self {
+        self.radix = Radix::Roman(style);
+        self
+    }
+
+    /// Moves the files into numbered subdirectories of the working directory by ranges of
+    /// `chunk_size` numbers, e.g., for archives that must be uploaded in size-limited batches.
+    ///
+    /// For a chunk size of 500, the numbers 1 to 500 go into `part-01/`, 501 to 1000 into
+    /// `part-02/`, and so on. The padding of the numbers stays the same in all subdirectories.
+    /// The subdirectories are created during [`NFLZAssistant::rename_all`]. `0` disables the
+    /// split, which is the default.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let assistant = NFLZAssistant::builder("./test-resources")
+    ///     .split_into_subdirectories(5)
+    ///     .build()
+    ///     .unwrap();
+    /// let file = assistant.files_to_rename()[5];
+    /// assert_eq!(file.subdirectory(), Some("part-002"));
+    /// assert_eq!(file.new_filename(), Some("paris (006).jpg"));
+    /// ```
+    pub const fn split_into_subdirectories(mut self, chunk_size: u64) -> Self {
+        self.split_chunk_size = chunk_size;
+        self
+    }
+
+    /// Removes trailing dots and spaces from the new filenames, as they are unusable on
+    /// Windows. The [`TrimTrailingDotsAndSpaces`] transform is applied after all other
+    /// transforms. Without this option, such filenames are reported by
+    /// [`NFLZAssistant::warnings`].
+    pub const fn trim_trailing_dots_and_spaces(mut self, trim: bool) -> Self {
+        self.trim_trailing_dots_and_spaces = trim;
+        self
+    }
+
+    /// Replaces all characters that are not allowed in filenames on Windows, such as `?` or
+    /// `:`, with `replacement` in the new filenames. The [`ReplaceIllegalCharacters`] transform
+    /// is applied after all other transforms. Every replacement is reported by
+    /// [`NFLZAssistant::warnings`].
+    pub const fn replace_illegal_characters(mut self, replacement: char) -> Self {
+        self.illegal_character_replacement = Some(replacement);
+        self
+    }
+
+    /// Whether hidden files, i.e., files whose name starts with a dot, such as
+    /// `._paris (1).jpg` from macOS, or files with the hidden attribute on Windows, are
+    /// processed. By default, they are ignored and only counted. See
+    /// [`NFLZAssistant::hidden_files_ignored`].
+    pub const fn include_hidden_files(mut self, include: bool) -> Self {
+        self.include_hidden_files = include;
+        self
+    }
+
+    /// Detects the numbered files that are currently opened by other processes, such as a
+    /// photo editor, and warns about them or skips them. Renaming a file under an editor
+    /// leads to a "file not found" error when it saves the file. The detection is best effort
+    /// and only supported on Linux and Windows. See [`OpenFilePolicy`]. By default, open
+    /// files are not detected.
+    pub const fn open_file_policy(mut self, policy: OpenFilePolicy) -> Self {
+        self.open_file_policy = policy;
+        self
+    }
+
+    /// Whether the `.nflzignore` file of the working directory is respected. It lists the
+    /// entries that must never be touched in gitignore syntax, e.g., `DSC_*` or `!keep.jpg`.
+    /// Matching entries are skipped with [`crate::SkipReason::IgnoredByFile`]. Enabled by
+    /// default; a missing file ignores nothing.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let assistant = NFLZAssistant::builder("./test-resources")
+    ///     .respect_ignore_file(false)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub const fn respect_ignore_file(mut self, respect: bool) -> Self {
+        self.respect_ignore_file = respect;
+        self
+    }
+
+    /// Whether the `.gitignore` rules are respected if the working directory is inside a git
+    /// repository, as generated or temporary files listed there usually shouldn't be renamed
+    /// either. Reads the `.gitignore` files from the root of the repository down to the
+    /// working directory and `.git/info/exclude`, but not the global excludes of git.
+    /// Matching entries are skipped with [`crate::SkipReason::IgnoredByFile`]. Disabled by
+    /// default.
+    pub const fn respect_gitignore(mut self, respect: bool) -> Self {
+        self.respect_gitignore = respect;
+        self
+    }
+
+    /// Only renames files whose content belongs to one of the given categories, detected via
+    /// [`ContentCategory::detect`]. All other files are skipped with
+    /// [`crate::SkipReason::ExcludedContentType`]. By default, the content is not checked.
+    ///
+    /// ```rust
+    /// use nflz::{ContentCategory, NFLZAssistant};
+    ///
+    /// let builder = NFLZAssistant::builder("./test-resources")
+    ///     .content_categories([ContentCategory::Image, ContentCategory::Video]);
+    /// ```
+    pub fn content_categories<I: IntoIterator<Item = ContentCategory>>(
+        mut self,
+        categories: I,
+    ) -> Self {
+        self.content_categories = categories.into_iter().collect();
+        self
+    }
+
+    /// Leaves the files whose number is in one of the ranges untouched, e.g., because other
+    /// systems reference them by their exact name. They are skipped with
+    /// [`crate::SkipReason::IgnoredNumber`]. When the files are renumbered, e.g., with
+    /// [`Self::start_at`], the ignored numbers are not assigned to other files.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let builder = NFLZAssistant::builder("./test-resources").ignore_numbers([13..=13, 100..=110]);
+    /// ```
+    pub fn ignore_numbers<I: IntoIterator<Item = RangeInclusive<u64>>>(
+        mut self,
+        ranges: I,
+    ) -> Self {
+        self.ignored_numbers = ranges.into_iter().collect();
+        self
+    }
+
+    /// Renames only the files whose new number is inside the range, so that a huge directory
+    /// can be processed and verified in slices. The padding is still computed from all files,
+    /// so the slices fit together. All other files are skipped with
+    /// [`crate::SkipReason::OutsideRange`].
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let builder = NFLZAssistant::builder("./test-resources").only_numbers(100..=199);
+    /// ```
+    pub const fn only_numbers(mut self, range: RangeInclusive<u64>) -> Self {
+        self.number_range = Some(range);
+        self
+    }
+
+    /// Renames only the first `count` files that need a rename, in the order of
+    /// [`NFLZAssistant::files_to_rename`], so that the effect can be tried on a small slice
+    /// of a huge directory first. Like [`Self::only_numbers`], the padding is still computed
+    /// from all files. The other files that need a rename are skipped with
+    /// [`crate::SkipReason::BeyondLimit`]. With [`Self::group_sidecars`], `count` is the
+    /// number of items, and the files of an item are renamed or skipped together.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let assistant = NFLZAssistant::builder("./test-resources")
+    ///     .limit(2)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(2, assistant.files_to_rename().len());
+    /// ```
+    pub const fn limit(mut self, count: usize) -> Self {
+        self.limit = Some(count);
+        self
+    }
+
+    /// Sets the Unicode normalization form that is used to compare prefixes, suffixes, and new
+    /// filenames, or `None` to compare them byte by byte. The default is
+    /// [`UnicodeNormalization::platform_default`].
+    pub const fn unicode_normalization(
+        mut self,
+        normalization: Option<UnicodeNormalization>,
+    ) -> Self {
+        self.unicode_normalization = normalization;
+        self
+    }
+
+    /// After renaming, updates the references to the old filenames in all files of the
+    /// working directory whose name matches one of the wildcard patterns (`*` and `?` are
+    /// supported), such as `.m3u` playlists, `.cue` sheets, or simple sidecar catalogs. Thus,
+    /// media players don't break.
+    ///
+    /// A reference is a whole line, as in a playlist, or a quoted token, as in a cue sheet,
+    /// that is an old filename, optionally with the prefix `./`. Filenames inside other text
+    /// or other directories are not replaced. The files must be UTF-8.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let builder = NFLZAssistant::builder("./test-resources")
+    ///     .update_references_in(["*.m3u", "*.cue"]);
+    /// ```
+    pub fn update_references_in<I: IntoIterator<Item = S>, S: Into<String>>(
+        mut self,
+        patterns: I,
+    ) -> Self {
+        self.reference_patterns = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// After renaming, updates the filenames of the renamed files inside the checksum
+    /// manifests of the working directory, such as `SHA256SUMS` or `md5sum.txt`, so that
+    /// integrity checks, e.g., `sha256sum -c`, keep passing. The GNU and the BSD (`--tag`)
+    /// formats of the checksum tools are supported; the checksums stay the same. Fails with
+    /// [`NFLZError::CantUpdateReferences`] if a manifest can't be updated. Disabled by default.
+    pub const fn update_checksum_manifests(mut self, update: bool) -> Self {
+        self.update_manifests = update;
+        self
+    }
+
+    /// After renaming, retargets the symlinks of the working directory that point at a renamed
+    /// file to its new name, so that link farms, such as a `latest.jpg` next to the photos,
+    /// don't break silently. Relative targets stay relative. Fails with
+    /// [`NFLZError::CantRetargetSymlink`] if a symlink can't be replaced. Disabled by default.
+    ///
+    /// The symlinks themselves are never renamed.
+    pub const fn retarget_symlinks(mut self, retarget: bool) -> Self {
+        self.retarget_symlinks = retarget;
+        self
+    }
+
+    /// Renames the files in chunks of at most `chunk_size` renames and records the progress
+    /// in the checkpoint file after each chunk, so that a renaming that was interrupted, e.g.,
+    /// by a power loss, can be resumed exactly where it stopped with [`crate::Checkpoint`].
+    /// The checkpoint file is written before the first rename and deleted after the last one.
+    /// `nflz resume` expects it as [`crate::CHECKPOINT_FILENAME`] inside the working
+    /// directory.
+    ///
+    /// Renames that depend on each other, such as the ones of a shift by one, never share a
+    /// chunk. Thus, such renames are recorded one by one.
+    ///
+    /// ```rust
+    /// use nflz::{NFLZAssistant, CHECKPOINT_FILENAME};
+    /// use std::num::NonZeroUsize;
+    /// use std::path::Path;
+    ///
+    /// let dir = Path::new("./test-resources");
+    /// let builder = NFLZAssistant::builder(dir)
+    ///     .checkpoint(dir.join(CHECKPOINT_FILENAME), NonZeroUsize::new(1000).unwrap());
+    /// ```
+    pub fn checkpoint<P: Into<PathBuf>>(mut self, path: P, chunk_size: NonZeroUsize) -> Self {
+        self.checkpoint = Some((path.into(), chunk_size));
+        self
+    }
+
+    /// Flushes the renames of all affected directories to the storage device with `fsync`
+    /// before [`NFLZAssistant::rename_all`] reports success, so that they survive, e.g.,
+    /// unplugging an external drive right afterwards. Fails with
+    /// [`NFLZError::CantSyncDirectory`] if a directory can't be synced. Only supported on
+    /// Unix; does nothing on other platforms. Disabled by default.
+    pub const fn sync_directories(mut self, sync: bool) -> Self {
+        self.sync_directories = sync;
+        self
+    }
+
+    /// Waits at least `interval` between the start of two renames, for folders whose sync
+    /// client, e.g., of Dropbox, OneDrive, or a NAS, chokes on thousands of renames at once.
+    /// Not throttled by default.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    /// use std::time::Duration;
+    ///
+    /// // at most 20 renames per second
+    /// let builder = NFLZAssistant::builder("./test-resources").throttle(Duration::from_millis(50));
+    /// ```
+    pub const fn throttle(mut self, interval: Duration) -> Self {
+        self.throttle = Some(interval);
+        self
+    }
+
+    /// Gives up on a single rename after the timeout, e.g., if a network mount hangs, instead
+    /// of blocking the whole run indefinitely. [`NFLZAssistant::rename_all`] then fails with
+    /// [`NFLZError::RenameTimedOut`]. The rename that timed out may still succeed later, hence,
+    /// nothing is rolled back and the checkpoint of [`Self::checkpoint`] doesn't record it. If
+    /// the rename finished just in time, the renaming goes on. No timeout by default.
+    ///
+    /// Each rename runs on its own thread if a timeout is set.
+    pub const fn rename_timeout(mut self, timeout: Duration) -> Self {
+        self.rename_timeout = Some(timeout);
+        self
+    }
+
+    /// Stops the renaming once the flag is set, e.g., by a Ctrl+C handler. The rename that is
+    /// in progress is finished, so that no file is left behind with a temporary name, and
+    /// [`NFLZAssistant::rename_all`] fails with [`NFLZError::Interrupted`]. With
+    /// [`Self::group_sidecars`], the renaming only stops once all files of the current groups
+    /// are renamed. The checkpoint
+    /// file of [`Self::checkpoint`] is kept, so that the run can be resumed. References of
+    /// [`Self::update_references_in`] are not updated.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    /// use std::sync::atomic::AtomicBool;
+    /// use std::sync::Arc;
+    ///
+    /// let interrupted = Arc::new(AtomicBool::new(false));
+    /// let builder = NFLZAssistant::builder("./test-resources").interrupt_flag(interrupted);
+    /// ```
+    pub fn interrupt_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.interrupt_flag = Some(flag);
+        self
+    }
+
+    /// Only processes the entries of the working directory with the given filenames, e.g., a
+    /// curated selection that was exported from another tool, instead of scanning the whole
+    /// directory. The padding width is computed from the selection only. Hidden files and
+    /// entries of ignore files are still skipped.
+    ///
+    /// [`Self::build`] fails with [`NFLZError::CantReadSelectedFile`] if an entry doesn't
+    /// exist or if a name isn't a plain filename, i.e., contains a path separator.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let assistant = NFLZAssistant::builder("./test-resources")
+    ///     .select_files(["paris (1).jpg", "paris (10).jpg"])
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     assistant.files_to_rename()[0].new_filename(),
+    ///     Some("paris (01).jpg")
+    /// );
+    /// ```
+    pub fn select_files<I: IntoIterator<Item = S>, S: Into<OsString>>(mut self, names: I) -> Self {
+        self.selected_files = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Registers a listener that receives every [`Event`] as soon as it happens, i.e., during
+    /// [`Self::build`] and [`NFLZAssistant::rename_all`]. This is useful to report progress.
+    pub fn event_listener<F: Fn(&Event<'_>) + 'static>(mut self, f: F) -> Self {
+        self.event_listener = EventListener::new(f);
+        self
+    }
+
+    /// Records the filename before the renaming in the extended attribute
+    /// `user.nflz.original` of every renamed file, unless it already has one from an earlier
+    /// run. This provides provenance and enables an undo without any other record of the
+    /// renaming. See [`crate::original_filename`]. File systems without support for extended
+    /// attributes are skipped with a warning in the log. Disabled by default.
+    #[cfg(feature = "xattr")]
+    pub const fn record_original_filenames(mut self, record: bool) -> Self {
+        self.record_original_filenames = record;
+        self
+    }
+
+    /// Applies the options that are pinned by the `.nflz.toml` file of the working directory,
+    /// if there is one. Options that are set afterwards override the ones of the file. Fails
+    /// with [`NFLZError::InvalidConfig`] if the file is invalid. See [`DirectoryConfig`].
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let assistant = NFLZAssistant::builder("./test-resources")
+    ///     .apply_directory_config()
+    ///     .unwrap()
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn apply_directory_config(self) -> Result<Self, NFLZError> {
+        Ok(match DirectoryConfig::read(&self.working_dir)? {
+            Some(config) => config.apply(self),
+            None => self,
+        })
+    }
+
+    /// Applies all options that are set, e.g., the ones of a CLI run that were printed with
+    /// `nflz --print-options`. Options that are set afterwards override them. The transforms
+    /// are added to the ones of the builder. See [`Options`].
+    pub fn apply_options(self, options: &Options) -> Self {
+        options.apply(self)
+    }
+
+    /// Scans the working directory and creates the [`NFLZAssistant`].
+    pub fn build(mut self) -> Result<NFLZAssistant, NFLZError> {
+        if let Some(replacement) = self.illegal_character_replacement {
+            self = self.transform(ReplaceIllegalCharacters::new(replacement));
+        }
+        if self.trim_trailing_dots_and_spaces {
+            self = self.transform(TrimTrailingDotsAndSpaces);
+        }
+        NFLZAssistant::from_builder(self)
+    }
+}
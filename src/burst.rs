@@ -0,0 +1,130 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the order of burst photos with identical timestamps. See [`sort_bursts`].
+
+use crate::file_info::FileInfo;
+use crate::fsutil::long_path;
+use exif::{Context, In, Reader, Tag};
+use std::path::Path;
+use std::time::SystemTime;
+
+/// The `ImageNumber` tag of TIFF/EP (0x9211). Cameras count the frames of a burst with it.
+/// Depending on the camera, it is stored in the primary IFD or in the Exif IFD.
+const IMAGE_NUMBERS: [Tag; 2] = [Tag(Context::Tiff, 0x9211), Tag(Context::Exif, 0x9211)];
+
+/// Reorders every run of files with the same timestamp by their EXIF image number, so that
+/// burst frames keep their true order. The files must be sorted by timestamp. Files without
+/// an image number come first; ties are broken by the number group. The EXIF data is only
+/// read for files that share their timestamp with another file.
+pub(crate) fn sort_bursts(files: &mut [(SystemTime, FileInfo)]) {
+    let mut start = 0;
+    while start < files.len() {
+        let time = files[start].0;
+        let len = files[start..]
+            .iter()
+            .take_while(|(other, _)| *other == time)
+            .count();
+        if len > 1 {
+            files[start..start + len]
+                .sort_by_cached_key(|(_, file)| (image_number(file.path()), file.clone()));
+        }
+        start += len;
+    }
+}
+
+/// Returns the EXIF image number of the file. `None` if the file has no EXIF data, no image
+/// number, or can't be read.
+fn image_number(path: &Path) -> Option<u32> {
+    let file = std::fs::File::open(long_path(path)).ok()?;
+    let exif = Reader::new()
+        .read_from_container(&mut std::io::BufReader::new(file))
+        .ok()?;
+    IMAGE_NUMBERS
+        .iter()
+        .find_map(|tag| exif.get_field(*tag, In::PRIMARY))
+        .and_then(|field| field.value.get_uint(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Returns a minimal little-endian TIFF file with the image number in the primary IFD.
+    fn tiff_with_image_number(number: u32) -> Vec<u8> {
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend(8_u32.to_le_bytes());
+        // one entry: tag, type LONG, count 1, value
+        tiff.extend(1_u16.to_le_bytes());
+        tiff.extend(0x9211_u16.to_le_bytes());
+        tiff.extend(4_u16.to_le_bytes());
+        tiff.extend(1_u32.to_le_bytes());
+        tiff.extend(number.to_le_bytes());
+        // no next IFD
+        tiff.extend(0_u32.to_le_bytes());
+        tiff
+    }
+
+    #[test]
+    fn test_sort_bursts() {
+        let dir = crate::testutil::FixtureBuilder::new("burst ")
+            .count(0)
+            .create()
+            .unwrap();
+        // the counter of the camera wrapped around in the middle of the burst
+        for (filename, image_number) in [
+            ("burst (1).tif", 3),
+            ("burst (2).tif", 1),
+            ("burst (9).tif", 2),
+        ] {
+            std::fs::write(
+                dir.path().join(filename),
+                tiff_with_image_number(image_number),
+            )
+            .unwrap();
+        }
+        let burst = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let mut files = [
+            (SystemTime::UNIX_EPOCH, "burst (5).jpg"),
+            (burst, "burst (1).tif"),
+            (burst, "burst (2).tif"),
+            (burst, "burst (9).tif"),
+        ]
+        .map(|(time, filename)| (time, FileInfo::new(dir.path().join(filename)).unwrap()));
+        sort_bursts(&mut files);
+        let filenames = files
+            .iter()
+            .map(|(_, file)| file.original_filename())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            [
+                "burst (5).jpg",
+                "burst (2).tif",
+                "burst (9).tif",
+                "burst (1).tif"
+            ],
+            *filenames
+        );
+    }
+}
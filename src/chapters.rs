@@ -0,0 +1,270 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the chaptered recordings of action cameras. See [`ChapterPlan`].
+
+use crate::error::NFLZError;
+use crate::fsutil::{long_path, read_directory_flat, EntryKind};
+use crate::math::count_digits_without_leading_zeroes;
+use crate::plan::{PlannedRename, RenamePlan};
+use crate::schedule::schedule;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// A file of a recording that the camera split into chapters, such as `GH010123.MP4`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ChapteredFile {
+    /// Current path of the file.
+    path: PathBuf,
+    /// Prefix of the camera that identifies the encoding, such as `GH`.
+    prefix: &'static str,
+    /// Number of the recording.
+    clip: u32,
+    /// Number of the chapter inside the recording, starting at 1.
+    chapter: u32,
+    /// Everything behind the numbers, such as `.MP4`.
+    extension: String,
+}
+
+impl ChapteredFile {
+    /// Parses the filename of a GoPro recording. Supports the scheme of the HERO6 and newer
+    /// (`GH010123.MP4`: chapter `01` of clip `0123`, also `GX` for HEVC and `GL` for the
+    /// low-resolution proxies) and the one of the older cameras (`GOPR0123.MP4` is the first
+    /// chapter, `GP010123.MP4` the second one). `None` for all other filenames.
+    fn parse(path: &Path) -> Option<Self> {
+        let filename = path.file_name()?.to_str()?;
+        let digits = |s: &str| s.bytes().all(|byte| byte.is_ascii_digit());
+        let (prefix, chapter, rest) = if let Some(rest) = filename.strip_prefix("GOPR") {
+            ("GP", 1, rest)
+        } else {
+            let prefix = ["GH", "GX", "GL", "GP"]
+                .into_iter()
+                .find(|prefix| filename.starts_with(prefix))?;
+            let chapter = filename.get(2..4).filter(|chapter| digits(chapter))?;
+            let chapter = chapter.parse::<u32>().ok()?;
+            // the older cameras count the second chapter as `01`
+            let chapter = if prefix == "GP" { chapter + 1 } else { chapter };
+            (prefix, chapter, &filename[4..])
+        };
+        let clip = rest.get(..4).filter(|clip| digits(clip))?;
+        let extension = &rest[4..];
+        if !(extension.is_empty() || extension.starts_with('.')) {
+            return None;
+        }
+        Some(Self {
+            path: path.to_path_buf(),
+            prefix,
+            clip: clip.parse().ok()?,
+            chapter,
+            extension: extension.to_string(),
+        })
+    }
+
+    /// Returns the new filename in which the clip comes before the chapter, so that the
+    /// chapters of a recording sort next to each other, such as `GH0123-01.MP4`.
+    fn new_filename(&self, chapter_digits: u64) -> String {
+        format!(
+            "{}{:04}-{:0width$}{}",
+            self.prefix,
+            self.clip,
+            self.chapter,
+            self.extension,
+            width = chapter_digits as usize
+        )
+    }
+}
+
+/// Renames the chaptered recordings of action cameras into a sortable sequence.
+///
+/// GoPro cameras split long recordings into chapters and put the chapter in front of the clip
+/// number, such as `GH010123.MP4`, `GH020123.MP4`, and `GH010124.MP4`. Sorted by name, the
+/// chapters of different recordings are mixed up. The plan moves the clip in front of the
+/// chapter, which is padded to at least two digits: `GH0123-01.MP4`, `GH0123-02.MP4`, and
+/// `GH0124-01.MP4`. The first chapters of the older cameras, such as `GOPR0123.MP4`, become
+/// `GP0123-01.MP4`, so that they sort before their other chapters. Sidecars, such as `.THM`
+/// and `.LRV` files, are renamed the same way.
+///
+/// ```rust,no_run
+/// use nflz::ChapterPlan;
+///
+/// let plan = ChapterPlan::new("./gopro").unwrap();
+/// for rename in plan.renames() {
+///     println!("{} => {}", rename.from().display(), rename.to().display());
+/// }
+/// plan.apply().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChapterPlan {
+    /// All files to rename, sorted by their current path.
+    renames: Vec<PlannedRename>,
+}
+
+impl ChapterPlan {
+    /// Scans the directory and computes the new names of all chaptered recordings. All other
+    /// files are ignored.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self, NFLZError> {
+        let dir = dir.as_ref();
+        let files = read_directory_flat(dir)
+            .map_err(|err| NFLZError::CantReadDirectory(dir.to_path_buf(), err))?
+            .into_iter()
+            .filter(|(_, kind)| *kind == EntryKind::File)
+            .filter_map(|(path, _)| ChapteredFile::parse(&path))
+            .collect::<Vec<_>>();
+        let max_chapter = files.iter().map(|file| file.chapter).max().unwrap_or(1);
+        let chapter_digits = count_digits_without_leading_zeroes(max_chapter.into()).max(2);
+        let renames = files
+            .iter()
+            .map(|file| {
+                PlannedRename::new(
+                    file.path.clone(),
+                    file.path.with_file_name(file.new_filename(chapter_digits)),
+                )
+            })
+            .collect();
+        Ok(Self { renames })
+    }
+
+    /// Returns all files to rename, sorted by their current path.
+    pub fn renames(&self) -> &[PlannedRename] {
+        &self.renames
+    }
+
+    /// Returns the renames as [`RenamePlan`], e.g., for [`RenamePlan::to_script`].
+    pub fn to_rename_plan(&self) -> RenamePlan {
+        RenamePlan::new(self.renames.clone())
+    }
+
+    /// Verifies that no file, that isn't renamed itself, would be overwritten. Fails with
+    /// [`NFLZError::ConflictingFiles`] otherwise.
+    pub fn check_can_apply(&self) -> Result<(), NFLZError> {
+        let sources = self
+            .renames
+            .iter()
+            .map(PlannedRename::from)
+            .collect::<HashSet<_>>();
+        let mut destinations = HashSet::new();
+        let conflicts = self
+            .renames
+            .iter()
+            .filter(|rename| {
+                !destinations.insert(rename.to())
+                    || (rename.to().exists() && !sources.contains(rename.to()))
+            })
+            .map(|rename| rename.to().to_path_buf())
+            .collect::<Vec<_>>();
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(NFLZError::ConflictingFiles(conflicts))
+        }
+    }
+
+    /// Renames all files. Runs [`Self::check_can_apply`] first.
+    pub fn apply(self) -> Result<Vec<PlannedRename>, NFLZError> {
+        self.check_can_apply()?;
+        let moves = self
+            .renames
+            .iter()
+            .map(|rename| (rename.from().to_path_buf(), rename.to().to_path_buf()))
+            .collect::<Vec<_>>();
+        for step in schedule(&moves) {
+            std::fs::rename(long_path(&step.from), long_path(&step.to))
+                .map_err(|err| NFLZError::RenameFailed(step.from, step.to, err))?;
+        }
+        Ok(self.renames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::FixtureBuilder;
+
+    #[test]
+    fn test_parse() {
+        let file = ChapteredFile::parse(Path::new("GX020123.MP4")).unwrap();
+        assert_eq!(
+            ("GX", 123, 2, ".MP4"),
+            (file.prefix, file.clip, file.chapter, &*file.extension)
+        );
+        assert_eq!("GX0123-002.MP4", file.new_filename(3));
+
+        let file = ChapteredFile::parse(Path::new("GOPR0123.MP4")).unwrap();
+        assert_eq!(("GP", 123, 1), (file.prefix, file.clip, file.chapter));
+        let file = ChapteredFile::parse(Path::new("GP010123.MP4")).unwrap();
+        assert_eq!(("GP", 123, 2), (file.prefix, file.clip, file.chapter));
+
+        for filename in [
+            "GH0123.MP4",
+            "GH01012345.MP4",
+            "paris (1).jpg",
+            "GHxx0123.MP4",
+        ] {
+            assert_eq!(
+                None,
+                ChapteredFile::parse(Path::new(filename)),
+                "{}",
+                filename
+            );
+        }
+    }
+
+    #[test]
+    fn test_chapter_plan() {
+        let fixture = FixtureBuilder::new("GH")
+            .count(0)
+            .extra_file("GH010123.MP4")
+            .extra_file("GH020123.MP4")
+            .extra_file("GH010123.THM")
+            .extra_file("GH010124.MP4")
+            .extra_file("notes.txt")
+            .create()
+            .unwrap();
+        let plan = ChapterPlan::new(fixture.path()).unwrap();
+        assert_eq!(4, plan.renames().len());
+        plan.apply().unwrap();
+
+        let mut filenames = read_directory_flat(fixture.path())
+            .unwrap()
+            .into_iter()
+            .map(|(path, _)| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        filenames.sort();
+        assert_eq!(
+            [
+                "GH0123-01.MP4",
+                "GH0123-01.THM",
+                "GH0123-02.MP4",
+                "GH0124-01.MP4",
+                "notes.txt"
+            ],
+            *filenames
+        );
+
+        // already renamed files are not recognized again
+        assert!(ChapterPlan::new(fixture.path())
+            .unwrap()
+            .renames()
+            .is_empty());
+    }
+}
@@ -0,0 +1,603 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for the renaming in chunks that can be resumed after an interruption, such as a
+//! power loss. See [`Checkpoint`].
+
+use crate::error::NFLZError;
+use crate::fsutil::long_path;
+use crate::plan::{PlanReport, PlanViolation};
+use crate::schedule::RenameStep;
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, ErrorKind, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Name of the checkpoint file inside the working directory. The file is hidden, so that
+/// it is never part of a plan.
+pub const CHECKPOINT_FILENAME: &str = ".nflz-checkpoint";
+
+/// First line of every checkpoint file. Contains the version of the format.
+const HEADER: &str = "nflz checkpoint 1";
+/// Line behind the last step of a checkpoint file.
+const END_OF_STEPS: &str = "end";
+
+/// A renaming that was interrupted, read from the checkpoint file that
+/// [`crate::NFLZAssistantBuilder::checkpoint`] writes, usually [`CHECKPOINT_FILENAME`]
+/// inside the working directory.
+///
+/// The file contains the renames in the order in that they are performed, split into chunks,
+/// and the chunks that were completed. Thus, the renaming continues exactly where it stopped,
+/// instead of planning again for a directory in which only some files are renamed. The paths
+/// are relative to the directory of the checkpoint file, so that the run can be resumed from
+/// any working directory.
+///
+/// ```rust,no_run
+/// use nflz::{Checkpoint, CHECKPOINT_FILENAME};
+/// use std::path::Path;
+///
+/// let checkpoint = Checkpoint::read(Path::new("./paris").join(CHECKPOINT_FILENAME)).unwrap();
+/// println!("{} renames left", checkpoint.remaining_renames());
+/// checkpoint.resume().unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Path of the checkpoint file.
+    path: PathBuf,
+    /// All renames, in order.
+    steps: Vec<RenameStep>,
+    /// Steps of all chunks.
+    chunks: Vec<Range<usize>>,
+    /// Number of completed chunks.
+    completed_chunks: usize,
+}
+
+impl Checkpoint {
+    /// Reads the checkpoint file. Fails with [`NFLZError::CantReadCheckpoint`] if the file
+    /// doesn't exist or is invalid.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, NFLZError> {
+        let path = path.as_ref().to_path_buf();
+        let content = std::fs::read_to_string(long_path(&path))
+            .map_err(|err| NFLZError::CantReadCheckpoint(path.clone(), err))?;
+        let base = checkpoint_directory(&path);
+        parse(&content).map_or_else(
+            || {
+                Err(NFLZError::CantReadCheckpoint(
+                    path.clone(),
+                    std::io::Error::new(ErrorKind::InvalidData, "invalid checkpoint file"),
+                ))
+            },
+            |(steps, chunks, completed_chunks)| {
+                let steps = steps
+                    .into_iter()
+                    .map(|step| RenameStep {
+                        from: base.join(step.from),
+                        to: base.join(step.to),
+                        completes: step.completes,
+                    })
+                    .collect();
+                Ok(Self {
+                    path: path.clone(),
+                    steps,
+                    chunks,
+                    completed_chunks,
+                })
+            },
+        )
+    }
+
+    /// Returns the path of the checkpoint file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the number of files that are not renamed yet, at most. Files of the
+    /// interrupted chunk may be renamed already.
+    pub fn remaining_renames(&self) -> usize {
+        self.chunks[self.completed_chunks..]
+            .iter()
+            .flat_map(|chunk| &self.steps[chunk.clone()])
+            .filter(|step| step.completes.is_some())
+            .count()
+    }
+
+    /// Checks the remaining renames against the current state of the directory: every source
+    /// must exist, unless an earlier remaining rename creates it, and every destination must
+    /// be free, unless an earlier remaining rename vacates it. Thus, changes of the directory
+    /// since the interruption are detected before anything is renamed. Only reads the file
+    /// system.
+    pub fn verify(&self) -> PlanReport {
+        let mut violations = Vec::new();
+        // whether the paths exist after the remaining renames so far
+        let mut exists = HashMap::<&Path, bool>::new();
+        let interrupted = self.interrupted_chunk();
+        for (index, step) in self.steps.iter().enumerate().skip(interrupted.start) {
+            if interrupted.contains(&index) && is_performed(step) {
+                continue;
+            }
+            let source_exists = *exists
+                .entry(&step.from)
+                .or_insert_with(|| step.from.symlink_metadata().is_ok());
+            let destination_exists = *exists
+                .entry(&step.to)
+                .or_insert_with(|| step.to.symlink_metadata().is_ok());
+            if !source_exists {
+                violations.push(PlanViolation::MissingSource {
+                    source: step.from.clone(),
+                });
+            }
+            if destination_exists {
+                violations.push(PlanViolation::DestinationOccupied {
+                    destination: step.to.clone(),
+                });
+            }
+            exists.insert(&step.from, false);
+            exists.insert(&step.to, true);
+        }
+        PlanReport::new(violations)
+    }
+
+    /// Performs the remaining renames and deletes the checkpoint file afterwards. Returns the
+    /// number of renamed files. The renames of the interrupted chunk whose source doesn't
+    /// exist anymore were performed before the interruption and are skipped. Runs
+    /// [`Self::verify`] first and fails with [`NFLZError::InvalidPlan`] if the directory
+    /// changed in the meantime.
+    ///
+    /// Other than [`crate::NFLZAssistant::rename_all`], this neither emits events nor
+    /// updates references or extended attributes.
+    pub fn resume(self) -> Result<usize, NFLZError> {
+        let report = self.verify();
+        if !report.is_ok() {
+            return Err(NFLZError::InvalidPlan(report.violations().to_vec()));
+        }
+        let interrupted = self.interrupted_chunk();
+        let mut log = CheckpointLog::open(&self.path, self.chunks)?;
+        let mut renamed = 0;
+        for (index, step) in self.steps.iter().enumerate().skip(interrupted.start) {
+            if !(interrupted.contains(&index) && is_performed(step)) {
+                std::fs::rename(long_path(&step.from), long_path(&step.to)).map_err(|err| {
+                    NFLZError::RenameFailed(step.from.clone(), step.to.clone(), err)
+                })?;
+                renamed += usize::from(step.completes.is_some());
+            }
+            log.record(index)?;
+        }
+        log.finish()?;
+        Ok(renamed)
+    }
+
+    /// Returns the steps of the chunk that was interrupted. Empty if all chunks were
+    /// completed.
+    fn interrupted_chunk(&self) -> Range<usize> {
+        self.chunks
+            .get(self.completed_chunks)
+            .cloned()
+            .unwrap_or(self.steps.len()..self.steps.len())
+    }
+}
+
+/// Returns true if the step of the interrupted chunk was performed before the interruption.
+/// See [`chunks`] for why the source tells that.
+fn is_performed(step: &RenameStep) -> bool {
+    step.from.symlink_metadata().is_err() && step.to.symlink_metadata().is_ok()
+}
+
+/// The checkpoint file of a running renaming. See [`Checkpoint`].
+#[derive(Debug)]
+pub(crate) struct CheckpointLog {
+    /// Path of the checkpoint file.
+    path: PathBuf,
+    /// The checkpoint file, opened for appending.
+    file: File,
+    /// Steps of all chunks.
+    chunks: Vec<Range<usize>>,
+}
+
+impl CheckpointLog {
+    /// Writes a new checkpoint file with all steps, split into chunks of at most
+    /// `chunk_size` steps. See [`chunks`]. The paths are written relative to the directory of
+    /// the checkpoint file, see [`relative_path`].
+    pub(crate) fn create(
+        path: &Path,
+        steps: &[RenameStep],
+        chunk_size: usize,
+    ) -> Result<Self, NFLZError> {
+        let chunks = chunks(steps, chunk_size);
+        let to_err = |err| NFLZError::CantWriteCheckpoint(path.to_path_buf(), err);
+        let base = checkpoint_directory(path);
+        let file = File::create(long_path(path)).map_err(to_err)?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "{}", HEADER).map_err(to_err)?;
+        for (index, chunk) in chunks.iter().enumerate() {
+            for step in &steps[chunk.clone()] {
+                let from = relative_path(base, &step.from).map_err(to_err)?;
+                let to = relative_path(base, &step.to).map_err(to_err)?;
+                let from = encode_path(&from).ok_or_else(|| to_err(non_utf8_error()))?;
+                let to = encode_path(&to).ok_or_else(|| to_err(non_utf8_error()))?;
+                let kind = if step.completes.is_some() { 'r' } else { 't' };
+                writeln!(writer, "{}\t{}\t{}\t{}", index, kind, from, to).map_err(to_err)?;
+            }
+        }
+        writeln!(writer, "{}", END_OF_STEPS).map_err(to_err)?;
+        let file = writer
+            .into_inner()
+            .map_err(|err| to_err(err.into_error()))?;
+        file.sync_all().map_err(to_err)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            chunks,
+        })
+    }
+
+    /// Opens an existing checkpoint file to record further progress.
+    fn open(path: &Path, chunks: Vec<Range<usize>>) -> Result<Self, NFLZError> {
+        let file = OpenOptions::new()
+            .append(true)
+            .open(long_path(path))
+            .map_err(|err| NFLZError::CantWriteCheckpoint(path.to_path_buf(), err))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            chunks,
+        })
+    }
+
+    /// Records that the step with the given index was performed. Persists the progress if
+    /// the step completes a chunk.
+    pub(crate) fn record(&mut self, index: usize) -> Result<(), NFLZError> {
+        let Some(chunk) = self.chunks.iter().position(|chunk| chunk.end == index + 1) else {
+            return Ok(());
+        };
+        writeln!(self.file, "done {}", chunk)
+            .and_then(|_| self.file.sync_data())
+            .map_err(|err| NFLZError::CantWriteCheckpoint(self.path.clone(), err))
+    }
+
+    /// Deletes the checkpoint file after all steps were performed.
+    pub(crate) fn finish(self) -> Result<(), NFLZError> {
+        drop(self.file);
+        std::fs::remove_file(long_path(&self.path))
+            .map_err(|err| NFLZError::CantWriteCheckpoint(self.path, err))
+    }
+}
+
+/// Splits the steps into chunks of at most `chunk_size` steps.
+///
+/// No path is the source of one step and the destination of another step of the same chunk.
+/// Thus, a step of an interrupted chunk was performed if and only if its source doesn't exist
+/// anymore. The steps of a chain, such as a shift by one, therefore end up in chunks of their
+/// own.
+fn chunks(steps: &[RenameStep], chunk_size: usize) -> Vec<Range<usize>> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut sources = HashSet::new();
+    let mut destinations = HashSet::new();
+    for (index, step) in steps.iter().enumerate() {
+        if index - start == chunk_size
+            || destinations.contains(step.from.as_path())
+            || sources.contains(step.to.as_path())
+        {
+            chunks.push(start..index);
+            start = index;
+            sources.clear();
+            destinations.clear();
+        }
+        sources.insert(step.from.as_path());
+        destinations.insert(step.to.as_path());
+    }
+    if start < steps.len() {
+        chunks.push(start..steps.len());
+    }
+    chunks
+}
+
+/// Parses the content of a checkpoint file into the steps, the chunks, and the number of
+/// completed chunks. `None` if the content is invalid.
+fn parse(content: &str) -> Option<(Vec<RenameStep>, Vec<Range<usize>>, usize)> {
+    let mut lines = content.lines();
+    if lines.next()? != HEADER {
+        return None;
+    }
+    let mut steps = Vec::new();
+    let mut chunks = Vec::<Range<usize>>::new();
+    let mut renames = 0;
+    loop {
+        let line = lines.next()?;
+        if line == END_OF_STEPS {
+            break;
+        }
+        let mut fields = line.split('\t');
+        let chunk = fields.next()?.parse::<usize>().ok()?;
+        let completes = match fields.next()? {
+            "r" => {
+                renames += 1;
+                Some(renames - 1)
+            }
+            "t" => None,
+            _ => return None,
+        };
+        let from = decode_path(fields.next()?)?;
+        let to = decode_path(fields.next()?)?;
+        if fields.next().is_some() {
+            return None;
+        }
+        // the steps of a chunk are consecutive
+        let count = chunks.len();
+        match chunks.last_mut() {
+            Some(last) if chunk + 1 == count => last.end += 1,
+            _ if chunk == count => chunks.push(steps.len()..steps.len() + 1),
+            _ => return None,
+        }
+        steps.push(RenameStep {
+            from,
+            to,
+            completes,
+        });
+    }
+    // the last line may be incomplete if the interruption happened while writing it
+    let mut completed_chunks = 0;
+    for line in lines {
+        match line.strip_prefix("done ").map(str::parse::<usize>) {
+            Some(Ok(chunk)) if chunk == completed_chunks => completed_chunks += 1,
+            _ => break,
+        }
+    }
+    Some((steps, chunks, completed_chunks))
+}
+
+/// Returns the directory of the checkpoint file, which the paths of the steps are relative to.
+fn checkpoint_directory(path: &Path) -> &Path {
+    path.parent().unwrap_or_else(|| Path::new(""))
+}
+
+/// Returns the path relative to `base`. Paths outside of `base` are made absolute instead, as a
+/// relative path would depend on the current working directory.
+fn relative_path(base: &Path, path: &Path) -> std::io::Result<PathBuf> {
+    match path.strip_prefix(base) {
+        Ok(relative) => Ok(relative.to_path_buf()),
+        Err(_) if path.is_absolute() => Ok(path.to_path_buf()),
+        Err(_) => Ok(std::env::current_dir()?.join(path)),
+    }
+}
+
+/// Returns the path as one line without tabs, or `None` if it isn't valid UTF-8.
+fn encode_path(path: &Path) -> Option<String> {
+    let path = path.to_str()?;
+    Some(
+        path.replace('\\', "\\\\")
+            .replace('\t', "\\t")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r"),
+    )
+}
+
+/// Inverse of [`encode_path`].
+fn decode_path(value: &str) -> Option<PathBuf> {
+    let mut path = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(char) = chars.next() {
+        if char == '\\' {
+            path.push(match chars.next()? {
+                '\\' => '\\',
+                't' => '\t',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            });
+        } else {
+            path.push(char);
+        }
+    }
+    Some(PathBuf::from(path))
+}
+
+/// Error for paths that can't be written into a checkpoint file.
+fn non_utf8_error() -> std::io::Error {
+    std::io::Error::new(ErrorKind::InvalidData, "path is not valid UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::schedule;
+    use crate::testutil::FixtureBuilder;
+
+    fn moves(dir: &Path, moves: &[(&str, &str)]) -> Vec<(PathBuf, PathBuf)> {
+        moves
+            .iter()
+            .map(|(from, to)| (dir.join(from), dir.join(to)))
+            .collect()
+    }
+
+    #[test]
+    fn test_chunks() {
+        let dir = Path::new("dir");
+        // independent renames share chunks
+        let steps = schedule(&moves(
+            dir,
+            &[
+                ("1", "01"),
+                ("2", "02"),
+                ("3", "03"),
+                ("4", "04"),
+                ("5", "05"),
+            ],
+        ));
+        assert_eq!(vec![0..2, 2..4, 4..5], chunks(&steps, 2));
+        // the renames of a chain depend on each other
+        let steps = schedule(&moves(dir, &[("1", "2"), ("2", "3"), ("3", "4")]));
+        assert_eq!(vec![0..1, 1..2, 2..3], chunks(&steps, 10));
+    }
+
+    #[test]
+    fn test_encode_path() {
+        let path = Path::new("dir\\a\tb\nc (1).jpg");
+        let encoded = encode_path(path).unwrap();
+        assert!(!encoded.contains(['\t', '\n']));
+        assert_eq!(Some(path.to_path_buf()), decode_path(&encoded));
+        assert_eq!(None, decode_path("a\\x"));
+    }
+
+    #[test]
+    fn test_resume() {
+        let fixture = FixtureBuilder::new("a ").count(5).create().unwrap();
+        let dir = fixture.path();
+        let checkpoint_path = dir.join("checkpoint");
+        // cycle and chain
+        let steps = schedule(&moves(
+            dir,
+            &[
+                ("a (1).jpg", "a (2).jpg"),
+                ("a (2).jpg", "a (1).jpg"),
+                ("a (3).jpg", "a (4).jpg"),
+                ("a (4).jpg", "a (6).jpg"),
+                ("a (5).jpg", "a (05).jpg"),
+            ],
+        ));
+        let mut log = CheckpointLog::create(&checkpoint_path, &steps, 2).unwrap();
+        let interrupted = log.chunks.iter().position(|chunk| chunk.len() > 1).unwrap();
+        // interrupted behind the first step of a chunk
+        for (index, step) in steps[..log.chunks[interrupted].start + 1]
+            .iter()
+            .enumerate()
+        {
+            std::fs::rename(&step.from, &step.to).unwrap();
+            log.record(index).unwrap();
+        }
+        drop(log);
+
+        let checkpoint = Checkpoint::read(&checkpoint_path).unwrap();
+        assert_eq!(interrupted, checkpoint.completed_chunks);
+        // the indices of the completed moves are not recorded
+        let summary = |steps: &[RenameStep]| {
+            steps
+                .iter()
+                .map(|step| (step.from.clone(), step.to.clone(), step.completes.is_some()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summary(&steps), summary(&checkpoint.steps));
+        checkpoint.resume().unwrap();
+        assert!(!checkpoint_path.exists());
+        for name in [
+            "a (1).jpg",
+            "a (2).jpg",
+            "a (4).jpg",
+            "a (6).jpg",
+            "a (05).jpg",
+        ] {
+            assert!(dir.join(name).is_file(), "{} must exist", name);
+        }
+        assert!(!dir.join("a (3).jpg").exists());
+        assert!(!dir.join("a (5).jpg").exists());
+
+        assert!(matches!(
+            Checkpoint::read(&checkpoint_path),
+            Err(NFLZError::CantReadCheckpoint(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_verify() {
+        let fixture = FixtureBuilder::new("a ").count(3).create().unwrap();
+        let dir = fixture.path();
+        let checkpoint_path = dir.join(CHECKPOINT_FILENAME);
+        let steps = schedule(&moves(
+            dir,
+            &[("a (1).jpg", "a (2).jpg"), ("a (2).jpg", "a (4).jpg")],
+        ));
+        drop(CheckpointLog::create(&checkpoint_path, &steps, 10).unwrap());
+        assert!(Checkpoint::read(&checkpoint_path).unwrap().verify().is_ok());
+
+        // the directory changed since the interruption
+        std::fs::remove_file(dir.join("a (1).jpg")).unwrap();
+        std::fs::write(dir.join("a (4).jpg"), "").unwrap();
+        let checkpoint = Checkpoint::read(&checkpoint_path).unwrap();
+        assert_eq!(
+            [
+                PlanViolation::DestinationOccupied {
+                    destination: dir.join("a (4).jpg")
+                },
+                PlanViolation::MissingSource {
+                    source: dir.join("a (1).jpg")
+                },
+            ],
+            checkpoint.verify().violations()
+        );
+        assert!(matches!(
+            checkpoint.resume(),
+            Err(NFLZError::InvalidPlan(violations)) if violations.len() == 2
+        ));
+        assert!(dir.join("a (2).jpg").is_file());
+    }
+
+    /// Resumes a run that was started with a relative path from another working directory.
+    /// The resume runs in a child process, as the working directory is shared by all tests.
+    #[cfg(unix)]
+    #[test]
+    fn test_resume_from_another_cwd() {
+        if let Some(checkpoint_path) = std::env::var_os("NFLZ_TEST_RESUME_CHECKPOINT") {
+            Checkpoint::read(checkpoint_path).unwrap().resume().unwrap();
+            return;
+        }
+        let fixture = FixtureBuilder::new("a ").count(3).create().unwrap();
+        let cwd = fixture.path().join("sub/deeper");
+        std::fs::create_dir_all(&cwd).unwrap();
+        // the run started in the working directory of the tests, e.g., `nflz ../../tmp/dir`
+        let dir = std::env::current_dir()
+            .unwrap()
+            .components()
+            .skip(1)
+            .map(|_| Path::new(".."))
+            .collect::<PathBuf>()
+            .join(fixture.path().strip_prefix("/").unwrap());
+        let steps = schedule(&moves(
+            &dir,
+            &[("a (1).jpg", "a (01).jpg"), ("a (2).jpg", "a (02).jpg")],
+        ));
+        let mut log = CheckpointLog::create(&dir.join(CHECKPOINT_FILENAME), &steps, 1).unwrap();
+        std::fs::rename(&steps[0].from, &steps[0].to).unwrap();
+        log.record(0).unwrap();
+        drop(log);
+
+        let status = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "checkpoint::tests::test_resume_from_another_cwd"])
+            .env(
+                "NFLZ_TEST_RESUME_CHECKPOINT",
+                Path::new("../..").join(CHECKPOINT_FILENAME),
+            )
+            .current_dir(&cwd)
+            .output()
+            .unwrap();
+        assert!(
+            status.status.success(),
+            "{}",
+            String::from_utf8_lossy(&status.stdout)
+        );
+        assert!(fixture.path().join("a (01).jpg").is_file());
+        assert!(fixture.path().join("a (02).jpg").is_file());
+        assert!(!fixture.path().join(CHECKPOINT_FILENAME).exists());
+    }
+}
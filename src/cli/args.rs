@@ -0,0 +1,1100 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Parsing of the command line arguments. See [`CliArgs`].
+
+use crate::cli::path_style::PathStyle;
+use nflz::{
+    BuiltinPattern, BuiltinTransform, ContentCategory, OpenFilePolicy, Options, RenumberOrder,
+    RomanNumerals, ScriptFormat, SortOrder, UnicodeNormalization,
+};
+use std::num::{NonZeroU32, NonZeroU64, NonZeroUsize};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Usage information printed by `--help`.
+pub const USAGE: &str = "\
+Usage: nflz [OPTIONS] [DIR]
+       nflz merge --into <DIR> [OPTIONS] <SOURCE>...
+       nflz daemon --socket <PATH> [OPTIONS]
+       nflz stats [OPTIONS] [DIR]
+       nflz chapters [OPTIONS] [DIR]
+       nflz resume [OPTIONS] [DIR]
+       nflz shell-integration <install|uninstall>
+
+Adds leading zeroes to the number group of all numbered files in DIR.
+DIR defaults to the present working directory.
+
+Commands:
+  merge                Moves the numbered files of all SOURCE directories into
+                       one continuous, renumbered sequence inside the directory
+                       of --into. The files must share the same prefix.
+  daemon               Long-running mode that listens on a Unix socket for
+                       JSON lines such as {\"command\": \"plan\", \"dir\": \"...\"}
+                       (commands: plan, apply) and answers each with a JSON
+                       line containing the result.
+  stats                Prints the number of files per padding width, the
+                       smallest and highest number, gaps, and duplicates
+                       without renaming anything.
+  chapters             Renames the chaptered recordings of GoPro cameras, such
+                       as GH010123.MP4, so that the chapters of a recording
+                       sort next to each other: GH0123-01.MP4.
+  resume               Continues the run in DIR that was interrupted, e.g., by a
+                       power loss, where it stopped. Requires that the run used
+                       --checkpoint.
+  shell-integration    Adds (install) or removes (uninstall) the entry \"Pad
+                       numbers with nflz\" to the context menu of directories in
+                       the Windows Explorer. It runs this executable in
+                       interactive mode on the selected directory. Windows only.
+
+Options:
+  --pattern <NAME>     Built-in pattern that finds the number group(s) in the
+                       filenames. Available: parentheses (default),
+                       season-episode, track, trailing, date-counter,
+                       android (IMG_20230101_123456.jpg), pixel
+                       (PXL_20230101_123456789.jpg), dcf (IMG_0001.JPG).
+                       'auto' selects the pattern that parses the most files
+                       with the most consecutive numbers and reports it.
+  --transform <NAME>   Applies a built-in transform on the new filenames after
+                       the padding. Can be specified multiple times; the
+                       transforms are applied in the given order.
+                       Available: lowercase-ext, uppercase-ext,
+                       spaces-to-underscores, underscores-to-spaces
+  --include-hidden     Also processes hidden files, such as ._paris (1).jpg.
+                       By default, they are ignored.
+  --open-files <POLICY>
+                       Detects files that are opened by other processes, such
+                       as a photo editor, and warns about them (warn) or leaves
+                       them untouched (skip). Linux and Windows only.
+  --no-ignore-file     Doesn't read the .nflzignore file of the directory,
+                       which lists the files to never touch (gitignore
+                       syntax).
+  --gitignore          Also skips the files that are ignored by the .gitignore
+                       files of the git repository of the directory.
+  --no-config          Ignores the .nflz.toml file of the directory, which pins
+                       options such as the pattern or the width. Otherwise,
+                       options on the command line override the file.
+  --options <FILE>     Applies the options of the JSON file, e.g., the output of
+                       '--print-options', after the .nflz.toml file. Options on
+                       the command line override the file.
+  --print-options      Prints the options of the command line that determine
+                       the plan as JSON for '--options' or the `Options` of
+                       the library and exits.
+  --only <CATEGORY>    Only renames files whose content (magic bytes or file
+                       extension) belongs to the category. Can be specified
+                       multiple times. Available: image, video, audio,
+                       document
+  --ignore-numbers <RANGES>
+                       Leaves the files with these numbers untouched, e.g.,
+                       13,100-110. Renumbering skips them as well.
+  --range <START..END> Renames only the files whose new number is in the range,
+                       e.g., 100..200 for the numbers 100 to 199. The padding
+                       is computed from all files. Either end may be omitted.
+  --reserve <N>        Pads the numbers as if N more files will be added later.
+  --width <N>          Pads the numbers to at least N digits, e.g., 4 for
+                       `ffmpeg -i frame%04d.png`.
+  --expect-count <N>   Pads the numbers for N files in total instead of the
+                       current highest number.
+  --sanitize           Replaces characters that are not allowed on Windows,
+                       such as ? or :, with _ in the new filenames.
+  --normalize <FORM>   Unicode normalization form to compare filenames.
+                       Available: nfc (default on macOS), nfd, none (default
+                       elsewhere)
+  --start-at <N>       Renumbers the files in their order, starting at N.
+  --step <N>           Renumbers the files in their order in increments of N,
+                       e.g., 10, 20, 30, ... to leave room for inserts.
+  --renumber-by <ORDER>
+                       Renumbers the files in the given order instead of
+                       their existing numbers. Available: number (default),
+                       mtime, created
+  --continue-from <DIR>
+                       Renumbers the files to continue after the highest
+                       number in DIR, e.g., an archive of a previous SD card.
+  --per-extension      Each file extension forms its own sequence with its own
+                       padding, e.g., IMG (0001).jpg and VID (01).mp4.
+  --group-sidecars     Treats files with the same stem, such as img (3).jpg and
+                       img (3).xmp, as one item that is renamed together.
+  --ignore-prefix-case
+                       Treats prefixes that only differ in their case, such as
+                       img (1).jpg and IMG (2).jpg, as the same sequence.
+  --ignore-prefix-whitespace
+                       Treats prefixes that only differ in leading, trailing,
+                       or repeated whitespace, such as img (1).jpg and
+                       img  (2).jpg, as the same sequence.
+  --unify-prefixes     Renames files with an equivalent prefix to the most
+                       common spelling of the prefix, e.g., with
+                       --ignore-prefix-case: IMG (2).jpg => img (2).jpg.
+  --all-groups         Pads every sequence of digits in the filenames, such as
+                       S01E3 - part (2).mkv => S01E03 - part (02).mkv.
+  --hex                Reads the number groups as hexadecimal counters, such as
+                       dump (1f).bin.
+  --roman <STYLE>      Reads the number groups as roman numerals, such as
+                       Chapter (IV).md. Styles: decimal (IV => 04), padded
+                       (IV => __IV next to VIII)
+  --split <N>          Moves the files into the subdirectories part-01, part-02,
+                       ... by ranges of N numbers, e.g., for uploads in
+                       size-limited batches.
+  --trim-trailing      Removes trailing dots and spaces from the new filenames
+                       as they are unusable on Windows.
+  --events <FORMAT>    Emits every event (scanned, skipped, planned, renamed,
+                       error) to stdout as soon as it happens. All other
+                       output goes to stderr. Available: jsonl
+  --script <FORMAT>    Prints a script that performs the renames to stdout
+                       instead of renaming, e.g., to execute the plan on another
+                       machine. All other output goes to stderr. Available: sh,
+                       powershell
+  --explain            Prints for every entry of DIR why it would be renamed,
+                       left alone, or skipped, including the chosen number
+                       group and the computed width, without renaming anything.
+  --sort <ORDER>       Order of the files in the printed tables. Doesn't
+                       influence the new filenames. Available: number
+                       (default), name, mtime, size
+  -r, --recursive      Also processes all subdirectories of DIR. Each directory
+                       is an independent set of numbered files.
+  --exclude-dir <GLOB> Doesn't descend into subdirectories whose name matches
+                       the pattern (`*` and `?` are supported) in recursive
+                       mode. Can be specified multiple times.
+  --files-from <FILE>  Only processes the files listed in FILE (one path per
+                       line or NUL-delimited) instead of scanning DIR. Each
+                       directory of the listed files is processed on its own.
+  --into <DIR>         Destination directory of the merge. Created if needed.
+  --socket <PATH>      Path of the Unix socket of the daemon. Everybody who can
+                       connect can rename files wherever the daemon can.
+  --socket-mode <MODE> Octal permissions of the socket of the daemon, such as
+                       660 to let the group connect. Default: 600
+  --metrics <ADDR>     Serves the counters of the daemon (directories processed,
+                       files renamed, errors) for Prometheus over HTTP at
+                       http://ADDR/metrics, e.g., 127.0.0.1:9184.
+  -y, --yes            Renames the files without asking for confirmation.
+  -n, --dry-run        Prints and verifies the plan without renaming anything.
+                       Exits with 3 if files would be renamed and with 0 if
+                       the files are already consistent, e.g., for cron jobs.
+  --i-know-what-i-am-doing
+                       Allows to work on system directories, such as / or
+                       /usr, and on the home directory itself.
+  -q, --quiet          Only prints the final summary and errors.
+  --absolute           Prints absolute paths in the tables, the events, and the
+                       scripts instead of filenames and the paths as given.
+  --relative           Like '--absolute' but prints the paths relative to the
+                       current directory.
+  --timings            Prints how long scan, plan, validation and apply took.
+  --checkpoint         Records the progress of the renaming in the hidden file
+                       .nflz-checkpoint of DIR, so that an interrupted run can
+                       be continued with 'nflz resume'.
+  --chunk-size <N>     Renames between two records of '--checkpoint'. Default:
+                       1000
+  --fsync              Syncs the renames to the storage device before reporting
+                       success, e.g., before unplugging an external drive.
+                       Only supported on Unix.
+  --limit <N>          Renames only the first N files, e.g., to try nflz on a
+                       small slice of a huge directory first. With
+                       '--group-sidecars', N counts the groups.
+  --rate-limit <N>     Renames at most N files per second, e.g., for folders of
+                       Dropbox, OneDrive, or a NAS whose sync client chokes on
+                       thousands of renames at once.
+  --rename-timeout <SECS>
+                       Gives up on a rename that takes longer than SECS
+                       seconds, e.g., on a hung network mount, instead of
+                       blocking forever. The run fails with the rename.
+  --update-refs <GLOB> Updates the references to the renamed files in all files
+                       of DIR whose name matches the pattern, e.g., `*.m3u`.
+                       Can be specified multiple times.
+  --update-manifests   Updates the filenames inside the checksum manifests of DIR,
+                       such as SHA256SUMS or md5sum.txt, so that integrity
+                       checks keep passing.
+  --retarget-symlinks  Points the symlinks in DIR that point at renamed files to
+                       their new names.
+  --notify             Sends a desktop notification when the renaming is done.
+                       Requires the `notify` feature.
+  --webhook <URL>      Posts the summary of the run as JSON to the URL when the
+                       renaming is done or failed, e.g., for chat or
+                       home-automation alerts. Requires the `webhook` feature.
+  --pick               Lists the numbered files in an interactive picker with
+                       fuzzy search to select the files that are renamed.
+                       Requires the `picker` feature.
+  --record-original    Records the original filename in the extended attribute
+                       user.nflz.original of every renamed file. Requires the
+                       `xattr` feature.
+  -h, --help           Prints this help.";
+
+/// Subcommand of the CLI. Without subcommand, the CLI works on a single directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subcommand {
+    /// Merges several directories into one sequence.
+    Merge,
+    /// Long-running mode with a control socket.
+    Daemon,
+    /// Read-only analysis of the numbered files.
+    Stats,
+    /// Renames the chaptered recordings of action cameras.
+    Chapters,
+    /// Continues an interrupted run.
+    Resume,
+    /// Installs or removes the context-menu entry of the Windows Explorer.
+    ShellIntegration(ShellIntegrationAction),
+}
+
+/// Action of the `shell-integration` subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellIntegrationAction {
+    /// Registers the context-menu entry.
+    Install,
+    /// Removes the context-menu entry.
+    Uninstall,
+}
+
+/// Format of the machine-readable event stream selected with `--events`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventFormat {
+    /// One JSON object per line.
+    Jsonl,
+}
+
+/// Parsed command line arguments of the CLI.
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    /// The subcommand, if any.
+    pub subcommand: Option<Subcommand>,
+    /// The directory to work in. `None` means the present working directory.
+    pub dir: Option<PathBuf>,
+    /// Source directories of the merge.
+    pub sources: Vec<PathBuf>,
+    /// Destination directory of the merge selected with `--into`.
+    pub into: Option<PathBuf>,
+    /// Pattern selected with `--pattern`. `None` means the pattern of the `.nflz.toml` file
+    /// or the default.
+    pub pattern: Option<BuiltinPattern>,
+    /// Whether the pattern is detected automatically (`--pattern auto`).
+    pub detect_pattern: bool,
+    /// Transforms selected with `--transform`, in the given order.
+    pub transforms: Vec<BuiltinTransform>,
+    /// Policy for open files selected with `--open-files`.
+    pub open_files: Option<OpenFilePolicy>,
+    /// `--include-hidden` was specified.
+    pub include_hidden: bool,
+    /// `--no-ignore-file` was specified.
+    pub no_ignore_file: bool,
+    /// `--gitignore` was specified.
+    pub gitignore: bool,
+    /// `--no-config` was specified.
+    pub no_config: bool,
+    /// JSON file with [`Options`] selected with `--options`.
+    pub options_file: Option<PathBuf>,
+    /// `--print-options` was specified.
+    pub print_options: bool,
+    /// Content categories selected with `--only`. Empty means all files.
+    pub content_categories: Vec<ContentCategory>,
+    /// Numbers of the files to leave untouched, selected with `--ignore-numbers`.
+    pub ignore_numbers: Vec<RangeInclusive<u64>>,
+    /// Range of the new numbers of the files to rename, selected with `--range`.
+    pub range: Option<RangeInclusive<u64>>,
+    /// Amount of files to reserve digits for, selected with `--reserve`.
+    pub reserve: u64,
+    /// Minimum amount of digits selected with `--width`.
+    pub width: u64,
+    /// Expected total amount of files selected with `--expect-count`.
+    pub expect_count: u64,
+    /// `--sanitize` was specified.
+    pub sanitize: bool,
+    /// Normalization form selected with `--normalize`. `None` means the platform default.
+    pub normalize: Option<Option<UnicodeNormalization>>,
+    /// First number selected with `--start-at`.
+    pub start_at: Option<u64>,
+    /// Increment selected with `--step`.
+    pub step: Option<NonZeroU64>,
+    /// Order selected with `--renumber-by`.
+    pub renumber_by: RenumberOrder,
+    /// Archive selected with `--continue-from`.
+    pub continue_from: Option<PathBuf>,
+    /// Amount of numbers per subdirectory selected with `--split`. `0` disables the split.
+    pub split: u64,
+    /// `--per-extension` was specified.
+    pub per_extension: bool,
+    /// `--group-sidecars` was specified.
+    pub group_sidecars: bool,
+    /// `--ignore-prefix-case` was specified.
+    pub ignore_prefix_case: bool,
+    /// `--ignore-prefix-whitespace` was specified.
+    pub ignore_prefix_whitespace: bool,
+    /// `--unify-prefixes` was specified.
+    pub unify_prefixes: bool,
+    /// `--all-groups` was specified.
+    pub all_groups: bool,
+    /// `--hex` was specified.
+    pub hex: bool,
+    /// Style of the roman numerals selected with `--roman`.
+    pub roman: Option<RomanNumerals>,
+    /// `--trim-trailing` was specified.
+    pub trim_trailing: bool,
+    /// Format of the event stream selected with `--events`.
+    pub events: Option<EventFormat>,
+    /// Format of the script selected with `--script`.
+    pub script: Option<ScriptFormat>,
+    /// `--explain` was specified.
+    pub explain: bool,
+    /// Order of the files in the printed tables selected with `--sort`.
+    pub sort: SortOrder,
+    /// `--recursive` was specified.
+    pub recursive: bool,
+    /// Patterns of directory names that are skipped in recursive mode.
+    pub exclude_dirs: Vec<String>,
+    /// List of the files to process selected with `--files-from`.
+    pub files_from: Option<PathBuf>,
+    /// Socket of the daemon selected with `--socket`.
+    pub socket: Option<PathBuf>,
+    /// Permissions of the socket of the daemon selected with `--socket-mode`.
+    pub socket_mode: Option<u32>,
+    /// Address of the metrics endpoint of the daemon selected with `--metrics`.
+    pub metrics: Option<String>,
+    /// `--yes` was specified.
+    pub yes: bool,
+    /// `--dry-run` was specified.
+    pub dry_run: bool,
+    /// `--i-know-what-i-am-doing` was specified.
+    pub allow_dangerous_paths: bool,
+    /// `--quiet` was specified.
+    pub quiet: bool,
+    /// How paths are printed, selected with `--absolute` or `--relative`.
+    pub path_style: Option<PathStyle>,
+    /// `--timings` was specified.
+    pub timings: bool,
+    /// `--checkpoint` was specified.
+    pub checkpoint: bool,
+    /// Renames per chunk selected with `--chunk-size`.
+    pub chunk_size: Option<NonZeroUsize>,
+    /// `--fsync` was specified.
+    pub fsync: bool,
+    /// Maximum number of renamed files selected with `--limit`.
+    pub limit: Option<usize>,
+    /// Maximum renames per second selected with `--rate-limit`.
+    pub rate_limit: Option<NonZeroU32>,
+    /// Maximum duration of a single rename selected with `--rename-timeout`.
+    pub rename_timeout: Option<Duration>,
+    /// Values of `--update-refs`.
+    pub update_refs: Vec<String>,
+    /// `--update-manifests` was specified.
+    pub update_manifests: bool,
+    /// `--retarget-symlinks` was specified.
+    pub retarget_symlinks: bool,
+    /// `--notify` was specified.
+    pub notify: bool,
+    /// URL selected with `--webhook`.
+    pub webhook: Option<String>,
+    /// `--pick` was specified.
+    pub pick: bool,
+    /// `--record-original` was specified.
+    pub record_original: bool,
+    /// `--help` was specified.
+    pub help: bool,
+}
+
+impl CliArgs {
+    /// Parses the arguments without the name of the binary. Returns a human-readable error
+    /// message if the arguments are invalid.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut cli_args = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            // support both "--flag value" and "--flag=value"
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("Missing value for '{}'.", flag))
+            };
+
+            match flag.as_str() {
+                "-h" | "--help" => cli_args.help = true,
+                "-y" | "--yes" => cli_args.yes = true,
+                "-n" | "--dry-run" => cli_args.dry_run = true,
+                "--explain" => cli_args.explain = true,
+                "--i-know-what-i-am-doing" => cli_args.allow_dangerous_paths = true,
+                "-q" | "--quiet" => cli_args.quiet = true,
+                "--absolute" | "--relative" => {
+                    let style = if flag == "--absolute" {
+                        PathStyle::Absolute
+                    } else {
+                        PathStyle::Relative
+                    };
+                    if matches!(cli_args.path_style, Some(other) if other != style) {
+                        return Err("'--absolute' and '--relative' can't be combined.".to_string());
+                    }
+                    cli_args.path_style = Some(style);
+                }
+                "--timings" => cli_args.timings = true,
+                "--update-refs" => cli_args.update_refs.push(value()?),
+                "--update-manifests" => cli_args.update_manifests = true,
+                "--retarget-symlinks" => cli_args.retarget_symlinks = true,
+                "--notify" if cfg!(feature = "notify") => cli_args.notify = true,
+                "--notify" => {
+                    return Err(
+                        "'--notify' requires nflz to be built with the 'notify' feature."
+                            .to_string(),
+                    )
+                }
+                "--webhook" if cfg!(feature = "webhook") => cli_args.webhook = Some(value()?),
+                "--webhook" => {
+                    return Err(
+                        "'--webhook' requires nflz to be built with the 'webhook' feature."
+                            .to_string(),
+                    )
+                }
+                "--pick" if cfg!(feature = "picker") => cli_args.pick = true,
+                "--pick" => {
+                    return Err(
+                        "'--pick' requires nflz to be built with the 'picker' feature.".to_string(),
+                    )
+                }
+                "--record-original" if cfg!(feature = "xattr") => cli_args.record_original = true,
+                "--record-original" => {
+                    return Err(
+                        "'--record-original' requires nflz to be built with the 'xattr' feature."
+                            .to_string(),
+                    )
+                }
+                "--pattern" => match value()?.as_str() {
+                    "auto" => cli_args.detect_pattern = true,
+                    pattern => {
+                        cli_args.pattern = Some(pattern.parse().map_err(|e| format!("{}", e))?);
+                    }
+                },
+                "--transform" => {
+                    let transform = value()?.parse().map_err(|e| format!("{}", e))?;
+                    cli_args.transforms.push(transform);
+                }
+                "--include-hidden" => cli_args.include_hidden = true,
+                "--no-ignore-file" => cli_args.no_ignore_file = true,
+                "--gitignore" => cli_args.gitignore = true,
+                "--no-config" => cli_args.no_config = true,
+                "--options" => cli_args.options_file = Some(PathBuf::from(value()?)),
+                "--print-options" => cli_args.print_options = true,
+                "--only" => {
+                    let category = value()?.parse().map_err(|e| format!("{}", e))?;
+                    cli_args.content_categories.push(category);
+                }
+                "--ignore-numbers" => {
+                    let value = value()?;
+                    let ranges = parse_number_ranges(&value).ok_or_else(|| {
+                        format!("Invalid numbers '{}' for '--ignore-numbers'.", value)
+                    })?;
+                    cli_args.ignore_numbers.extend(ranges);
+                }
+                "--range" => {
+                    let value = value()?;
+                    let range = parse_range(&value)
+                        .ok_or_else(|| format!("Invalid range '{}' for '--range'.", value))?;
+                    cli_args.range = Some(range);
+                }
+                "--reserve" => {
+                    let value = value()?;
+                    cli_args.reserve = value
+                        .parse()
+                        .map_err(|_| format!("Invalid number '{}' for '--reserve'.", value))?;
+                }
+                "--width" => {
+                    let value = value()?;
+                    cli_args.width = value
+                        .parse()
+                        .map_err(|_| format!("Invalid number '{}' for '--width'.", value))?;
+                }
+                "--expect-count" => {
+                    let value = value()?;
+                    cli_args.expect_count = value
+                        .parse()
+                        .map_err(|_| format!("Invalid number '{}' for '--expect-count'.", value))?;
+                }
+                "--sanitize" => cli_args.sanitize = true,
+                "--normalize" => {
+                    cli_args.normalize = match value()?.as_str() {
+                        "nfc" => Some(Some(UnicodeNormalization::Nfc)),
+                        "nfd" => Some(Some(UnicodeNormalization::Nfd)),
+                        "none" => Some(None),
+                        form => return Err(format!("Unknown normalization form '{}'.", form)),
+                    };
+                }
+                "--start-at" => {
+                    let value = value()?;
+                    cli_args.start_at =
+                        Some(value.parse().map_err(|_| {
+                            format!("Invalid number '{}' for '--start-at'.", value)
+                        })?);
+                }
+                "--step" => {
+                    let value = value()?;
+                    cli_args.step = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid number '{}' for '--step'.", value))?,
+                    );
+                }
+                "--open-files" => {
+                    cli_args.open_files = match value()?.as_str() {
+                        "warn" => Some(OpenFilePolicy::Warn),
+                        "skip" => Some(OpenFilePolicy::Skip),
+                        policy => return Err(format!("Unknown open-file policy '{}'.", policy)),
+                    };
+                }
+                "--renumber-by" => {
+                    cli_args.renumber_by = match value()?.as_str() {
+                        "number" => RenumberOrder::Number,
+                        "mtime" => RenumberOrder::Mtime,
+                        "created" => RenumberOrder::Created,
+                        order => return Err(format!("Unknown renumber order '{}'.", order)),
+                    };
+                }
+                "--continue-from" => cli_args.continue_from = Some(PathBuf::from(value()?)),
+                "--per-extension" => cli_args.per_extension = true,
+                "--group-sidecars" => cli_args.group_sidecars = true,
+                "--ignore-prefix-case" => cli_args.ignore_prefix_case = true,
+                "--ignore-prefix-whitespace" => cli_args.ignore_prefix_whitespace = true,
+                "--unify-prefixes" => cli_args.unify_prefixes = true,
+                "--all-groups" => cli_args.all_groups = true,
+                "--hex" => cli_args.hex = true,
+                "--roman" => {
+                    cli_args.roman = match value()?.as_str() {
+                        "decimal" => Some(RomanNumerals::ToDecimal),
+                        "padded" => Some(RomanNumerals::Padded),
+                        style => return Err(format!("Unknown roman numeral style '{}'.", style)),
+                    };
+                }
+                "--split" => {
+                    let value = value()?;
+                    cli_args.split = match value.parse() {
+                        Ok(chunk_size) if chunk_size > 0 => chunk_size,
+                        _ => return Err(format!("Invalid number '{}' for '--split'.", value)),
+                    };
+                }
+                "--trim-trailing" => cli_args.trim_trailing = true,
+                "--events" => {
+                    cli_args.events = match value()?.as_str() {
+                        "jsonl" => Some(EventFormat::Jsonl),
+                        format => return Err(format!("Unknown event format '{}'.", format)),
+                    };
+                }
+                "--script" => {
+                    cli_args.script = Some(value()?.parse().map_err(|e| format!("{}", e))?);
+                }
+                "--sort" => {
+                    cli_args.sort = match value()?.as_str() {
+                        "number" => SortOrder::Number,
+                        "name" => SortOrder::Name,
+                        "mtime" => SortOrder::Mtime,
+                        "size" => SortOrder::Size,
+                        order => return Err(format!("Unknown sort order '{}'.", order)),
+                    };
+                }
+                "-r" | "--recursive" => cli_args.recursive = true,
+                "--exclude-dir" => cli_args.exclude_dirs.push(value()?),
+                "--files-from" => cli_args.files_from = Some(PathBuf::from(value()?)),
+                "--socket" => cli_args.socket = Some(PathBuf::from(value()?)),
+                "--socket-mode" => {
+                    let value = value()?;
+                    let mode = u32::from_str_radix(&value, 8)
+                        .ok()
+                        .filter(|mode| *mode <= 0o777)
+                        .ok_or_else(|| format!("'{}' is not an octal mode, such as 600.", value))?;
+                    cli_args.socket_mode = Some(mode);
+                }
+                "--metrics" => cli_args.metrics = Some(value()?),
+                "--into" => cli_args.into = Some(PathBuf::from(value()?)),
+                "--checkpoint" => cli_args.checkpoint = true,
+                "--chunk-size" => {
+                    let value = value()?;
+                    let size = value
+                        .parse()
+                        .map_err(|_| format!("Invalid number '{}' for '--chunk-size'.", value))?;
+                    cli_args.chunk_size = Some(size);
+                }
+                "--fsync" => cli_args.fsync = true,
+                "--limit" => {
+                    let value = value()?;
+                    let limit = value
+                        .parse()
+                        .map_err(|_| format!("Invalid number '{}' for '--limit'.", value))?;
+                    cli_args.limit = Some(limit);
+                }
+                "--rate-limit" => {
+                    let value = value()?;
+                    let rate = value
+                        .parse()
+                        .map_err(|_| format!("Invalid number '{}' for '--rate-limit'.", value))?;
+                    cli_args.rate_limit = Some(rate);
+                }
+                "--rename-timeout" => {
+                    let value = value()?;
+                    let seconds = value.parse::<NonZeroU64>().map_err(|_| {
+                        format!(
+                            "Invalid number of seconds '{}' for '--rename-timeout'.",
+                            value
+                        )
+                    })?;
+                    cli_args.rename_timeout = Some(Duration::from_secs(seconds.get()));
+                }
+                _ if flag.starts_with('-') => return Err(format!("Unknown option '{}'.", flag)),
+                // subcommands must come first. Use "./daemon" for a directory called "daemon"
+                "daemon" if cli_args.subcommand.is_none() && cli_args.dir.is_none() => {
+                    cli_args.subcommand = Some(Subcommand::Daemon);
+                }
+                "merge" if cli_args.subcommand.is_none() && cli_args.dir.is_none() => {
+                    cli_args.subcommand = Some(Subcommand::Merge);
+                }
+                "stats" if cli_args.subcommand.is_none() && cli_args.dir.is_none() => {
+                    cli_args.subcommand = Some(Subcommand::Stats);
+                }
+                "chapters" if cli_args.subcommand.is_none() && cli_args.dir.is_none() => {
+                    cli_args.subcommand = Some(Subcommand::Chapters);
+                }
+                "resume" if cli_args.subcommand.is_none() && cli_args.dir.is_none() => {
+                    cli_args.subcommand = Some(Subcommand::Resume);
+                }
+                "shell-integration" if cli_args.subcommand.is_none() && cli_args.dir.is_none() => {
+                    let action = match args.next().as_deref() {
+                        Some("install") => ShellIntegrationAction::Install,
+                        Some("uninstall") => ShellIntegrationAction::Uninstall,
+                        _ => {
+                            return Err(
+                                "shell-integration requires 'install' or 'uninstall'.".to_string()
+                            )
+                        }
+                    };
+                    cli_args.subcommand = Some(Subcommand::ShellIntegration(action));
+                }
+                _ if cli_args.subcommand == Some(Subcommand::Merge) => {
+                    cli_args.sources.push(PathBuf::from(arg));
+                }
+                _ => {
+                    if cli_args.dir.is_some() {
+                        return Err(format!("Unexpected argument '{}'.", arg));
+                    }
+                    cli_args.dir = Some(PathBuf::from(arg));
+                }
+            }
+        }
+
+        if cli_args.start_at.is_some() && cli_args.continue_from.is_some() {
+            return Err("'--start-at' and '--continue-from' can't be combined.".to_string());
+        }
+        if cli_args.hex && cli_args.roman.is_some() {
+            return Err("'--hex' and '--roman' can't be combined.".to_string());
+        }
+        if cli_args.dry_run && cli_args.script.is_some() {
+            return Err("'--dry-run' and '--script' can't be combined.".to_string());
+        }
+        if !cli_args.exclude_dirs.is_empty() && !cli_args.recursive {
+            return Err("'--exclude-dir' requires '--recursive'.".to_string());
+        }
+        if cli_args.files_from.is_some()
+            && (cli_args.dir.is_some() || cli_args.recursive || cli_args.subcommand.is_some())
+        {
+            return Err(
+                "'--files-from' can't be combined with DIR, '--recursive', or a command."
+                    .to_string(),
+            );
+        }
+        if cli_args.subcommand == Some(Subcommand::Daemon) && cli_args.socket.is_none() {
+            return Err("The daemon requires '--socket'.".to_string());
+        }
+        if cli_args.chunk_size.is_some() && !cli_args.checkpoint {
+            return Err("'--chunk-size' requires '--checkpoint'.".to_string());
+        }
+        if cli_args.metrics.is_some() && cli_args.subcommand != Some(Subcommand::Daemon) {
+            return Err("'--metrics' requires the daemon.".to_string());
+        }
+        if cli_args.socket_mode.is_some() && cli_args.subcommand != Some(Subcommand::Daemon) {
+            return Err("'--socket-mode' requires the daemon.".to_string());
+        }
+        if cli_args.subcommand == Some(Subcommand::Merge)
+            && (cli_args.into.is_none() || cli_args.sources.len() < 2)
+        {
+            return Err("Merge requires '--into' and at least two source directories.".to_string());
+        }
+
+        Ok(cli_args)
+    }
+
+    /// Returns the directory to work in. Either the PWD or the one specified by the user.
+    pub fn dir(&self) -> PathBuf {
+        self.dir
+            .clone()
+            .unwrap_or_else(|| std::env::current_dir().unwrap())
+    }
+
+    /// Returns the options of the command line that determine the plan. Flags that are not
+    /// given are `None` or empty, so that they don't override the `.nflz.toml` file or
+    /// `--options`.
+    pub fn options(&self) -> Options {
+        let mut options = Options::default();
+        options.pattern = self.pattern;
+        options.transform = self.transforms.clone();
+        options.width = (self.width > 0).then_some(self.width);
+        options.reserve = (self.reserve > 0).then_some(self.reserve);
+        options.expect_count = (self.expect_count > 0).then_some(self.expect_count);
+        options.per_extension = self.per_extension.then_some(true);
+        options.group_sidecars = self.group_sidecars.then_some(true);
+        options.all_groups = self.all_groups.then_some(true);
+        options.hex = self.hex.then_some(true);
+        options.roman = self.roman;
+        options.include_hidden = self.include_hidden.then_some(true);
+        options.only = self.content_categories.clone();
+        options.no_ignore_file = self.no_ignore_file.then_some(true);
+        options.gitignore = self.gitignore.then_some(true);
+        options.ignore_numbers = self.ignore_numbers.clone();
+        options.range = self.range.clone();
+        options.limit = self.limit;
+        options.split = (self.split > 0).then_some(self.split);
+        options.renumber_by =
+            (self.renumber_by != RenumberOrder::Number).then_some(self.renumber_by);
+        options.start_at = self.start_at;
+        options.step = self.step;
+        options.continue_from = self.continue_from.clone();
+        options.update_refs = self.update_refs.clone();
+        options.update_manifests = self.update_manifests.then_some(true);
+        options.retarget_symlinks = self.retarget_symlinks.then_some(true);
+        options.trim_trailing = self.trim_trailing.then_some(true);
+        options.sanitize = self.sanitize.then_some(true);
+        options.open_files = self.open_files;
+        options.ignore_prefix_case = self.ignore_prefix_case.then_some(true);
+        options.ignore_prefix_whitespace = self.ignore_prefix_whitespace.then_some(true);
+        options.unify_prefixes = self.unify_prefixes.then_some(true);
+        options.normalize = self.normalize;
+        options
+    }
+}
+
+/// Parses a comma-separated list of numbers and ranges, such as `13,100-110`. Returns `None`
+/// if an element is not a number or a range is empty.
+fn parse_number_ranges(value: &str) -> Option<Vec<RangeInclusive<u64>>> {
+    value
+        .split(',')
+        .map(|element| {
+            let element = element.trim();
+            let (start, end) = element.split_once('-').unwrap_or((element, element));
+            let range = start.trim().parse().ok()?..=end.trim().parse().ok()?;
+            (!range.is_empty()).then_some(range)
+        })
+        .collect()
+}
+
+/// Parses a range like `100..200`, which excludes 200, or `100..=200`. Either end may be
+/// omitted. `None` if the value is invalid or the range is empty.
+fn parse_range(value: &str) -> Option<RangeInclusive<u64>> {
+    let (start, end) = value.trim().split_once("..")?;
+    let start = if start.is_empty() {
+        0
+    } else {
+        start.parse().ok()?
+    };
+    let end = match end.strip_prefix('=') {
+        Some(end) => end.parse().ok()?,
+        None if end.is_empty() => u64::MAX,
+        None => end.parse::<u64>().ok()?.checked_sub(1)?,
+    };
+    let range = start..=end;
+    (!range.is_empty()).then_some(range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        CliArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse() {
+        let args = parse(&[
+            "--transform",
+            "lowercase-ext",
+            "./foo",
+            "--transform=spaces-to-underscores",
+            "--events",
+            "jsonl",
+        ])
+        .unwrap();
+        assert_eq!(Some(PathBuf::from("./foo")), args.dir);
+        assert_eq!(
+            vec![
+                BuiltinTransform::LowercaseExtension,
+                BuiltinTransform::SpacesToUnderscores
+            ],
+            args.transforms
+        );
+        assert_eq!(Some(EventFormat::Jsonl), args.events);
+
+        assert_eq!(
+            Some(BuiltinPattern::SeasonEpisode),
+            parse(&["--pattern=season-episode"]).unwrap().pattern
+        );
+        assert!(parse(&["--pattern", "foobar"]).is_err());
+        let args = parse(&["--pattern", "auto"]).unwrap();
+        assert!(args.detect_pattern);
+        assert_eq!(None, args.pattern);
+        assert!(parse(&["--transform"]).is_err());
+        assert!(parse(&["--transform", "foobar"]).is_err());
+        assert!(parse(&["--foobar"]).is_err());
+        assert!(parse(&["--events", "xml"]).is_err());
+        assert_eq!(
+            Some(ScriptFormat::PowerShell),
+            parse(&["--script", "powershell"]).unwrap().script
+        );
+        assert!(parse(&["--script", "bat"]).is_err());
+        assert!(parse(&["./foo", "./bar"]).is_err());
+
+        let args = parse(&["-r", "--exclude-dir", ".git", "--exclude-dir=thumb*"]).unwrap();
+        assert!(args.recursive);
+        assert_eq!(vec![".git", "thumb*"], args.exclude_dirs);
+
+        let args = parse(&["--files-from", "selection.txt"]).unwrap();
+        assert_eq!(Some(PathBuf::from("selection.txt")), args.files_from);
+        assert!(parse(&["--files-from", "selection.txt", "./foo"]).is_err());
+        assert!(parse(&["--files-from", "selection.txt", "-r"]).is_err());
+        assert!(parse(&["--exclude-dir", ".git"]).is_err());
+
+        assert_eq!(SortOrder::Mtime, parse(&["--sort", "mtime"]).unwrap().sort);
+        assert!(parse(&["--sort", "foobar"]).is_err());
+
+        assert_eq!(100, parse(&["--reserve", "100"]).unwrap().reserve);
+        assert_eq!(
+            25000,
+            parse(&["--expect-count=25000"]).unwrap().expect_count
+        );
+        let args = parse(&["-q", "-y"]).unwrap();
+        assert!(args.quiet && args.yes);
+        assert!(parse(&["--timings"]).unwrap().timings);
+        assert_eq!(
+            vec!["*.m3u", "*.cue"],
+            parse(&["--update-refs", "*.m3u", "--update-refs=*.cue"])
+                .unwrap()
+                .update_refs
+        );
+        assert!(parse(&["--update-manifests"]).unwrap().update_manifests);
+        assert!(parse(&["--retarget-symlinks"]).unwrap().retarget_symlinks);
+        assert_eq!(cfg!(feature = "notify"), parse(&["--notify"]).is_ok());
+        assert_eq!(
+            cfg!(feature = "webhook"),
+            parse(&["--webhook", "http://nas:8123/api/webhook/nflz"]).is_ok()
+        );
+        assert_eq!(cfg!(feature = "picker"), parse(&["--pick"]).is_ok());
+        assert_eq!(
+            cfg!(feature = "xattr"),
+            parse(&["--record-original"]).is_ok()
+        );
+        assert!(
+            parse(&["--i-know-what-i-am-doing"])
+                .unwrap()
+                .allow_dangerous_paths
+        );
+        assert!(parse(&["--reserve", "-1"]).is_err());
+        assert!(parse(&["--expect-count", "many"]).is_err());
+        assert_eq!(
+            vec![13..=13, 100..=110, 7..=7],
+            parse(&["--ignore-numbers", "13, 100-110", "--ignore-numbers=7"])
+                .unwrap()
+                .ignore_numbers
+        );
+        assert!(parse(&["--ignore-numbers", "110-100"]).is_err());
+        assert!(parse(&["--ignore-numbers", "1,,2"]).is_err());
+        assert_eq!(
+            Some(100..=199),
+            parse(&["--range", "100..200"]).unwrap().range
+        );
+        assert_eq!(
+            Some(100..=200),
+            parse(&["--range=100..=200"]).unwrap().range
+        );
+        assert_eq!(Some(0..=9), parse(&["--range", "..10"]).unwrap().range);
+        assert_eq!(
+            Some(5..=u64::MAX),
+            parse(&["--range", "5.."]).unwrap().range
+        );
+        assert!(parse(&["--range", "200..100"]).is_err());
+        assert!(parse(&["--range", "100"]).is_err());
+        assert_eq!(4, parse(&["--width=4"]).unwrap().width);
+        assert_eq!(
+            Some(Some(UnicodeNormalization::Nfd)),
+            parse(&["--normalize", "nfd"]).unwrap().normalize
+        );
+        assert_eq!(Some(None), parse(&["--normalize=none"]).unwrap().normalize);
+        assert!(parse(&["--normalize", "nfkc"]).is_err());
+        assert!(parse(&["--sanitize"]).unwrap().sanitize);
+        assert_eq!(
+            vec![ContentCategory::Image, ContentCategory::Video],
+            parse(&["--only", "image", "--only=video"])
+                .unwrap()
+                .content_categories
+        );
+        assert!(parse(&["--only", "logs"]).is_err());
+        assert!(parse(&["--include-hidden"]).unwrap().include_hidden);
+        assert!(parse(&["--no-ignore-file"]).unwrap().no_ignore_file);
+        assert!(parse(&["--gitignore"]).unwrap().gitignore);
+        assert!(parse(&["--no-config"]).unwrap().no_config);
+        assert_eq!(
+            Some(PathBuf::from("run.json")),
+            parse(&["--options", "run.json"]).unwrap().options_file
+        );
+        assert!(parse(&["--print-options"]).unwrap().print_options);
+        assert_eq!(500, parse(&["--split", "500"]).unwrap().split);
+        assert!(parse(&["--split", "0"]).is_err());
+        assert!(parse(&["--per-extension"]).unwrap().per_extension);
+        assert!(parse(&["--group-sidecars"]).unwrap().group_sidecars);
+        let args = parse(&["--ignore-prefix-case", "--unify-prefixes"]).unwrap();
+        assert!(args.ignore_prefix_case && args.unify_prefixes);
+        assert!(parse(&["-n"]).unwrap().dry_run);
+        assert!(parse(&["--explain"]).unwrap().explain);
+        assert_eq!(
+            Some(OpenFilePolicy::Skip),
+            parse(&["--open-files", "skip"]).unwrap().open_files
+        );
+        assert!(parse(&["--open-files", "close"]).is_err());
+        assert!(parse(&["--dry-run", "--script", "sh"]).is_err());
+        assert!(
+            parse(&["--ignore-prefix-whitespace"])
+                .unwrap()
+                .ignore_prefix_whitespace
+        );
+        assert!(parse(&["--all-groups"]).unwrap().all_groups);
+        assert!(parse(&["--hex"]).unwrap().hex);
+        assert_eq!(
+            Some(RomanNumerals::Padded),
+            parse(&["--roman=padded"]).unwrap().roman
+        );
+        assert!(parse(&["--roman", "arabic"]).is_err());
+        assert!(parse(&["--hex", "--roman", "decimal"]).is_err());
+        assert_eq!(
+            Some(PathBuf::from("/archive")),
+            parse(&["--continue-from", "/archive"])
+                .unwrap()
+                .continue_from
+        );
+        assert_eq!(Some(100), parse(&["--start-at=100"]).unwrap().start_at);
+        assert!(parse(&["--start-at", "1", "--continue-from", "/archive"]).is_err());
+        assert_eq!(NonZeroU64::new(10), parse(&["--step", "10"]).unwrap().step);
+        assert!(parse(&["--step", "0"]).is_err());
+        assert_eq!(
+            RenumberOrder::Mtime,
+            parse(&["--renumber-by", "mtime"]).unwrap().renumber_by
+        );
+        assert!(parse(&["--renumber-by", "size"]).is_err());
+
+        let args = parse(&["daemon", "--socket", "/tmp/nflz.sock"]).unwrap();
+        assert_eq!(Some(Subcommand::Daemon), args.subcommand);
+        assert_eq!(Some(PathBuf::from("/tmp/nflz.sock")), args.socket);
+        let args = parse(&[
+            "daemon",
+            "--socket",
+            "/tmp/nflz.sock",
+            "--metrics",
+            "127.0.0.1:9184",
+        ])
+        .unwrap();
+        assert_eq!(Some("127.0.0.1:9184"), args.metrics.as_deref());
+        assert!(parse(&["--metrics", "127.0.0.1:9184"]).is_err());
+        let args = parse(&["daemon", "--socket", "a.sock", "--socket-mode", "660"]).unwrap();
+        assert_eq!(Some(0o660), args.socket_mode);
+        assert!(parse(&["daemon", "--socket", "a.sock", "--socket-mode", "1777"]).is_err());
+        assert!(parse(&["daemon", "--socket", "a.sock", "--socket-mode", "rw"]).is_err());
+        assert!(parse(&["--socket-mode", "600"]).is_err());
+        assert!(parse(&["daemon"]).is_err());
+
+        let args = parse(&["--checkpoint", "--chunk-size", "100"]).unwrap();
+        assert!(args.checkpoint);
+        assert_eq!(NonZeroUsize::new(100), args.chunk_size);
+        assert_eq!(
+            Some(Duration::from_secs(30)),
+            parse(&["--rename-timeout", "30"]).unwrap().rename_timeout
+        );
+        assert!(parse(&["--rename-timeout", "0"]).is_err());
+        assert_eq!(
+            NonZeroU32::new(20),
+            parse(&["--rate-limit=20"]).unwrap().rate_limit
+        );
+        assert!(parse(&["--rate-limit", "0"]).is_err());
+        assert!(parse(&["--fsync"]).unwrap().fsync);
+        assert_eq!(Some(10), parse(&["--limit", "10"]).unwrap().limit);
+        assert!(parse(&["--limit", "-1"]).is_err());
+        assert_eq!(
+            Some(PathStyle::Absolute),
+            parse(&["--absolute"]).unwrap().path_style
+        );
+        assert_eq!(
+            Some(PathStyle::Relative),
+            parse(&["--relative"]).unwrap().path_style
+        );
+        assert!(parse(&["--absolute", "--relative"]).is_err());
+        assert!(parse(&["--checkpoint", "--chunk-size", "0"]).is_err());
+        assert!(parse(&["--chunk-size", "100"]).is_err());
+        assert_eq!(None, parse(&["./daemon"]).unwrap().subcommand);
+
+        let args = parse(&["merge", "a", "b", "--into", "c"]).unwrap();
+        assert_eq!(Some(Subcommand::Merge), args.subcommand);
+        assert_eq!(vec![PathBuf::from("a"), PathBuf::from("b")], args.sources);
+        assert_eq!(Some(PathBuf::from("c")), args.into);
+        assert!(parse(&["merge", "a", "b"]).is_err());
+        assert!(parse(&["merge", "a", "--into", "c"]).is_err());
+
+        let args = parse(&["stats", "./photos"]).unwrap();
+        assert_eq!(Some(Subcommand::Stats), args.subcommand);
+        assert_eq!(Some(PathBuf::from("./photos")), args.dir);
+        assert_eq!(None, parse(&["./stats"]).unwrap().subcommand);
+
+        let args = parse(&["chapters", "./gopro"]).unwrap();
+        assert_eq!(Some(Subcommand::Chapters), args.subcommand);
+        assert_eq!(Some(PathBuf::from("./gopro")), args.dir);
+
+        let args = parse(&["resume", "./nas"]).unwrap();
+        assert_eq!(Some(Subcommand::Resume), args.subcommand);
+        assert_eq!(Some(PathBuf::from("./nas")), args.dir);
+
+        assert_eq!(
+            Some(Subcommand::ShellIntegration(
+                ShellIntegrationAction::Uninstall
+            )),
+            parse(&["shell-integration", "uninstall"])
+                .unwrap()
+                .subcommand
+        );
+        assert!(parse(&["shell-integration"]).is_err());
+        assert!(parse(&["shell-integration", "./photos"]).is_err());
+    }
+
+    #[test]
+    fn test_options() {
+        assert_eq!(Options::default(), parse(&[]).unwrap().options());
+        let options = parse(&["--width", "3", "--per-extension", "--renumber-by", "mtime"])
+            .unwrap()
+            .options();
+        assert_eq!(Some(3), options.width);
+        assert_eq!(Some(true), options.per_extension);
+        assert_eq!(Some(RenumberOrder::Mtime), options.renumber_by);
+        assert_eq!(None, options.hex);
+    }
+}
@@ -0,0 +1,669 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Long-running daemon mode that processes directories on request. See [`run`].
+//!
+//! The daemon listens on a Unix socket. Clients send one JSON object per line:
+//! `{"command": "plan", "dir": "/photos/paris"}` or `{"command": "apply", "dir": "..."}`.
+//! Like `nflz <dir>`, the daemon applies the `.nflz.toml` file of the directory and then the
+//! options of its command line. A request may override them with the field `options`, which
+//! contains [`nflz::Options`], e.g., `{"command": "plan", "dir": "...", "options": {"width": 3}}`.
+//! Only the options in [`ALLOWED_OVERRIDES`] may be overridden. Options that touch other files
+//! than the numbered ones, such as `update-refs` or `retarget-symlinks`, are up to the operator.
+//! The daemon answers every request with one JSON object per line:
+//! - `{"ok": true, "files_to_rename": [{"path": "...", "new_path": "..."}],
+//!   "files_without_rename": ["..."], "skipped_files": [{"path": "...", "reason": "..."}],
+//!   "warnings": [...]}` for `plan`
+//! - `{"ok": true, "renamed": [{"path": "...", "new_path": "..."}]}` for `apply`
+//! - `{"ok": false, "error": {...}}` if the request failed, with a serialized
+//!   [`nflz::NFLZError`], `{"kind": "invalid_request", "message": "..."}`, or
+//!   `{"kind": "dangerous_path", "message": "..."}` for system directories and the home
+//!   directory, unless the daemon was started with `--i-know-what-i-am-doing`.
+//!
+//! Every client is served on its own thread, so that a slow client doesn't block the others,
+//! but the requests are processed one after another so that no two operations run on the
+//! same directory at the same time. Clients that send nothing for [`IDLE_TIMEOUT`] are
+//! disconnected. At most [`MAX_CLIENTS`] clients are served at the same time; further clients
+//! receive `{"ok": false, "error": {"kind": "too_many_clients", ...}}` and are disconnected.
+//!
+//! Everybody who can connect to the socket can rename files in every directory that the
+//! daemon can write to. Hence, only the user of the daemon may connect by default
+//! (permissions `0600`, see [`DaemonConfig::socket_mode`]).
+//!
+//! Optionally, the daemon exposes its [`Metrics`] over HTTP for monitoring.
+
+use crate::cli::guard::{home_dir, is_dangerous_path};
+use crate::cli::metrics::Metrics;
+use nflz::{FileInfoWithRenameAdvice, NFLZAssistant, NFLZError, Options};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Time after which a client that sends nothing is disconnected.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Default permissions of the socket: only the user of the daemon may connect.
+pub const DEFAULT_SOCKET_MODE: u32 = 0o600;
+
+/// Maximum number of clients that are served at the same time.
+pub const MAX_CLIENTS: usize = 16;
+
+/// Options (the keys of [`nflz::Options`]) that a request may override. They only change how
+/// the numbered files are selected and named. All other options, e.g., the ones that rewrite
+/// playlists, manifests, or symlinks, or that read other files, are rejected.
+pub const ALLOWED_OVERRIDES: &[&str] = &[
+    "pattern",
+    "transform",
+    "width",
+    "reserve",
+    "expect-count",
+    "per-extension",
+    "group-sidecars",
+    "all-groups",
+    "hex",
+    "roman",
+    "only",
+    "ignore-numbers",
+    "range",
+    "limit",
+    "renumber-by",
+    "start-at",
+    "step",
+    "trim-trailing",
+    "sanitize",
+    "ignore-prefix-case",
+    "ignore-prefix-whitespace",
+    "unify-prefixes",
+    "normalize",
+];
+
+/// Settings of the daemon. See [`run`].
+#[derive(Debug)]
+pub struct DaemonConfig<'a> {
+    /// Path of the Unix socket.
+    pub socket: &'a Path,
+    /// Permissions of the socket, such as `0o660` to let the group of the daemon connect.
+    /// See [`DEFAULT_SOCKET_MODE`].
+    pub socket_mode: u32,
+    /// Options of the command line of the daemon, which are applied in every directory.
+    pub options: &'a Options,
+    /// Whether the `.nflz.toml` files of the directories are ignored (`--no-config`).
+    pub no_config: bool,
+    /// Whether requests for system directories and the home directory are processed
+    /// (`--i-know-what-i-am-doing`). See [`crate::cli::guard::is_dangerous_path`].
+    pub allow_dangerous_paths: bool,
+    /// Address of the HTTP endpoint of the [`Metrics`], if any.
+    pub metrics_addr: Option<&'a str>,
+}
+
+/// Command of a client.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Command {
+    /// Computes the new filenames without touching the file system.
+    Plan,
+    /// Computes the new filenames and renames the files.
+    Apply,
+}
+
+/// Request of a client.
+#[derive(Debug, Deserialize)]
+struct Request {
+    /// What to do.
+    command: Command,
+    /// Directory to work on.
+    dir: PathBuf,
+    /// Options that override the ones of the daemon. Only the keys in [`ALLOWED_OVERRIDES`]
+    /// are accepted, see [`parse_overrides`].
+    #[serde(default)]
+    options: serde_json::Map<String, Value>,
+}
+
+/// Listens on the Unix socket and processes all requests until the process is killed.
+/// A stale socket file from a previous run is replaced. If `metrics_addr` is given, the
+/// [`Metrics`] are served over HTTP at `http://<metrics_addr>/metrics`.
+#[cfg(unix)]
+pub fn run(config: &DaemonConfig<'_>) -> std::io::Result<()> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    let DaemonConfig {
+        socket,
+        metrics_addr,
+        ..
+    } = *config;
+    let metrics = Arc::new(Metrics::default());
+    if let Some(addr) = metrics_addr {
+        let listener = std::net::TcpListener::bind(addr)?;
+        log::info!(
+            "Serving metrics on 'http://{}/metrics'",
+            listener.local_addr()?
+        );
+        crate::cli::metrics::serve(listener, metrics.clone());
+    }
+
+    let listener = bind(socket, config.socket_mode)?;
+    log::info!(
+        "Listening on '{}' (permissions {:o})",
+        socket.display(),
+        config.socket_mode
+    );
+
+    let metrics = &*metrics;
+    let processing = &Mutex::new(());
+    let clients = &AtomicUsize::new(0);
+    std::thread::scope(|scope| {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) if clients.load(Ordering::SeqCst) >= MAX_CLIENTS => {
+                    log::warn!("Refusing a client: {} clients connected", MAX_CLIENTS);
+                    metrics.record_error();
+                    refuse_connection(stream);
+                }
+                Ok(stream) => {
+                    clients.fetch_add(1, Ordering::SeqCst);
+                    scope.spawn(move || {
+                        match serve_connection(stream, config, metrics, processing) {
+                            Ok(()) => {}
+                            Err(err) if is_timeout(&err) => {
+                                log::info!("Disconnected an idle client");
+                            }
+                            Err(err) => log::warn!("Connection failed: {}", err),
+                        }
+                        clients.fetch_sub(1, Ordering::SeqCst);
+                    });
+                }
+                Err(err) => log::warn!("Can't accept connection: {}", err),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Tells a client that exceeds [`MAX_CLIENTS`] why it is disconnected.
+#[cfg(unix)]
+fn refuse_connection(mut stream: std::os::unix::net::UnixStream) {
+    use std::io::Write;
+
+    let response = json!({
+        "ok": false,
+        "error": {
+            "kind": "too_many_clients",
+            "message": format!("The daemon already serves {} clients.", MAX_CLIENTS),
+        },
+    });
+    // the client must not block the accept loop
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(1)));
+    let _ = writeln!(stream, "{}", response);
+}
+
+/// Answers all requests of a client until it disconnects or is idle for [`IDLE_TIMEOUT`].
+/// `processing` is locked while a request is processed.
+#[cfg(unix)]
+fn serve_connection(
+    stream: std::os::unix::net::UnixStream,
+    config: &DaemonConfig<'_>,
+    metrics: &Metrics,
+    processing: &std::sync::Mutex<()>,
+) -> std::io::Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+
+    stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        let response = {
+            // a panic of another request doesn't corrupt the guarded state
+            let _processing = processing
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            handle_request(&line, config, metrics)
+        };
+        writeln!(writer, "{}", response)?;
+    }
+    Ok(())
+}
+
+/// Returns true if the error is the read timeout of a socket.
+#[cfg(unix)]
+fn is_timeout(err: &std::io::Error) -> bool {
+    matches!(
+        err.kind(),
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+    )
+}
+
+/// Binds the Unix socket with the given permissions. The socket is bound inside a new private
+/// directory next to it and only moved to its path once the permissions are set, so that
+/// nobody can connect in between. A stale socket file from a previous run is replaced, any
+/// other file and the socket of a running daemon are not.
+#[cfg(unix)]
+fn bind(socket: &Path, mode: u32) -> std::io::Result<std::os::unix::net::UnixListener> {
+    use std::io::{Error, ErrorKind};
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+    use std::os::unix::net::UnixListener;
+
+    let file_name = socket
+        .file_name()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "the socket must be a file"))?;
+    if std::fs::symlink_metadata(socket).is_ok_and(|meta| !meta.file_type().is_socket()) {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("'{}' exists and is not a socket", socket.display()),
+        ));
+    }
+    if socket.exists() {
+        // only a socket that nobody listens on is stale
+        match std::os::unix::net::UnixStream::connect(socket) {
+            Ok(_) => {
+                return Err(Error::new(
+                    ErrorKind::AddrInUse,
+                    format!("another daemon is listening on '{}'", socket.display()),
+                ))
+            }
+            Err(err) if err.kind() == ErrorKind::ConnectionRefused => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    let private_dir = socket.with_file_name(format!(".{}.nflz", file_name.to_string_lossy()));
+    if private_dir.exists() {
+        // left over by a daemon that was killed during the start
+        std::fs::remove_dir_all(&private_dir)?;
+    }
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)?;
+    let temporary_socket = private_dir.join(file_name);
+    let listener = UnixListener::bind(&temporary_socket).and_then(|listener| {
+        std::fs::set_permissions(&temporary_socket, std::fs::Permissions::from_mode(mode))?;
+        std::fs::rename(&temporary_socket, socket)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_dir_all(&private_dir);
+    listener
+}
+
+/// Named pipes are not supported (yet). Always fails.
+#[cfg(not(unix))]
+pub fn run(_config: &DaemonConfig<'_>) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "the daemon mode is only supported on Unix",
+    ))
+}
+
+/// Processes a single request (a JSON line) and returns the JSON response. Updates the
+/// metrics.
+fn handle_request(line: &str, config: &DaemonConfig<'_>, metrics: &Metrics) -> Value {
+    let request = match serde_json::from_str::<Request>(line) {
+        Ok(request) => request,
+        Err(err) => {
+            metrics.record_error();
+            return json!({
+                "ok": false,
+                "error": { "kind": "invalid_request", "message": err.to_string() },
+            });
+        }
+    };
+    let overrides = match parse_overrides(&request.options) {
+        Ok(overrides) => overrides,
+        Err(message) => {
+            metrics.record_error();
+            return json!({
+                "ok": false,
+                "error": { "kind": "invalid_request", "message": message },
+            });
+        }
+    };
+    log::info!("{:?} '{}'", request.command, request.dir.display());
+    metrics.record_directory();
+
+    if !config.allow_dangerous_paths {
+        let dir = request
+            .dir
+            .canonicalize()
+            .unwrap_or_else(|_| request.dir.clone());
+        if is_dangerous_path(&dir, home_dir().as_deref()) {
+            log::warn!("Refusing to work on '{}'", dir.display());
+            metrics.record_error();
+            return json!({
+                "ok": false,
+                "error": {
+                    "kind": "dangerous_path",
+                    "message": format!(
+                        "Refusing to work on '{}' because it is a system directory or the home directory.",
+                        dir.display()
+                    ),
+                },
+            });
+        }
+    }
+
+    let result = build_assistant(&request.dir, &overrides, config).and_then(|assistant| {
+        match request.command {
+            Command::Plan => {
+                assistant.check_can_rename_all()?;
+                Ok(json!({
+                    "ok": true,
+                    "files_to_rename": assistant
+                        .files_to_rename()
+                        .into_iter()
+                        .map(mapping_to_json)
+                        .collect::<Vec<_>>(),
+                    "files_without_rename": assistant
+                        .files_without_rename()
+                        .into_iter()
+                        .map(|file| file.file_info().path())
+                        .collect::<Vec<_>>(),
+                    "skipped_files": assistant.skipped_files(),
+                    "warnings": assistant.warnings(),
+                }))
+            }
+            Command::Apply => {
+                let renamed = assistant
+                    .rename_all()?
+                    .iter()
+                    .filter(|file| file.needs_rename())
+                    .map(mapping_to_json)
+                    .collect::<Vec<_>>();
+                metrics.record_renamed_files(renamed.len());
+                Ok(json!({ "ok": true, "renamed": renamed }))
+            }
+        }
+    });
+    result.unwrap_or_else(|err| {
+        metrics.record_error();
+        json!({ "ok": false, "error": err })
+    })
+}
+
+/// Parses the options of a request. Fails with a message if they contain an option that is not
+/// in [`ALLOWED_OVERRIDES`] or are invalid.
+fn parse_overrides(options: &serde_json::Map<String, Value>) -> Result<Options, String> {
+    if let Some(key) = options
+        .keys()
+        .find(|key| !ALLOWED_OVERRIDES.contains(&key.as_str()))
+    {
+        return Err(format!("The option '{}' can't be set by a request.", key));
+    }
+    serde_json::from_value(Value::Object(options.clone())).map_err(|err| err.to_string())
+}
+
+/// Builds the assistant for the directory with the same options as `nflz <dir>`, overridden by
+/// the options of the request.
+fn build_assistant(
+    dir: &Path,
+    overrides: &Options,
+    config: &DaemonConfig<'_>,
+) -> Result<NFLZAssistant, NFLZError> {
+    let mut builder = NFLZAssistant::builder(dir);
+    if !config.no_config {
+        builder = builder.apply_directory_config()?;
+    }
+    builder
+        .apply_options(config.options)
+        .apply_options(overrides)
+        .build()
+}
+
+/// Returns the old and the new path of a file as JSON.
+fn mapping_to_json(file: &FileInfoWithRenameAdvice) -> Value {
+    json!({
+        "path": file.file_info().path(),
+        "new_path": file.path_with_new_filename(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the settings of a daemon with the given options.
+    fn config(options: &Options) -> DaemonConfig<'_> {
+        DaemonConfig {
+            socket: Path::new("nflz.sock"),
+            socket_mode: DEFAULT_SOCKET_MODE,
+            options,
+            no_config: false,
+            allow_dangerous_paths: false,
+            metrics_addr: None,
+        }
+    }
+
+    #[test]
+    fn test_handle_request() {
+        let options = Options::default();
+        let config = config(&options);
+        let metrics = Metrics::default();
+        let response = handle_request(
+            r#"{"command": "plan", "dir": "./test-resources"}"#,
+            &config,
+            &metrics,
+        );
+        assert_eq!(true, response["ok"]);
+        assert_eq!(10, response["files_to_rename"].as_array().unwrap().len());
+        assert_eq!(
+            1,
+            response["files_without_rename"].as_array().unwrap().len()
+        );
+        assert_eq!(
+            "multiple_number_groups",
+            response["skipped_files"][0]["reason"]
+        );
+
+        let response = handle_request(
+            r#"{"command": "plan", "dir": "./foobar"}"#,
+            &config,
+            &metrics,
+        );
+        assert_eq!(false, response["ok"]);
+        assert_eq!("cant_read_directory", response["error"]["kind"]);
+
+        let response = handle_request("foobar", &config, &metrics);
+        assert_eq!("invalid_request", response["error"]["kind"]);
+
+        let text = metrics.render();
+        assert!(text.contains("\nnflz_directories_processed_total 2\n"));
+        assert!(text.contains("\nnflz_errors_total 2\n"));
+    }
+
+    #[test]
+    fn test_handle_request_with_options() {
+        let mut options = Options::default();
+        options.width = Some(3);
+        let config = config(&options);
+        let metrics = Metrics::default();
+        let response = handle_request(
+            r#"{"command": "plan", "dir": "./test-resources"}"#,
+            &config,
+            &metrics,
+        );
+        let new_path = response["files_to_rename"][0]["new_path"].as_str().unwrap();
+        assert!(new_path.ends_with("paris (001).jpg"), "{}", new_path);
+
+        // the request overrides the daemon
+        let response = handle_request(
+            r#"{"command": "plan", "dir": "./test-resources", "options": {"width": 4}}"#,
+            &config,
+            &metrics,
+        );
+        let new_path = response["files_to_rename"][0]["new_path"].as_str().unwrap();
+        assert!(new_path.ends_with("paris (0001).jpg"), "{}", new_path);
+
+        let response = handle_request(
+            r#"{"command": "plan", "dir": "./test-resources", "options": {"foo": 4}}"#,
+            &config,
+            &metrics,
+        );
+        assert_eq!("invalid_request", response["error"]["kind"]);
+
+        let response = handle_request(
+            r#"{"command": "plan", "dir": "./test-resources", "options": {"width": "foo"}}"#,
+            &config,
+            &metrics,
+        );
+        assert_eq!("invalid_request", response["error"]["kind"]);
+    }
+
+    #[test]
+    fn test_handle_request_rejects_other_overrides() {
+        let options = Options::default();
+        let config = config(&options);
+        let metrics = Metrics::default();
+        for options in [
+            r#"{"update-refs": ["*.m3u"]}"#,
+            r#"{"update-manifests": true}"#,
+            r#"{"retarget-symlinks": true}"#,
+            r#"{"continue-from": "/etc"}"#,
+            r#"{"width": 3, "include-hidden": true}"#,
+        ] {
+            let request = format!(
+                r#"{{"command": "apply", "dir": "./test-resources", "options": {}}}"#,
+                options
+            );
+            let response = handle_request(&request, &config, &metrics);
+            assert_eq!("invalid_request", response["error"]["kind"], "{}", options);
+            assert!(response["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("can't be set by a request"));
+        }
+        assert!(metrics
+            .render()
+            .contains("\nnflz_directories_processed_total 0\n"));
+    }
+
+    #[test]
+    fn test_handle_request_rejects_dangerous_paths() {
+        let options = Options::default();
+        let config = config(&options);
+        let metrics = Metrics::default();
+        let dir = if cfg!(windows) {
+            r"C:\\Windows"
+        } else {
+            "/usr"
+        };
+        let request = format!(r#"{{"command": "apply", "dir": "{}"}}"#, dir);
+        let response = handle_request(&request, &config, &metrics);
+        assert_eq!(false, response["ok"]);
+        assert_eq!("dangerous_path", response["error"]["kind"]);
+        assert!(metrics.render().contains("\nnflz_errors_total 1\n"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_concurrent_clients() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::os::unix::net::UnixStream;
+
+        let dir = std::env::temp_dir().join(format!("nflz-daemon-clients-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket = dir.join("nflz.sock");
+        let thread_socket = socket.clone();
+        // runs until the test process exits
+        std::thread::spawn(move || {
+            run(&DaemonConfig {
+                socket: &thread_socket,
+                socket_mode: DEFAULT_SOCKET_MODE,
+                options: &Options::default(),
+                no_config: false,
+                allow_dangerous_paths: false,
+                metrics_addr: None,
+            })
+        });
+        while !socket.exists() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        let plan = |client: &mut UnixStream| {
+            client
+                .set_read_timeout(Some(Duration::from_secs(10)))
+                .unwrap();
+            // the daemon may already have disconnected a refused client
+            let _ = writeln!(
+                client,
+                r#"{{"command": "plan", "dir": "./test-resources"}}"#
+            );
+            let mut response = String::new();
+            BufReader::new(&*client).read_line(&mut response).unwrap();
+            serde_json::from_str::<Value>(&response).unwrap()
+        };
+
+        let _idle = UnixStream::connect(&socket).unwrap();
+        let mut client = UnixStream::connect(&socket).unwrap();
+        let response = plan(&mut client);
+        assert_eq!(true, response["ok"], "must not wait for the idle client");
+
+        let _more_idle = (2..MAX_CLIENTS)
+            .map(|_| UnixStream::connect(&socket).unwrap())
+            .collect::<Vec<_>>();
+        let response = plan(&mut UnixStream::connect(&socket).unwrap());
+        assert_eq!("too_many_clients", response["error"]["kind"]);
+
+        // a slot becomes free when a client disconnects
+        drop(client);
+        std::thread::sleep(Duration::from_millis(100));
+        let response = plan(&mut UnixStream::connect(&socket).unwrap());
+        assert_eq!(true, response["ok"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bind() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("nflz-daemon-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket = dir.join("nflz.sock");
+        let listener = bind(&socket, DEFAULT_SOCKET_MODE).unwrap();
+        let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(0o600, mode & 0o777);
+        std::os::unix::net::UnixStream::connect(&socket).unwrap();
+
+        // the socket of a running daemon is not replaced
+        let err = bind(&socket, 0o660).unwrap_err();
+        assert_eq!(std::io::ErrorKind::AddrInUse, err.kind());
+        let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(0o600, mode & 0o777);
+        drop(listener);
+
+        // the stale socket is replaced, other files are not
+        bind(&socket, 0o660).unwrap();
+        let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(0o660, mode & 0o777);
+        std::fs::remove_file(&socket).unwrap();
+        std::fs::write(&socket, "data").unwrap();
+        assert!(bind(&socket, DEFAULT_SOCKET_MODE).is_err());
+        assert_eq!(
+            vec![socket],
+            std::fs::read_dir(&dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .collect::<Vec<_>>()
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,96 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Explicit list of files to process (`--files-from`). See [`read`].
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Reads the list of file paths, one per line or NUL-delimited, such as the output of
+/// `find -print0`. Relative paths are relative to the present working directory. Returns the
+/// filenames grouped by their directory, as each directory is processed on its own.
+pub fn read(list: &Path) -> io::Result<BTreeMap<PathBuf, Vec<OsString>>> {
+    parse(&std::fs::read_to_string(list)?)
+}
+
+/// Parses the content of a list. NUL-delimited if it contains a NUL byte, otherwise one path
+/// per line. Empty entries are skipped.
+fn parse(content: &str) -> io::Result<BTreeMap<PathBuf, Vec<OsString>>> {
+    let entries = if content.contains('\0') {
+        content.split('\0').collect::<Vec<_>>()
+    } else {
+        content.lines().collect()
+    };
+
+    let mut selections = BTreeMap::<PathBuf, Vec<OsString>>::new();
+    for entry in entries.into_iter().filter(|entry| !entry.is_empty()) {
+        let path = Path::new(entry);
+        let name = path.file_name().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("'{}' is not a path to a file", entry),
+            )
+        })?;
+        let dir = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        selections
+            .entry(dir.to_path_buf())
+            .or_default()
+            .push(name.to_os_string());
+    }
+    Ok(selections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let selections = parse("a (1).jpg\nphotos/b (2).jpg\n\nphotos/b (10).jpg\n").unwrap();
+        assert_eq!(2, selections.len());
+        assert_eq!(
+            vec![OsString::from("a (1).jpg")],
+            selections[Path::new(".")]
+        );
+        assert_eq!(
+            vec![OsString::from("b (2).jpg"), OsString::from("b (10).jpg")],
+            selections[Path::new("photos")]
+        );
+
+        let selections = parse("photos/line\nbreak (1).jpg\0photos/c (2).jpg\0").unwrap();
+        assert_eq!(
+            vec![
+                OsString::from("line\nbreak (1).jpg"),
+                OsString::from("c (2).jpg")
+            ],
+            selections[Path::new("photos")]
+        );
+
+        assert!(parse("photos/..\n").is_err());
+    }
+}
@@ -0,0 +1,123 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Guard against working on system directories by accident. See [`is_dangerous_path`].
+
+use std::path::{Path, PathBuf};
+
+/// Directories that contain files of the operating system or of all users.
+const DANGEROUS_PATHS: &[&str] = &[
+    "/",
+    "/bin",
+    "/boot",
+    "/dev",
+    "/etc",
+    "/home",
+    "/lib",
+    "/lib64",
+    "/opt",
+    "/proc",
+    "/root",
+    "/sbin",
+    "/sys",
+    "/usr",
+    "/usr/bin",
+    "/usr/lib",
+    "/usr/local",
+    "/var",
+    "/Applications",
+    "/Library",
+    "/System",
+    "/Users",
+    "C:\\",
+    "C:\\Program Files",
+    "C:\\Program Files (x86)",
+    "C:\\ProgramData",
+    "C:\\Users",
+    "C:\\Windows",
+];
+
+/// Returns true if the directory is a system directory or the home directory itself, where
+/// renaming files is most likely a mistake, such as a stray argument. The path should be
+/// canonicalized. Windows paths are compared case-insensitively.
+pub fn is_dangerous_path(path: &Path, home: Option<&Path>) -> bool {
+    // canonicalized Windows paths have the "\\?\" prefix
+    let path = path.to_string_lossy();
+    let path = path.strip_prefix(r"\\?\").unwrap_or(&path);
+    let path = if path.len() > 1 {
+        path.trim_end_matches(['/', '\\'])
+    } else {
+        path
+    };
+    let is_home = home.is_some_and(|home| {
+        let home = home.to_string_lossy();
+        home.trim_end_matches(['/', '\\']) == path
+    });
+    is_home
+        || DANGEROUS_PATHS.iter().any(|dangerous| {
+            if dangerous.contains('\\') {
+                dangerous.eq_ignore_ascii_case(path)
+                    || dangerous.eq_ignore_ascii_case(&format!("{}\\", path))
+            } else {
+                *dangerous == path
+            }
+        })
+}
+
+/// Returns the home directory of the current user, if known.
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_dangerous_path() {
+        let home = Some(Path::new("/home/user"));
+        for path in [
+            "/",
+            "/usr",
+            "/usr/",
+            "/home/user",
+            "/home/user/",
+            "C:\\",
+            "c:\\windows",
+        ] {
+            assert!(is_dangerous_path(Path::new(path), home), "{}", path);
+        }
+        assert!(is_dangerous_path(Path::new(r"\\?\C:\Windows"), None));
+        for path in [
+            "/home/user/photos",
+            "/usr/share/photos",
+            "D:\\Photos",
+            "./photos",
+        ] {
+            assert!(!is_dangerous_path(Path::new(path), home), "{}", path);
+        }
+    }
+}
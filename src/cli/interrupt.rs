@@ -0,0 +1,61 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Graceful handling of Ctrl+C during the renaming. See [`install_handler`].
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// Exit code if Ctrl+C is pressed a second time, like a shell for a process that was
+/// terminated by `SIGINT`.
+const EXIT_CODE_SIGINT: i32 = 130;
+
+/// Set by the first Ctrl+C. See [`flag`].
+static INTERRUPTED: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+
+/// Returns the flag that is set by the first Ctrl+C after [`install_handler`]. Passed to
+/// [`nflz::NFLZAssistantBuilder::interrupt_flag`].
+pub fn flag() -> Arc<AtomicBool> {
+    INTERRUPTED
+        .get_or_init(|| Arc::new(AtomicBool::new(false)))
+        .clone()
+}
+
+/// Replaces the default behaviour of Ctrl+C, which terminates the process immediately,
+/// possibly in the middle of a rename. The first Ctrl+C sets the [`flag`], so that the
+/// renaming stops after the current rename. A second Ctrl+C terminates the process anyway.
+///
+/// Must only be called right before the renaming, as Ctrl+C doesn't terminate the process
+/// anymore afterwards.
+pub fn install_handler() {
+    let flag = flag();
+    let res = ctrlc::set_handler(move || {
+        if flag.swap(true, Ordering::SeqCst) {
+            std::process::exit(EXIT_CODE_SIGINT);
+        }
+        eprintln!("Interrupted. Stopping after the current rename. Press Ctrl+C again to abort immediately.");
+    });
+    if let Err(err) = res {
+        log::warn!("Can't install the handler for Ctrl+C: {}", err);
+    }
+}
@@ -0,0 +1,168 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Counters of the daemon that are exposed for monitoring (`--metrics`). See [`Metrics`].
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Counters of a long-running daemon since its start.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Number of `plan` and `apply` requests, including the failed ones.
+    directories_processed: AtomicU64,
+    /// Number of files renamed by `apply` requests.
+    files_renamed: AtomicU64,
+    /// Number of failed or invalid requests.
+    errors: AtomicU64,
+}
+
+impl Metrics {
+    /// Counts a processed directory.
+    pub fn record_directory(&self) {
+        self.directories_processed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts renamed files.
+    pub fn record_renamed_files(&self, count: usize) {
+        self.files_renamed
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// Counts a failed or invalid request.
+    pub fn record_error(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the counters in the text-based exposition format of Prometheus.
+    pub fn render(&self) -> String {
+        [
+            (
+                "nflz_directories_processed_total",
+                "Directories processed by plan and apply requests.",
+                &self.directories_processed,
+            ),
+            (
+                "nflz_files_renamed_total",
+                "Files renamed by apply requests.",
+                &self.files_renamed,
+            ),
+            (
+                "nflz_errors_total",
+                "Failed or invalid requests.",
+                &self.errors,
+            ),
+        ]
+        .iter()
+        .map(|(name, help, counter)| {
+            format!(
+                "# HELP {name} {help}\n# TYPE {name} counter\n{name} {}\n",
+                counter.load(Ordering::Relaxed)
+            )
+        })
+        .collect()
+    }
+}
+
+/// Serves the metrics over HTTP at `/metrics` in a background thread until the process is
+/// killed. Every connection is answered and closed right away.
+pub fn serve(listener: TcpListener, metrics: Arc<Metrics>) {
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let res = stream.and_then(|stream| answer(stream, &metrics));
+            if let Err(err) = res {
+                log::warn!("Can't serve the metrics: {}", err);
+            }
+        }
+    });
+}
+
+/// Reads the request head and answers with the metrics or `404 Not Found`.
+fn answer(stream: TcpStream, metrics: &Metrics) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // skip the headers; a GET request has no body
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+    let (status, body) = if path == "/metrics" {
+        ("200 OK", metrics.render())
+    } else {
+        ("404 Not Found", String::new())
+    };
+    write!(
+        reader.get_mut(),
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::default();
+        metrics.record_directory();
+        metrics.record_directory();
+        metrics.record_renamed_files(12);
+        metrics.record_error();
+        let text = metrics.render();
+        assert!(text.contains("# TYPE nflz_files_renamed_total counter\n"));
+        assert!(text.contains("\nnflz_directories_processed_total 2\n"));
+        assert!(text.contains("\nnflz_files_renamed_total 12\n"));
+        assert!(text.contains("\nnflz_errors_total 1\n"));
+    }
+
+    #[test]
+    fn test_serve() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let metrics = Arc::new(Metrics::default());
+        metrics.record_renamed_files(3);
+        serve(listener, metrics);
+
+        let get = |path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let response = get("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("\nnflz_errors_total 0\n"));
+        assert!(response.contains("\nnflz_files_renamed_total 3\n"));
+        assert!(get("/").starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
@@ -0,0 +1,89 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Modules of the CLI binary that are not part of the library.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether human-readable output goes to stderr, because stdout is reserved for
+/// machine-readable output, such as `--events jsonl`.
+static HUMAN_OUTPUT_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Whether only the summary and errors are printed (`--quiet`).
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Like `println!` but for human-readable output that is always printed, such as errors and
+/// the final summary. Prints to stderr if stdout is reserved for machine-readable output. See
+/// [`set_human_output_to_stderr`].
+macro_rules! report {
+    ($($arg:tt)*) => {
+        if $crate::cli::human_output_to_stderr() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Like [`report`] but for detailed output, such as the tables with all files, that is
+/// suppressed by `--quiet`.
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if !$crate::cli::quiet() {
+            report!($($arg)*);
+        }
+    };
+}
+
+pub mod args;
+pub mod daemon;
+pub mod file_list;
+pub mod guard;
+pub mod interrupt;
+pub mod metrics;
+pub mod notification;
+pub mod path_style;
+pub mod picker;
+pub mod shell_integration;
+pub mod tree;
+pub mod webhook;
+
+/// Suppresses all output of [`say`].
+pub fn set_quiet() {
+    QUIET.store(true, Ordering::Relaxed);
+}
+
+/// See [`set_quiet`].
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Redirects all output of [`report`] and [`say`] to stderr.
+pub fn set_human_output_to_stderr() {
+    HUMAN_OUTPUT_TO_STDERR.store(true, Ordering::Relaxed);
+}
+
+/// See [`set_human_output_to_stderr`].
+pub fn human_output_to_stderr() -> bool {
+    HUMAN_OUTPUT_TO_STDERR.load(Ordering::Relaxed)
+}
@@ -0,0 +1,47 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Desktop notification when a run completes (`--notify`). See [`send`].
+
+/// Sends a desktop notification with the summary of a completed run. As the run itself is
+/// already done at this point, a failure is only logged.
+#[cfg(feature = "notify")]
+pub fn send(failed: bool, body: &str) {
+    let summary = if failed {
+        "NFLZ failed"
+    } else {
+        "NFLZ finished"
+    };
+    let res = notify_rust::Notification::new()
+        .appname("nflz")
+        .summary(summary)
+        .body(body)
+        .show();
+    if let Err(err) = res {
+        log::warn!("Can't send the desktop notification: {}", err);
+    }
+}
+
+/// Does nothing. `--notify` is rejected by the parser without the `notify` feature.
+#[cfg(not(feature = "notify"))]
+pub const fn send(_failed: bool, _body: &str) {}
@@ -0,0 +1,171 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! How paths are printed (`--absolute`, `--relative`). See [`PathStyle`].
+
+use nflz::RenamePlan;
+use std::fmt::Write;
+use std::path::{Component, Path, PathBuf};
+
+/// How paths are printed in tables, JSON, and scripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathStyle {
+    /// Absolute paths (`--absolute`).
+    Absolute,
+    /// Paths relative to the current working directory (`--relative`).
+    Relative,
+}
+
+impl PathStyle {
+    /// Returns the path in this style. The path is normalized lexically, i.e., symlinks are
+    /// not resolved and the path doesn't have to exist.
+    pub fn apply(self, path: &Path) -> PathBuf {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        let absolute = normalize(&cwd.join(path));
+        match self {
+            Self::Absolute => absolute,
+            Self::Relative => relative_to(&absolute, &normalize(&cwd)),
+        }
+    }
+}
+
+/// Renders the plan like its [`std::fmt::Display`] implementation, but with the full paths
+/// instead of the filenames.
+pub fn table(plan: &RenamePlan) -> String {
+    let rows = plan
+        .renames()
+        .iter()
+        .map(|rename| {
+            (
+                rename.from().display().to_string(),
+                rename.to().display().to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let width = rows
+        .iter()
+        .map(|(from, _)| from.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut table = String::new();
+    for (index, (from, to)) in rows.iter().enumerate() {
+        if index > 0 {
+            table.push('\n');
+        }
+        let _ = write!(table, "{:width$} => {}", from, to, width = width);
+    }
+    table
+}
+
+/// Removes `.` and resolves `..` without accessing the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Returns the absolute `path` relative to the absolute directory `base`, with `..` if it
+/// isn't inside of `base`. Returns `path` unchanged if they don't share a root, e.g., on
+/// different drives on Windows.
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    let common = path
+        .components()
+        .zip(base.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common == 0 {
+        return path.to_path_buf();
+    }
+    let relative = base
+        .components()
+        .skip(common)
+        .map(|_| Component::ParentDir)
+        .chain(path.components().skip(common))
+        .collect::<PathBuf>();
+    if relative.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        relative
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nflz::PlannedRename;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(Path::new("/a/c"), normalize(Path::new("/a/./b/../c")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_relative_to() {
+        let base = Path::new("/home/user");
+        assert_eq!(Path::new("."), relative_to(Path::new("/home/user"), base));
+        assert_eq!(
+            Path::new("photos/paris"),
+            relative_to(Path::new("/home/user/photos/paris"), base)
+        );
+        assert_eq!(
+            Path::new("../other/photos"),
+            relative_to(Path::new("/home/other/photos"), base)
+        );
+        assert_eq!(Path::new("../.."), relative_to(Path::new("/"), base));
+    }
+
+    #[test]
+    fn test_apply() {
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(
+            cwd.join("photos"),
+            PathStyle::Absolute.apply(Path::new("photos"))
+        );
+        assert_eq!(Path::new("."), PathStyle::Relative.apply(&cwd));
+        assert_eq!(
+            Path::new("photos"),
+            PathStyle::Relative.apply(&cwd.join("photos"))
+        );
+    }
+
+    #[test]
+    fn test_table() {
+        let plan = RenamePlan::new(vec![
+            PlannedRename::new("dir/a (1).jpg", "dir/a (01).jpg"),
+            PlannedRename::new("dir/a (10) long.jpg", "dir/a (10).jpg"),
+        ]);
+        assert_eq!(
+            "dir/a (1).jpg       => dir/a (01).jpg\ndir/a (10) long.jpg => dir/a (10).jpg",
+            table(&plan)
+        );
+    }
+}
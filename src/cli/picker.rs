@@ -0,0 +1,59 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Interactive selection of the files that are renamed (`--pick`). See [`pick`].
+
+/// Lists the candidates in an interactive picker with fuzzy search, in which the user
+/// selects and deselects the files that are renamed. All candidates are selected at the
+/// start. Returns the selected candidates, or `None` if the user aborted with Esc or Ctrl+C.
+#[cfg(feature = "picker")]
+// `std::io::Error::other` is not available on the MSRV
+#[allow(clippy::io_other_error)]
+pub fn pick(
+    dir: &std::path::Path,
+    candidates: Vec<String>,
+) -> std::io::Result<Option<Vec<String>>> {
+    use inquire::InquireError;
+
+    let message = format!("Files to rename in '{}':", dir.display());
+    let res = inquire::MultiSelect::new(&message, candidates)
+        .with_all_selected_by_default()
+        .with_help_message("type to search, space to toggle, enter to confirm, esc to abort")
+        .with_page_size(20)
+        .prompt_skippable();
+    match res {
+        Ok(selection) => Ok(selection),
+        Err(InquireError::OperationInterrupted) => Ok(None),
+        Err(InquireError::IO(err)) => Err(err),
+        Err(err) => Err(std::io::Error::new(std::io::ErrorKind::Other, err)),
+    }
+}
+
+/// Selects all candidates. `--pick` is rejected by the parser without the `picker` feature.
+#[cfg(not(feature = "picker"))]
+pub const fn pick(
+    _dir: &std::path::Path,
+    candidates: Vec<String>,
+) -> std::io::Result<Option<Vec<String>>> {
+    Ok(Some(candidates))
+}
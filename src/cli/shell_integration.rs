@@ -0,0 +1,174 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Context-menu entry "Pad numbers with nflz" for directories in the Windows Explorer. See
+//! [`install`] and [`uninstall`].
+//!
+//! The entry is registered for the current user only, in `HKCU\Software\Classes`, both for
+//! the right-click on a directory and on the background of an opened directory. It opens a
+//! console window that runs the CLI in interactive mode on the directory, i.e., the plan is
+//! shown and must be confirmed. The registry keys are written with `reg.exe`, so that no
+//! administrator rights and no additional dependency are needed.
+
+/// Registry keys of the context-menu entries, together with the placeholder that the Explorer
+/// replaces with the selected directory.
+const KEYS: [(&str, &str); 2] = [
+    (r"HKCU\Software\Classes\Directory\shell\nflz", "%1"),
+    (
+        r"HKCU\Software\Classes\Directory\Background\shell\nflz",
+        "%V",
+    ),
+];
+
+/// Label of the context-menu entry.
+const LABEL: &str = "Pad numbers with nflz";
+
+/// Registers the context-menu entries that invoke the currently running executable.
+pub fn install() -> std::io::Result<()> {
+    check_supported()?;
+    let exe = std::env::current_exe()?;
+    for args in install_commands(&exe.display().to_string()) {
+        reg(&args)?;
+    }
+    Ok(())
+}
+
+/// Removes the context-menu entries again.
+pub fn uninstall() -> std::io::Result<()> {
+    check_supported()?;
+    for args in uninstall_commands() {
+        reg(&args)?;
+    }
+    Ok(())
+}
+
+/// The Windows registry only exists on Windows. Fails on all other platforms.
+fn check_supported() -> std::io::Result<()> {
+    if cfg!(windows) {
+        Ok(())
+    } else {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "the shell integration is only supported on Windows",
+        ))
+    }
+}
+
+/// Runs `reg.exe` with the given arguments and fails if it exits unsuccessfully.
+// `std::io::Error::other` is not available on the MSRV
+#[allow(clippy::io_other_error)]
+fn reg(args: &[String]) -> std::io::Result<()> {
+    let output = std::process::Command::new("reg.exe").args(args).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!(
+                "'reg.exe {}' failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        ))
+    }
+}
+
+/// Returns the arguments of all `reg.exe` invocations that register the context-menu entries
+/// for the executable at the given path.
+///
+/// The console window stays open after the CLI exits, so that the user can read the result.
+fn install_commands(exe: &str) -> Vec<Vec<String>> {
+    KEYS.iter()
+        .flat_map(|(key, placeholder)| {
+            // `cmd /c` strips the outer quotes of the whole command line
+            let command = format!("cmd.exe /c \"\"{}\" \"{}\" & pause\"", exe, placeholder);
+            [
+                reg_add(key, None, LABEL),
+                reg_add(key, Some("Icon"), exe),
+                reg_add(&format!(r"{}\command", key), None, &command),
+            ]
+        })
+        .collect()
+}
+
+/// Returns the arguments of all `reg.exe` invocations that remove the context-menu entries.
+fn uninstall_commands() -> Vec<Vec<String>> {
+    KEYS.iter()
+        .map(|(key, _)| vec!["delete".to_string(), key.to_string(), "/f".to_string()])
+        .collect()
+}
+
+/// Returns the arguments of `reg.exe` to set a value of the key. `None` is the default value.
+fn reg_add(key: &str, name: Option<&str>, data: &str) -> Vec<String> {
+    let name = name.map_or_else(
+        || vec!["/ve".to_string()],
+        |name| vec!["/v".to_string(), name.to_string()],
+    );
+    ["add", key]
+        .into_iter()
+        .map(String::from)
+        .chain(name)
+        .chain(["/d", data, "/f"].into_iter().map(String::from))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_commands() {
+        let commands = install_commands(r"C:\Tools\nflz.exe");
+        assert_eq!(6, commands.len());
+        assert_eq!(
+            vec![
+                "add",
+                r"HKCU\Software\Classes\Directory\shell\nflz",
+                "/ve",
+                "/d",
+                "Pad numbers with nflz",
+                "/f"
+            ],
+            commands[0]
+        );
+        assert_eq!(
+            vec![
+                "add",
+                r"HKCU\Software\Classes\Directory\Background\shell\nflz\command",
+                "/ve",
+                "/d",
+                r#"cmd.exe /c ""C:\Tools\nflz.exe" "%V" & pause""#,
+                "/f"
+            ],
+            commands[5]
+        );
+        assert_eq!(
+            vec![
+                "delete",
+                r"HKCU\Software\Classes\Directory\shell\nflz",
+                "/f"
+            ],
+            uninstall_commands()[0]
+        );
+    }
+}
@@ -0,0 +1,103 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Tree-style rendering of the plan in recursive mode.
+
+use std::path::{Path, PathBuf};
+
+/// Renders the lines of each directory as an indented tree below `root`. The lines of a
+/// directory are indented one level deeper than the directory itself. Intermediate
+/// directories without own lines are rendered as well, to keep the structure visible.
+pub fn render(root: &Path, mut dirs: Vec<(PathBuf, Vec<String>)>) -> Vec<String> {
+    const INDENT: usize = 2;
+
+    dirs.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let mut lines = vec![format!("{}", root.display())];
+    let mut previous = Vec::new();
+    for (dir, dir_lines) in dirs {
+        let components = dir
+            .strip_prefix(root)
+            .unwrap_or(&dir)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        let common = previous
+            .iter()
+            .zip(&components)
+            .take_while(|(a, b)| a == b)
+            .count();
+        for (depth, component) in components.iter().enumerate().skip(common) {
+            lines.push(format!(
+                "{:indent$}{}/",
+                "",
+                component,
+                indent = (depth + 1) * INDENT
+            ));
+        }
+        let indent = (components.len() + 1) * INDENT;
+        lines.extend(
+            dir_lines
+                .into_iter()
+                .map(|line| format!("{:indent$}{}", "", line, indent = indent)),
+        );
+        previous = components;
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let root = Path::new("photos");
+        let dirs = vec![
+            (
+                root.join("2022/paris"),
+                vec!["paris (1).jpg => paris (01).jpg".to_string()],
+            ),
+            (
+                root.to_path_buf(),
+                vec!["a (1).jpg => a (01).jpg".to_string()],
+            ),
+            (
+                root.join("2022/london"),
+                vec!["london (1).jpg => london (01).jpg".to_string()],
+            ),
+        ];
+        assert_eq!(
+            vec![
+                "photos",
+                "  a (1).jpg => a (01).jpg",
+                "  2022/",
+                "    london/",
+                "      london (1).jpg => london (01).jpg",
+                "    paris/",
+                "      paris (1).jpg => paris (01).jpg",
+            ],
+            render(root, dirs)
+        );
+    }
+}
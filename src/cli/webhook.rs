@@ -0,0 +1,158 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Posts the summary of a completed run to a webhook (`--webhook`). See [`post`].
+//!
+//! The body is one JSON object, such as
+//! `{"ok": true, "command": "pad", "renamed_files": 12, "unchanged_files": 3, "error": null}`,
+//! with a serialized [`nflz::NFLZError`] as `error` if the run failed. Chat services, such as
+//! Slack or Discord, and home-automation systems can consume it with a small adapter or
+//! template.
+
+use nflz::NFLZError;
+use serde_json::{json, Value};
+
+/// Summary of a completed run of the CLI.
+#[derive(Debug)]
+// only read by the webhook
+#[cfg_attr(not(feature = "webhook"), allow(dead_code))]
+pub struct RunSummary<'a> {
+    /// What the run did: `pad`, `merge`, or `chapters`.
+    pub command: &'static str,
+    /// Number of renamed or moved files, also if the run failed later.
+    pub renamed_files: usize,
+    /// Number of files that didn't need to be renamed.
+    pub unchanged_files: usize,
+    /// The error that aborted the run, if any.
+    pub error: Option<&'a NFLZError>,
+}
+
+#[cfg_attr(not(feature = "webhook"), allow(dead_code))]
+impl RunSummary<'_> {
+    /// Returns the JSON object that is posted to the webhook.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "ok": self.error.is_none(),
+            "command": self.command,
+            "renamed_files": self.renamed_files,
+            "unchanged_files": self.unchanged_files,
+            "error": self.error,
+        })
+    }
+}
+
+/// Posts the summary to the URL. As the run itself is already done at this point, a failure is
+/// only logged.
+#[cfg(feature = "webhook")]
+pub fn post(url: &str, summary: &RunSummary) {
+    let res = ureq::post(url)
+        .timeout(std::time::Duration::from_secs(10))
+        .set("Content-Type", "application/json")
+        .send_string(&summary.to_json().to_string());
+    if let Err(err) = res {
+        log::warn!("Can't post the summary to the webhook: {}", err);
+    }
+}
+
+/// Does nothing. `--webhook` is rejected by the parser without the `webhook` feature.
+#[cfg(not(feature = "webhook"))]
+pub const fn post(_url: &str, _summary: &RunSummary) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_to_json() {
+        let summary = RunSummary {
+            command: "pad",
+            renamed_files: 12,
+            unchanged_files: 3,
+            error: None,
+        };
+        assert_eq!(
+            json!({
+                "ok": true,
+                "command": "pad",
+                "renamed_files": 12,
+                "unchanged_files": 3,
+                "error": null,
+            }),
+            summary.to_json()
+        );
+
+        let err = NFLZError::ConflictingFiles(vec![PathBuf::from("paris (1).jpg")]);
+        let summary = RunSummary {
+            command: "merge",
+            renamed_files: 0,
+            unchanged_files: 0,
+            error: Some(&err),
+        };
+        let json = summary.to_json();
+        assert_eq!(Some(false), json["ok"].as_bool());
+        assert!(json["error"].is_object());
+    }
+
+    #[cfg(feature = "webhook")]
+    #[test]
+    fn test_post() {
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end().to_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(value) = line.strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            serde_json::from_slice::<Value>(&body).unwrap()
+        });
+
+        let summary = RunSummary {
+            command: "chapters",
+            renamed_files: 4,
+            unchanged_files: 0,
+            error: None,
+        };
+        post(&url, &summary);
+        assert_eq!(summary.to_json(), server.join().unwrap());
+    }
+}
@@ -0,0 +1,358 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for the configuration file that pins the options of a directory. See
+//! [`DirectoryConfig`].
+
+use crate::builder::NFLZAssistantBuilder;
+use crate::error::NFLZError;
+use crate::options::Options;
+use crate::roman::RomanNumerals;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Name of the configuration file inside the working directory.
+pub const CONFIG_FILENAME: &str = ".nflz.toml";
+
+/// A value of the configuration file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    /// A quoted string, such as `"track"`.
+    String(String),
+    /// A non-negative integer, such as `4` or `25_000`.
+    Integer(u64),
+    /// `true` or `false`.
+    Boolean(bool),
+    /// An array of strings, such as `["image", "video"]`.
+    Array(Vec<String>),
+}
+
+impl Value {
+    /// Returns the string or an error message.
+    fn into_string(self) -> Result<String, String> {
+        match self {
+            Self::String(s) => Ok(s),
+            _ => Err("expected a string".to_string()),
+        }
+    }
+
+    /// Returns the integer or an error message.
+    fn into_integer(self) -> Result<u64, String> {
+        match self {
+            Self::Integer(n) => Ok(n),
+            _ => Err("expected a number".to_string()),
+        }
+    }
+
+    /// Returns the boolean or an error message.
+    fn into_boolean(self) -> Result<bool, String> {
+        match self {
+            Self::Boolean(b) => Ok(b),
+            _ => Err("expected true or false".to_string()),
+        }
+    }
+
+    /// Returns the strings of the array or an error message.
+    fn into_array(self) -> Result<Vec<String>, String> {
+        match self {
+            Self::Array(strings) => Ok(strings),
+            _ => Err("expected an array of strings".to_string()),
+        }
+    }
+}
+
+/// Options that are pinned for a directory by its `.nflz.toml` file, so that repeated runs by
+/// different people behave identically. Options that are not in the file keep the defaults.
+///
+/// The file consists of `key = value` lines in TOML syntax. Tables are not supported. The
+/// keys are named like the options of the CLI:
+///
+/// ```toml
+/// # chapters of the audio book
+/// pattern = "track"
+/// width = 3
+/// per-extension = true
+/// transform = ["lowercase-ext"]
+/// ```
+///
+/// Supported keys: `pattern` (requires the `regex` feature), `width`, `reserve`,
+/// `expect-count`, `per-extension`, `group-sidecars`, `all-groups`, `hex`, `roman`
+/// (`"decimal"` or `"padded"`), `include-hidden`, `transform`, and `only`. See
+/// [`NFLZAssistantBuilder::apply_directory_config`] and [`Options`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirectoryConfig {
+    /// The options of the file. Options that are not in the file are `None` or empty.
+    options: Options,
+}
+
+impl DirectoryConfig {
+    /// Reads the `.nflz.toml` file of the directory. `None` if there is none. Fails with
+    /// [`NFLZError::InvalidConfig`] if the file can't be read or is invalid.
+    pub fn read<P: AsRef<Path>>(dir: P) -> Result<Option<Self>, NFLZError> {
+        let path = dir.as_ref().join(CONFIG_FILENAME);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content)
+                .map(Some)
+                .map_err(|message| NFLZError::InvalidConfig(path, message)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(NFLZError::InvalidConfig(path, err.to_string())),
+        }
+    }
+
+    /// Parses the content of a configuration file. Returns a human-readable error message
+    /// with the line number otherwise.
+    pub(crate) fn parse(content: &str) -> Result<Self, String> {
+        let mut config = Self::default();
+        for (index, line) in content.lines().enumerate() {
+            let with_line = |message: String| format!("line {}: {}", index + 1, message);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') {
+                return Err(with_line("tables are not supported".to_string()));
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| with_line(format!("expected 'key = value', found '{}'", line)))?;
+            let key = key.trim();
+            let (value, rest) = parse_value(value.trim_start()).map_err(with_line)?;
+            let rest = rest.trim_start();
+            if !rest.is_empty() && !rest.starts_with('#') {
+                return Err(with_line(format!("unexpected '{}'", rest)));
+            }
+            config
+                .set(key, value)
+                .map_err(|message| with_line(format!("'{}': {}", key, message)))?;
+        }
+        if config.options.hex == Some(true) && config.options.roman.is_some() {
+            return Err("'hex' and 'roman' can't be combined".to_string());
+        }
+        Ok(config)
+    }
+
+    /// Returns the options of the file.
+    pub const fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Sets the option with the given key.
+    fn set(&mut self, key: &str, value: Value) -> Result<(), String> {
+        let options = &mut self.options;
+        match key {
+            #[cfg(feature = "regex")]
+            "pattern" => {
+                let pattern = value.into_string()?;
+                options.pattern = Some(pattern.parse().map_err(|e: NFLZError| e.to_string())?);
+            }
+            #[cfg(not(feature = "regex"))]
+            "pattern" => return Err("requires the 'regex' feature".to_string()),
+            "width" => options.width = Some(value.into_integer()?),
+            "reserve" => options.reserve = Some(value.into_integer()?),
+            "expect-count" => options.expect_count = Some(value.into_integer()?),
+            "per-extension" => options.per_extension = Some(value.into_boolean()?),
+            "group-sidecars" => options.group_sidecars = Some(value.into_boolean()?),
+            "all-groups" => options.all_groups = Some(value.into_boolean()?),
+            "hex" => options.hex = Some(value.into_boolean()?),
+            "roman" => {
+                options.roman = match value.into_string()?.as_str() {
+                    "decimal" => Some(RomanNumerals::ToDecimal),
+                    "padded" => Some(RomanNumerals::Padded),
+                    style => return Err(format!("unknown roman numeral style '{}'", style)),
+                };
+            }
+            "include-hidden" => options.include_hidden = Some(value.into_boolean()?),
+            "transform" => {
+                options.transform = value
+                    .into_array()?
+                    .iter()
+                    .map(|name| name.parse().map_err(|e: NFLZError| e.to_string()))
+                    .collect::<Result<_, _>>()?;
+            }
+            "only" => {
+                options.only = value
+                    .into_array()?
+                    .iter()
+                    .map(|name| name.parse().map_err(|e: NFLZError| e.to_string()))
+                    .collect::<Result<_, _>>()?;
+            }
+            _ => return Err("unknown option".to_string()),
+        }
+        Ok(())
+    }
+
+    /// Applies all options of the file on the builder. Options that are not in the file are
+    /// not changed. The transforms are added to the ones of the builder.
+    pub fn apply(&self, builder: NFLZAssistantBuilder) -> NFLZAssistantBuilder {
+        self.options.apply(builder)
+    }
+}
+
+/// Parses the value at the beginning of `s`. Returns the value and the rest of the line.
+fn parse_value(s: &str) -> Result<(Value, &str), String> {
+    if let Some(s) = s.strip_prefix('"') {
+        let (string, rest) = parse_string(s)?;
+        Ok((Value::String(string), rest))
+    } else if let Some(mut s) = s.strip_prefix('[') {
+        let mut strings = Vec::new();
+        loop {
+            s = s.trim_start();
+            if let Some(rest) = s.strip_prefix(']') {
+                return Ok((Value::Array(strings), rest));
+            }
+            let string = s
+                .strip_prefix('"')
+                .ok_or_else(|| "expected a string inside the array".to_string())?;
+            let (string, rest) = parse_string(string)?;
+            strings.push(string);
+            s = rest.trim_start();
+            if let Some(rest) = s.strip_prefix(',') {
+                s = rest;
+            } else if !s.starts_with(']') {
+                return Err("expected ',' or ']' inside the array".to_string());
+            }
+        }
+    } else if let Some(rest) = s.strip_prefix("true") {
+        Ok((Value::Boolean(true), rest))
+    } else if let Some(rest) = s.strip_prefix("false") {
+        Ok((Value::Boolean(false), rest))
+    } else {
+        let end = s
+            .find(|c: char| !c.is_ascii_digit() && c != '_')
+            .unwrap_or(s.len());
+        let digits = s[..end].replace('_', "");
+        let number = digits
+            .parse()
+            .map_err(|_| format!("unsupported value '{}'", s))?;
+        Ok((Value::Integer(number), &s[end..]))
+    }
+}
+
+/// Parses the rest of a string behind the opening quote. Supports the escapes `\"`, `\\`,
+/// `\n`, and `\t`. Returns the string and the rest of the line behind the closing quote.
+fn parse_string(s: &str) -> Result<(String, &str), String> {
+    let mut string = String::new();
+    let mut chars = s.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Ok((string, &s[index + 1..])),
+            '\\' => match chars.next() {
+                Some((_, '"')) => string.push('"'),
+                Some((_, '\\')) => string.push('\\'),
+                Some((_, 'n')) => string.push('\n'),
+                Some((_, 't')) => string.push('\t'),
+                _ => return Err("unsupported escape sequence".to_string()),
+            },
+            c => string.push(c),
+        }
+    }
+    Err("unterminated string".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::ContentCategory;
+    use crate::testutil::FixtureBuilder;
+    use crate::transform::BuiltinTransform;
+    use crate::NFLZAssistant;
+
+    #[test]
+    fn test_parse() {
+        let config = DirectoryConfig::parse(
+            "# pinned options\n\
+             width = 3 # at least three digits\n\
+             expect-count = 25_000\n\
+             per-extension = true\n\
+             roman = \"padded\"\n\
+             transform = [\"lowercase-ext\", \"spaces-to-underscores\"]\n\
+             only = [ \"image\" ]\n",
+        )
+        .unwrap();
+        let options = config.options();
+        assert_eq!(Some(3), options.width);
+        assert_eq!(Some(25000), options.expect_count);
+        assert_eq!(Some(true), options.per_extension);
+        assert_eq!(Some(RomanNumerals::Padded), options.roman);
+        assert_eq!(
+            vec![
+                BuiltinTransform::LowercaseExtension,
+                BuiltinTransform::SpacesToUnderscores
+            ],
+            options.transform
+        );
+        assert_eq!(vec![ContentCategory::Image], options.only);
+        assert_eq!(
+            DirectoryConfig::default(),
+            DirectoryConfig::parse("").unwrap()
+        );
+
+        for (content, message) in [
+            ("width = \"3\"", "line 1: 'width': expected a number"),
+            ("\nfoo = 1", "line 2: 'foo': unknown option"),
+            ("[nflz]", "line 1: tables are not supported"),
+            ("width = 3 4", "line 1: unexpected '4'"),
+            ("roman = \"decimal", "line 1: unterminated string"),
+            (
+                "hex = true\nroman = \"decimal\"",
+                "'hex' and 'roman' can't be combined",
+            ),
+        ] {
+            assert_eq!(Err(message.to_string()), DirectoryConfig::parse(content));
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let fixture = FixtureBuilder::new("a ").count(3).create().unwrap();
+        assert_eq!(None, DirectoryConfig::read(fixture.path()).unwrap());
+        std::fs::write(fixture.path().join(CONFIG_FILENAME), "width = 3\n").unwrap();
+        let config = DirectoryConfig::read(fixture.path()).unwrap().unwrap();
+
+        let assistant = config
+            .apply(NFLZAssistant::builder(fixture.path()))
+            .build()
+            .unwrap();
+        assert_eq!(
+            Some("a (001).jpg"),
+            assistant.files_to_rename()[0].new_filename()
+        );
+
+        // explicit options of the builder override the file
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .apply_directory_config()
+            .unwrap()
+            .fixed_width(2)
+            .build()
+            .unwrap();
+        assert_eq!(
+            Some("a (01).jpg"),
+            assistant.files_to_rename()[0].new_filename()
+        );
+
+        std::fs::write(fixture.path().join(CONFIG_FILENAME), "width = -1\n").unwrap();
+        let err = DirectoryConfig::read(fixture.path()).unwrap_err();
+        assert!(matches!(err, NFLZError::InvalidConfig(_, _)));
+    }
+}
@@ -0,0 +1,103 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for asking the user before the files are renamed. See [`ConfirmationProvider`].
+
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use std::io;
+use std::io::{BufRead, IsTerminal};
+
+/// Asks the user whether the files should be renamed, e.g., after the plan was shown.
+///
+/// Implementations are [`StdinConfirmation`] for interactive terminals, [`AlwaysYes`] for
+/// scripts, and [`FnConfirmation`] for everything else, such as a dialog of a GUI.
+pub trait ConfirmationProvider: Debug {
+    /// Asks the human-readable question and returns true if the user agreed. Fails if the
+    /// user can't be asked at all.
+    fn confirm(&self, question: &str) -> io::Result<bool>;
+}
+
+/// Asks on stderr and reads the answer from stdin.
+///
+/// Only `y` (case-insensitive) is a confirmation. Fails immediately if stdin is not a
+/// terminal, instead of waiting for input that never comes, for example in a cron job.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdinConfirmation;
+
+impl ConfirmationProvider for StdinConfirmation {
+    fn confirm(&self, question: &str) -> io::Result<bool> {
+        let stdin = io::stdin();
+        if !stdin.is_terminal() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "stdin is not a terminal",
+            ));
+        }
+        eprintln!("{}", question);
+        let mut input = String::new();
+        stdin.lock().read_line(&mut input)?;
+        // trim to remove \r\n | \n
+        Ok(input.trim().eq_ignore_ascii_case("y"))
+    }
+}
+
+/// Confirms every question without asking anyone.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlwaysYes;
+
+impl ConfirmationProvider for AlwaysYes {
+    fn confirm(&self, _question: &str) -> io::Result<bool> {
+        Ok(true)
+    }
+}
+
+/// Wraps a closure as [`ConfirmationProvider`]. The closure receives the question and
+/// returns true if the user agreed.
+pub struct FnConfirmation<F: Fn(&str) -> bool>(pub F);
+
+impl<F: Fn(&str) -> bool> Debug for FnConfirmation<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FnConfirmation").field(&"<closure>").finish()
+    }
+}
+
+impl<F: Fn(&str) -> bool> ConfirmationProvider for FnConfirmation<F> {
+    fn confirm(&self, question: &str) -> io::Result<bool> {
+        Ok((self.0)(question))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confirmation_providers() {
+        assert!(AlwaysYes.confirm("Rename?").unwrap());
+        let provider = FnConfirmation(|question: &str| question.ends_with('?'));
+        assert!(provider.confirm("Rename?").unwrap());
+        assert!(!provider.confirm("Rename").unwrap());
+    }
+}
@@ -0,0 +1,226 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for the detection of the content type of files. See [`ContentCategory`].
+
+use crate::error::NFLZError;
+use crate::fsutil::long_path;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Amount of bytes at the beginning of a file that are used to detect its content type.
+const MAGIC_BYTES_LEN: usize = 16;
+
+/// Coarse category of the content of a file.
+///
+/// See [`crate::NFLZAssistantBuilder::content_categories`] to only rename files of certain
+/// categories, so that stray logs or scripts in a photo dump are never renamed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ContentCategory {
+    /// Photos and graphics, such as JPEG, PNG, or HEIC. Name: `image`.
+    Image,
+    /// Videos, such as MP4, MOV, or MKV. Name: `video`.
+    Video,
+    /// Music and recordings, such as MP3, FLAC, or WAV. Name: `audio`.
+    Audio,
+    /// Documents, such as PDF, office documents, or plain text. Name: `document`.
+    Document,
+}
+
+impl ContentCategory {
+    /// All categories.
+    pub const ALL: [Self; 4] = [Self::Image, Self::Video, Self::Audio, Self::Document];
+
+    /// Returns the name under that the category can be selected.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Image => "image",
+            Self::Video => "video",
+            Self::Audio => "audio",
+            Self::Document => "document",
+        }
+    }
+
+    /// Detects the category of the file.
+    ///
+    /// The magic bytes at the beginning of the file take precedence. If they are unknown or
+    /// ambiguous, such as for ZIP-based office documents, or if the file can't be read, the
+    /// file extension decides. Returns `None` for everything else, e.g., logs or scripts.
+    pub fn detect(path: &Path) -> Option<Self> {
+        let mut magic_bytes = Vec::with_capacity(MAGIC_BYTES_LEN);
+        let magic = File::open(long_path(path))
+            .and_then(|file| {
+                file.take(MAGIC_BYTES_LEN as u64)
+                    .read_to_end(&mut magic_bytes)
+            })
+            .ok()
+            .and_then(|_| Self::from_magic_bytes(&magic_bytes));
+        magic.or_else(|| {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .and_then(Self::from_extension)
+        })
+    }
+
+    /// Returns the category for the first bytes of a file, if they are known.
+    pub fn from_magic_bytes(bytes: &[u8]) -> Option<Self> {
+        // ISO base media file format: the brand decides between image, audio, and video
+        if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+            return match &bytes[8..12] {
+                b"heic" | b"heix" | b"mif1" | b"msf1" | b"avif" => Some(Self::Image),
+                b"M4A " | b"M4B " => Some(Self::Audio),
+                _ => Some(Self::Video),
+            };
+        }
+        if bytes.len() >= 12 && bytes.starts_with(b"RIFF") {
+            return match &bytes[8..12] {
+                b"WEBP" => Some(Self::Image),
+                b"AVI " => Some(Self::Video),
+                b"WAVE" => Some(Self::Audio),
+                _ => None,
+            };
+        }
+
+        const SIGNATURES: [(&[u8], ContentCategory); 14] = [
+            (b"\xFF\xD8\xFF", ContentCategory::Image),
+            (b"\x89PNG", ContentCategory::Image),
+            (b"GIF8", ContentCategory::Image),
+            (b"BM", ContentCategory::Image),
+            (b"II*\x00", ContentCategory::Image),
+            (b"MM\x00*", ContentCategory::Image),
+            (b"\x1A\x45\xDF\xA3", ContentCategory::Video),
+            (b"ID3", ContentCategory::Audio),
+            (b"\xFF\xFB", ContentCategory::Audio),
+            (b"\xFF\xF3", ContentCategory::Audio),
+            (b"fLaC", ContentCategory::Audio),
+            (b"OggS", ContentCategory::Audio),
+            (b"%PDF", ContentCategory::Document),
+            (b"\xD0\xCF\x11\xE0", ContentCategory::Document),
+        ];
+        SIGNATURES
+            .iter()
+            .find(|(signature, _)| bytes.starts_with(signature))
+            .map(|(_, category)| *category)
+    }
+
+    /// Returns the category for a file extension, such as `jpg`, if it is known. The
+    /// comparison is case-insensitive.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let extension = extension.to_ascii_lowercase();
+        match extension.as_str() {
+            "jpg" | "jpeg" | "png" | "gif" | "bmp" | "tif" | "tiff" | "webp" | "heic" | "heif"
+            | "avif" | "raw" | "cr2" | "cr3" | "nef" | "arw" | "dng" | "orf" | "rw2" | "svg" => {
+                Some(Self::Image)
+            }
+            "mp4" | "m4v" | "mov" | "avi" | "mkv" | "webm" | "mts" | "m2ts" | "3gp" | "wmv"
+            | "mpg" | "mpeg" => Some(Self::Video),
+            "mp3" | "m4a" | "m4b" | "flac" | "wav" | "ogg" | "opus" | "aac" | "wma" | "aiff" => {
+                Some(Self::Audio)
+            }
+            "pdf" | "doc" | "docx" | "odt" | "rtf" | "xls" | "xlsx" | "ods" | "ppt" | "pptx"
+            | "odp" | "epub" | "txt" | "md" => Some(Self::Document),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ContentCategory {
+    type Err = NFLZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.name() == s)
+            .ok_or_else(|| NFLZError::UnknownContentCategory(s.to_string()))
+    }
+}
+
+impl Display for ContentCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::FixtureBuilder;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            Some(ContentCategory::Image),
+            ContentCategory::from_magic_bytes(b"\xFF\xD8\xFF\xE0\x00\x10JFIF")
+        );
+        assert_eq!(
+            Some(ContentCategory::Video),
+            ContentCategory::from_magic_bytes(b"\x00\x00\x00\x18ftypmp42")
+        );
+        assert_eq!(
+            Some(ContentCategory::Image),
+            ContentCategory::from_magic_bytes(b"\x00\x00\x00\x18ftypheic")
+        );
+        assert_eq!(None, ContentCategory::from_magic_bytes(b"#!/bin/sh"));
+        assert_eq!(
+            Some(ContentCategory::Audio),
+            ContentCategory::from_extension("MP3")
+        );
+        assert_eq!(None, ContentCategory::from_extension("log"));
+
+        let fixture = FixtureBuilder::new("content_type")
+            .count(0)
+            .extra_file("photo (1).jpg")
+            .extra_file("script (1).sh")
+            .create()
+            .unwrap();
+        // the fixture files are empty, hence the extension decides
+        assert_eq!(
+            Some(ContentCategory::Image),
+            ContentCategory::detect(&fixture.path().join("photo (1).jpg"))
+        );
+        assert_eq!(
+            None,
+            ContentCategory::detect(&fixture.path().join("script (1).sh"))
+        );
+        std::fs::write(fixture.path().join("script (1).sh"), b"%PDF-1.7").unwrap();
+        assert_eq!(
+            Some(ContentCategory::Document),
+            ContentCategory::detect(&fixture.path().join("script (1).sh"))
+        );
+    }
+
+    #[test]
+    fn test_content_category_by_name() {
+        for category in ContentCategory::ALL {
+            assert_eq!(category, category.name().parse().unwrap());
+        }
+        assert!("foobar".parse::<ContentCategory>().is_err());
+    }
+}
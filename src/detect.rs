@@ -0,0 +1,251 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the automatic selection of the pattern of a directory. See [`detect_pattern`].
+//! Only available with the `regex` feature.
+
+use crate::error::NFLZError;
+use crate::file_info::FileInfo;
+use crate::pattern::BuiltinPattern;
+use crate::probe::visible_files;
+use crate::scheme::{NumberingScheme, Parentheses};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+/// How well a [`BuiltinPattern`] fits the files of a directory. See [`detect_pattern`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PatternCandidate {
+    /// The pattern.
+    pattern: BuiltinPattern,
+    /// Amount of files whose filename the pattern can parse.
+    parsed_files: usize,
+    /// Amount of files of the directory.
+    total_files: usize,
+    /// Share of the numbers that directly follow another number. See
+    /// [`Self::consecutiveness`].
+    consecutiveness: f64,
+}
+
+impl PatternCandidate {
+    /// Returns the pattern.
+    pub const fn pattern(&self) -> BuiltinPattern {
+        self.pattern
+    }
+
+    /// Returns the amount of files whose filename the pattern can parse.
+    pub const fn parsed_files(&self) -> usize {
+        self.parsed_files
+    }
+
+    /// Returns the share of the distinct numbers, between `0.0` and `1.0`, that directly
+    /// follow another number, such as `2` after `1`. With two number groups, such as season
+    /// and episode, the second one is used, as it is the one that counts the files.
+    pub const fn consecutiveness(&self) -> f64 {
+        self.consecutiveness
+    }
+
+    /// Returns the confidence between `0.0` and `1.0` that the pattern is the right one for
+    /// the directory: the mean of the share of parsed files and the [`Self::consecutiveness`].
+    pub fn score(&self) -> f64 {
+        let coverage = if self.total_files == 0 {
+            0.0
+        } else {
+            self.parsed_files as f64 / self.total_files as f64
+        };
+        (coverage + self.consecutiveness) / 2.0
+    }
+}
+
+impl Display for PatternCandidate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (parses {} of {} files, {:.0}% consecutive, score {:.2})",
+            self.pattern,
+            self.parsed_files,
+            self.total_files,
+            self.consecutiveness * 100.0,
+            self.score()
+        )
+    }
+}
+
+/// Result of [`detect_pattern`]: all built-in patterns, ordered from the best to the worst.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternDetection {
+    /// All candidates, ordered by their score, the best first.
+    candidates: Vec<PatternCandidate>,
+}
+
+impl PatternDetection {
+    /// Returns the best pattern. `None` if no pattern can parse any file.
+    pub fn best(&self) -> Option<&PatternCandidate> {
+        self.candidates
+            .first()
+            .filter(|candidate| candidate.parsed_files > 0)
+    }
+
+    /// Returns all candidates, ordered by their score, the best first.
+    pub fn candidates(&self) -> &[PatternCandidate] {
+        &self.candidates
+    }
+}
+
+/// Finds the [`BuiltinPattern`] that fits the files of the directory best, so that the user
+/// doesn't have to select it.
+///
+/// Every built-in pattern is scored by how many files it parses and how consecutive the
+/// resulting numbers are (see [`PatternCandidate::score`]). On a tie, the more specific
+/// pattern wins, i.e., [`BuiltinPattern::Trailing`] loses against all others. Like
+/// [`crate::needs_work`], only the filenames are read, and hidden files and the entries of the
+/// `.nflzignore` file are skipped.
+///
+/// ```rust
+/// use nflz::{detect_pattern, BuiltinPattern};
+///
+/// let detection = detect_pattern("./test-resources").unwrap();
+/// let best = detection.best().unwrap();
+/// assert_eq!(BuiltinPattern::Parentheses, best.pattern());
+/// println!("Detected {}", best);
+/// ```
+pub fn detect_pattern<P: AsRef<Path>>(dir: P) -> Result<PatternDetection, NFLZError> {
+    let files = visible_files(dir.as_ref())?;
+    let mut candidates = BuiltinPattern::ALL
+        .into_iter()
+        .map(|pattern| {
+            let scheme = pattern
+                .scheme()
+                .unwrap_or_else(|| Box::new(Parentheses::default()));
+            score(pattern, scheme.as_ref(), &files)
+        })
+        .collect::<Vec<_>>();
+    // stable, hence, the order of `BuiltinPattern::ALL` breaks the remaining ties
+    candidates.sort_by(|a, b| {
+        b.score().total_cmp(&a.score()).then_with(|| {
+            (a.pattern == BuiltinPattern::Trailing).cmp(&(b.pattern == BuiltinPattern::Trailing))
+        })
+    });
+    Ok(PatternDetection { candidates })
+}
+
+/// Parses all files with the pattern and computes its [`PatternCandidate`].
+fn score<P: AsRef<Path>>(
+    pattern: BuiltinPattern,
+    scheme: &dyn NumberingScheme,
+    files: &[P],
+) -> PatternCandidate {
+    let parsed = files
+        .iter()
+        .filter_map(|path| FileInfo::new_with_scheme(path, scheme).ok())
+        .collect::<Vec<_>>();
+    let mut numbers = parsed
+        .iter()
+        .map(|file| {
+            file.second_number_group_value()
+                .unwrap_or_else(|| file.number_group_value())
+        })
+        .collect::<Vec<_>>();
+    numbers.sort_unstable();
+    numbers.dedup();
+    let consecutive = numbers
+        .windows(2)
+        .filter(|pair| pair[1] - pair[0] == 1)
+        .count();
+    let consecutiveness = if numbers.len() < 2 {
+        0.0
+    } else {
+        consecutive as f64 / (numbers.len() - 1) as f64
+    };
+    PatternCandidate {
+        pattern,
+        parsed_files: parsed.len(),
+        total_files: files.len(),
+        consecutiveness,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::FixtureBuilder;
+
+    #[test]
+    fn test_detect_pattern() {
+        let fixtures = [
+            (
+                FixtureBuilder::new("paris ").count(12),
+                BuiltinPattern::Parentheses,
+            ),
+            (
+                FixtureBuilder::new("")
+                    .count(0)
+                    .extra_file("Show S1E1.mkv")
+                    .extra_file("Show S1E2.mkv")
+                    .extra_file("Show S1E10.mkv"),
+                BuiltinPattern::SeasonEpisode,
+            ),
+            (
+                FixtureBuilder::new("")
+                    .count(0)
+                    .extra_file("1 - Intro.mp3")
+                    .extra_file("2 - Song.mp3")
+                    .extra_file("cover.jpg"),
+                BuiltinPattern::TrackNumber,
+            ),
+            (
+                FixtureBuilder::new("")
+                    .count(0)
+                    .extra_file("frame1.png")
+                    .extra_file("frame2.png")
+                    .extra_file("frame3.png"),
+                BuiltinPattern::Trailing,
+            ),
+            (
+                FixtureBuilder::new("")
+                    .count(0)
+                    .extra_file("DSC_0001.JPG")
+                    .extra_file("DSC_0002.JPG"),
+                BuiltinPattern::Dcf,
+            ),
+        ];
+        for (fixture, expected) in fixtures {
+            let fixture = fixture.create().unwrap();
+            let detection = detect_pattern(fixture.path()).unwrap();
+            assert_eq!(
+                expected,
+                detection.best().unwrap().pattern(),
+                "{:#?}",
+                detection
+            );
+            assert_eq!(BuiltinPattern::ALL.len(), detection.candidates().len());
+        }
+
+        let fixture = FixtureBuilder::new("")
+            .count(0)
+            .extra_file("readme.txt")
+            .create()
+            .unwrap();
+        assert!(detect_pattern(fixture.path()).unwrap().best().is_none());
+    }
+}
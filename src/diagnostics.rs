@@ -0,0 +1,263 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for detailed information about how a filename was parsed. See [`ParseDiagnostics`].
+
+use crate::file_info::FileInfo;
+use crate::math::Radix;
+use crate::scheme::Parentheses;
+use crate::skip::SkipReason;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::str::FromStr;
+
+/// Why a sequence of digits inside the filename isn't the number group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CandidateRejection {
+    /// The digits are not directly enclosed in parentheses, such as `2022` in
+    /// `paris 2022 (1).jpg`.
+    NotEnclosedInParentheses,
+    /// There is more than one sequence of digits in parentheses, hence, it's unclear which one
+    /// is the number group.
+    OneOfMultipleGroups,
+    /// The number has more digits than fit into a [`u64`].
+    NumberTooLarge,
+}
+
+impl Display for CandidateRejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NotEnclosedInParentheses => "not enclosed in parentheses",
+            Self::OneOfMultipleGroups => "one of multiple number groups",
+            Self::NumberTooLarge => "number too large",
+        })
+    }
+}
+
+/// A sequence of digits inside the filename that could be the number group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NumberGroupCandidate {
+    /// Byte indices of the digits inside the filename. The end is exclusive.
+    indices: Range<usize>,
+    /// The digits.
+    digits: String,
+    /// Why the candidate isn't the number group. `None` if it is the number group.
+    rejection: Option<CandidateRejection>,
+}
+
+impl NumberGroupCandidate {
+    /// Returns the byte indices of the digits inside the filename. The end is exclusive.
+    pub fn indices(&self) -> Range<usize> {
+        self.indices.clone()
+    }
+
+    /// Returns the digits, such as `"007"`.
+    pub fn digits(&self) -> &str {
+        &self.digits
+    }
+
+    /// Returns why the candidate isn't the number group. `None` if it is the number group.
+    pub const fn rejection(&self) -> Option<CandidateRejection> {
+        self.rejection
+    }
+
+    /// Returns true if the candidate is the number group of the filename.
+    pub const fn is_accepted(&self) -> bool {
+        self.rejection.is_none()
+    }
+}
+
+/// Detailed information about how a filename was parsed with the default `(<digits>)`
+/// pattern. See [`FileInfo::try_parse_with_diagnostics`].
+///
+/// The [`Display`] implementation explains the result in a human-readable way.
+#[derive(Debug, Clone)]
+pub struct ParseDiagnostics {
+    /// The parsed filename.
+    filename: String,
+    /// All sequences of digits in the filename, in the order of their occurrence.
+    candidates: Vec<NumberGroupCandidate>,
+    /// The result of the parsing.
+    result: Result<FileInfo, SkipReason>,
+}
+
+impl ParseDiagnostics {
+    /// Parses the filename and records every candidate for the number group.
+    pub(crate) fn parse(filename: &str) -> Self {
+        let bytes = filename.as_bytes();
+        let mut candidates = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            if !bytes[i].is_ascii_digit() {
+                i += 1;
+                continue;
+            }
+            let begin = i;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            let in_parentheses =
+                begin > 0 && bytes[begin - 1] == b'(' && bytes.get(i) == Some(&b')');
+            candidates.push(NumberGroupCandidate {
+                indices: begin..i,
+                digits: filename[begin..i].to_string(),
+                rejection: (!in_parentheses)
+                    .then_some(CandidateRejection::NotEnclosedInParentheses),
+            });
+        }
+
+        let mut groups = candidates
+            .iter_mut()
+            .filter(|candidate| candidate.rejection.is_none())
+            .collect::<Vec<_>>();
+        if groups.len() > 1 {
+            for group in groups.iter_mut() {
+                group.rejection = Some(CandidateRejection::OneOfMultipleGroups);
+            }
+        } else if let Some(group) = groups.first_mut() {
+            if u64::from_str(&group.digits).is_err() {
+                group.rejection = Some(CandidateRejection::NumberTooLarge);
+            }
+        }
+
+        let result = FileInfo::new(filename).map_err(|err| {
+            SkipReason::from_parse_error(&err, &Parentheses::default(), Radix::Decimal)
+                .expect("only parsing errors can occur")
+        });
+        Self {
+            filename: filename.to_string(),
+            candidates,
+            result,
+        }
+    }
+
+    /// Returns the parsed filename.
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Returns all sequences of digits in the filename, in the order of their occurrence.
+    pub fn candidates(&self) -> &[NumberGroupCandidate] {
+        &self.candidates
+    }
+
+    /// Returns the [`FileInfo`] if the filename has a valid number group.
+    pub const fn file_info(&self) -> Option<&FileInfo> {
+        match &self.result {
+            Ok(file_info) => Some(file_info),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns why the file would be skipped. `None` if the filename has a valid number group.
+    pub const fn skip_reason(&self) -> Option<SkipReason> {
+        match self.result {
+            Ok(_) => None,
+            Err(reason) => Some(reason),
+        }
+    }
+}
+
+impl Display for ParseDiagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.result {
+            Ok(file_info) => writeln!(
+                f,
+                "'{}': number group is {}",
+                self.filename,
+                file_info.number_group_value()
+            )?,
+            Err(reason) => writeln!(f, "'{}': skipped ({})", self.filename, reason)?,
+        }
+        for candidate in &self.candidates {
+            write!(
+                f,
+                "  '{}' at {}..{}: ",
+                candidate.digits, candidate.indices.start, candidate.indices.end
+            )?;
+            match candidate.rejection {
+                None => writeln!(f, "number group")?,
+                Some(rejection) => writeln!(f, "rejected, {}", rejection)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejections(filename: &str) -> Vec<Option<CandidateRejection>> {
+        FileInfo::try_parse_with_diagnostics(filename)
+            .candidates()
+            .iter()
+            .map(NumberGroupCandidate::rejection)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let diagnostics = FileInfo::try_parse_with_diagnostics("paris 2022 (07).jpg");
+        assert_eq!(7, diagnostics.file_info().unwrap().number_group_value());
+        assert_eq!(None, diagnostics.skip_reason());
+        assert_eq!(12..14, diagnostics.candidates()[1].indices());
+        assert_eq!("07", diagnostics.candidates()[1].digits());
+        assert_eq!(
+            vec![Some(CandidateRejection::NotEnclosedInParentheses), None],
+            rejections("paris 2022 (07).jpg")
+        );
+        assert_eq!(
+            "'paris 2022 (07).jpg': number group is 7\n  \
+             '2022' at 6..10: rejected, not enclosed in parentheses\n  \
+             '07' at 12..14: number group\n",
+            diagnostics.to_string()
+        );
+
+        let diagnostics = FileInfo::try_parse_with_diagnostics("a (1) (2).jpg");
+        assert_eq!(
+            Some(SkipReason::MultipleNumberGroups),
+            diagnostics.skip_reason()
+        );
+        assert_eq!(
+            vec![Some(CandidateRejection::OneOfMultipleGroups); 2],
+            rejections("a (1) (2).jpg")
+        );
+
+        let diagnostics = FileInfo::try_parse_with_diagnostics("a (99999999999999999999).jpg");
+        assert_eq!(Some(SkipReason::NumberTooLarge), diagnostics.skip_reason());
+        assert_eq!(
+            vec![Some(CandidateRejection::NumberTooLarge)],
+            rejections("a (99999999999999999999).jpg")
+        );
+
+        let diagnostics = FileInfo::try_parse_with_diagnostics("notes.txt");
+        assert_eq!(Some(SkipReason::NoNumberGroup), diagnostics.skip_reason());
+        assert!(diagnostics.candidates().is_empty());
+    }
+}
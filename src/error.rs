@@ -23,90 +23,639 @@ SOFTWARE.
 */
 //! Module for errors inside NFLZ library. See [`NFLZError`].
 
+use crate::file_info::FileInfo;
+use crate::fsutil::DirectoryChange;
+use crate::plan::PlanViolation;
+use crate::suggestion::Suggestion;
 use std::collections::HashSet;
 use std::error::Error;
 use std::fmt;
 use std::fmt::{Display, Formatter};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Main error of the library.
 #[derive(Debug)]
 pub enum NFLZError {
-    /// File names must include at least one numbered group.
+    /// File names must include at least one numbered group. Contains the path of the file.
     /// Example: "Img (1).jpg" is valid but "Img (2) (4).jpg" is not.
-    FilenameMustIncludeExactlyOneNumberedGroup(String),
-    /// The value inside the group must be a valid number.
-    ValueInNumberedGroupNotANumber(String),
+    FilenameMustIncludeExactlyOneNumberedGroup(PathBuf),
+    /// The value inside the group must be a valid number. Contains the path of the file and
+    /// the value.
+    ValueInNumberedGroupNotANumber(PathBuf, String),
     /// Can't read the specified directory,
     CantReadDirectory(PathBuf, std::io::Error),
+    /// Can't create the specified directory, e.g., the destination of a
+    /// [`crate::MergePlan`].
+    CantCreateDirectory(PathBuf, std::io::Error),
+    /// The references to the renamed files can't be updated in the specified file. See
+    /// [`crate::NFLZAssistantBuilder::update_references_in`] and
+    /// [`crate::NFLZAssistantBuilder::update_checksum_manifests`]. The files are already
+    /// renamed.
+    CantUpdateReferences(PathBuf, std::io::Error),
+    /// An ignore file, such as the `.nflzignore` file of the working directory or a
+    /// `.gitignore` file, exists but can't be read. See
+    /// [`crate::NFLZAssistantBuilder::respect_ignore_file`].
+    CantReadIgnoreFile(PathBuf, std::io::Error),
+    /// A file of the explicit selection doesn't exist, can't be accessed, or isn't directly
+    /// inside the working directory. See [`crate::NFLZAssistantBuilder::select_files`].
+    CantReadSelectedFile(PathBuf, std::io::Error),
+    /// The checkpoint file can't be written or deleted. See
+    /// [`crate::NFLZAssistantBuilder::checkpoint`].
+    CantWriteCheckpoint(PathBuf, std::io::Error),
+    /// The checkpoint file doesn't exist or is invalid. See [`crate::Checkpoint::read`].
+    CantReadCheckpoint(PathBuf, std::io::Error),
+    /// The directory can't be synced after the renaming. See
+    /// [`crate::NFLZAssistantBuilder::sync_directories`].
+    CantSyncDirectory(PathBuf, std::io::Error),
+    /// The specified symlink points at a renamed file but can't be retargeted. See
+    /// [`crate::NFLZAssistantBuilder::retarget_symlinks`]. The files are already renamed.
+    CantRetargetSymlink(PathBuf, std::io::Error),
     /// There are files that would have the same filename in the end.
     /// Would overwrite files.
     ConflictingFiles(Vec<PathBuf>),
-    /// The renaming failed.
-    RenameFailed(String, String, std::io::Error),
+    /// The renaming failed. Contains the old and the new path of the file.
+    RenameFailed(PathBuf, PathBuf, std::io::Error),
+    /// The renaming was stopped by [`crate::NFLZAssistantBuilder::interrupt_flag`]. Contains
+    /// the number of renamed files and the number of files to rename.
+    Interrupted(usize, usize),
+    /// The renaming failed while a file had a temporary name to break a cycle, such as
+    /// `a => b` and `b => a`, and the file can't be renamed back. Contains the error of the
+    /// renaming and the temporary and the original path of every file that still has its
+    /// temporary name. As hidden files are skipped, these files are not part of later runs
+    /// until they are renamed back.
+    TemporaryFilesLeft(Box<Self>, Vec<(PathBuf, PathBuf)>),
+    /// A rename didn't finish within [`crate::NFLZAssistantBuilder::rename_timeout`].
+    /// Contains the old and the new path of the file. The rename may still finish in the
+    /// background, hence, the file may have either name. Nothing was rolled back.
+    RenameTimedOut(PathBuf, PathBuf),
     /// The prefixes of all files inside the directory before the rename group
     /// must be unambiguous. Hence, "Img (1).jpg" and "Photo (2).jpg" will result in an error.
-    AmbiguousPrefixes(HashSet<String>),
+    /// Contains all prefixes and the files whose prefix differs from the most common one.
+    AmbiguousPrefixes(HashSet<String>, Vec<PathBuf>),
     /// The suffixes of all files inside the directory after the rename group
     /// must be unambiguous. Hence, "Img (1) foobar.jpg" and "Img (1) barfoo.png" will result
     /// in an error. The only allowed exception is if one file is named "Img (1).jpg" and the
-    /// other is called "Img (1).JPG" (different font casing of the file extension).
-    AmbiguousSuffixes(HashSet<String>),
+    /// other is called "Img (1).JPG" (different font casing of the file extension). Contains
+    /// all suffixes and the files whose suffix differs from the most common one.
+    AmbiguousSuffixes(HashSet<String>, Vec<PathBuf>),
+    /// The new path of a file would be outside of the working directory, for example because
+    /// a transform returned a new filename with `..` or a path separator. Contains the file
+    /// and its new path.
+    DestinationOutsideDirectory(Box<FileInfo>, PathBuf),
+    /// The directory is given more than once as source of a [`crate::MergePlan`], e.g., as
+    /// `a` and `./a`, which would move every file twice.
+    DuplicateMergeSource(PathBuf),
+    /// A [`crate::RenamePlan`] violates its invariants and can't be applied. Contains all
+    /// violations. See [`crate::RenamePlan::verify`].
+    InvalidPlan(Vec<PlanViolation>),
+    /// There is no [`crate::BuiltinTransform`] with the given name.
+    UnknownTransform(String),
+    /// There is no built-in pattern (`BuiltinPattern`) with the given name.
+    UnknownPattern(String),
+    /// There is no [`crate::ContentCategory`] with the given name.
+    UnknownContentCategory(String),
+    /// The `.nflz.toml` file of the directory can't be read or is invalid. Contains the path
+    /// of the file and a description of the problem. See [`crate::DirectoryConfig`].
+    InvalidConfig(PathBuf, String),
+    /// There is no [`crate::ScriptFormat`] with the given name.
+    UnknownScriptFormat(String),
+    /// The renumbered sequence doesn't fit into a [`u64`], e.g., with a
+    /// [`crate::NFLZAssistantBuilder::start_at`] close to [`u64::MAX`]. Contains the first
+    /// number, the step, and the number of items of the sequence.
+    RenumberingOverflow(u64, u64, u64),
+    /// The number groups would be padded to more characters than a filename can have, e.g.,
+    /// roman numerals with a large [`crate::NFLZAssistantBuilder::reserve_additional`], as
+    /// every thousand adds another `M`. Contains the width.
+    NumberGroupTooWide(u64),
+    /// The directory was modified by someone else after the new filenames were computed.
+    /// Contains all differences. The renaming was aborted before any file was renamed.
+    DirectoryChanged(PathBuf, Vec<DirectoryChange>),
 }
 
 impl NFLZError {
-    /// The filename that resulted in an error.
+    /// Returns a stable, machine-readable identifier of the error variant, such as
+    /// `"ambiguous_prefixes"`. The identifiers don't change between releases.
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::FilenameMustIncludeExactlyOneNumberedGroup(_) => {
+                "filename_must_include_exactly_one_numbered_group"
+            }
+            Self::ValueInNumberedGroupNotANumber(_, _) => "value_in_numbered_group_not_a_number",
+            Self::CantReadDirectory(_, _) => "cant_read_directory",
+            Self::CantCreateDirectory(_, _) => "cant_create_directory",
+            Self::CantUpdateReferences(_, _) => "cant_update_references",
+            Self::CantReadIgnoreFile(_, _) => "cant_read_ignore_file",
+            Self::CantReadSelectedFile(_, _) => "cant_read_selected_file",
+            Self::CantWriteCheckpoint(_, _) => "cant_write_checkpoint",
+            Self::CantReadCheckpoint(_, _) => "cant_read_checkpoint",
+            Self::CantSyncDirectory(_, _) => "cant_sync_directory",
+            Self::CantRetargetSymlink(_, _) => "cant_retarget_symlink",
+            Self::ConflictingFiles(_) => "conflicting_files",
+            Self::DestinationOutsideDirectory(_, _) => "destination_outside_directory",
+            Self::InvalidPlan(_) => "invalid_plan",
+            Self::DuplicateMergeSource(_) => "duplicate_merge_source",
+            Self::RenameFailed(_, _, _) => "rename_failed",
+            Self::Interrupted(_, _) => "interrupted",
+            Self::TemporaryFilesLeft(_, _) => "temporary_files_left",
+            Self::RenameTimedOut(_, _) => "rename_timed_out",
+            Self::AmbiguousPrefixes(_, _) => "ambiguous_prefixes",
+            Self::AmbiguousSuffixes(_, _) => "ambiguous_suffixes",
+            Self::UnknownTransform(_) => "unknown_transform",
+            Self::UnknownPattern(_) => "unknown_pattern",
+            Self::UnknownContentCategory(_) => "unknown_content_category",
+            Self::InvalidConfig(_, _) => "invalid_config",
+            Self::UnknownScriptFormat(_) => "unknown_script_format",
+            Self::RenumberingOverflow(_, _, _) => "renumbering_overflow",
+            Self::NumberGroupTooWide(_) => "number_group_too_wide",
+            Self::DirectoryChanged(_, _) => "directory_changed",
+        }
+    }
+
+    /// Returns a stable numeric code of the error variant. The CLI uses it as exit code. The
+    /// codes are grouped by failure class:
+    ///
+    /// | Code | Class       | Variant                                              |
+    /// |------|-------------|------------------------------------------------------|
+    /// | 10   | parsing     | [`Self::FilenameMustIncludeExactlyOneNumberedGroup`] |
+    /// | 11   | parsing     | [`Self::ValueInNumberedGroupNotANumber`]             |
+    /// | 20   | file system | [`Self::CantReadDirectory`]                          |
+    /// | 21   | file system | [`Self::DirectoryChanged`]                           |
+    /// | 22   | file system | [`Self::CantCreateDirectory`]                        |
+    /// | 23   | file system | [`Self::CantUpdateReferences`]                       |
+    /// | 24   | file system | [`Self::CantReadIgnoreFile`]                         |
+    /// | 25   | file system | [`Self::CantReadSelectedFile`]                       |
+    /// | 26   | file system | [`Self::CantWriteCheckpoint`]                        |
+    /// | 27   | file system | [`Self::CantReadCheckpoint`]                         |
+    /// | 28   | file system | [`Self::CantSyncDirectory`]                          |
+    /// | 29   | file system | [`Self::CantRetargetSymlink`]                        |
+    /// | 30   | validation  | [`Self::ConflictingFiles`]                           |
+    /// | 31   | validation  | [`Self::AmbiguousPrefixes`]                          |
+    /// | 32   | validation  | [`Self::AmbiguousSuffixes`]                          |
+    /// | 33   | validation  | [`Self::DestinationOutsideDirectory`]                |
+    /// | 34   | validation  | [`Self::InvalidPlan`]                                |
+    /// | 35   | validation  | [`Self::DuplicateMergeSource`]                       |
+    /// | 40   | renaming    | [`Self::RenameFailed`]                               |
+    /// | 41   | renaming    | [`Self::Interrupted`]                                |
+    /// | 42   | renaming    | [`Self::TemporaryFilesLeft`]                         |
+    /// | 43   | renaming    | [`Self::RenameTimedOut`]                             |
+    /// | 50   | config      | [`Self::UnknownTransform`]                           |
+    /// | 51   | config      | [`Self::UnknownPattern`]                             |
+    /// | 52   | config      | [`Self::UnknownContentCategory`]                     |
+    /// | 53   | config      | [`Self::InvalidConfig`]                              |
+    /// | 54   | config      | [`Self::UnknownScriptFormat`]                        |
+    /// | 55   | config      | [`Self::RenumberingOverflow`]                        |
+    /// | 56   | config      | [`Self::NumberGroupTooWide`]                         |
+    ///
+    /// The codes don't change between releases.
+    pub const fn code(&self) -> u8 {
+        match self {
+            Self::FilenameMustIncludeExactlyOneNumberedGroup(_) => 10,
+            Self::ValueInNumberedGroupNotANumber(_, _) => 11,
+            Self::CantReadDirectory(_, _) => 20,
+            Self::DirectoryChanged(_, _) => 21,
+            Self::CantCreateDirectory(_, _) => 22,
+            Self::CantUpdateReferences(_, _) => 23,
+            Self::CantReadIgnoreFile(_, _) => 24,
+            Self::CantReadSelectedFile(_, _) => 25,
+            Self::CantWriteCheckpoint(_, _) => 26,
+            Self::CantReadCheckpoint(_, _) => 27,
+            Self::CantSyncDirectory(_, _) => 28,
+            Self::CantRetargetSymlink(_, _) => 29,
+            Self::ConflictingFiles(_) => 30,
+            Self::AmbiguousPrefixes(_, _) => 31,
+            Self::AmbiguousSuffixes(_, _) => 32,
+            Self::DestinationOutsideDirectory(_, _) => 33,
+            Self::InvalidPlan(_) => 34,
+            Self::DuplicateMergeSource(_) => 35,
+            Self::RenameFailed(_, _, _) => 40,
+            Self::Interrupted(_, _) => 41,
+            Self::TemporaryFilesLeft(_, _) => 42,
+            Self::RenameTimedOut(_, _) => 43,
+            Self::UnknownTransform(_) => 50,
+            Self::UnknownPattern(_) => 51,
+            Self::UnknownContentCategory(_) => 52,
+            Self::InvalidConfig(_, _) => 53,
+            Self::UnknownScriptFormat(_) => 54,
+            Self::RenumberingOverflow(_, _, _) => 55,
+            Self::NumberGroupTooWide(_) => 56,
+        }
+    }
+
+    /// The filename of the file that resulted in an error. See [`Self::path`].
     pub fn filename(&self) -> Option<&str> {
         match self {
-            Self::FilenameMustIncludeExactlyOneNumberedGroup(fln) => Option::from(fln.as_str()),
-            Self::ValueInNumberedGroupNotANumber(fln) => Option::from(fln.as_str()),
-            Self::RenameFailed(fln, _, _) => Option::from(fln.as_str()),
+            Self::FilenameMustIncludeExactlyOneNumberedGroup(path)
+            | Self::ValueInNumberedGroupNotANumber(path, _)
+            | Self::RenameFailed(path, _, _)
+            | Self::RenameTimedOut(path, _) => path.file_name()?.to_str(),
             _ => None,
         }
     }
-}
 
-impl Display for NFLZError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    /// The path of the file or directory that resulted in an error. For
+    /// [`Self::RenameFailed`] and [`Self::RenameTimedOut`], this is the old path, and for
+    /// [`Self::DestinationOutsideDirectory`], the current path of the file. `None` if the
+    /// error isn't about a single file or directory.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::FilenameMustIncludeExactlyOneNumberedGroup(path)
+            | Self::ValueInNumberedGroupNotANumber(path, _)
+            | Self::CantReadDirectory(path, _)
+            | Self::CantCreateDirectory(path, _)
+            | Self::CantUpdateReferences(path, _)
+            | Self::CantReadIgnoreFile(path, _)
+            | Self::CantReadSelectedFile(path, _)
+            | Self::CantWriteCheckpoint(path, _)
+            | Self::CantReadCheckpoint(path, _)
+            | Self::CantSyncDirectory(path, _)
+            | Self::CantRetargetSymlink(path, _)
+            | Self::InvalidConfig(path, _)
+            | Self::DuplicateMergeSource(path)
+            | Self::RenameFailed(path, _, _)
+            | Self::RenameTimedOut(path, _)
+            | Self::DirectoryChanged(path, _) => Some(path),
+            Self::DestinationOutsideDirectory(file, _) => Some(file.path()),
+            Self::TemporaryFilesLeft(err, _) => err.path(),
+            _ => None,
+        }
+    }
+
+    /// Returns concrete ways to resolve the error, such as the option that tolerates the
+    /// difference between the prefixes or the files with an unusual prefix. Empty if there is
+    /// nothing to suggest. The suggestions are part of the [`Display`] output as well.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let assistant = NFLZAssistant::new("./test-resources").unwrap();
+    /// if let Err(err) = assistant.check_can_rename_all() {
+    ///     for suggestion in err.suggestions() {
+    ///         println!("{}", suggestion);
+    ///     }
+    /// }
+    /// ```
+    pub fn suggestions(&self) -> Vec<Suggestion> {
+        crate::suggestion::suggest(self)
+    }
+
+    /// Writes the message of the error without the suggestions.
+    fn fmt_message(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
-            Self::FilenameMustIncludeExactlyOneNumberedGroup(fln) => f.write_str(&format!(
+            Self::FilenameMustIncludeExactlyOneNumberedGroup(path) => f.write_str(&format!(
                 "The filename '{}' must include exactly one numbered group.",
-                fln
+                path.display()
             )),
-            Self::ValueInNumberedGroupNotANumber(value) => f.write_str(&format!(
-                "The value '{}' in the numbered group is not a number.",
-                value
+            Self::ValueInNumberedGroupNotANumber(path, value) => f.write_str(&format!(
+                "The value '{}' in the numbered group of '{}' is not a number.",
+                value,
+                path.display()
             )),
-            Self::CantReadDirectory(value, os_err) => f.write_str(&format!(
+            Self::CantReadDirectory(path, os_err) => f.write_str(&format!(
                 "The directory  ('{}') or the files in it can't be read because: {}",
-                value.as_os_str().to_str().unwrap(),
+                path.display(),
+                os_err
+            )),
+            Self::CantCreateDirectory(path, os_err) => f.write_str(&format!(
+                "The directory '{}' can't be created because: {}",
+                path.display(),
+                os_err
+            )),
+            Self::CantUpdateReferences(path, os_err) => f.write_str(&format!(
+                "The files were renamed but the references in '{}' can't be updated because: {}",
+                path.display(),
+                os_err
+            )),
+            Self::CantReadIgnoreFile(path, os_err) => f.write_str(&format!(
+                "The ignore file '{}' can't be read because: {}",
+                path.display(),
+                os_err
+            )),
+            Self::CantReadSelectedFile(path, os_err) => f.write_str(&format!(
+                "The selected file '{}' can't be used because: {}",
+                path.display(),
+                os_err
+            )),
+            Self::CantWriteCheckpoint(path, os_err) => f.write_str(&format!(
+                "The checkpoint file '{}' can't be written because: {}",
+                path.display(),
+                os_err
+            )),
+            Self::CantReadCheckpoint(path, os_err) => f.write_str(&format!(
+                "The checkpoint file '{}' can't be read because: {}",
+                path.display(),
+                os_err
+            )),
+            Self::CantSyncDirectory(path, os_err) => f.write_str(&format!(
+                "The renames in the directory '{}' can't be synced to the storage device because: {}",
+                path.display(),
+                os_err
+            )),
+            Self::CantRetargetSymlink(path, os_err) => f.write_str(&format!(
+                "The files were renamed but the symlink '{}' can't be retargeted because: {}",
+                path.display(),
                 os_err
             )),
             Self::ConflictingFiles(files) => f.write_str(&format!(
                 "Can't rename files because {} new file names are in conflict with existing ones.",
                 files.len()
             )),
-            Self::RenameFailed(old_filename, new_filename, os_err) => f.write_str(&format!(
+            Self::DestinationOutsideDirectory(file, new_path) => f.write_str(&format!(
+                "Can't rename '{}' to '{}' because it is outside of the directory.",
+                file.path().display(),
+                new_path.display()
+            )),
+            Self::DuplicateMergeSource(path) => f.write_str(&format!(
+                "The directory '{}' is given more than once as source of the merge.",
+                path.display()
+            )),
+            Self::InvalidPlan(violations) => f.write_str(&format!(
+                "Can't apply the rename plan because it has {} violations: {}",
+                violations.len(),
+                violations
+                    .iter()
+                    .map(|violation| violation.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )),
+            Self::RenameFailed(old_path, new_path, os_err) => f.write_str(&format!(
                 "Can't rename file '{}' to '{}' because: {}",
-                old_filename, new_filename, os_err,
+                old_path.display(),
+                new_path.display(),
+                os_err,
+            )),
+            Self::Interrupted(renamed, total) => f.write_str(&format!(
+                "The renaming was interrupted after {} of {} files.",
+                renamed, total
             )),
-            Self::AmbiguousSuffixes(suffixes) => f.write_str(&format!(
+            Self::RenameTimedOut(old_path, new_path) => f.write_str(&format!(
+                "The rename of '{}' to '{}' timed out. It may still finish, so the file has either name.",
+                old_path.display(),
+                new_path.display()
+            )),
+            Self::TemporaryFilesLeft(err, files) => {
+                err.fmt_message(f)?;
+                f.write_str(&format!(
+                    " These files still have a temporary name and must be renamed back manually: {}",
+                    files
+                        .iter()
+                        .map(|(temporary, original)| format!(
+                            "'{}' => '{}'",
+                            temporary.display(),
+                            original.display()
+                        ))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            }
+            Self::AmbiguousSuffixes(suffixes, _) => f.write_str(&format!(
                 "There are multiple (and therefore ambiguous) suffixes in this directory: {:?}",
                 suffixes,
             )),
-            Self::AmbiguousPrefixes(prefixes) => f.write_str(&format!(
+            Self::AmbiguousPrefixes(prefixes, _) => f.write_str(&format!(
                 "There are multiple (and therefore ambiguous) prefixes in this directory: {:?}",
                 prefixes,
             )),
+            Self::UnknownTransform(name) => f.write_str(&format!(
+                "There is no built-in transform with the name '{}'.",
+                name
+            )),
+            Self::UnknownPattern(name) => f.write_str(&format!(
+                "There is no built-in pattern with the name '{}'.",
+                name
+            )),
+            Self::UnknownContentCategory(name) => f.write_str(&format!(
+                "There is no content category with the name '{}'.",
+                name
+            )),
+            Self::UnknownScriptFormat(name) => f.write_str(&format!(
+                "There is no script format with the name '{}'.",
+                name
+            )),
+            Self::RenumberingOverflow(start, step, count) => f.write_str(&format!(
+                "Can't renumber {} items from {} with a step of {} because the numbers exceed {}.",
+                count,
+                start,
+                step,
+                u64::MAX
+            )),
+            Self::NumberGroupTooWide(width) => f.write_str(&format!(
+                "The number groups would be padded to {} characters, which is more than a filename can have.",
+                width
+            )),
+            Self::InvalidConfig(path, message) => f.write_str(&format!(
+                "The configuration file '{}' is invalid: {}",
+                path.display(),
+                message
+            )),
+            Self::DirectoryChanged(path, changes) => f.write_str(&format!(
+                "The directory '{}' was modified after the new filenames were computed: {}",
+                path.display(),
+                changes
+                    .iter()
+                    .map(|change| change.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+impl Display for NFLZError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_message(f)?;
+        for suggestion in self.suggestions() {
+            write!(f, "\n  Suggestion: {}", suggestion)?;
         }
+        Ok(())
     }
 }
 
+/// Serializes the error as map with the stable [`NFLZError::kind`] and [`NFLZError::code`],
+/// the human-readable `message`, the structured fields of the variant, and the
+/// [`NFLZError::suggestions`]. For example:
+/// `{"kind": "rename_failed", "code": 40, "message": "...", "old_path": "dir/a (1).jpg",
+/// "new_path": "dir/a (01).jpg", "io_error": "..."}`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for NFLZError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("kind", self.kind())?;
+        map.serialize_entry("code", &self.code())?;
+        map.serialize_entry("message", &self.to_string())?;
+        match self {
+            Self::FilenameMustIncludeExactlyOneNumberedGroup(path)
+            | Self::DuplicateMergeSource(path) => {
+                map.serialize_entry("path", path)?;
+            }
+            Self::ValueInNumberedGroupNotANumber(path, value) => {
+                map.serialize_entry("path", path)?;
+                map.serialize_entry("value", value)?;
+            }
+            Self::CantReadDirectory(path, io_error)
+            | Self::CantCreateDirectory(path, io_error)
+            | Self::CantUpdateReferences(path, io_error)
+            | Self::CantReadIgnoreFile(path, io_error)
+            | Self::CantReadSelectedFile(path, io_error)
+            | Self::CantWriteCheckpoint(path, io_error)
+            | Self::CantReadCheckpoint(path, io_error)
+            | Self::CantSyncDirectory(path, io_error)
+            | Self::CantRetargetSymlink(path, io_error) => {
+                map.serialize_entry("path", path)?;
+                map.serialize_entry("io_error", &io_error.to_string())?;
+            }
+            Self::ConflictingFiles(paths) => {
+                map.serialize_entry("paths", paths)?;
+            }
+            Self::DestinationOutsideDirectory(file, new_path) => {
+                map.serialize_entry("path", file.path())?;
+                map.serialize_entry("new_path", new_path)?;
+            }
+            Self::InvalidPlan(violations) => {
+                map.serialize_entry("violations", violations)?;
+            }
+            Self::RenameFailed(old_path, new_path, io_error) => {
+                map.serialize_entry("old_path", old_path)?;
+                map.serialize_entry("new_path", new_path)?;
+                map.serialize_entry("io_error", &io_error.to_string())?;
+            }
+            Self::Interrupted(renamed, total) => {
+                map.serialize_entry("renamed", renamed)?;
+                map.serialize_entry("total", total)?;
+            }
+            Self::RenameTimedOut(old_path, new_path) => {
+                map.serialize_entry("old_path", old_path)?;
+                map.serialize_entry("new_path", new_path)?;
+            }
+            Self::TemporaryFilesLeft(err, files) => {
+                map.serialize_entry("error", err)?;
+                map.serialize_entry(
+                    "files",
+                    &files
+                        .iter()
+                        .map(|(temporary, original)| {
+                            [("temporary_path", temporary), ("original_path", original)]
+                                .into_iter()
+                                .collect::<std::collections::BTreeMap<_, _>>()
+                        })
+                        .collect::<Vec<_>>(),
+                )?;
+            }
+            Self::AmbiguousPrefixes(prefixes, paths) => {
+                map.serialize_entry("prefixes", &sorted(prefixes))?;
+                map.serialize_entry("paths", paths)?;
+            }
+            Self::AmbiguousSuffixes(suffixes, paths) => {
+                map.serialize_entry("suffixes", &sorted(suffixes))?;
+                map.serialize_entry("paths", paths)?;
+            }
+            Self::UnknownTransform(name)
+            | Self::UnknownPattern(name)
+            | Self::UnknownContentCategory(name)
+            | Self::UnknownScriptFormat(name) => {
+                map.serialize_entry("name", name)?;
+            }
+            Self::RenumberingOverflow(start, step, count) => {
+                map.serialize_entry("start", start)?;
+                map.serialize_entry("step", step)?;
+                map.serialize_entry("count", count)?;
+            }
+            Self::NumberGroupTooWide(width) => {
+                map.serialize_entry("width", width)?;
+            }
+            Self::InvalidConfig(path, message) => {
+                map.serialize_entry("path", path)?;
+                map.serialize_entry("problem", message)?;
+            }
+            Self::DirectoryChanged(path, changes) => {
+                map.serialize_entry("path", path)?;
+                map.serialize_entry("changes", changes)?;
+            }
+        }
+        map.serialize_entry("suggestions", &self.suggestions())?;
+        map.end()
+    }
+}
+
+/// Returns the elements of the set in a deterministic order.
+#[cfg(feature = "serde")]
+fn sorted(set: &HashSet<String>) -> Vec<&String> {
+    let mut vec = set.iter().collect::<Vec<_>>();
+    vec.sort();
+    vec
+}
+
 impl Error for NFLZError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
-            Self::CantReadDirectory(_, os_err) => Some(os_err),
+            Self::CantReadDirectory(_, os_err)
+            | Self::CantCreateDirectory(_, os_err)
+            | Self::CantUpdateReferences(_, os_err)
+            | Self::CantReadIgnoreFile(_, os_err)
+            | Self::CantReadSelectedFile(_, os_err)
+            | Self::CantWriteCheckpoint(_, os_err)
+            | Self::CantReadCheckpoint(_, os_err)
+            | Self::CantSyncDirectory(_, os_err)
+            | Self::CantRetargetSymlink(_, os_err)
+            | Self::RenameFailed(_, _, os_err) => Some(os_err),
+            Self::TemporaryFilesLeft(err, _) => Some(err.as_ref()),
             _ => None,
         }
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        let err = NFLZError::RenameFailed(
+            PathBuf::from("a (1).jpg"),
+            PathBuf::from("a (01).jpg"),
+            std::io::Error::from(std::io::ErrorKind::NotFound),
+        );
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!("rename_failed", json["kind"]);
+        assert_eq!(40, json["code"]);
+        assert_eq!(err.to_string(), json["message"]);
+        assert_eq!("a (1).jpg", json["old_path"]);
+        assert_eq!("a (01).jpg", json["new_path"]);
+
+        let err = NFLZError::AmbiguousPrefixes(
+            ["b (".to_string(), "a (".to_string()].into_iter().collect(),
+            vec![PathBuf::from("b (2).jpg")],
+        );
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(serde_json::json!(["a (", "b ("]), json["prefixes"]);
+        assert_eq!(serde_json::json!(["b (2).jpg"]), json["paths"]);
+        assert_eq!(
+            serde_json::json!(["b (2).jpg"]),
+            json["suggestions"][0]["files"]
+        );
+    }
+
+    #[test]
+    fn test_path_and_source() {
+        let err = NFLZError::RenameFailed(
+            PathBuf::from("dir/a (1).jpg"),
+            PathBuf::from("dir/a (01).jpg"),
+            std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+        );
+        assert_eq!(Some(Path::new("dir/a (1).jpg")), err.path());
+        assert_eq!(Some("a (1).jpg"), err.filename());
+        let source = err.source().unwrap();
+        assert_eq!(
+            std::io::ErrorKind::PermissionDenied,
+            source.downcast_ref::<std::io::Error>().unwrap().kind()
+        );
+
+        let err = FileInfo::new("dir/a.jpg").unwrap_err();
+        assert!(matches!(
+            &err,
+            NFLZError::FilenameMustIncludeExactlyOneNumberedGroup(path) if path == Path::new("dir/a.jpg")
+        ));
+        assert!(err.source().is_none());
+    }
+}
@@ -0,0 +1,141 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for events that are emitted while the library works. See [`Event`].
+
+use crate::error::NFLZError;
+use crate::skip::SkipReason;
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Event that is emitted to the listener registered with
+/// [`crate::NFLZAssistantBuilder::event_listener`] as soon as it happens.
+///
+/// With the `serde` feature, events serialize to a map with the name of the event in the
+/// field `event`, for example `{"event": "renamed", "path": "...", "new_path": "..."}`.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "event", rename_all = "snake_case"))]
+pub enum Event<'a> {
+    /// A file was found in the working directory.
+    Scanned {
+        /// Path to the file.
+        path: &'a Path,
+    },
+    /// A file was skipped because its filename doesn't match the pattern.
+    Skipped {
+        /// Path to the file.
+        path: &'a Path,
+        /// Why the file was skipped.
+        reason: SkipReason,
+    },
+    /// The new filename of a file was computed.
+    Planned {
+        /// Path to the file.
+        path: &'a Path,
+        /// Path to the file after the rename. `None` if the file already has the correct name.
+        new_path: Option<PathBuf>,
+    },
+    /// A file was renamed in the file system.
+    Renamed {
+        /// Path to the file before the rename.
+        path: &'a Path,
+        /// Path to the file after the rename.
+        new_path: PathBuf,
+    },
+    /// The references to the renamed files were updated in a file. See
+    /// [`crate::NFLZAssistantBuilder::update_references_in`].
+    ReferencesUpdated {
+        /// Path to the updated file, e.g., a playlist.
+        path: &'a Path,
+        /// Number of replaced references.
+        count: usize,
+    },
+    /// A symlink that pointed at a renamed file was retargeted. See
+    /// [`crate::NFLZAssistantBuilder::retarget_symlinks`].
+    SymlinkRetargeted {
+        /// Path to the symlink.
+        path: &'a Path,
+        /// New target of the symlink.
+        target: PathBuf,
+    },
+    /// The operation failed.
+    Error {
+        /// The error that ended the operation.
+        error: &'a NFLZError,
+    },
+}
+
+/// Callback of an [`EventListener`].
+type EventListenerFn = dyn Fn(&Event<'_>);
+
+/// Optional listener that receives all [`Event`]s.
+#[derive(Default)]
+pub(crate) struct EventListener(Option<Box<EventListenerFn>>);
+
+impl EventListener {
+    /// Constructor.
+    pub(crate) fn new<F: Fn(&Event<'_>) + 'static>(f: F) -> Self {
+        Self(Some(Box::new(f)))
+    }
+
+    /// Passes the event to the listener, if there is one.
+    pub(crate) fn emit(&self, event: Event<'_>) {
+        if let Some(f) = &self.0 {
+            f(&event);
+        }
+    }
+
+    /// Emits [`Event::Error`] if the result is an error and returns the result unchanged.
+    pub(crate) fn emit_if_err<T>(&self, result: Result<T, NFLZError>) -> Result<T, NFLZError> {
+        if let Err(error) = &result {
+            self.emit(Event::Error { error });
+        }
+        result
+    }
+}
+
+impl Debug for EventListener {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let listener = self.0.as_ref().map(|_| "<closure>");
+        f.debug_tuple("EventListener").field(&listener).finish()
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        let event = Event::Renamed {
+            path: Path::new("a (1).jpg"),
+            new_path: PathBuf::from("a (01).jpg"),
+        };
+        assert_eq!(
+            r#"{"event":"renamed","path":"a (1).jpg","new_path":"a (01).jpg"}"#,
+            serde_json::to_string(&event).unwrap()
+        );
+    }
+}
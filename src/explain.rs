@@ -0,0 +1,228 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for explaining what happens to every entry of the working directory. See
+//! [`Explanation`].
+
+use crate::skip::SkipReason;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// What happens to an entry of the working directory. See [`Explanation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "verdict", rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum Verdict {
+    /// The file is renamed to the new path.
+    Rename {
+        /// Path to the file after the renaming.
+        new_path: PathBuf,
+    },
+    /// The file already has the correct name.
+    Unchanged,
+    /// The entry is ignored for the reason. See [`crate::NFLZAssistant::skipped_files`].
+    Skipped {
+        /// Why the entry is ignored.
+        reason: SkipReason,
+    },
+    /// The entry is a hidden file. See [`crate::NFLZAssistantBuilder::include_hidden_files`].
+    Hidden,
+    /// The entry is a junk file of the operating system, such as `.DS_Store`. See
+    /// [`crate::NFLZAssistant::system_files`].
+    SystemFile,
+}
+
+/// The number group that was chosen in a filename and the width it is padded to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ChosenNumberGroup {
+    /// Everything in front of the number group, such as `img (`.
+    prefix: String,
+    /// The number group as it is written in the filename, such as `7`.
+    digits: String,
+    /// The number of digits the number group is padded to.
+    width: u64,
+}
+
+impl ChosenNumberGroup {
+    /// Returns everything in front of the number group, such as `img (`.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Returns the number group as it is written in the filename, such as `7`.
+    pub fn digits(&self) -> &str {
+        &self.digits
+    }
+
+    /// Returns the number of digits the number group is padded to.
+    pub const fn width(&self) -> u64 {
+        self.width
+    }
+}
+
+/// Why an entry of the working directory is renamed, left alone, or skipped. See
+/// [`crate::NFLZAssistant::explain`].
+///
+/// Displays as a single line, such as
+/// `img (7).jpg => img (07).jpg: number group "7" behind "img (" is padded to 2 digits`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Explanation {
+    /// Path to the entry.
+    path: PathBuf,
+    /// What happens to the entry.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    verdict: Verdict,
+    /// The chosen number group. `None` if the filename wasn't parsed.
+    number_group: Option<ChosenNumberGroup>,
+}
+
+impl Explanation {
+    /// Constructor for an entry whose filename wasn't parsed.
+    pub(crate) const fn new(path: PathBuf, verdict: Verdict) -> Self {
+        Self {
+            path,
+            verdict,
+            number_group: None,
+        }
+    }
+
+    /// Constructor for a parsed file.
+    pub(crate) fn with_number_group(
+        path: PathBuf,
+        verdict: Verdict,
+        prefix: &str,
+        digits: &str,
+        width: u64,
+    ) -> Self {
+        Self {
+            path,
+            verdict,
+            number_group: Some(ChosenNumberGroup {
+                prefix: prefix.to_string(),
+                digits: digits.to_string(),
+                width,
+            }),
+        }
+    }
+
+    /// Returns the path to the entry.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns what happens to the entry.
+    pub const fn verdict(&self) -> &Verdict {
+        &self.verdict
+    }
+
+    /// Returns the chosen number group. `None` if the filename wasn't parsed, e.g., for
+    /// hidden files.
+    pub const fn number_group(&self) -> Option<&ChosenNumberGroup> {
+        self.number_group.as_ref()
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = self
+            .path
+            .file_name()
+            .unwrap_or(self.path.as_os_str())
+            .to_string_lossy();
+        match &self.verdict {
+            Verdict::Rename { new_path } => {
+                // show the subdirectory, if the file is moved into one
+                let new_name = self
+                    .path
+                    .parent()
+                    .and_then(|parent| new_path.strip_prefix(parent).ok())
+                    .unwrap_or(new_path);
+                write!(f, "{} => {}", name, new_name.display())?;
+            }
+            Verdict::Unchanged => write!(f, "{} stays", name)?,
+            Verdict::Skipped { reason } => return write!(f, "{} is skipped: {}", name, reason),
+            Verdict::Hidden => return write!(f, "{} is ignored: hidden file", name),
+            Verdict::SystemFile => return write!(f, "{} is ignored: system file", name),
+        }
+        if let Some(group) = &self.number_group {
+            write!(
+                f,
+                ": number group {:?} behind {:?} ",
+                group.digits, group.prefix
+            )?;
+            if self.verdict == Verdict::Unchanged {
+                write!(f, "already has {} digits", group.width)?;
+            } else {
+                write!(f, "is padded to {} digits", group.width)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testutil::FixtureBuilder;
+    use crate::{NFLZAssistant, SkipReason, Verdict};
+
+    #[test]
+    fn test_explain() {
+        let fixture = FixtureBuilder::new("img ")
+            .count(10)
+            .extra_file("notes.txt")
+            .extra_file("._img (3).jpg")
+            .extra_file(".DS_Store")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        let explanations = assistant.explain();
+        assert_eq!(13, explanations.len());
+        let lines = explanations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(".DS_Store is ignored: system file", lines[0]);
+        assert_eq!("._img (3).jpg is ignored: hidden file", lines[1]);
+        assert_eq!(
+            "img (1).jpg => img (01).jpg: number group \"1\" behind \"img (\" is padded to 2 digits",
+            lines[2]
+        );
+        assert_eq!(
+            "img (10).jpg stays: number group \"10\" behind \"img (\" already has 2 digits",
+            lines[3]
+        );
+        assert_eq!("notes.txt is skipped: no number group", lines[12]);
+        assert_eq!(
+            &Verdict::Skipped {
+                reason: SkipReason::NoNumberGroup
+            },
+            explanations[12].verdict()
+        );
+        assert_eq!(None, explanations[12].number_group());
+        assert_eq!(2, explanations[3].number_group().unwrap().width());
+    }
+}
@@ -23,10 +23,15 @@ SOFTWARE.
 */
 //! Module for parsing of file names.
 
+use crate::diagnostics::ParseDiagnostics;
 use crate::error::NFLZError;
-use crate::math::count_digits_without_leading_zeroes;
-use regex::Regex;
+use crate::math::Radix;
+use crate::scheme::{NumberingScheme, Parentheses};
+use crate::transform::{apply_transforms, FilenameTransform};
+#[cfg(feature = "regex")]
+pub(crate) use regex::Regex;
 use std::cmp::Ordering;
+use std::ops::Range;
 use std::path::{Component, Path, PathBuf};
 use std::str::FromStr;
 
@@ -43,10 +48,19 @@ pub struct FileInfo {
     number_group_indices: (u16, u16),
     /// The string inside the filename encapsulated by the indices of field `number_group_indices`.
     /// Might be `"0"`, `"1"`, `"12"`, or `0012`.
-    #[allow(unused)]
     number_group_str: String,
     /// Field `number_group_str` parsed as number. Useful for sorting the files.
     number_group_value: u64,
+    /// Indices of the optional second number group behind the first one, such as the
+    /// episode in `Show S1E3.mkv`.
+    second_number_group_indices: Option<(u16, u16)>,
+    /// Value of the second number group.
+    second_number_group_value: Option<u64>,
+    /// Base of the number groups.
+    radix: Radix,
+    /// Whether the letters of the number group, such as the hexadecimal digits of `1F` or the
+    /// roman numeral `IV`, are written in uppercase. New numbers are written the same way.
+    uppercase_digits: bool,
 }
 
 impl FileInfo {
@@ -54,21 +68,114 @@ impl FileInfo {
     /// `Img ([0-9]+).jpg` or similar. The constructor does not access the file in the
     /// file system. It relies on that the file actually exists for the lifetime of this struct.
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, NFLZError> {
-        let filename = path_to_filename(path.as_ref()).to_owned();
+        Self::new_with_radix(path.as_ref(), Radix::Decimal)
+    }
+
+    /// Like [`Self::new`] but the number group contains digits of the given base.
+    pub(crate) fn new_with_radix(path: &Path, radix: Radix) -> Result<Self, NFLZError> {
+        Self::new_with_scheme_and_radix(path, &Parentheses::with_radix(radix), radix)
+    }
+
+    /// Like [`Self::new`] but uses a custom regex to find the number group instead of the
+    /// default `(<digits>)` pattern. The regex must match exactly once inside the filename
+    /// and its first capture group must contain the digits of the number group. For example,
+    /// `Img_([0-9]+)\.jpg` for files such as `Img_1.jpg`.
+    ///
+    /// If the regex has a second capture group, it is a second number group that is padded
+    /// independently of the first one, such as the episode in `S([0-9]+)E([0-9]+)`.
+    #[cfg(feature = "regex")]
+    pub fn new_with_regex<P: AsRef<Path>>(path: P, regex: &Regex) -> Result<Self, NFLZError> {
+        Self::new_with_scheme(path, regex)
+    }
+
+    /// Like [`Self::new`] but uses the [`NumberingScheme`] to find the number group(s). The
+    /// scheme must find exactly one match inside the filename.
+    pub fn new_with_scheme<P: AsRef<Path>>(
+        path: P,
+        scheme: &dyn NumberingScheme,
+    ) -> Result<Self, NFLZError> {
+        Self::new_with_scheme_and_radix(path.as_ref(), scheme, Radix::Decimal)
+    }
+
+    /// Like [`Self::new_with_scheme`] but the number groups contain digits of the given base.
+    pub(crate) fn new_with_scheme_and_radix(
+        path: &Path,
+        scheme: &dyn NumberingScheme,
+        radix: Radix,
+    ) -> Result<Self, NFLZError> {
+        let filename = path_to_filename(path);
+        let (number_group_indices, second_number_group_indices) =
+            number_group_indices(filename, scheme).ok_or_else(|| {
+                NFLZError::FilenameMustIncludeExactlyOneNumberedGroup(path.to_path_buf())
+            })?;
+        let mut file_info = Self::from_number_group_indices(path, number_group_indices, radix)?;
+        if let Some((from, to)) = second_number_group_indices {
+            let value_str = &filename[from as usize..to as usize];
+            let value = radix.parse(value_str).ok_or_else(|| {
+                NFLZError::ValueInNumberedGroupNotANumber(path.to_path_buf(), value_str.to_string())
+            })?;
+            file_info.second_number_group_indices = Some((from, to));
+            file_info.second_number_group_value = Some(value);
+        }
+        Ok(file_info)
+    }
+
+    /// Like [`Self::new`] but accepts every filename with at least one sequence of digits.
+    /// The last sequence is the number group. All sequences must fit into a [`u64`], as
+    /// they are padded as well. See
+    /// [`crate::NFLZAssistantBuilder::pad_all_number_groups`].
+    pub(crate) fn new_with_digit_runs<P: AsRef<Path>>(path: P) -> Result<Self, NFLZError> {
+        let path = path.as_ref();
+        let filename = path_to_filename(path);
+        let runs = digit_runs(filename);
+        let last = runs.last().copied().ok_or_else(|| {
+            NFLZError::FilenameMustIncludeExactlyOneNumberedGroup(path.to_path_buf())
+        })?;
+        for (from, to) in runs {
+            let value_str = &filename[from as usize..to as usize];
+            u64::from_str(value_str).map_err(|_| {
+                NFLZError::ValueInNumberedGroupNotANumber(path.to_path_buf(), value_str.to_string())
+            })?;
+        }
+        Self::from_number_group_indices(path, last, Radix::Decimal)
+    }
 
-        let number_group_indices = get_number_group_indices_from_actual_filename(&filename)?;
+    /// Like [`Self::new`] but never fails. Instead, it returns detailed information about
+    /// every sequence of digits in the filename and why it is or isn't the number group. This
+    /// is useful to explain to users why a file is skipped.
+    pub fn try_parse_with_diagnostics(filename: &str) -> ParseDiagnostics {
+        ParseDiagnostics::parse(filename)
+    }
+
+    /// Creates the object from the indices of the number group inside the filename.
+    fn from_number_group_indices(
+        path: &Path,
+        number_group_indices: (u16, u16),
+        radix: Radix,
+    ) -> Result<Self, NFLZError> {
+        let filename = path_to_filename(path).to_owned();
         let (from, to) = number_group_indices;
         let number_group_value_str = &filename[from as usize..to as usize];
-        let number_group_value = u64::from_str(number_group_value_str).map_err(|_| {
-            NFLZError::ValueInNumberedGroupNotANumber(number_group_value_str.to_string())
+        let number_group_value = radix.parse(number_group_value_str).ok_or_else(|| {
+            NFLZError::ValueInNumberedGroupNotANumber(
+                path.to_path_buf(),
+                number_group_value_str.to_string(),
+            )
         })?;
+        let uppercase_digits = number_group_value_str
+            .bytes()
+            .any(|byte| byte.is_ascii_uppercase());
 
         Ok(Self {
-            path: PathBuf::from(path.as_ref()),
+            path: PathBuf::from(path),
             number_group_str: number_group_value_str.to_string(),
             original_filename: filename,
             number_group_indices,
             number_group_value,
+            second_number_group_indices: None,
+            second_number_group_value: None,
+            radix,
+            uppercase_digits,
         })
     }
 
@@ -79,11 +186,21 @@ impl FileInfo {
         prefix
     }
     /// Prefix including ")" after the number group inside field [`Self::original_filename`].
+    /// If there is a second number group, this is the part after the second number group.
     pub fn filename_suffix(&self) -> &str {
-        let (_, suffix) =
-            get_filename_prefix_and_suffix(self.original_filename(), self.number_group_indices());
+        let indices = self
+            .second_number_group_indices
+            .unwrap_or_else(|| self.number_group_indices());
+        let (_, suffix) = get_filename_prefix_and_suffix(self.original_filename(), indices);
         suffix
     }
+    /// The part between the first and the second number group, such as `E` in
+    /// `Show S1E3.mkv`. `None` if there is no second number group.
+    pub fn filename_infix(&self) -> Option<&str> {
+        self.second_number_group_indices.map(|(from, _)| {
+            &self.original_filename[self.number_group_indices.1 as usize..from as usize]
+        })
+    }
     /// Getter for field `number_group_indices`.
     const fn number_group_indices(&self) -> (u16, u16) {
         self.number_group_indices
@@ -94,6 +211,54 @@ impl FileInfo {
         self.number_group_value
     }
 
+    /// Returns the digits of the number group as they are written in the filename, including
+    /// leading zeroes. `paris (007).jpg` => `"007"`.
+    pub fn number_group_str(&self) -> &str {
+        &self.number_group_str
+    }
+
+    /// Returns how many digits the number group currently has, including leading zeroes.
+    /// `paris (007).jpg` => `3`, `paris (7).jpg` => `1`. Useful to see how a set of files is
+    /// padded before the renaming.
+    pub fn existing_padding_width(&self) -> usize {
+        self.number_group_str().len()
+    }
+
+    /// Returns how many digits the number has if it is written like the number group of this
+    /// file, i.e., in the same base.
+    pub(crate) fn count_digits(&self, number: u64) -> u64 {
+        self.radix.count_digits(number)
+    }
+
+    /// Returns whether the letters of the number group, such as the hexadecimal digits of
+    /// `1F` or the roman numeral `IV`, are written in uppercase. `None` if the number group
+    /// has no letters, such as `19`.
+    pub(crate) fn uppercase_digits(&self) -> Option<bool> {
+        self.number_group_str
+            .bytes()
+            .any(|byte| byte.is_ascii_alphabetic())
+            .then_some(self.uppercase_digits)
+    }
+
+    /// Writes the new number in uppercase or lowercase letters, no matter the case of the
+    /// number group, e.g., so that all files of a sequence use the same case.
+    pub(crate) const fn with_uppercase_digits(mut self, uppercase: bool) -> Self {
+        self.uppercase_digits = uppercase;
+        self
+    }
+
+    /// Formats the number like the number group of this file, i.e., in the same base and
+    /// letter case, with leading zeroes such that it has at least `digits` digits.
+    fn format_number(&self, number: u64, digits: u64) -> String {
+        self.radix.format(number, digits, self.uppercase_digits)
+    }
+
+    /// Getter for the value of the second number group, such as the episode in
+    /// `Show S1E3.mkv`. Only a custom regex can find a second number group.
+    pub const fn second_number_group_value(&self) -> Option<u64> {
+        self.second_number_group_value
+    }
+
     /// Returns the original filename. The filename is obtained by the field `path`.
     /// `/foo/bar/file.ext` => `file.ext`.
     pub fn original_filename(&self) -> &str {
@@ -108,8 +273,7 @@ impl FileInfo {
 
 impl PartialOrd for FileInfo {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.number_group_value
-            .partial_cmp(&other.number_group_value)
+        Some(self.cmp(other))
     }
 }
 
@@ -121,9 +285,21 @@ impl PartialEq for FileInfo {
 
 impl Eq for FileInfo {}
 
+/// Total order of the files: by the value of the number group, then by the value of the
+/// second number group, then by [`FileInfo::filename_prefix`], and finally by the whole
+/// original filename. Thus, files with the same number but different prefixes, such as
+/// `a (1).jpg` and `b (1).jpg`, have a stable order. Consistent with [`PartialEq`], which
+/// compares the original filenames.
 impl Ord for FileInfo {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        self.number_group_value
+            .cmp(&other.number_group_value)
+            .then(
+                self.second_number_group_value
+                    .cmp(&other.second_number_group_value),
+            )
+            .then_with(|| self.filename_prefix().cmp(other.filename_prefix()))
+            .then_with(|| self.original_filename.cmp(&other.original_filename))
     }
 }
 
@@ -137,6 +313,13 @@ pub struct FileInfoWithRenameAdvice {
     /// This filename includes the value inside the number group with an appropriate amount of
     /// leading zeroes.
     new_filename: Option<String>,
+    /// Subdirectory of the working directory the file is moved into, if any. See
+    /// [`crate::NFLZAssistantBuilder::split_into_subdirectories`].
+    subdirectory: Option<String>,
+    /// The number of digits the number group is padded to.
+    width: u64,
+    /// The illegal characters that the transforms replaced in the new filename.
+    replaced_characters: Vec<char>,
 }
 
 impl FileInfoWithRenameAdvice {
@@ -145,13 +328,107 @@ impl FileInfoWithRenameAdvice {
     /// # Parameters
     /// - `file_info`: [`FileInfo`]
     /// - `max_digits`: The maximum amount of digits across all processed  [`FileInfo`] files.
-    ///                 For example 4 if the file with the highest number is named `Img (9141).jpg`.
+    ///   For example 4 if the file with the highest number is named `Img (9141).jpg`.
     pub fn new(file_info: FileInfo, max_digits: u64) -> Self {
+        Self::new_with_transforms(file_info, max_digits, &[])
+    }
+
+    /// Like [`Self::new`] but additionally applies the given [`FilenameTransform`]s in order
+    /// on the new filename after the leading zeroes were added.
+    pub fn new_with_transforms(
+        file_info: FileInfo,
+        max_digits: u64,
+        transforms: &[Box<dyn FilenameTransform>],
+    ) -> Self {
+        // keep the second number group as it is
+        let second_max_digits = file_info
+            .second_number_group_value()
+            .map_or(1, |value| file_info.count_digits(value));
+        let number = file_info.number_group_value();
+        Self::new_with_max_digits(
+            file_info,
+            number,
+            max_digits,
+            second_max_digits,
+            &Parentheses::default(),
+            transforms,
+        )
+    }
+
+    /// Like [`Self::new_with_transforms`] but also pads the second number group of the
+    /// file, if there is one, to `second_max_digits`. The number group gets the value
+    /// `number`, which differs from [`FileInfo::number_group_value`] if the files are
+    /// renumbered. The [`NumberingScheme`] builds the new filename.
+    pub(crate) fn new_with_max_digits(
+        file_info: FileInfo,
+        number: u64,
+        max_digits: u64,
+        second_max_digits: u64,
+        scheme: &dyn NumberingScheme,
+        transforms: &[Box<dyn FilenameTransform>],
+    ) -> Self {
         assert_ne!(max_digits, 0, "max digits must be bigger than zero");
-        let digits = count_digits_without_leading_zeroes(file_info.number_group_value());
-        let digits_to_add_count = max_digits - digits;
+        // "0001" for example
+        let value_str_with_leading_zeros = file_info.format_number(number, max_digits);
+        let second_value_str_with_leading_zeros = file_info
+            .second_number_group_value()
+            .map(|value| file_info.format_number(value, second_max_digits));
+
+        // "IMG (001).jpg" or "Show S01E03.mkv" for example
+        let new_filename = scheme.format(
+            &file_info,
+            &value_str_with_leading_zeros,
+            second_value_str_with_leading_zeros.as_deref(),
+        );
+        Self::from_new_filename(file_info, new_filename, max_digits, transforms)
+    }
 
-        if digits_to_add_count == 0 {
+    /// Like [`Self::new_with_max_digits`] but pads every sequence of digits in the filename
+    /// to the width of its position, e.g., `S01E3 - part (2).mkv` to `S01E03 - part (02).mkv`
+    /// for the widths `[2, 2, 2]`. The last sequence, i.e., the number group, gets the value
+    /// `number`. See [`crate::NFLZAssistantBuilder::pad_all_number_groups`].
+    pub(crate) fn new_with_padded_digit_runs(
+        file_info: FileInfo,
+        number: u64,
+        widths: &[u64],
+        transforms: &[Box<dyn FilenameTransform>],
+    ) -> Self {
+        let filename = file_info.original_filename();
+        let runs = digit_runs(filename);
+        let mut new_filename = String::with_capacity(filename.len());
+        let mut end_of_previous = 0;
+        for (position, (from, to)) in runs
+            .iter()
+            .map(|&(from, to)| (from as usize, to as usize))
+            .enumerate()
+        {
+            let value = if position == runs.len() - 1 {
+                number
+            } else {
+                u64::from_str(&filename[from..to]).expect("checked during parsing")
+            };
+            new_filename.push_str(&filename[end_of_previous..from]);
+            new_filename.push_str(&Radix::Decimal.format(value, widths[position], false));
+            end_of_previous = to;
+        }
+        new_filename.push_str(&filename[end_of_previous..]);
+        // the last sequence is the number group
+        let width = runs.len().checked_sub(1).map_or(0, |last| widths[last]);
+        Self::from_new_filename(file_info, new_filename, width, transforms)
+    }
+
+    /// Applies the transforms on the new filename. The file only needs a rename if the
+    /// result differs from the original filename.
+    fn from_new_filename(
+        file_info: FileInfo,
+        new_filename: String,
+        width: u64,
+        transforms: &[Box<dyn FilenameTransform>],
+    ) -> Self {
+        let (new_filename, replaced_characters) =
+            apply_transforms(transforms, &file_info, new_filename);
+
+        if new_filename == file_info.original_filename {
             log::debug!(
                 "No rename required. File '{}' already has the correct name.",
                 file_info.original_filename()
@@ -159,36 +436,31 @@ impl FileInfoWithRenameAdvice {
             Self {
                 file_info,
                 new_filename: None,
+                subdirectory: None,
+                width,
+                replaced_characters,
             }
         } else {
-            // "0001" for example
-            let value_str_with_leading_zeros = format!(
-                "{}{}",
-                String::from("0").repeat(digits_to_add_count as usize),
-                file_info.number_group_value()
-            );
-
-            // "IMG (001).jpg" for example
-            let new_filename = format!(
-                "{}{}{}",
-                file_info.filename_prefix(),
-                value_str_with_leading_zeros,
-                file_info.filename_suffix(),
-            );
-
-            // should never happen because I have the check for `digits_to_add_count` above
-            assert_ne!(
-                file_info.original_filename, new_filename,
-                "original_filename and new_filename are equal!"
-            );
-
             Self {
                 file_info,
                 new_filename: Some(new_filename),
+                subdirectory: None,
+                width,
+                replaced_characters,
             }
         }
     }
 
+    /// Moves the file into the given subdirectory of its directory. The file needs a rename
+    /// afterwards, even if it already has the correct filename.
+    pub(crate) fn with_subdirectory(mut self, subdirectory: String) -> Self {
+        if self.new_filename.is_none() {
+            self.new_filename = Some(self.file_info.original_filename.clone());
+        }
+        self.subdirectory = Some(subdirectory);
+        self
+    }
+
     /// Returns true if the file needs a rename, hence, additional
     /// leading zeroes inside the number group.
     pub const fn needs_rename(&self) -> bool {
@@ -206,16 +478,26 @@ impl FileInfoWithRenameAdvice {
         &self.file_info
     }
 
-    /// Returns the path to the new file with respect to [`Self::new_filename`].
-    /// None if [`Self::new_filename`] returns `None`.
+    /// Returns the path to the new file with respect to [`Self::new_filename`] and
+    /// [`Self::subdirectory`]. None if [`Self::new_filename`] returns `None`.
     pub fn path_with_new_filename(&self) -> Option<PathBuf> {
         self.new_filename.as_ref().map(|new_filename| {
             let mut parent_dir = PathBuf::from(self.file_info.path.parent().unwrap());
+            if let Some(subdirectory) = &self.subdirectory {
+                parent_dir.push(subdirectory);
+            }
             parent_dir.push(new_filename);
             parent_dir
         })
     }
 
+    /// Returns the name of the subdirectory the file is moved into, such as `part-01`, if
+    /// the files are split into subdirectories. See
+    /// [`crate::NFLZAssistantBuilder::split_into_subdirectories`].
+    pub fn subdirectory(&self) -> Option<&str> {
+        self.subdirectory.as_deref()
+    }
+
     /// Returns the new filename if the file needs to be renamed. This name includes
     /// the additional leading zeroes inside the number group. If this is None, the
     /// file doesn't need to be renamed. For example, `Img (109).jpg` is already
@@ -224,6 +506,18 @@ impl FileInfoWithRenameAdvice {
         self.new_filename.as_deref()
     }
 
+    /// Returns the number of digits the number group is padded to, such as `3` for
+    /// `img (001).jpg`.
+    pub const fn width(&self) -> u64 {
+        self.width
+    }
+
+    /// Returns the illegal characters that the transforms replaced in the new filename,
+    /// such as `['?']` for `What? (1).jpg` => `What_ (01).jpg`.
+    pub(crate) fn replaced_characters(&self) -> &[char] {
+        &self.replaced_characters
+    }
+
     /// Check if the path returned by [`Self::path_with_new_filename`] already exists, hence,
     /// the rename operation can not continue. Returns always false if [`Self::new_filename`]
     /// is `None`.
@@ -236,7 +530,7 @@ impl FileInfoWithRenameAdvice {
 
 impl PartialOrd for FileInfoWithRenameAdvice {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.file_info.partial_cmp(&other.file_info)
+        Some(self.cmp(other))
     }
 }
 
@@ -250,50 +544,108 @@ impl Eq for FileInfoWithRenameAdvice {}
 
 impl Ord for FileInfoWithRenameAdvice {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.file_info.partial_cmp(other.file_info()).unwrap()
+        self.file_info.cmp(other.file_info())
     }
 }
 
 /// Convenient helper function that transforms a path into the filename.
 pub(crate) fn path_to_filename(path: &Path) -> &str {
-    match path.components().last().unwrap() {
+    match path.components().next_back().unwrap() {
         Component::Normal(name) => name.to_str().expect("path must be valid utf-8"),
         // if we land here, we received a wrong list of files. Should never happen.
         _ => panic!("Unexpected file path component."),
     }
 }
 
-/// Returns either Ok with the indices of the number group or Err. The index
-/// doesn't include the parentheses. The first index is inclusive and the last
-/// one is exclusive.
+/// Returns either the indices of the number group(s) or `None` if the scheme doesn't find
+/// exactly one match. The indices don't include delimiters, such as the parentheses. The
+/// first index is inclusive and the last one is exclusive.
 /// Example:
-/// * `paris (100)` => `Ok((6, 11))` (end is exclusive)
-/// * `paris (100) (100)` => `Err()`
-fn get_number_group_indices_from_actual_filename(
+/// * `paris (100)` => `Some(((7, 10), None))`
+/// * `paris (100) (100)` => `None`
+fn number_group_indices(
     actual_filename: &str,
-) -> Result<(u16, u16), NFLZError> {
-    // let regex = Regex::new(r"(?P<main_group>\([0-9]+\)).*(?P<forbidden_group>\([0-9]+\))?").unwrap();
-    let regex = Regex::new(r"(\([0-9]+\))").unwrap();
-
-    // get indices of all matches
-    let match_indices = regex
-        .find_iter(actual_filename)
-        .map(|m| (m.start() as u16, m.end() as u16))
-        .collect::<Vec<(u16, u16)>>();
-
-    if match_indices.is_empty() || match_indices.len() > 1 {
-        Err(NFLZError::FilenameMustIncludeExactlyOneNumberedGroup(
-            actual_filename.to_string(),
-        ))
-    } else {
-        // +-1: remove parentheses
-        let from = match_indices[0].0 + 1;
-        let to = match_indices[0].1 - 1;
-        Ok((from, to))
+    scheme: &dyn NumberingScheme,
+) -> Option<NumberGroupIndices> {
+    let indices = |range: Range<usize>| (range.start as u16, range.end as u16);
+    let mut matches = scheme.find_number_groups(actual_filename).into_iter();
+    match (matches.next(), matches.next()) {
+        (Some(groups), None) => Some((indices(groups.first()), groups.second().map(indices))),
+        _ => None,
     }
 }
 
-/// Uses the actual filename and the indices obtained by [`get_number_group_indices_from_actual_filename`]
+/// Indices of the first and the optional second number group inside a filename.
+type NumberGroupIndices = ((u16, u16), Option<(u16, u16)>);
+
+/// Iterator over the indices of all `(<digits>)` groups inside a filename, without the
+/// parentheses. This is a hand-written parser for the default `\([0-9]+\)` pattern because it
+/// is significantly faster than a regex for directories with lots of files. See
+/// [`Parentheses`].
+pub(crate) struct ParenthesesNumberGroups<'a> {
+    bytes: &'a [u8],
+    i: usize,
+    radix: Radix,
+}
+
+impl<'a> ParenthesesNumberGroups<'a> {
+    /// Constructor.
+    pub(crate) const fn new(actual_filename: &'a str, radix: Radix) -> Self {
+        Self {
+            bytes: actual_filename.as_bytes(),
+            i: 0,
+            radix,
+        }
+    }
+}
+
+impl Iterator for ParenthesesNumberGroups<'_> {
+    type Item = (u16, u16);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.bytes;
+        while self.i < bytes.len() {
+            if bytes[self.i] != b'(' {
+                self.i += 1;
+                continue;
+            }
+            let digits_begin = self.i + 1;
+            let digits_end = bytes[digits_begin..]
+                .iter()
+                .position(|&byte| !self.radix.is_digit(byte))
+                .map_or(bytes.len(), |len| digits_begin + len);
+
+            if digits_end > digits_begin && bytes.get(digits_end) == Some(&b')') {
+                // continue behind the closing parenthesis
+                self.i = digits_end + 1;
+                return Some((digits_begin as u16, digits_end as u16));
+            }
+            // the digits can't contain another opening parenthesis
+            self.i = digits_end.max(digits_begin);
+        }
+        None
+    }
+}
+
+/// Returns the byte indices of all sequences of ASCII digits in the filename, such as the
+/// ones of `01`, `3`, and `2` in `S01E3 - part (2).mkv`. The end is exclusive.
+pub(crate) fn digit_runs(filename: &str) -> Vec<(u16, u16)> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for (index, byte) in filename.bytes().enumerate().chain([(filename.len(), b' ')]) {
+        match (start, byte.is_ascii_digit()) {
+            (None, true) => start = Some(index),
+            (Some(from), false) => {
+                runs.push((from as u16, index as u16));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    runs
+}
+
+/// Uses the actual filename and the indices obtained by [`number_group_indices`]
 /// to get the prefix before the (...)-group and the suffix behind the (...)-group.
 /// * `actual_filename`: Actual filename, like "paris (100)" (without extension).
 fn get_filename_prefix_and_suffix(actual_filename: &str, (begin, end): (u16, u16)) -> (&str, &str) {
@@ -307,13 +659,28 @@ fn get_filename_prefix_and_suffix(actual_filename: &str, (begin, end): (u16, u16
 mod tests {
     use super::*;
 
+    /// Returns the indices of the number group of the default scheme.
+    fn parentheses_indices(actual_filename: &str) -> Option<(u16, u16)> {
+        number_group_indices(actual_filename, &Parentheses::default()).map(|(indices, _)| indices)
+    }
+
+    #[test]
+    fn test_digit_runs() {
+        assert_eq!(
+            vec![(1, 3), (4, 5), (14, 15)],
+            digit_runs("S01E3 - part (2).mkv")
+        );
+        assert_eq!(vec![(0, 4)], digit_runs("2022"));
+        assert!(digit_runs("notes.txt").is_empty());
+    }
+
     #[test]
-    fn test_get_number_group_indices_from_actual_filename() {
+    fn test_parentheses_number_group_indices() {
         let input1 = "img (100)";
         let input2 = "img (1) (100)";
         let input3 = "img (1) 100)";
 
-        let actual1 = get_number_group_indices_from_actual_filename(input1).unwrap();
+        let actual1 = parentheses_indices(input1).unwrap();
         assert_eq!(
             5, actual1.0,
             "Number parentheses group starts at index 4 (inclusive)"
@@ -323,10 +690,10 @@ mod tests {
             "Number parentheses group ends at index 9 (exclusive)"
         );
 
-        let actual2 = get_number_group_indices_from_actual_filename(input2);
-        assert!(actual2.is_err());
+        let actual2 = parentheses_indices(input2);
+        assert!(actual2.is_none());
 
-        let actual3 = get_number_group_indices_from_actual_filename(input3).unwrap();
+        let actual3 = parentheses_indices(input3).unwrap();
         assert_eq!(
             5, actual3.0,
             "Number parentheses group starts at index 4 (inclusive)"
@@ -337,18 +704,156 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "regex")]
+    fn test_get_number_group_indices_parser_equals_regex() {
+        let regex = Regex::new(r"\(([0-9]+)\)").unwrap();
+        let inputs = [
+            "img (100).jpg",
+            "img ((100).jpg",
+            "img (1)(2).jpg",
+            "img (1a) (2).jpg",
+            "img ().jpg",
+            "img (12",
+            "(7)",
+            "img.jpg",
+            "über (3) ä.jpg",
+        ];
+        for input in inputs {
+            assert_eq!(
+                number_group_indices(input, &regex).map(|(indices, _)| indices),
+                parentheses_indices(input),
+                "parser and regex must behave the same for '{}'",
+                input
+            );
+        }
+
+        let regex = Regex::new(r"^Img_([0-9]+)\.jpg$").unwrap();
+        let file_info = FileInfo::new_with_regex("Img_42.jpg", &regex).unwrap();
+        assert_eq!(42, file_info.number_group_value());
+        assert_eq!("Img_", file_info.filename_prefix());
+        assert_eq!(".jpg", file_info.filename_suffix());
+    }
+
+    #[test]
+    fn test_hexadecimal_number_group() {
+        assert!(FileInfo::new("dump (1f).bin").is_err());
+        let file_info =
+            FileInfo::new_with_radix(Path::new("dump (1f).bin"), Radix::Hexadecimal).unwrap();
+        assert_eq!(31, file_info.number_group_value());
+        assert_eq!(2, file_info.count_digits(31));
+        let advice = FileInfoWithRenameAdvice::new(file_info, 3);
+        assert_eq!(Some("dump (01f).bin"), advice.new_filename());
+
+        let file_info =
+            FileInfo::new_with_radix(Path::new("dump (A).bin"), Radix::Hexadecimal).unwrap();
+        let advice = FileInfoWithRenameAdvice::new_with_max_digits(
+            file_info,
+            171,
+            3,
+            1,
+            &Parentheses::default(),
+            &[],
+        );
+        assert_eq!(Some("dump (0AB).bin"), advice.new_filename());
+    }
+
+    #[test]
+    fn test_ord() {
+        let mut files = [
+            "b (1).jpg",
+            "a (2).jpg",
+            "a (1).png",
+            "a (1).jpg",
+            "a (01).jpg",
+        ]
+        .map(|name| FileInfo::new(name).unwrap());
+        files.sort();
+        let names = files
+            .iter()
+            .map(|file| file.original_filename())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            [
+                "a (01).jpg",
+                "a (1).jpg",
+                "a (1).png",
+                "b (1).jpg",
+                "a (2).jpg"
+            ],
+            names.as_slice()
+        );
+        assert_ne!(Ordering::Equal, files[0].cmp(&files[1]));
+    }
+
+    #[test]
+    fn test_number_group_str() {
+        let file_info = FileInfo::new("paris (007).jpg").unwrap();
+        assert_eq!("007", file_info.number_group_str());
+        assert_eq!(7, file_info.number_group_value());
+        assert_eq!(3, file_info.existing_padding_width());
+        assert_eq!(
+            1,
+            FileInfo::new("paris (7).jpg")
+                .unwrap()
+                .existing_padding_width()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn test_second_number_group() {
+        let regex = Regex::new(r"S([0-9]+)E([0-9]+)").unwrap();
+        let file_info = FileInfo::new_with_regex("Show S1E3 - Pilot.mkv", &regex).unwrap();
+        assert_eq!(1, file_info.number_group_value());
+        assert_eq!(Some(3), file_info.second_number_group_value());
+        assert_eq!("Show S", file_info.filename_prefix());
+        assert_eq!(Some("E"), file_info.filename_infix());
+        assert_eq!(" - Pilot.mkv", file_info.filename_suffix());
+
+        let advice =
+            FileInfoWithRenameAdvice::new_with_max_digits(file_info.clone(), 1, 2, 3, &regex, &[]);
+        assert_eq!(Some("Show S01E003 - Pilot.mkv"), advice.new_filename());
+        let advice =
+            FileInfoWithRenameAdvice::new_with_max_digits(file_info.clone(), 7, 2, 1, &regex, &[]);
+        assert_eq!(Some("Show S07E3 - Pilot.mkv"), advice.new_filename());
+        // without explicit max digits, the second number group stays as it is
+        let advice = FileInfoWithRenameAdvice::new(file_info, 2);
+        assert_eq!(Some("Show S01E3 - Pilot.mkv"), advice.new_filename());
+
+        assert!(
+            FileInfo::new_with_regex("Show S1E3.mkv", &regex).unwrap()
+                < FileInfo::new_with_regex("Show S1E10.mkv", &regex).unwrap()
+        );
+    }
+
     #[test]
     fn test_get_filename_prefix_and_suffix() {
         let input1 = "img (100).jpg";
-        let indices1 = get_number_group_indices_from_actual_filename(input1).unwrap();
+        let indices1 = parentheses_indices(input1).unwrap();
         let (prefix1, suffix1) = get_filename_prefix_and_suffix(input1, indices1);
         assert_eq!("img (", prefix1);
         assert_eq!(").jpg", suffix1);
 
         let input2 = "(100) foobar.png";
-        let indices2 = get_number_group_indices_from_actual_filename(input2).unwrap();
+        let indices2 = parentheses_indices(input2).unwrap();
         let (prefix2, suffix2) = get_filename_prefix_and_suffix(input2, indices2);
         assert_eq!("(", prefix2);
         assert_eq!(") foobar.png", suffix2);
     }
+
+    #[test]
+    fn test_rename_advice_with_transforms() {
+        let advice = FileInfoWithRenameAdvice::new(FileInfo::new("img (007).jpg").unwrap(), 3);
+        assert!(advice.is_already_properly_named());
+
+        let transforms: Vec<Box<dyn FilenameTransform>> =
+            vec![Box::new(crate::transform::UppercaseExtension)];
+        let advice = FileInfoWithRenameAdvice::new_with_transforms(
+            FileInfo::new("img (100).jpg").unwrap(),
+            3,
+            &transforms,
+        );
+        assert_eq!(Some("img (100).JPG"), advice.new_filename());
+    }
 }
@@ -24,51 +24,516 @@ SOFTWARE.
 //! Utility functions to interact with the file system. Main function of this module is
 //! [`read_directory_flat`].
 
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
 
-/// Reads all matching files for the purpose of this library from the specified directory. The
-/// search depth is 0, i.e., the function doesn't look for files in subdirectories.
+/// Kind of a directory entry. See [`read_directory_flat`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    /// A regular file.
+    File,
+    /// A directory.
+    Directory,
+    /// A symbolic link. Symlinks are not followed, no matter where they point to.
+    Symlink,
+    /// Everything else, such as sockets, FIFOs, or device files.
+    Special,
+}
+
+impl EntryKind {
+    /// Classifies the entry by its file type, which doesn't follow symlinks.
+    fn from_file_type(typ: fs::FileType) -> Self {
+        if typ.is_symlink() {
+            Self::Symlink
+        } else if typ.is_file() {
+            Self::File
+        } else if typ.is_dir() {
+            Self::Directory
+        } else {
+            Self::Special
+        }
+    }
+}
+
+/// Reads all entries of the specified directory and classifies them. The search depth is 0,
+/// i.e., the function doesn't look for files in subdirectories.
 ///
 /// # Parameters
 /// * `dir_path` Directory to search for files. Expected to be a directory with files in the form
-///              `Img (1).jpg`, `Img (2).jpg`, ..., `Img (99).jpg`, ... `Img (124).jpg`.
+///   `Img (1).jpg`, `Img (2).jpg`, ..., `Img (99).jpg`, ... `Img (124).jpg`.
 ///
 /// # Return Type
-/// The returned type is a sorted vector of [`PathBuf`].
-pub fn read_directory_flat<P: AsRef<Path>>(dir_path: P) -> std::io::Result<Vec<PathBuf>> {
-    let mut files = Vec::new();
+/// The returned type is a vector of all entries, including the ones that are not a
+/// [`EntryKind::File`], sorted by path.
+pub fn read_directory_flat<P: AsRef<Path>>(
+    dir_path: P,
+) -> std::io::Result<Vec<(PathBuf, EntryKind)>> {
+    let mut entries = Vec::new();
 
-    let dir_handle = fs::read_dir(dir_path)?;
+    let dir_path = dir_path.as_ref();
+    let dir_handle = fs::read_dir(long_path(dir_path))?;
 
-    for path in dir_handle {
+    for entry in dir_handle {
         // errors only if during the process the file system gets changed or a
         // similar weird situation occurs
-        let entry = path?;
+        let entry = entry?;
+        let kind = EntryKind::from_file_type(entry.file_type()?);
+        // not `entry.path()`, which would contain the prefix of `long_path`
+        entries.push((dir_path.join(entry.file_name()), kind))
+    }
+
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(entries)
+}
+
+/// Classifies the entry at the given path like [`read_directory_flat`], i.e., without
+/// following symlinks.
+pub fn entry_kind<P: AsRef<Path>>(path: P) -> std::io::Result<EntryKind> {
+    let metadata = fs::symlink_metadata(long_path(path.as_ref()))?;
+    Ok(EntryKind::from_file_type(metadata.file_type()))
+}
+
+/// Returns the path in a form that the file system APIs accept even if it exceeds `MAX_PATH`
+/// (260 characters) on Windows, i.e., absolute with the `\\?\` prefix. Without it, deep
+/// archives fail with misleading IO errors, such as "file not found". Short paths and paths
+/// that already have a verbatim or device prefix are returned unchanged.
+///
+/// The result is only meant to be passed to the file system, not to be displayed.
+#[cfg(windows)]
+pub(crate) fn long_path(path: &Path) -> Cow<'_, Path> {
+    use std::ffi::OsString;
+    use std::path::{Component, Prefix};
+
+    /// Directories are limited to `MAX_PATH - 12` to leave room for an 8.3 filename. The
+    /// length in bytes overestimates non-ASCII paths, which only prefixes them earlier.
+    const MAX_DIR_PATH: usize = 260 - 12;
+
+    if path.as_os_str().len() < MAX_DIR_PATH {
+        return Cow::Borrowed(path);
+    }
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else if let Ok(cwd) = std::env::current_dir() {
+        cwd.join(path)
+    } else {
+        return Cow::Borrowed(path);
+    };
+
+    // verbatim paths are not normalized by Windows, hence, this must be done here
+    let mut long_path = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::Prefix(prefix) => {
+                let mut verbatim = OsString::from(r"\\?\");
+                match prefix.kind() {
+                    Prefix::Disk(_) => verbatim.push(prefix.as_os_str()),
+                    Prefix::UNC(server, share) => {
+                        verbatim.push(r"UNC\");
+                        verbatim.push(server);
+                        verbatim.push(r"\");
+                        verbatim.push(share);
+                    }
+                    _ => return Cow::Borrowed(path),
+                }
+                long_path.push(verbatim);
+            }
+            Component::RootDir => long_path.push(r"\"),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                long_path.pop();
+            }
+            Component::Normal(name) => long_path.push(name),
+        }
+    }
+    Cow::Owned(long_path)
+}
+
+/// Returns the path unchanged. Only Windows limits the length of paths to `MAX_PATH`.
+#[cfg(not(windows))]
+pub(crate) const fn long_path(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
+/// Renames the file like [`fs::rename`] but gives up after the timeout, e.g., if a network
+/// mount hangs. A rename can't be cancelled, hence, it goes on in the background and may still
+/// succeed later. Thus, after the timeout, the result depends on the paths: if `from` is gone
+/// and `to` exists, the rename finished just in time. Otherwise, the outcome is indeterminate
+/// and the rename fails with [`std::io::ErrorKind::TimedOut`]. See [`timed_out_rename`].
+pub(crate) fn rename_with_timeout(
+    from: &Path,
+    to: &Path,
+    timeout: Option<Duration>,
+) -> std::io::Result<()> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return fs::rename(long_path(from), long_path(to)),
+    };
+    let (from, to) = (long_path(from), long_path(to));
+    let (thread_from, thread_to) = (from.to_path_buf(), to.to_path_buf());
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        // the receiver is gone if the rename timed out
+        let _ = sender.send(fs::rename(thread_from, thread_to));
+    });
+    receiver
+        .recv_timeout(timeout)
+        .unwrap_or_else(|_| timed_out_rename(&from, &to, timeout))
+}
+
+/// Returns the result of a rename that didn't report back within the timeout. See
+/// [`rename_with_timeout`].
+fn timed_out_rename(from: &Path, to: &Path, timeout: Duration) -> std::io::Result<()> {
+    if from.symlink_metadata().is_err() && to.symlink_metadata().is_ok() {
+        return Ok(());
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::TimedOut,
+        format!(
+            "the rename didn't finish within {:?} and may still finish later",
+            timeout
+        ),
+    ))
+}
+
+/// Flushes the entries of the directory, such as renames, to the storage device with
+/// `fsync`. Does nothing on other platforms than Unix, where directories can't be synced this
+/// way.
+pub(crate) fn sync_directory(path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    fs::File::open(long_path(path))?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Replaces the symlink with one that points at `target`. The new symlink is created next to
+/// the old one and renamed over it, so that the link never disappears. Fails with
+/// [`std::io::ErrorKind::Unsupported`] on platforms without symlinks.
+// `Result::inspect_err` is not available on the MSRV
+#[allow(clippy::manual_inspect)]
+pub(crate) fn replace_symlink(link: &Path, target: &Path) -> std::io::Result<()> {
+    let name = link.file_name().unwrap_or_default().to_string_lossy();
+    let temporary_link = link.with_file_name(format!(".nflz-{}.tmp", name));
+    create_symlink(link, target, &temporary_link)?;
+    fs::rename(long_path(&temporary_link), long_path(link)).map_err(|err| {
+        let _ = fs::remove_file(long_path(&temporary_link));
+        err
+    })
+}
+
+/// Creates the symlink `path` that points at `target` like the symlink `link`.
+#[cfg(unix)]
+fn create_symlink(_link: &Path, target: &Path, path: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, long_path(path))
+}
+
+/// Creates the symlink `path` that points at `target` like the symlink `link`, i.e., as a
+/// directory symlink if `link` is one.
+#[cfg(windows)]
+fn create_symlink(link: &Path, target: &Path, path: &Path) -> std::io::Result<()> {
+    use std::os::windows::fs::FileTypeExt;
+    if fs::symlink_metadata(long_path(link))?
+        .file_type()
+        .is_symlink_dir()
+    {
+        std::os::windows::fs::symlink_dir(target, long_path(path))
+    } else {
+        std::os::windows::fs::symlink_file(target, long_path(path))
+    }
+}
+
+/// Fails because there are no symlinks on this platform.
+#[cfg(not(any(unix, windows)))]
+fn create_symlink(_link: &Path, _target: &Path, _path: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "symlinks are not supported on this platform",
+    ))
+}
+
+/// Returns whether the file is hidden, i.e., its name starts with a dot, such as `.DS_Store`,
+/// or, on Windows, it has the hidden attribute.
+pub fn is_hidden<P: AsRef<Path>>(path: P) -> bool {
+    let path = path.as_ref();
+    let dotfile = path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+    #[cfg(windows)]
+    let hidden_attribute = {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        fs::symlink_metadata(long_path(path))
+            .is_ok_and(|metadata| metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0)
+    };
+    #[cfg(not(windows))]
+    let hidden_attribute = false;
+    dotfile || hidden_attribute
+}
+
+/// Names of the metadata files and directories that operating systems and file managers
+/// create on their own, such as `.DS_Store` of the macOS Finder or `Thumbs.db` of the Windows
+/// Explorer. Compared case-insensitively.
+const SYSTEM_FILES: [&str; 14] = [
+    ".DS_Store",
+    ".AppleDouble",
+    ".localized",
+    ".Spotlight-V100",
+    ".Trashes",
+    ".fseventsd",
+    ".TemporaryItems",
+    "Icon\r",
+    "Thumbs.db",
+    "ehthumbs.db",
+    "ehthumbs_vista.db",
+    "desktop.ini",
+    "$RECYCLE.BIN",
+    ".directory",
+];
+
+/// Returns whether the entry is junk that the operating system or a file manager created,
+/// such as `.DS_Store`, `Thumbs.db`, or `desktop.ini`. See [`SYSTEM_FILES`].
+pub fn is_system_file<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref().file_name().is_some_and(|name| {
+        let name = name.to_string_lossy();
+        SYSTEM_FILES
+            .iter()
+            .any(|system_file| system_file.eq_ignore_ascii_case(&name))
+    })
+}
+
+/// Returns the directory and all its subdirectories, recursively, sorted by path.
+///
+/// Directories whose name matches one of the wildcard patterns in `exclude_dirs`, such as `.git` or
+/// `thumb*`, are skipped together with their subdirectories. In the patterns, `*` matches any
+/// sequence of characters and `?` matches exactly one character. Symlinks to directories are
+/// not followed.
+pub fn find_directories_recursive<P: AsRef<Path>>(
+    dir_path: P,
+    exclude_dirs: &[String],
+) -> std::io::Result<Vec<PathBuf>> {
+    let mut dirs = vec![PathBuf::from(dir_path.as_ref())];
+    let mut i = 0;
+    while i < dirs.len() {
+        for entry in fs::read_dir(long_path(&dirs[i]))? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let path = dirs[i].join(entry.file_name());
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if exclude_dirs
+                .iter()
+                .any(|pattern| crate::wildcard::matches(pattern, &name))
+            {
+                log::debug!("Excluding directory '{}'", path.display());
+                continue;
+            }
+            dirs.push(path);
+        }
+        i += 1;
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// State of a single directory entry at the time of a [`DirectorySnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EntryState {
+    /// Inode number of the entry. Only available on Unix.
+    inode: Option<u64>,
+    /// Size in bytes.
+    len: u64,
+    /// Last modification time, if supported by the platform.
+    modified: Option<SystemTime>,
+}
 
-        let typ = entry.file_type()?;
+impl EntryState {
+    /// Creates the state from the metadata of an entry.
+    fn from_metadata(metadata: &fs::Metadata) -> Self {
+        #[cfg(unix)]
+        let inode = {
+            use std::os::unix::fs::MetadataExt;
+            Some(metadata.ino())
+        };
+        #[cfg(not(unix))]
+        let inode = None;
+        Self {
+            inode,
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        }
+    }
+}
 
-        if !typ.is_file() {
-            break;
+/// Snapshot of the names and states (inode, size, mtime) of all entries of a directory.
+/// Used to detect external modifications between planning and renaming.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectorySnapshot {
+    /// State of all entries by their name.
+    entries: BTreeMap<String, EntryState>,
+}
+
+impl DirectorySnapshot {
+    /// Captures the current state of all entries of the directory (not recursive).
+    pub fn capture<P: AsRef<Path>>(dir_path: P) -> std::io::Result<Self> {
+        let mut entries = BTreeMap::new();
+        for entry in fs::read_dir(long_path(dir_path.as_ref()))? {
+            let entry = entry?;
+            // doesn't follow symlinks
+            let metadata = entry.metadata()?;
+            entries.insert(
+                entry.file_name().to_string_lossy().to_string(),
+                EntryState::from_metadata(&metadata),
+            );
         }
+        Ok(Self { entries })
+    }
 
-        files.push(entry.path())
+    /// Returns the names of all entries, sorted.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
     }
 
-    files.sort();
+    /// Returns all differences between this (older) snapshot and the newer one, sorted by
+    /// filename.
+    pub fn diff(&self, newer: &Self) -> Vec<DirectoryChange> {
+        let mut changes = Vec::new();
+        for (name, state) in &self.entries {
+            match newer.entries.get(name) {
+                None => changes.push(DirectoryChange::Removed(name.clone())),
+                Some(newer_state) if newer_state != state => {
+                    changes.push(DirectoryChange::Modified(name.clone()))
+                }
+                _ => (),
+            }
+        }
+        for name in newer.entries.keys() {
+            if !self.entries.contains_key(name) {
+                changes.push(DirectoryChange::Added(name.clone()));
+            }
+        }
+        changes.sort_by(|a, b| a.filename().cmp(b.filename()));
+        changes
+    }
+}
+
+/// A difference between two snapshots of the same directory. See
+/// [`crate::NFLZAssistant::check_directory_unchanged`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DirectoryChange {
+    /// A new entry with the given filename was added.
+    Added(String),
+    /// The entry with the given filename was removed.
+    Removed(String),
+    /// The entry with the given filename was modified or replaced.
+    Modified(String),
+}
+
+impl DirectoryChange {
+    /// Returns the filename of the affected entry.
+    pub fn filename(&self) -> &str {
+        match self {
+            Self::Added(name) | Self::Removed(name) | Self::Modified(name) => name,
+        }
+    }
+}
 
-    Ok(files)
+impl Display for DirectoryChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added(name) => write!(f, "added '{}'", name),
+            Self::Removed(name) => write!(f, "removed '{}'", name),
+            Self::Modified(name) => write!(f, "modified '{}'", name),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::file_info::path_to_filename;
+    use crate::testutil::FixtureBuilder;
+
+    #[test]
+    fn test_long_path() {
+        let short = Path::new("./test-resources");
+        assert_eq!(short, long_path(short));
+
+        let long = PathBuf::from("./test-resources").join("a".repeat(300));
+        #[cfg(not(windows))]
+        assert_eq!(long, long_path(&long));
+        #[cfg(windows)]
+        {
+            let long_path = long_path(&long);
+            assert!(long_path.to_string_lossy().starts_with(r"\\?\"));
+            assert!(long_path.ends_with(Path::new("test-resources").join("a".repeat(300))));
+        }
+    }
+
+    #[test]
+    fn test_rename_with_timeout() {
+        let fixture = FixtureBuilder::new("a ").count(2).create().unwrap();
+        let dir = fixture.path();
+        rename_with_timeout(&dir.join("a (1).jpg"), &dir.join("b (1).jpg"), None).unwrap();
+        rename_with_timeout(
+            &dir.join("a (2).jpg"),
+            &dir.join("b (2).jpg"),
+            Some(Duration::from_secs(60)),
+        )
+        .unwrap();
+        assert!(dir.join("b (1).jpg").is_file());
+        assert!(dir.join("b (2).jpg").is_file());
+
+        let err = rename_with_timeout(
+            &dir.join("a (3).jpg"),
+            &dir.join("b (3).jpg"),
+            Some(Duration::from_secs(60)),
+        )
+        .unwrap_err();
+        assert_eq!(std::io::ErrorKind::NotFound, err.kind());
+
+        // the rename is still pending, or finished just in time
+        let timeout = Duration::from_secs(1);
+        let err =
+            timed_out_rename(&dir.join("b (1).jpg"), &dir.join("c (1).jpg"), timeout).unwrap_err();
+        assert_eq!(std::io::ErrorKind::TimedOut, err.kind());
+        timed_out_rename(&dir.join("a (1).jpg"), &dir.join("b (1).jpg"), timeout).unwrap();
+    }
+
+    #[test]
+    fn test_sync_directory() {
+        let fixture = FixtureBuilder::new("a ").count(2).create().unwrap();
+        sync_directory(fixture.path()).unwrap();
+        #[cfg(unix)]
+        assert!(sync_directory(&fixture.path().join("missing")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_replace_symlink() {
+        let fixture = FixtureBuilder::new("a ").count(2).create().unwrap();
+        let link = fixture.path().join("latest");
+        std::os::unix::fs::symlink("a (1).jpg", &link).unwrap();
+        replace_symlink(&link, Path::new("a (2).jpg")).unwrap();
+        assert_eq!(PathBuf::from("a (2).jpg"), fs::read_link(&link).unwrap());
+        assert_eq!(3, read_directory_flat(fixture.path()).unwrap().len());
+    }
 
     #[test]
     fn test_read_directory_flat() {
         let files = read_directory_flat("./test-resources").unwrap();
         assert_eq!(12, files.len());
+        assert!(files.iter().all(|(_, kind)| *kind == EntryKind::File));
         let mut expected = vec![
             "invalid (100) (19231).jpg",
             "paris (1).jpg",
@@ -86,9 +551,85 @@ mod tests {
         expected.sort();
         let mut actual = files
             .iter()
-            .map(|path| path_to_filename(path))
+            .map(|(path, _)| path_to_filename(path))
             .collect::<Vec<_>>();
         actual.sort();
         assert_eq!(actual.as_slice(), expected);
     }
+
+    #[test]
+    fn test_read_directory_flat_classifies_entries() {
+        let fixture = FixtureBuilder::new("b ").count(3).create().unwrap();
+        let dir = fixture.path();
+        // sorts before all files; must not hide any of them
+        fs::create_dir(dir.join("a")).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.join("b (1).jpg"), dir.join("c (1).jpg")).unwrap();
+
+        let entries = read_directory_flat(dir)
+            .unwrap()
+            .into_iter()
+            .map(|(path, kind)| (path_to_filename(&path).to_string(), kind))
+            .collect::<Vec<_>>();
+        let mut expected = vec![
+            ("a".to_string(), EntryKind::Directory),
+            ("b (1).jpg".to_string(), EntryKind::File),
+            ("b (2).jpg".to_string(), EntryKind::File),
+            ("b (3).jpg".to_string(), EntryKind::File),
+        ];
+        #[cfg(unix)]
+        expected.push(("c (1).jpg".to_string(), EntryKind::Symlink));
+        assert_eq!(expected, entries);
+    }
+
+    #[test]
+    fn test_find_directories_recursive() {
+        let dir = std::env::temp_dir().join("nflz-test-find-directories-recursive");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("2022/paris/thumbnails")).unwrap();
+        fs::create_dir_all(dir.join("2022/london")).unwrap();
+        fs::create_dir_all(dir.join(".git/objects")).unwrap();
+        fs::write(dir.join("2022/a (1).jpg"), "a").unwrap();
+
+        let dirs = find_directories_recursive(&dir, &[".git".to_string(), "thumb*".to_string()])
+            .unwrap()
+            .into_iter()
+            .map(|path| path.strip_prefix(&dir).unwrap().to_path_buf())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                PathBuf::from(""),
+                PathBuf::from("2022"),
+                PathBuf::from("2022/london"),
+                PathBuf::from("2022/paris"),
+            ],
+            dirs
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_directory_snapshot_diff() {
+        let fixture = FixtureBuilder::new("a ").count(3).create().unwrap();
+        let dir = fixture.path();
+
+        let snapshot = DirectorySnapshot::capture(dir).unwrap();
+        assert!(snapshot
+            .diff(&DirectorySnapshot::capture(dir).unwrap())
+            .is_empty());
+
+        fs::remove_file(dir.join("a (1).jpg")).unwrap();
+        fs::write(dir.join("a (2).jpg"), "modified").unwrap();
+        fs::write(dir.join("a (4).jpg"), "a").unwrap();
+        let changes = snapshot.diff(&DirectorySnapshot::capture(dir).unwrap());
+        assert_eq!(
+            vec![
+                DirectoryChange::Removed("a (1).jpg".to_string()),
+                DirectoryChange::Modified("a (2).jpg".to_string()),
+                DirectoryChange::Added("a (4).jpg".to_string()),
+            ],
+            changes
+        );
+    }
 }
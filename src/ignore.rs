@@ -0,0 +1,247 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for the ignore files that protect entries of the working directory from being
+//! renamed. See [`IgnoreRules`].
+
+use crate::error::NFLZError;
+use crate::wildcard::matches_gitignore;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Name of the ignore file inside the working directory.
+pub(crate) const IGNORE_FILENAME: &str = ".nflzignore";
+
+/// Name of the ignore files of git.
+const GITIGNORE_FILENAME: &str = ".gitignore";
+
+/// A single pattern of an ignore file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    /// Wildcard pattern in gitignore syntax. See [`matches_gitignore`].
+    pattern: String,
+    /// Whether the rule re-includes matching entries (`!pattern`).
+    negated: bool,
+    /// Whether the rule only matches directories (`pattern/`).
+    only_directories: bool,
+    /// Whether the pattern contains a `/` and thus matches the path relative to the directory
+    /// of its ignore file instead of the name.
+    anchored: bool,
+    /// Amount of directories between the top directory of the [`IgnoreRules`] and the
+    /// directory of the ignore file.
+    depth: usize,
+}
+
+/// The rules of the ignore files in gitignore syntax: the `.nflzignore` file of the working
+/// directory and, optionally, the `.gitignore` files of the repository that contains the
+/// working directory. Matching entries of the working directory are never touched. See
+/// [`crate::NFLZAssistantBuilder::respect_ignore_file`] and
+/// [`crate::NFLZAssistantBuilder::respect_gitignore`].
+///
+/// Supported are comments (`#`), negations (`!`), patterns that only match directories
+/// (trailing `/`), patterns relative to the directory of the ignore file (with a `/`), the
+/// wildcards `*`, `?`, `**`, and `[...]`, and escapes with a backslash. The last matching
+/// rule wins, and the entries of an ignored directory are always ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct IgnoreRules {
+    /// Names of the directories from the root of the repository to the working directory.
+    /// Empty if the `.gitignore` files are not respected.
+    dirs: Vec<String>,
+    /// All rules, from the top directory to the working directory and in the order of the
+    /// files.
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    /// Reads the `.nflzignore` file of the directory if `nflzignore` is set, and the
+    /// `.gitignore` files of the repository, including `.git/info/exclude`, if `gitignore`
+    /// is set. Missing files have no rules.
+    pub(crate) fn read(dir: &Path, nflzignore: bool, gitignore: bool) -> Result<Self, NFLZError> {
+        let mut rules = Self::default();
+        if let Some((root, dir)) = gitignore.then(|| find_repository(dir)).flatten() {
+            rules.dirs = dir
+                .strip_prefix(&root)
+                .unwrap_or(&dir)
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            let git_dir = root.join(".git");
+            if git_dir.is_dir() {
+                rules.read_file(&git_dir.join("info").join("exclude"), 0)?;
+            }
+            let mut dir = root;
+            for depth in 0..=rules.dirs.len() {
+                if depth > 0 {
+                    dir.push(&rules.dirs[depth - 1]);
+                }
+                rules.read_file(&dir.join(GITIGNORE_FILENAME), depth)?;
+            }
+        }
+        if nflzignore {
+            rules.read_file(&dir.join(IGNORE_FILENAME), rules.dirs.len())?;
+        }
+        Ok(rules)
+    }
+
+    /// Adds the rules of the ignore file, if it exists, for the directory at the given depth.
+    fn read_file(&mut self, path: &Path, depth: usize) -> Result<(), NFLZError> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => {
+                self.add(&content, depth);
+                Ok(())
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(NFLZError::CantReadIgnoreFile(path.to_path_buf(), err)),
+        }
+    }
+
+    /// Adds the rules of the content of an ignore file for the directory at the given depth.
+    fn add(&mut self, content: &str, depth: usize) {
+        let rules = content.lines().filter_map(|line| {
+            let mut line = line;
+            // trailing spaces are ignored, unless they are escaped
+            while line.ends_with(' ') && !line.ends_with("\\ ") {
+                line = &line[..line.len() - 1];
+            }
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let (negated, line) = line
+                .strip_prefix('!')
+                .map_or((false, line), |line| (true, line));
+            let (only_directories, line) = line
+                .strip_suffix('/')
+                .map_or((false, line), |line| (true, line));
+            Some(Rule {
+                pattern: line.strip_prefix('/').unwrap_or(line).to_string(),
+                negated,
+                only_directories,
+                anchored: line.contains('/'),
+                depth,
+            })
+        });
+        self.rules.extend(rules);
+    }
+
+    /// Returns true if the entry of the working directory with the given name is ignored.
+    pub(crate) fn is_ignored(&self, name: &str, is_directory: bool) -> bool {
+        let mut path = self.dirs.iter().map(String::as_str).collect::<Vec<_>>();
+        // the entries of an ignored directory are ignored, too
+        if (1..=path.len()).any(|len| self.matches(&path[..len], true)) {
+            return true;
+        }
+        path.push(name);
+        self.matches(&path, is_directory)
+    }
+
+    /// Returns true if the last matching rule for the path, given by the names of the
+    /// directories from the top directory, ignores it.
+    fn matches(&self, path: &[&str], is_directory: bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                rule.depth < path.len()
+                    && (is_directory || !rule.only_directories)
+                    && if rule.anchored {
+                        matches_gitignore(&rule.pattern, &path[rule.depth..].join("/"))
+                    } else {
+                        matches_gitignore(&rule.pattern, path[path.len() - 1])
+                    }
+            })
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+/// Returns the root of the git repository that contains the directory, i.e., the next
+/// ancestor with a `.git` entry, and the canonicalized directory.
+fn find_repository(dir: &Path) -> Option<(PathBuf, PathBuf)> {
+    let dir = dir.canonicalize().ok()?;
+    let root = dir
+        .ancestors()
+        .find(|ancestor| ancestor.join(".git").exists())?
+        .to_path_buf();
+    Some((root, dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::FixtureBuilder;
+
+    #[test]
+    fn test_ignore_rules() {
+        let mut rules = IgnoreRules::default();
+        rules.add(
+            "# originals of the camera\n\
+             DSC_*\n\
+             *.xmp\n\
+             !keep.xmp\n\
+             \n\
+             /drafts/\n\
+             **/cover (1).jpg   \n\
+             \\#notes (1).txt\n\
+             raw/*.jpg\n",
+            0,
+        );
+        assert!(rules.is_ignored("DSC_0001 (1).jpg", false));
+        assert!(rules.is_ignored("paris (1).xmp", false));
+        assert!(!rules.is_ignored("keep.xmp", false));
+        assert!(rules.is_ignored("drafts", true));
+        assert!(!rules.is_ignored("drafts", false));
+        assert!(rules.is_ignored("cover (1).jpg", false));
+        assert!(rules.is_ignored("#notes (1).txt", false));
+        assert!(!rules.is_ignored("paris (1).jpg", false));
+        assert!(!rules.is_ignored("raw", true));
+    }
+
+    #[test]
+    fn test_gitignore() {
+        let repository = FixtureBuilder::new("a ").count(0).create().unwrap();
+        let root = repository.path();
+        let dir = root.join("photos");
+        std::fs::create_dir_all(root.join(".git").join("info")).unwrap();
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(root.join(".git/info/exclude"), "e*\n").unwrap();
+        std::fs::write(root.join(".gitignore"), "*.tmp\nphotos/b*\n!keep.tmp\n").unwrap();
+        std::fs::write(dir.join(".gitignore"), "c*\n").unwrap();
+        std::fs::write(dir.join(IGNORE_FILENAME), "d*\n").unwrap();
+
+        let rules = IgnoreRules::read(&dir, true, true).unwrap();
+        for name in ["x.tmp", "b (1).jpg", "c (1).jpg", "d (1).jpg", "e (1).jpg"] {
+            assert!(rules.is_ignored(name, false), "{}", name);
+        }
+        assert!(!rules.is_ignored("keep.tmp", false));
+        assert!(!rules.is_ignored("a (1).jpg", false));
+
+        let rules = IgnoreRules::read(&dir, true, false).unwrap();
+        assert!(rules.is_ignored("d (1).jpg", false));
+        assert!(!rules.is_ignored("c (1).jpg", false));
+
+        // everything inside an ignored directory is ignored
+        std::fs::write(root.join(".gitignore"), "photos/\n").unwrap();
+        let rules = IgnoreRules::read(&dir, true, true).unwrap();
+        assert!(rules.is_ignored("a (1).jpg", false));
+    }
+}
@@ -77,7 +77,9 @@ SOFTWARE.
 //! ```
 //!
 //! # Library Design
-//!
+//! [`NFLZAssistant::new`] scans the directory and computes a new filename for every file with
+//! the default behaviour. Use [`NFLZAssistant::builder`] to customize this, e.g., to append
+//! [`FilenameTransform`]s that are applied on the new filenames after the padding.
 
 #![deny(
     clippy::all,
@@ -99,14 +101,159 @@ SOFTWARE.
 #![deny(rustdoc::all)]
 // #![allow(rustdoc::missing_doc_code_examples)]
 
+/// See [`crate::analysis::ArchiveAnalysis`].
+pub use crate::analysis::ArchiveAnalysis;
+
+/// See [`crate::content::ContentCategory`].
+pub use crate::content::ContentCategory;
+
+/// See [`crate::diagnostics::ParseDiagnostics`].
+pub use crate::diagnostics::{CandidateRejection, NumberGroupCandidate, ParseDiagnostics};
+
+/// See [`crate::confirm::ConfirmationProvider`].
+pub use crate::confirm::{AlwaysYes, ConfirmationProvider, FnConfirmation, StdinConfirmation};
+
 /// See [`crate::error::NFLZError`].
 pub use crate::error::NFLZError;
 
+/// See [`crate::suggestion::Suggestion`].
+pub use crate::suggestion::Suggestion;
+
+/// See [`crate::chapters::ChapterPlan`].
+pub use crate::chapters::ChapterPlan;
+
+/// See [`crate::merge::MergePlan`].
+pub use crate::merge::{MergeMove, MergePlan};
+
 /// See [`crate::nflz::NFLZAssistant`].
 pub use crate::nflz::NFLZAssistant;
 
+/// See [`crate::builder::NFLZAssistantBuilder`].
+pub use crate::builder::NFLZAssistantBuilder;
+
+/// See [`crate::config::DirectoryConfig`].
+pub use crate::config::{DirectoryConfig, CONFIG_FILENAME};
+
+/// See [`crate::event::Event`].
+pub use crate::event::Event;
+
+/// See [`crate::fsutil::DirectoryChange`] and [`crate::fsutil::find_directories_recursive`].
+pub use crate::fsutil::{find_directories_recursive, DirectoryChange};
+
+/// See [`crate::file_info::FileInfo`] and [`crate::file_info::FileInfoWithRenameAdvice`].
+pub use crate::file_info::{FileInfo, FileInfoWithRenameAdvice};
+
+/// See [`crate::plan::RenamePlan`].
+pub use crate::plan::{PlanReport, PlanViolation, PlannedRename, RenamePlan};
+
+/// See [`crate::checkpoint::Checkpoint`] and [`crate::checkpoint::CHECKPOINT_FILENAME`].
+pub use crate::checkpoint::{Checkpoint, CHECKPOINT_FILENAME};
+
+/// See [`crate::provenance::original_filename`].
+#[cfg(feature = "xattr")]
+pub use crate::provenance::{original_filename, ORIGINAL_FILENAME_ATTRIBUTE};
+
+/// See [`crate::probe::needs_work`].
+pub use crate::probe::needs_work;
+
+/// See [`crate::script::ScriptFormat`].
+pub use crate::script::ScriptFormat;
+
+/// See [`crate::roman::RomanNumerals`].
+pub use crate::roman::RomanNumerals;
+
+/// See [`crate::pattern::BuiltinPattern`].
+#[cfg(feature = "regex")]
+pub use crate::pattern::BuiltinPattern;
+
+/// See [`crate::detect::detect_pattern`].
+#[cfg(feature = "regex")]
+pub use crate::detect::{detect_pattern, PatternCandidate, PatternDetection};
+
+/// See [`crate::scheme::NumberingScheme`], [`crate::scheme::NumberGroups`], and
+/// [`crate::scheme::Parentheses`].
+pub use crate::scheme::{NumberGroups, NumberingScheme, Parentheses};
+
+/// See [`crate::unicode::UnicodeNormalization`].
+pub use crate::unicode::UnicodeNormalization;
+
+/// See [`crate::open_files::OpenFilePolicy`].
+pub use crate::open_files::OpenFilePolicy;
+
+/// See [`crate::options::Options`].
+pub use crate::options::Options;
+
+/// See [`crate::warning::PlanWarning`].
+pub use crate::warning::PlanWarning;
+
+/// See [`crate::skip::SkippedFile`] and [`crate::skip::SkipReason`].
+pub use crate::skip::{SkipReason, SkippedFile};
+
+/// See [`crate::explain::Explanation`], [`crate::explain::Verdict`], and
+/// [`crate::explain::ChosenNumberGroup`].
+pub use crate::explain::{ChosenNumberGroup, Explanation, Verdict};
+
+/// See [`crate::snapshot::AssistantSnapshot`].
+pub use crate::snapshot::{AssistantSnapshot, SnapshotEntry};
+
+/// See [`crate::stats::Stats`] and [`crate::stats::PhaseTimings`].
+pub use crate::stats::{PhaseTimings, Stats};
+
+/// See [`crate::sort::SortOrder`] and [`crate::sort::RenumberOrder`].
+pub use crate::sort::{RenumberOrder, SortOrder};
+
+/// See [`crate::transform::FilenameTransform`].
+pub use crate::transform::{
+    BuiltinTransform, FilenameTransform, FnTransform, LowercaseExtension, ReplaceIllegalCharacters,
+    ReplacePrefix, ReplaceSeparator, TrimTrailingDotsAndSpaces, UppercaseExtension,
+    WINDOWS_ILLEGAL_CHARACTERS,
+};
+
+mod analysis;
+mod builder;
+#[cfg(feature = "exif")]
+mod burst;
+mod chapters;
+mod checkpoint;
+mod config;
+mod confirm;
+mod content;
+#[cfg(feature = "regex")]
+mod detect;
+mod diagnostics;
 mod error;
+mod event;
+mod explain;
 mod file_info;
 mod fsutil;
+mod ignore;
+mod manifest;
 mod math;
+mod merge;
 mod nflz;
+mod open_files;
+mod options;
+#[cfg(feature = "regex")]
+mod pattern;
+mod plan;
+mod prefix;
+mod probe;
+#[cfg(feature = "xattr")]
+mod provenance;
+mod references;
+mod roman;
+mod schedule;
+mod scheme;
+mod script;
+mod skip;
+mod snapshot;
+mod sort;
+mod stats;
+mod suggestion;
+mod symlinks;
+#[cfg(any(test, feature = "testutil"))]
+pub mod testutil;
+mod transform;
+mod unicode;
+mod warning;
+mod wildcard;
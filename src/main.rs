@@ -41,117 +41,793 @@ SOFTWARE.
 #![deny(missing_debug_implementations)]
 #![deny(rustdoc::all)]
 
+use crate::cli::args::{CliArgs, EventFormat, ShellIntegrationAction, Subcommand, USAGE};
+use crate::cli::path_style::PathStyle;
+use crate::cli::webhook::RunSummary;
 use log::LevelFilter;
-use nflz::{NFLZAssistant, NFLZError};
-use std::io::stdin;
-use std::path::{Path, PathBuf};
+use nflz::{
+    AlwaysYes, ChapterPlan, Checkpoint, ConfirmationProvider, MergePlan, NFLZAssistant, NFLZError,
+    Options, PhaseTimings, PlannedRename, RenamePlan, ScriptFormat, SortOrder, StdinConfirmation,
+    CHECKPOINT_FILENAME,
+};
+use std::ffi::OsString;
+use std::num::NonZeroUsize;
+use std::path::Path;
 use std::process::exit;
+use std::time::Duration;
+
+#[macro_use]
+mod cli;
+
+/// Exit code for invalid command line arguments. All other non-zero exit codes are the ones of
+/// [`NFLZError::code`].
+const EXIT_CODE_USAGE: i32 = 2;
+
+/// Exit code of `--dry-run` if files would be renamed. Like `git diff --exit-code`, this lets
+/// wrappers distinguish pending changes from an already consistent directory (`0`).
+const EXIT_CODE_PENDING_CHANGES: i32 = 3;
+
+/// Renames between two records of the checkpoint file if `--chunk-size` isn't specified.
+const DEFAULT_CHUNK_SIZE: usize = 1000;
+
+/// Question to confirm the renaming.
+const CONFIRMATION_QUESTION: &str = "\nPlease confirm with 'y' or abort with 'n'
+  NFLZ can't guarantee you 100% safety. Always make a backup first (:
+  But to the best of my knowledge this should work if no catastrophic failure occurs.";
 
 fn main() {
-    let dir = get_dir();
+    let mut args = CliArgs::parse(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, USAGE);
+        exit(EXIT_CODE_USAGE);
+    });
+    if args.help {
+        println!("{}", USAGE);
+        exit(0);
+    }
+    if args.print_options {
+        println!("{}", serde_json::to_string_pretty(&args.options()).unwrap());
+        exit(0);
+    }
+
+    // all printed paths are derived from these
+    if let Some(style) = args.path_style {
+        args.dir = Some(style.apply(&args.dir()));
+        args.sources = args
+            .sources
+            .iter()
+            .map(|source| style.apply(source))
+            .collect();
+        args.into = args.into.as_deref().map(|into| style.apply(into));
+    }
 
     log::set_logger(&logger::StdErrLogger).unwrap();
-    log::set_max_level(LevelFilter::max());
+    if args.quiet {
+        cli::set_quiet();
+        log::set_max_level(LevelFilter::Error);
+    } else {
+        log::set_max_level(LevelFilter::max());
+    }
+    if args.script.is_some() {
+        cli::set_human_output_to_stderr();
+    }
 
-    let assistant = NFLZAssistant::new(dir);
+    if args.subcommand == Some(Subcommand::Daemon) {
+        let config = cli::daemon::DaemonConfig {
+            socket: args.socket.as_ref().expect("checked by the parser"),
+            socket_mode: args.socket_mode.unwrap_or(cli::daemon::DEFAULT_SOCKET_MODE),
+            options: &args.options(),
+            no_config: args.no_config,
+            allow_dangerous_paths: args.allow_dangerous_paths,
+            metrics_addr: args.metrics.as_deref(),
+        };
+        if let Err(err) = cli::daemon::run(&config) {
+            eprintln!("The daemon failed: {}", err);
+            exit(1);
+        }
+        exit(0);
+    }
 
-    if let Err(err) = assistant {
-        println!(
-            "Can't perform the desired action on the given directory. Error:\n{}",
-            err
-        );
-        exit(1);
+    if args.subcommand == Some(Subcommand::Merge) {
+        let into = args.into.as_ref().expect("checked by the parser");
+        for dir in args.sources.iter().chain([into]) {
+            check_path_allowed(&args, dir);
+        }
+        merge(&args, into);
+        exit(0);
     }
-    let assistant = assistant.unwrap();
 
-    if assistant.files_to_rename().is_empty() {
-        println!("Found no files to rename. Exit.");
+    if let Some(Subcommand::ShellIntegration(action)) = args.subcommand {
+        let res = match action {
+            ShellIntegrationAction::Install => cli::shell_integration::install(),
+            ShellIntegrationAction::Uninstall => cli::shell_integration::uninstall(),
+        };
+        if let Err(err) = res {
+            eprintln!("Can't change the context menu of the Explorer: {}", err);
+            exit(1);
+        }
+        report!("Done.");
         exit(0);
     }
 
-    println!("NFLZ would not rename the following files:");
-    for skipped_file in assistant.files_without_rename() {
-        println!("  {}", skipped_file.file_info().original_filename());
+    if args.subcommand == Some(Subcommand::Stats) {
+        print_stats(&args, &args.dir());
+        exit(0);
     }
 
-    println!("NFLZ would rename the following files:");
-    for file in assistant.files_to_rename() {
-        // todo make this more dynamic
-        println!(
-            "  {:25} => {}",
-            file.file_info().original_filename(),
-            file.new_filename().expect("must exist at that point"),
-        );
+    if args.subcommand == Some(Subcommand::Chapters) {
+        let dir = args.dir();
+        check_path_allowed(&args, &dir);
+        rename_chapters(&args, &dir);
+        exit(0);
     }
 
-    let res = ask_for_confirmation();
-    if !res {
-        println!("Aborted");
+    if args.subcommand == Some(Subcommand::Resume) {
+        let dir = args.dir();
+        check_path_allowed(&args, &dir);
+        resume(&args, &dir);
         exit(0);
     }
 
-    let res = assistant.rename_all();
-
-    match res {
-        Ok(files) => {
-            let renamed_files_count = files
-                .iter()
-                .filter(|x| !x.is_already_properly_named())
-                .count();
-            let unchanged_files_count = files
-                .iter()
-                .filter(|x| x.is_already_properly_named())
-                .count();
-            println!(
-                "Successfully renamed {} files. {} files did not need to be renamed.",
-                renamed_files_count, unchanged_files_count
+    let selections = args.files_from.as_ref().map(|list| {
+        let selections = cli::file_list::read(list).unwrap_or_else(|err| {
+            report!("Can't read the list '{}'. Error:\n{}", list.display(), err);
+            exit(EXIT_CODE_USAGE);
+        });
+        match args.path_style {
+            Some(style) => selections
+                .into_iter()
+                .map(|(dir, names)| (style.apply(&dir), names))
+                .collect(),
+            None => selections,
+        }
+    });
+
+    let dirs = if let Some(selections) = &selections {
+        selections.keys().cloned().collect()
+    } else if args.recursive {
+        nflz::find_directories_recursive(args.dir(), &args.exclude_dirs).unwrap_or_else(|err| {
+            report!("Can't read the directories recursively. Error:\n{}", err);
+            exit(1);
+        })
+    } else {
+        vec![args.dir()]
+    };
+    for dir in &dirs {
+        check_path_allowed(&args, dir);
+        // planning again would start from a half-renamed directory
+        if dir.join(CHECKPOINT_FILENAME).exists() {
+            report!(
+                "The directory '{}' contains an interrupted run. Continue it with 'nflz resume'.",
+                dir.display()
             );
+            exit(EXIT_CODE_USAGE);
+        }
+    }
+
+    if args.explain {
+        for dir in &dirs {
+            let selection = selections
+                .as_ref()
+                .and_then(|selections| selections.get(dir))
+                .map(Vec::as_slice);
+            explain(&args, dir, selection, dirs.len() > 1);
         }
-        Err(err) => match &err {
-            NFLZError::AmbiguousPrefixes(_) | NFLZError::AmbiguousSuffixes(_) => {
-                println!(
-                    "Aborted renaming early. No changes made to the file system. Error is:\n{}",
+        exit(0);
+    }
+
+    let assistants = dirs
+        .iter()
+        .map(|dir| {
+            let selection = selections
+                .as_ref()
+                .and_then(|selections| selections.get(dir))
+                .map(Vec::as_slice);
+            let assistant = build_assistant(&args, dir, selection).unwrap_or_else(|err| {
+                report!(
+                    "Can't perform the desired action on the given directory. Error:\n{}",
                     err
                 );
+                exit(err.code().into());
+            });
+            if args.pick && !assistant.files_to_rename().is_empty() {
+                pick_files(&args, dir, &assistant)
+            } else {
+                assistant
             }
-            NFLZError::RenameFailed(old, new, ioerror) => {
-                println!("Failure during renaming. File state might be inconsistent now.");
-                println!(
-                    "Could not rename '{}' to '{} because of: {}'",
-                    old, new, ioerror
-                );
+        })
+        .filter(|assistant| !assistant.files_to_rename().is_empty())
+        .collect::<Vec<_>>();
+
+    if assistants.is_empty() {
+        report!("Found no files to rename. Exit.");
+        exit(0);
+    }
+
+    if args.recursive {
+        print_plan_tree(&args.dir(), &assistants, args.sort);
+    } else {
+        for assistant in &assistants {
+            print_plan(assistant, args.sort, args.path_style);
+        }
+    }
+
+    if let Some(format) = args.script {
+        print_script(&assistants, format);
+        exit(0);
+    }
+
+    if args.dry_run {
+        for assistant in &assistants {
+            if let Err(err) = assistant.check_can_rename_all() {
+                report!("The renaming would fail. Error:\n{}", err);
+                exit(err.code().into());
             }
-            _ => {
-                panic!("Unexpected error! {:#?}", err);
+        }
+    }
+    exit_if_dry_run(&args);
+    confirm_or_exit(&args);
+    cli::interrupt::install_handler();
+
+    let mut renamed_files_count = 0;
+    let mut unchanged_files_count = 0;
+    let mut timings = PhaseTimings::default();
+    // directories whose files are all renamed, e.g., with `--recursive`
+    let mut renamed_dirs = Vec::new();
+    for assistant in assistants {
+        let dir = assistant.path().clone();
+        match assistant.rename_all_with_stats() {
+            Ok(stats) => {
+                renamed_files_count += stats.files_to_rename;
+                unchanged_files_count += stats.files_without_rename;
+                timings += stats.timings;
+                renamed_dirs.push(dir);
+            }
+            Err(err) => {
+                if let NFLZError::Interrupted(renamed, _) = err {
+                    renamed_files_count += renamed;
+                }
+                announce(
+                    &args,
+                    &RunSummary {
+                        command: "pad",
+                        renamed_files: renamed_files_count,
+                        unchanged_files: unchanged_files_count,
+                        error: Some(&err),
+                    },
+                    &format!(
+                        "Renamed {} files before the error:\n{}",
+                        renamed_files_count, err
+                    ),
+                );
+                match &err {
+                    NFLZError::RenameFailed(old, new, ioerror) => {
+                        report!("Failure during renaming. File state might be inconsistent now.");
+                        report!(
+                            "Could not rename '{}' to '{} because of: {}'",
+                            old.display(),
+                            new.display(),
+                            ioerror
+                        );
+                    }
+                    NFLZError::TemporaryFilesLeft(_, files) => {
+                        report!("Failure during renaming. File state might be inconsistent now.");
+                        report!("{}", err);
+                        for (temporary, original) in files {
+                            report!(
+                                "Rename '{}' back to '{}'.",
+                                temporary.display(),
+                                original.display()
+                            );
+                        }
+                    }
+                    NFLZError::CantUpdateReferences(_, _)
+                    | NFLZError::CantRetargetSymlink(_, _)
+                    | NFLZError::RenameTimedOut(_, _) => report!("{}", err),
+                    NFLZError::Interrupted(_, _) if args.checkpoint => {
+                        report!("{} Continue the run with 'nflz resume'.", err);
+                    }
+                    NFLZError::Interrupted(_, _) => {
+                        report!(
+                            "{} All files have a valid filename, either the old or the new one.",
+                            err
+                        );
+                    }
+                    _ if renamed_dirs.is_empty() => {
+                        report!(
+                            "Aborted renaming early. No changes made to the file system. Error is:\n{}",
+                            err
+                        );
+                    }
+                    _ => {
+                        report!(
+                            "Aborted renaming early in '{}'. Error is:\n{}",
+                            dir.display(),
+                            err
+                        );
+                        report!(
+                            "Renamed {} files in these directories before the error:",
+                            renamed_files_count
+                        );
+                        for dir in &renamed_dirs {
+                            report!("  {}", dir.display());
+                        }
+                    }
+                }
+                exit(err.code().into());
             }
+        }
+    }
+    report!(
+        "Successfully renamed {} files. {} files did not need to be renamed.",
+        renamed_files_count,
+        unchanged_files_count
+    );
+    if args.timings {
+        report!("Timings: {}", timings);
+    }
+    announce(
+        &args,
+        &RunSummary {
+            command: "pad",
+            renamed_files: renamed_files_count,
+            unchanged_files: unchanged_files_count,
+            error: None,
         },
+        &format!("Renamed {} files.", renamed_files_count),
+    );
+}
+
+/// Announces the end of a run with a desktop notification (`--notify`) and on the webhook
+/// (`--webhook`), if requested.
+fn announce(args: &CliArgs, summary: &RunSummary, message: &str) {
+    if args.notify {
+        cli::notification::send(summary.error.is_some(), message);
+    }
+    if let Some(url) = &args.webhook {
+        cli::webhook::post(url, summary);
+    }
+}
+
+/// Exits if the directory is a system directory or the home directory, unless the user
+/// explicitly allowed it.
+fn check_path_allowed(args: &CliArgs, dir: &Path) {
+    if args.allow_dangerous_paths {
+        return;
+    }
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    if cli::guard::is_dangerous_path(&dir, cli::guard::home_dir().as_deref()) {
+        report!(
+            "Refusing to work on '{}' because it is a system directory or your home directory. Use --i-know-what-i-am-doing if you really want this.",
+            dir.display()
+        );
+        exit(EXIT_CODE_USAGE);
+    }
+}
+
+/// Exits with [`EXIT_CODE_PENDING_CHANGES`] if `--dry-run` was specified. Must be called after
+/// the plan was printed and verified.
+fn exit_if_dry_run(args: &CliArgs) {
+    if args.dry_run {
+        report!("Dry run. No changes made to the file system.");
+        exit(EXIT_CODE_PENDING_CHANGES);
     }
 }
 
-/// Returns either PWD or the dir specified by first argument as [`PathBuf`].
-fn get_dir() -> PathBuf {
-    let args = std::env::args().collect::<Vec<String>>();
-    if args.len() > 1 {
-        Path::new(&args[1]).to_path_buf()
+/// Asks the user for confirmation, unless `--yes` was specified. Exits if the user declines
+/// or if there is no terminal to ask.
+fn confirm_or_exit(args: &CliArgs) {
+    let confirmation: Box<dyn ConfirmationProvider> = if args.yes {
+        Box::new(AlwaysYes)
     } else {
-        std::env::current_dir().unwrap()
+        Box::new(StdinConfirmation)
+    };
+    match confirmation.confirm(CONFIRMATION_QUESTION) {
+        Ok(true) => {}
+        Ok(false) => {
+            report!("Aborted");
+            exit(0);
+        }
+        Err(err) => {
+            report!(
+                "Can't ask for confirmation ({}). Use --yes to rename without confirmation.",
+                err
+            );
+            exit(EXIT_CODE_USAGE);
+        }
     }
 }
 
-/// Asks the user to confirm the action.
-fn ask_for_confirmation() -> bool {
-    println!("\nPlease confirm with 'y' or abort with 'n'");
-    println!("  NFLZ can't guarantee you 100% safety. Always make a backup first (:");
-    println!(
-        "  But to the best of my knowledge this should work if no catastrophic failure occurs."
+/// Continues the interrupted run in the directory (`nflz resume`) from its checkpoint file.
+/// Exits on failure.
+fn resume(args: &CliArgs, dir: &Path) {
+    let checkpoint = Checkpoint::read(dir.join(CHECKPOINT_FILENAME)).unwrap_or_else(|err| {
+        report!("Can't resume the interrupted run. Error:\n{}", err);
+        exit(err.code().into());
+    });
+    let report = checkpoint.verify();
+    if !report.is_ok() {
+        let err = NFLZError::InvalidPlan(report.violations().to_vec());
+        report!(
+            "The directory changed since the interruption. Can't resume the run. Error:\n{}",
+            err
+        );
+        exit(err.code().into());
+    }
+    say!(
+        "NFLZ would continue the interrupted run in '{}' with at most {} renames left.",
+        dir.display(),
+        checkpoint.remaining_renames()
     );
-    let mut input = String::new();
-    match stdin().read_line(&mut input) {
-        Ok(_s) => {
-            // Strings equal?
-            input.trim().to_lowercase() == "y" // trim to remove \r\n | \n
+    exit_if_dry_run(args);
+    confirm_or_exit(args);
+    match checkpoint.resume() {
+        Ok(count) => report!("Successfully renamed the remaining {} files.", count),
+        Err(err) => {
+            report!("Failure during renaming. Error:\n{}", err);
+            exit(err.code().into());
+        }
+    }
+}
+
+/// Renames the chaptered recordings of action cameras in the directory. Exits on failure.
+fn rename_chapters(args: &CliArgs, dir: &Path) {
+    let plan = ChapterPlan::new(dir).unwrap_or_else(|err| {
+        report!("Can't read the chaptered recordings. Error:\n{}", err);
+        exit(err.code().into());
+    });
+    if plan.renames().is_empty() {
+        report!("Found no chaptered recordings. Exit.");
+        exit(0);
+    }
+
+    say!("NFLZ would rename the following files:");
+    for rename in plan.renames() {
+        say!("  {} => {}", rename.from().display(), rename.to().display());
+    }
+    if args.dry_run {
+        if let Err(err) = plan.check_can_apply() {
+            report!("The renaming would fail. Error:\n{}", err);
+            exit(err.code().into());
         }
-        Err(_) => false,
+    }
+    exit_if_dry_run(args);
+    confirm_or_exit(args);
+
+    match plan.apply() {
+        Ok(renames) => {
+            report!("Successfully renamed {} files.", renames.len());
+            announce(
+                args,
+                &RunSummary {
+                    command: "chapters",
+                    renamed_files: renames.len(),
+                    unchanged_files: 0,
+                    error: None,
+                },
+                &format!("Renamed {} files.", renames.len()),
+            );
+        }
+        Err(err) => {
+            report!("Failure during renaming. Error is:\n{}", err);
+            announce(
+                args,
+                &RunSummary {
+                    command: "chapters",
+                    renamed_files: 0,
+                    unchanged_files: 0,
+                    error: Some(&err),
+                },
+                &format!("Renaming failed:\n{}", err),
+            );
+            exit(err.code().into());
+        }
+    }
+}
+
+/// Merges the source directories into the destination directory. Exits on failure.
+fn merge(args: &CliArgs, into: &Path) {
+    let plan = MergePlan::new(&args.sources, into).unwrap_or_else(|err| {
+        report!("Can't merge the directories. Error:\n{}", err);
+        exit(err.code().into());
+    });
+    if plan.moves().is_empty() {
+        report!("Found no files to merge. Exit.");
+        exit(0);
+    }
+
+    say!("NFLZ would move the following files:");
+    for file in plan.moves() {
+        say!("  {} => {}", file.from().display(), file.to().display());
+    }
+    if args.dry_run {
+        if let Err(err) = plan.check_can_merge() {
+            report!("The merge would fail. Error:\n{}", err);
+            exit(err.code().into());
+        }
+    }
+    exit_if_dry_run(args);
+    confirm_or_exit(args);
+
+    match plan.apply() {
+        Ok(moves) => {
+            report!(
+                "Successfully merged {} files into '{}'.",
+                moves.len(),
+                into.display()
+            );
+            announce(
+                args,
+                &RunSummary {
+                    command: "merge",
+                    renamed_files: moves.len(),
+                    unchanged_files: 0,
+                    error: None,
+                },
+                &format!("Merged {} files into '{}'.", moves.len(), into.display()),
+            );
+        }
+        Err(err) => {
+            report!("Failure during merging. Error is:\n{}", err);
+            announce(
+                args,
+                &RunSummary {
+                    command: "merge",
+                    renamed_files: 0,
+                    unchanged_files: 0,
+                    error: Some(&err),
+                },
+                &format!("Merging failed:\n{}", err),
+            );
+            exit(err.code().into());
+        }
+    }
+}
+
+/// Prints the [`nflz::Explanation`] of every entry of the directory, or of the selected
+/// entries of `--files-from`. Exits on failure.
+fn explain(args: &CliArgs, dir: &Path, selection: Option<&[OsString]>, with_header: bool) {
+    let assistant = build_assistant(args, dir, selection).unwrap_or_else(|err| {
+        report!("Can't explain the directory. Error:\n{}", err);
+        exit(err.code().into());
+    });
+    if with_header {
+        report!("{}:", dir.display());
+    }
+    for explanation in assistant.explain() {
+        report!("  {}", explanation);
+    }
+}
+
+/// Prints the [`nflz::ArchiveAnalysis`] of the directory. Exits on failure.
+fn print_stats(args: &CliArgs, dir: &Path) {
+    let assistant = build_assistant(args, dir, None).unwrap_or_else(|err| {
+        report!("Can't analyze the directory. Error:\n{}", err);
+        exit(err.code().into());
+    });
+    let analysis = assistant.analysis();
+    if analysis.file_count() == 0 {
+        report!("Found no numbered files.");
+    } else {
+        report!("{}", analysis);
+    }
+}
+
+/// Creates the [`NFLZAssistant`] for the directory with the configuration of the CLI
+/// arguments. `selection` are the filenames of `--files-from` in the directory, if any.
+fn build_assistant(
+    args: &CliArgs,
+    dir: &Path,
+    selection: Option<&[OsString]>,
+) -> Result<NFLZAssistant, NFLZError> {
+    let mut builder = NFLZAssistant::builder(dir);
+    if let Some(names) = selection {
+        builder = builder.select_files(names.iter().cloned());
+    }
+    if !args.no_config {
+        builder = builder.apply_directory_config()?;
+    }
+    if let Some(path) = &args.options_file {
+        builder = builder.apply_options(&read_options(path)?);
+    }
+    // the options of the command line are only set if given, so they override the files
+    builder = builder.apply_options(&args.options());
+    if args.detect_pattern {
+        let detection = nflz::detect_pattern(dir)?;
+        match detection.best() {
+            Some(best) => {
+                say!("Detected the pattern {}.", best);
+                builder = builder.pattern(best.pattern());
+            }
+            None => say!("Detected no pattern; using the default one."),
+        }
+    }
+    #[cfg(feature = "xattr")]
+    {
+        builder = builder.record_original_filenames(args.record_original);
+    }
+    builder = builder.interrupt_flag(cli::interrupt::flag());
+    if args.fsync {
+        builder = builder.sync_directories(true);
+    }
+    if let Some(rate) = args.rate_limit {
+        builder = builder.throttle(Duration::from_secs(1) / rate.get());
+    }
+    if let Some(timeout) = args.rename_timeout {
+        builder = builder.rename_timeout(timeout);
+    }
+    if args.checkpoint {
+        let chunk_size = args
+            .chunk_size
+            .or(NonZeroUsize::new(DEFAULT_CHUNK_SIZE))
+            .expect("not zero");
+        builder = builder.checkpoint(dir.join(CHECKPOINT_FILENAME), chunk_size);
+    }
+    if args.events == Some(EventFormat::Jsonl) {
+        cli::set_human_output_to_stderr();
+        builder = builder.event_listener(|event| {
+            println!("{}", serde_json::to_string(event).unwrap());
+        });
+    }
+    builder.build()
+}
+
+/// Reads the [`Options`] of the JSON file of `--options`. Fails with
+/// [`NFLZError::InvalidConfig`] if the file can't be read or is invalid.
+fn read_options(path: &Path) -> Result<Options, NFLZError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|err| NFLZError::InvalidConfig(path.to_path_buf(), err.to_string()))?;
+    serde_json::from_str(&content)
+        .map_err(|err| NFLZError::InvalidConfig(path.to_path_buf(), err.to_string()))
+}
+
+/// Lets the user select the numbered files of the assistant that are renamed (`--pick`) and
+/// returns the assistant for the selection. Exits if the user aborts.
+fn pick_files(args: &CliArgs, dir: &Path, assistant: &NFLZAssistant) -> NFLZAssistant {
+    let mut files = assistant.files_to_rename();
+    files.extend(assistant.files_without_rename());
+    files.sort();
+    let candidates = files
+        .iter()
+        .map(|file| file.file_info().original_filename().to_string())
+        .collect();
+    let selection = match cli::picker::pick(dir, candidates) {
+        Ok(Some(selection)) => selection,
+        Ok(None) => {
+            report!("Aborted");
+            exit(0);
+        }
+        Err(err) => {
+            report!("Can't show the picker. Error:\n{}", err);
+            exit(1);
+        }
+    };
+    let selection = selection
+        .into_iter()
+        .map(OsString::from)
+        .collect::<Vec<_>>();
+    build_assistant(args, dir, Some(&selection)).unwrap_or_else(|err| {
+        report!(
+            "Can't perform the desired action on the selected files. Error:\n{}",
+            err
+        );
+        exit(err.code().into());
+    })
+}
+
+/// Verifies the plans of all assistants and prints one script that performs all renames to
+/// stdout.
+fn print_script(assistants: &[NFLZAssistant], format: ScriptFormat) {
+    for assistant in assistants {
+        if let Err(err) = assistant.check_can_rename_all() {
+            report!("Can't create a script for the renames. Error:\n{}", err);
+            exit(err.code().into());
+        }
+    }
+    let renames = assistants
+        .iter()
+        .flat_map(|assistant| assistant.plan().renames().to_vec())
+        .collect();
+    print!("{}", RenamePlan::new(renames).to_script(format));
+}
+
+/// Prints the files that would not be renamed and the files that would be renamed in the
+/// given order. The files are printed by their filenames, unless a [`PathStyle`] is given.
+fn print_plan(assistant: &NFLZAssistant, sort: SortOrder, style: Option<PathStyle>) {
+    let name = |path: &Path| match style {
+        Some(_) => path.display().to_string(),
+        None => path.file_name().unwrap().to_string_lossy().to_string(),
+    };
+
+    if !assistant.system_files().is_empty() {
+        say!("NFLZ ignores the following system files:");
+        for path in assistant.system_files() {
+            say!("  {}", name(path));
+        }
+    }
+
+    if !assistant.skipped_files().is_empty() {
+        say!("NFLZ ignores the following files:");
+        for skipped_file in assistant.skipped_files() {
+            say!(
+                "  {:25} ({})",
+                name(skipped_file.path()),
+                skipped_file.reason()
+            );
+        }
+    }
+
+    if assistant.hidden_files_ignored() > 0 {
+        say!(
+            "NFLZ ignores {} hidden files. Use --include-hidden to process them.",
+            assistant.hidden_files_ignored()
+        );
+    }
+
+    let mut files_without_rename = assistant.files_without_rename();
+    sort.sort(&mut files_without_rename);
+    say!("NFLZ would not rename the following files:");
+    for skipped_file in files_without_rename {
+        say!("  {}", name(skipped_file.file_info().path()));
+    }
+
+    say!("NFLZ would rename the following files:");
+    let plan = sorted_plan(assistant, sort);
+    let table = match style {
+        Some(_) => cli::path_style::table(&plan),
+        None => plan.to_string(),
+    };
+    for line in table.lines() {
+        say!("  {}", line);
+    }
+
+    if !assistant.warnings().is_empty() {
+        say!("Warnings:");
+        for warning in assistant.warnings() {
+            say!("  {}", warning);
+        }
+    }
+}
+
+/// Returns the [`RenamePlan`] of the files that need to be renamed in the given order.
+fn sorted_plan(assistant: &NFLZAssistant, sort: SortOrder) -> RenamePlan {
+    let mut files_to_rename = assistant.files_to_rename();
+    sort.sort(&mut files_to_rename);
+    RenamePlan::new(
+        files_to_rename
+            .iter()
+            .map(|file| {
+                let new_path = file
+                    .path_with_new_filename()
+                    .expect("must exist at that point");
+                PlannedRename::new(file.file_info().path(), new_path)
+            })
+            .collect(),
+    )
+}
+
+/// Prints the renames of all directories as an indented tree below `root`.
+fn print_plan_tree(root: &Path, assistants: &[NFLZAssistant], sort: SortOrder) {
+    let dirs = assistants
+        .iter()
+        .map(|assistant| {
+            let lines = sorted_plan(assistant, sort)
+                .to_string()
+                .lines()
+                .map(str::to_string)
+                .chain(
+                    assistant
+                        .warnings()
+                        .iter()
+                        .map(|warning| format!("warning: {}", warning)),
+                )
+                .collect();
+            (assistant.path().to_path_buf(), lines)
+        })
+        .collect();
+
+    say!("NFLZ would rename the following files:");
+    for line in cli::tree::render(root, dirs) {
+        say!("{}", line);
     }
 }
 
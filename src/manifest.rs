@@ -0,0 +1,200 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Updates the filenames inside checksum manifests, such as `SHA256SUMS`, so that integrity
+//! checks keep passing after the renaming. See [`update_manifests`].
+
+use crate::error::NFLZError;
+use crate::fsutil::{long_path, read_directory_flat, EntryKind};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Names of the checksum manifests that are updated, compared case-insensitively. They are
+/// written by `sha256sum`, `md5sum`, and the like, either in the GNU or in the BSD (`--tag`)
+/// format.
+const MANIFEST_FILENAMES: [&str; 8] = [
+    "MD5SUMS",
+    "SHA1SUMS",
+    "SHA256SUMS",
+    "SHA512SUMS",
+    "md5sum.txt",
+    "sha1sum.txt",
+    "sha256sum.txt",
+    "sha512sum.txt",
+];
+
+/// Returns true if the file is a checksum manifest. See [`MANIFEST_FILENAMES`].
+fn is_manifest(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| {
+        let name = name.to_string_lossy();
+        MANIFEST_FILENAMES
+            .iter()
+            .any(|manifest| manifest.eq_ignore_ascii_case(&name))
+    })
+}
+
+/// Returns true if the string is a non-empty hexadecimal checksum.
+fn is_checksum(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Returns the new name of the file, keeping a leading `./`.
+fn renamed(name: &str, renames: &HashMap<&str, &str>) -> Option<String> {
+    let (dot_slash, name) = name
+        .strip_prefix("./")
+        .map_or(("", name), |name| ("./", name));
+    renames
+        .get(name)
+        .map(|new_name| format!("{}{}", dot_slash, new_name))
+}
+
+/// Replaces the filename of a single line of a manifest (without the line break) if the file
+/// was renamed. Supports the GNU format, such as `<checksum>  a (1).jpg` or
+/// `<checksum> *a (1).jpg` for binary mode, and the BSD format, such as
+/// `SHA256 (a (1).jpg) = <checksum>`. Escaped GNU lines, which start with a backslash, are
+/// left as they are. Returns `None` if the line doesn't change.
+fn replace_filename(line: &str, renames: &HashMap<&str, &str>) -> Option<String> {
+    // BSD: the filename may contain parentheses itself
+    let bsd = line.rsplit_once(") = ").and_then(|(start, checksum)| {
+        let (algorithm, name) = start.split_once(" (")?;
+        let valid = is_checksum(checksum)
+            && !algorithm.is_empty()
+            && algorithm
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-');
+        valid.then_some((algorithm, name, checksum))
+    });
+    if let Some((algorithm, name, checksum)) = bsd {
+        let new_name = renamed(name, renames)?;
+        return Some(format!("{} ({}) = {}", algorithm, new_name, checksum));
+    }
+
+    let (checksum, rest) = line.split_once(' ')?;
+    if !is_checksum(checksum) {
+        return None;
+    }
+    let mode = rest.chars().next().filter(|c| matches!(c, ' ' | '*'))?;
+    let new_name = renamed(&rest[1..], renames)?;
+    Some(format!("{} {}{}", checksum, mode, new_name))
+}
+
+/// Replaces the filenames of all lines of the manifest. See [`replace_filename`]. The line
+/// breaks, `\n` or `\r\n`, stay the same.
+///
+/// Returns the new text and the number of replaced filenames.
+pub(crate) fn replace_filenames(text: &str, renames: &[(String, String)]) -> (String, usize) {
+    let renames = renames
+        .iter()
+        .map(|(old, new)| (old.as_str(), new.as_str()))
+        .collect::<HashMap<_, _>>();
+    let mut replaced = String::with_capacity(text.len());
+    let mut count = 0;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        match replace_filename(content, &renames) {
+            Some(new_content) => {
+                replaced.push_str(&new_content);
+                replaced.push_str(&line[content.len()..]);
+                count += 1;
+            }
+            None => replaced.push_str(line),
+        }
+    }
+    (replaced, count)
+}
+
+/// Updates the filenames of the renamed files in all checksum manifests of the directory
+/// (not recursive). See [`MANIFEST_FILENAMES`] and [`replace_filenames`]. Manifests that are
+/// not valid UTF-8 are skipped with a warning.
+///
+/// # Parameters
+/// * `dir` The working directory.
+/// * `renames` Old and new names of the renamed files, relative to the directory.
+///
+/// # Return Type
+/// All updated manifests with the number of replaced filenames.
+pub(crate) fn update_manifests(
+    dir: &Path,
+    renames: &[(String, String)],
+) -> Result<Vec<(PathBuf, usize)>, NFLZError> {
+    let entries =
+        read_directory_flat(dir).map_err(|err| NFLZError::CantReadDirectory(dir.into(), err))?;
+    let mut updated = Vec::new();
+    for (path, kind) in entries {
+        if kind != EntryKind::File || !is_manifest(&path) {
+            continue;
+        }
+        let bytes = std::fs::read(long_path(&path))
+            .map_err(|err| NFLZError::CantUpdateReferences(path.clone(), err))?;
+        let Ok(text) = String::from_utf8(bytes) else {
+            log::warn!("Skipping '{}' because it is not UTF-8", path.display());
+            continue;
+        };
+        let (text, count) = replace_filenames(&text, renames);
+        if count > 0 {
+            std::fs::write(long_path(&path), text)
+                .map_err(|err| NFLZError::CantUpdateReferences(path.clone(), err))?;
+            updated.push((path, count));
+        }
+    }
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_manifest() {
+        assert!(is_manifest(Path::new("photos/SHA256SUMS")));
+        assert!(is_manifest(Path::new("MD5SUM.TXT")));
+        assert!(!is_manifest(Path::new("SHA256SUMS.sig")));
+    }
+
+    #[test]
+    fn test_replace_filenames() {
+        let renames = [
+            ("a (1).jpg".to_string(), "a (01).jpg".to_string()),
+            ("a (2).jpg".to_string(), "part-01/a (02).jpg".to_string()),
+        ];
+        let (text, count) = replace_filenames(
+            "0f1e  a (1).jpg\n\
+             0f1e *./a (2).jpg\r\n\
+             0f1e  a (10).jpg\n\
+             SHA256 (a (1).jpg) = 0f1e\n\
+             \\0f1e  a\\\\(1).jpg\n\
+             a (1).jpg",
+            &renames,
+        );
+        assert_eq!(3, count);
+        assert_eq!(
+            "0f1e  a (01).jpg\n\
+             0f1e *./part-01/a (02).jpg\r\n\
+             0f1e  a (10).jpg\n\
+             SHA256 (a (01).jpg) = 0f1e\n\
+             \\0f1e  a\\\\(1).jpg\n\
+             a (1).jpg",
+            text
+        );
+    }
+}
@@ -23,16 +23,91 @@ SOFTWARE.
 */
 //! Math utility functions.
 
+use crate::roman::{is_roman_digit, parse_roman, roman_len, to_roman, RomanNumerals, PADDING};
+
 /// Returns the amount of digits of a number.
 /// For example: 12345 => 5
 pub fn count_digits_without_leading_zeroes(number: u64) -> u64 {
-    let x = (number + 1) as f64;
-    x.log10().ceil() as u64
+    number.checked_ilog10().map_or(0, |log| u64::from(log) + 1)
+}
+
+/// Numeral system of the numbers inside the number groups. See
+/// [`crate::NFLZAssistantBuilder::hexadecimal`] and
+/// [`crate::NFLZAssistantBuilder::roman_numerals`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Radix {
+    /// `0` to `9`.
+    #[default]
+    Decimal,
+    /// `0` to `9` and `a` to `f`, case-insensitive.
+    Hexadecimal,
+    /// Roman numerals, such as `IV`, case-insensitive. The new numbers are written as
+    /// selected by [`RomanNumerals`].
+    Roman(RomanNumerals),
+}
+
+impl Radix {
+    /// Returns whether the byte is a digit in this numeral system.
+    pub(crate) const fn is_digit(self, byte: u8) -> bool {
+        match self {
+            Self::Decimal => byte.is_ascii_digit(),
+            Self::Hexadecimal => byte.is_ascii_hexdigit(),
+            Self::Roman(_) => is_roman_digit(byte),
+        }
+    }
+
+    /// Parses the digits of a number group. `None` if they aren't a number in this numeral
+    /// system or don't fit into a [`u64`].
+    pub(crate) fn parse(self, digits: &str) -> Option<u64> {
+        match self {
+            Self::Decimal => digits.parse().ok(),
+            Self::Hexadecimal => u64::from_str_radix(digits, 16).ok(),
+            Self::Roman(_) => parse_roman(digits),
+        }
+    }
+
+    /// Returns the amount of digits of a number as it is written in the new filenames. For
+    /// example: `0x1f` => 2, `VIII` => 4
+    pub(crate) fn count_digits(self, number: u64) -> u64 {
+        match self {
+            Self::Decimal | Self::Roman(RomanNumerals::ToDecimal) => {
+                count_digits_without_leading_zeroes(number)
+            }
+            Self::Hexadecimal => u64::from(u64::BITS - number.leading_zeros() + 3) / 4,
+            Self::Roman(RomanNumerals::Padded) => roman_len(number),
+        }
+    }
+
+    /// Formats the number as it is written in the new filenames with leading zeroes, or
+    /// leading underscores for roman numerals, such that it has at least `digits` digits.
+    pub(crate) fn format(self, number: u64, digits: u64, uppercase: bool) -> String {
+        let padding = digits.saturating_sub(self.count_digits(number)) as usize;
+        let zeroes = "0".repeat(padding);
+        match self {
+            Self::Decimal | Self::Roman(RomanNumerals::ToDecimal) => {
+                format!("{}{}", zeroes, number)
+            }
+            Self::Hexadecimal if uppercase => format!("{}{:X}", zeroes, number),
+            Self::Hexadecimal => format!("{}{:x}", zeroes, number),
+            Self::Roman(RomanNumerals::Padded) => format!(
+                "{}{}",
+                (PADDING as char).to_string().repeat(padding),
+                to_roman(number, uppercase)
+            ),
+        }
+    }
+
+    /// Returns whether a sequence of digits that can't be parsed is too large, as opposed to
+    /// malformed, such as the roman numeral `IIII`.
+    pub(crate) const fn only_overflows(self) -> bool {
+        !matches!(self, Self::Roman(_))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::math::count_digits_without_leading_zeroes;
+    use crate::math::{count_digits_without_leading_zeroes, Radix};
+    use crate::roman::RomanNumerals;
 
     #[test]
     fn test_count_digits_without_leading_zeroes() {
@@ -41,5 +116,34 @@ mod tests {
         assert_eq!(count_digits_without_leading_zeroes(9), 1);
         assert_eq!(count_digits_without_leading_zeroes(10), 2);
         assert_eq!(count_digits_without_leading_zeroes(999), 3);
+        assert_eq!(
+            count_digits_without_leading_zeroes(999_999_999_999_999_999),
+            18
+        );
+        assert_eq!(count_digits_without_leading_zeroes(u64::MAX), 20);
+    }
+
+    #[test]
+    fn test_radix() {
+        assert_eq!(Some(31), Radix::Hexadecimal.parse("1f"));
+        assert_eq!(Some(31), Radix::Hexadecimal.parse("001F"));
+        assert_eq!(None, Radix::Decimal.parse("1f"));
+        assert_eq!(0, Radix::Hexadecimal.count_digits(0));
+        assert_eq!(1, Radix::Hexadecimal.count_digits(15));
+        assert_eq!(2, Radix::Hexadecimal.count_digits(16));
+        assert_eq!(16, Radix::Hexadecimal.count_digits(u64::MAX));
+        assert_eq!("001f", Radix::Hexadecimal.format(31, 4, false));
+        assert_eq!("01F", Radix::Hexadecimal.format(31, 3, true));
+        assert_eq!("1F", Radix::Hexadecimal.format(31, 1, true));
+        assert_eq!("031", Radix::Decimal.format(31, 3, true));
+        let padded = Radix::Roman(RomanNumerals::Padded);
+        assert_eq!(Some(4), padded.parse("IV"));
+        assert_eq!(4, padded.count_digits(8));
+        assert_eq!("__IV", padded.format(4, 4, true));
+        assert_eq!("xii", padded.format(12, 2, false));
+        assert_eq!(
+            "04",
+            Radix::Roman(RomanNumerals::ToDecimal).format(4, 2, true)
+        );
     }
 }
@@ -0,0 +1,280 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for merging several directories into one numbered sequence. See [`MergePlan`].
+
+use crate::error::NFLZError;
+use crate::file_info::FileInfoWithRenameAdvice;
+use crate::fsutil::long_path;
+use crate::math::count_digits_without_leading_zeroes;
+use crate::nflz::NFLZAssistant;
+use crate::schedule::schedule;
+use crate::suggestion::outliers;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// A single file that is moved by a [`MergePlan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MergeMove {
+    /// Path to the original file.
+    from: PathBuf,
+    /// Path to the file in the destination directory after the merge.
+    to: PathBuf,
+}
+
+impl MergeMove {
+    /// Returns the path to the original file.
+    pub fn from(&self) -> &Path {
+        &self.from
+    }
+
+    /// Returns the path to the file in the destination directory after the merge.
+    pub fn to(&self) -> &Path {
+        &self.to
+    }
+}
+
+/// Merges the numbered files of several source directories with the same prefix into one
+/// continuous, consistently padded sequence inside a destination directory.
+///
+/// The files are renumbered from 1 in the order of the source directories and, within each
+/// directory, by their number. Thus, colliding numbers, such as `paris (1).jpg` in two
+/// source directories, are resolved automatically. The destination may be one of the source
+/// directories.
+///
+/// ```rust,no_run
+/// use nflz::MergePlan;
+///
+/// let plan = MergePlan::new(&["./camera-a", "./camera-b"], "./paris").unwrap();
+/// for file in plan.moves() {
+///     println!("{} => {}", file.from().display(), file.to().display());
+/// }
+/// plan.apply().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct MergePlan {
+    /// The directory that contains the merged sequence.
+    destination: PathBuf,
+    /// All files to move, in the order of the new sequence.
+    moves: Vec<MergeMove>,
+}
+
+impl MergePlan {
+    /// Scans the source directories and computes the new paths of all numbered files. Files
+    /// that are ignored by [`NFLZAssistant`], such as files without a number group, stay
+    /// where they are. Fails with [`NFLZError::AmbiguousPrefixes`] if the files of the source
+    /// directories don't share the same prefix, and with [`NFLZError::DuplicateMergeSource`]
+    /// if a source directory is given twice, e.g., as `a` and `./a`.
+    pub fn new<S: AsRef<Path>, D: AsRef<Path>>(
+        sources: &[S],
+        destination: D,
+    ) -> Result<Self, NFLZError> {
+        let destination = PathBuf::from(destination.as_ref());
+        let mut canonical_sources = HashSet::new();
+        for source in sources {
+            let source = source.as_ref();
+            let canonical_source = source
+                .canonicalize()
+                .map_err(|err| NFLZError::CantReadDirectory(source.to_path_buf(), err))?;
+            if !canonical_sources.insert(canonical_source) {
+                return Err(NFLZError::DuplicateMergeSource(source.to_path_buf()));
+            }
+        }
+        let assistants = sources
+            .iter()
+            .map(NFLZAssistant::new)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let files = assistants
+            .iter()
+            .flat_map(|assistant| {
+                let mut files = assistant.files_to_rename();
+                files.extend(assistant.files_without_rename());
+                files.sort();
+                files
+            })
+            .collect::<Vec<_>>();
+
+        let prefixes = files
+            .iter()
+            .map(|file| file.file_info().filename_prefix().to_string())
+            .collect::<HashSet<_>>();
+        if prefixes.len() > 1 {
+            let outliers = outliers(files.iter().map(|file| {
+                let info = file.file_info();
+                (info.path(), info.filename_prefix())
+            }));
+            return Err(NFLZError::AmbiguousPrefixes(prefixes, outliers));
+        }
+
+        let digits = count_digits_without_leading_zeroes(files.len() as u64);
+        let moves = files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| MergeMove {
+                from: PathBuf::from(file.file_info().path()),
+                to: destination.join(merged_filename(file, index as u64 + 1, digits)),
+            })
+            .collect();
+
+        Ok(Self { destination, moves })
+    }
+
+    /// Returns the directory that contains the merged sequence.
+    pub fn destination(&self) -> &Path {
+        &self.destination
+    }
+
+    /// Returns all files to move, in the order of the new sequence.
+    pub fn moves(&self) -> &[MergeMove] {
+        &self.moves
+    }
+
+    /// Verifies that no file of the destination directory, that isn't part of the merge,
+    /// would be overwritten. Fails with [`NFLZError::ConflictingFiles`] otherwise.
+    pub fn check_can_merge(&self) -> Result<(), NFLZError> {
+        let sources = self
+            .moves
+            .iter()
+            .map(|file| file.from.as_path())
+            .collect::<HashSet<_>>();
+        let conflicts = self
+            .moves
+            .iter()
+            .filter(|file| file.to.exists() && !sources.contains(file.to.as_path()))
+            .map(|file| file.to.clone())
+            .collect::<Vec<_>>();
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(NFLZError::ConflictingFiles(conflicts))
+        }
+    }
+
+    /// Moves all files into the destination directory, which is created if necessary. Runs
+    /// [`Self::check_can_merge`] first.
+    ///
+    /// The files are moved in an order that never overwrites a file that is moved later, so
+    /// that files that are already in the destination can be renumbered in place. The source
+    /// directories must be on the same file system as the destination.
+    pub fn apply(self) -> Result<Vec<MergeMove>, NFLZError> {
+        self.check_can_merge()?;
+        std::fs::create_dir_all(long_path(&self.destination))
+            .map_err(|err| NFLZError::CantCreateDirectory(self.destination.clone(), err))?;
+
+        let moves = self
+            .moves
+            .iter()
+            .filter(|file| file.from != file.to)
+            .map(|file| (file.from.clone(), file.to.clone()))
+            .collect::<Vec<_>>();
+        for step in schedule(&moves) {
+            rename(&step.from, &step.to)?;
+        }
+        Ok(self.moves)
+    }
+}
+
+/// Returns the filename of the file with the new number, padded to `digits` digits. Everything
+/// behind the first number group, including a second number group, stays the same.
+fn merged_filename(file: &FileInfoWithRenameAdvice, number: u64, digits: u64) -> String {
+    let info = file.file_info();
+    let prefix = info.filename_prefix();
+    let remainder = &info.original_filename()[prefix.len() + info.number_group_str().len()..];
+    format!(
+        "{}{:0width$}{}",
+        prefix,
+        number,
+        remainder,
+        width = digits as usize
+    )
+}
+
+/// Renames the file and maps the error.
+fn rename(from: &Path, to: &Path) -> Result<(), NFLZError> {
+    std::fs::rename(long_path(from), long_path(to))
+        .map_err(|io_err| NFLZError::RenameFailed(from.to_path_buf(), to.to_path_buf(), io_err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::FixtureBuilder;
+
+    #[test]
+    fn test_merge() {
+        let a = FixtureBuilder::new("paris ").count(3).create().unwrap();
+        let b = FixtureBuilder::new("paris ")
+            .count(8)
+            .padding(2)
+            .create()
+            .unwrap();
+        let plan = MergePlan::new(&[a.path(), b.path()], a.path()).unwrap();
+        assert_eq!(11, plan.moves().len());
+        assert_eq!(a.path().join("paris (01).jpg"), plan.moves()[0].to());
+        assert_eq!(b.path().join("paris (01).jpg"), plan.moves()[3].from());
+        assert_eq!(a.path().join("paris (04).jpg"), plan.moves()[3].to());
+        plan.apply().unwrap();
+
+        for number in 1..=11 {
+            assert!(a.path().join(format!("paris ({:02}).jpg", number)).exists());
+        }
+        assert!(!a.path().join("paris (1).jpg").exists());
+        assert!(!b.path().join("paris (01).jpg").exists());
+    }
+
+    #[test]
+    fn test_merge_fails_for_different_prefixes() {
+        let a = FixtureBuilder::new("paris ").count(3).create().unwrap();
+        let b = FixtureBuilder::new("london ").count(3).create().unwrap();
+        let err = MergePlan::new(&[a.path(), b.path()], a.path()).unwrap_err();
+        assert!(matches!(err, NFLZError::AmbiguousPrefixes(_, _)));
+    }
+
+    #[test]
+    fn test_merge_fails_for_duplicate_sources() {
+        let a = FixtureBuilder::new("paris ").count(3).create().unwrap();
+        let b = FixtureBuilder::new("paris ").count(3).create().unwrap();
+        let same_a = a.path().join(".");
+        let err = MergePlan::new(&[a.path(), b.path(), &same_a], b.path()).unwrap_err();
+        assert!(matches!(&err, NFLZError::DuplicateMergeSource(path) if path == &same_a));
+        assert_eq!(35, err.code());
+        assert!(a.path().join("paris (1).jpg").exists());
+    }
+
+    #[test]
+    fn test_merge_fails_for_existing_files() {
+        let a = FixtureBuilder::new("paris ").count(3).create().unwrap();
+        let b = FixtureBuilder::new("paris ").count(3).create().unwrap();
+        let destination = FixtureBuilder::new("paris ")
+            .count(0)
+            .extra_file("paris (5).jpg")
+            .create()
+            .unwrap();
+        let plan = MergePlan::new(&[a.path(), b.path()], destination.path()).unwrap();
+        let err = plan.apply().unwrap_err();
+        assert!(matches!(err, NFLZError::ConflictingFiles(files) if files.len() == 1));
+    }
+}
@@ -23,11 +23,43 @@ SOFTWARE.
 */
 //! Module related to renaming files.
 
+use crate::analysis::ArchiveAnalysis;
+use crate::builder::{NFLZAssistantBuilder, RenumberStart};
+use crate::checkpoint::CheckpointLog;
+use crate::content::ContentCategory;
 use crate::error::NFLZError;
-use crate::file_info::{FileInfo, FileInfoWithRenameAdvice};
-use crate::math::count_digits_without_leading_zeroes;
-use std::collections::HashSet;
-use std::path::{Path, PathBuf};
+use crate::event::{Event, EventListener};
+use crate::explain::{Explanation, Verdict};
+use crate::file_info::{digit_runs, path_to_filename, FileInfo, FileInfoWithRenameAdvice};
+use crate::fsutil::{long_path, rename_with_timeout, sync_directory, DirectorySnapshot, EntryKind};
+use crate::ignore::IgnoreRules;
+use crate::manifest::update_manifests;
+use crate::math::{count_digits_without_leading_zeroes, Radix};
+use crate::open_files::{find_open_files, OpenFilePolicy};
+use crate::plan::{PlanReport, PlanViolation, PlannedRename, RenamePlan, MAX_FILENAME_LEN};
+use crate::prefix::{PrefixEquivalence, UnifiedPrefix};
+use crate::references::update_references;
+use crate::roman::RomanNumerals;
+use crate::schedule::{schedule, RenameStep};
+use crate::scheme::{NumberingScheme, Parentheses};
+use crate::skip::{SkipReason, SkippedFile};
+use crate::snapshot::{AssistantSnapshot, SnapshotEntry};
+use crate::sort::RenumberOrder;
+use crate::stats::{PhaseTimings, Stats};
+use crate::suggestion::outliers;
+use crate::symlinks::retarget_symlinks;
+use crate::unicode::{normalize, UnicodeNormalization};
+use crate::warning::{find_warnings, PlanWarning};
+use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ffi::OsString;
+use std::io::ErrorKind;
+use std::num::NonZeroUsize;
+use std::ops::RangeInclusive;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Main entry point into the library. Helper struct that guides a user through the whole
 /// process of the library.
@@ -38,6 +70,52 @@ pub struct NFLZAssistant {
     /// Vector with all relevant rename information.
     /// The vector is sorted by the order of numbers inside the filename number groups.
     files_with_rename_info: Vec<FileInfoWithRenameAdvice>,
+    /// Receives all events. See [`crate::NFLZAssistantBuilder::event_listener`].
+    event_listener: EventListener,
+    /// State of the directory when the new filenames were computed.
+    snapshot: DirectorySnapshot,
+    /// Files of the working directory that are ignored.
+    skipped_files: Vec<SkippedFile>,
+    /// Hidden files that are ignored.
+    hidden_files: Vec<PathBuf>,
+    /// Junk files of the operating system that are ignored.
+    system_files: Vec<PathBuf>,
+    /// Warnings about the new filenames.
+    warnings: Vec<PlanWarning>,
+    /// Whether the text behind the number groups must be the same for all files.
+    unique_suffix: bool,
+    /// Normalization form to compare filenames.
+    unicode_normalization: Option<UnicodeNormalization>,
+    /// Which differences between prefixes are tolerated.
+    prefix_equivalence: PrefixEquivalence,
+    /// Wildcard patterns of the files whose references are updated after renaming.
+    reference_patterns: Vec<String>,
+    /// Whether the filenames inside checksum manifests are updated after renaming.
+    update_manifests: bool,
+    /// Whether the symlinks to renamed files are retargeted after renaming.
+    retarget_symlinks: bool,
+    /// Path of the checkpoint file and the number of renames per chunk.
+    checkpoint: Option<(PathBuf, NonZeroUsize)>,
+    /// Whether the directories are synced after the renaming.
+    sync_directories: bool,
+    /// Minimum interval between the start of two renames.
+    throttle: Option<Duration>,
+    /// Maximum duration of a single rename.
+    rename_timeout: Option<Duration>,
+    /// Stops the renaming once it is set.
+    interrupt_flag: Option<Arc<AtomicBool>>,
+    /// Whether each file extension forms its own sequence.
+    sequence_per_extension: bool,
+    /// Whether files with the same stem are one logical item.
+    group_sidecars: bool,
+    /// Whether every sequence of digits is padded.
+    pad_all_number_groups: bool,
+    /// Whether the original filenames are recorded in an extended attribute.
+    #[cfg(feature = "xattr")]
+    record_original_filenames: bool,
+    /// How long the phases took so far. Interior mutability because the validation can
+    /// be triggered via `&self`.
+    timings: Cell<PhaseTimings>,
 }
 
 impl NFLZAssistant {
@@ -46,30 +124,385 @@ impl NFLZAssistant {
     ///
     /// # Parameters
     /// * `working_dir` Directory to search for files. Expected to be a directory with files in
-    ///                 the form `Img (1).jpg`, `Img (2).jpg`, ..., `Img (99).jpg`, ...
-    ///                 `Img (124).jpg`.
+    ///   the form `Img (1).jpg`, `Img (2).jpg`, ..., `Img (99).jpg`, ... `Img (124).jpg`.
     pub fn new<P: AsRef<Path>>(working_dir: P) -> Result<Self, NFLZError> {
-        // all files inside the directory
-        let paths = crate::fsutil::read_directory_flat(working_dir.as_ref()).map_err(|err| {
-            NFLZError::CantReadDirectory(PathBuf::from(working_dir.as_ref()), err)
-        })?;
+        Self::builder(working_dir).build()
+    }
+
+    /// Returns a [`NFLZAssistantBuilder`] to configure the assistant in more detail.
+    ///
+    /// # Parameters
+    /// * `working_dir` Directory to search for files. See [`Self::new`].
+    pub fn builder<P: AsRef<Path>>(working_dir: P) -> NFLZAssistantBuilder {
+        NFLZAssistantBuilder::new(working_dir)
+    }
+
+    /// Creates the object from the configuration of the builder. See
+    /// [`NFLZAssistantBuilder::build`].
+    pub(crate) fn from_builder(builder: NFLZAssistantBuilder) -> Result<Self, NFLZError> {
+        let working_dir = builder.working_dir;
+        let event_listener = builder.event_listener;
+
+        let now = Instant::now();
+        // all entries inside the directory, or only the selected ones
+        let entries = builder.selected_files.as_ref().map_or_else(
+            || {
+                crate::fsutil::read_directory_flat(&working_dir)
+                    .map_err(|err| NFLZError::CantReadDirectory(working_dir.clone(), err))
+            },
+            |names| selected_entries(&working_dir, names),
+        );
+        let entries = event_listener.emit_if_err(entries)?;
+        let (system_entries, entries): (Vec<_>, Vec<_>) = entries
+            .into_iter()
+            .partition(|(path, _)| crate::fsutil::is_system_file(path));
+        let (hidden_entries, entries): (Vec<_>, Vec<_>) = entries
+            .into_iter()
+            .partition(|(path, _)| !builder.include_hidden_files && crate::fsutil::is_hidden(path));
+        if !hidden_entries.is_empty() {
+            log::info!("Ignoring {} hidden files", hidden_entries.len());
+        }
+        let ignore_rules = IgnoreRules::read(
+            &working_dir,
+            builder.respect_ignore_file,
+            builder.respect_gitignore,
+        );
+        let ignore_rules = event_listener.emit_if_err(ignore_rules)?;
+        let (ignored_entries, entries): (Vec<_>, Vec<_>) =
+            entries.into_iter().partition(|(path, kind)| {
+                path.file_name().is_some_and(|name| {
+                    ignore_rules.is_ignored(&name.to_string_lossy(), *kind == EntryKind::Directory)
+                })
+            });
+        let snapshot = DirectorySnapshot::capture(&working_dir)
+            .map_err(|err| NFLZError::CantReadDirectory(working_dir.clone(), err));
+        let snapshot = event_listener.emit_if_err(snapshot)?;
+        for (path, _) in &entries {
+            event_listener.emit(Event::Scanned { path });
+        }
+        let scan_duration = now.elapsed();
+        let now = Instant::now();
 
+        let default_scheme = Parentheses::with_radix(builder.radix);
+        let scheme = builder.scheme.as_deref().unwrap_or(&default_scheme);
         // all valid files that could be parsed
-        let files = files_to_nflz_file_info_vec(paths)?;
+        let files = files_to_nflz_file_info_vec(
+            entries,
+            scheme,
+            builder.pad_all_number_groups,
+            builder.radix,
+            &builder.content_categories,
+            &builder.ignored_numbers,
+            &event_listener,
+        );
+        let (mut files, mut skipped_files) = event_listener.emit_if_err(files)?;
+        for (path, _) in ignored_entries {
+            log::info!("Skipping '{}' because of an ignore file", path.display());
+            event_listener.emit(Event::Skipped {
+                path: &path,
+                reason: SkipReason::IgnoredByFile,
+            });
+            skipped_files.push(SkippedFile::new(path, SkipReason::IgnoredByFile));
+        }
+        let open_files = if builder.open_file_policy == OpenFilePolicy::Ignore {
+            HashSet::new()
+        } else {
+            find_open_files(files.iter().map(FileInfo::path))
+        };
+        if builder.open_file_policy == OpenFilePolicy::Skip {
+            let (open, not_open): (Vec<_>, Vec<_>) = files
+                .into_iter()
+                .partition(|file| open_files.contains(file.path()));
+            files = not_open;
+            for file in open {
+                let path = file.path().to_path_buf();
+                log::info!("Skipping '{}' because it is open", path.display());
+                event_listener.emit(Event::Skipped {
+                    path: &path,
+                    reason: SkipReason::OpenInOtherProcess,
+                });
+                skipped_files.push(
+                    SkippedFile::new(path, SkipReason::OpenInOtherProcess)
+                        .with_number(file.number_group_value()),
+                );
+            }
+        }
+        // the renumbering relies on this order
+        builder.renumber_order.sort(&mut files);
+
+        let step = builder.renumber_step.get();
+        let (start, archive_width) = match &builder.renumber_start {
+            Some(RenumberStart::At(start)) => (Some(*start), 0),
+            Some(RenumberStart::After(archive)) => {
+                let archive = scan_archive(archive, scheme, builder.radix);
+                let (max_number, width) = event_listener.emit_if_err(archive)?;
+                (Some(max_number.saturating_add(step)), width)
+            }
+            None if step > 1 || builder.renumber_order != RenumberOrder::Number => (Some(step), 0),
+            None => (None, 0),
+        };
+        // computes the new filenames of one sequence with its own padding
+        let plan_sequence = |mut files: Vec<FileInfo>| -> Result<Vec<_>, NFLZError> {
+            // hexadecimal numbers and padded roman numerals only sort and line up if all of
+            // them have the same case, e.g., `0a` and `0b` or `__IV` and `___X`; the case of the
+            // majority of the numbers with letters wins
+            if matches!(
+                builder.radix,
+                Radix::Hexadecimal | Radix::Roman(RomanNumerals::Padded)
+            ) {
+                let (uppercase, lowercase) = files
+                    .iter()
+                    .filter_map(FileInfo::uppercase_digits)
+                    .fold((0, 0), |(uppercase, lowercase), is_uppercase| {
+                        if is_uppercase {
+                            (uppercase + 1, lowercase)
+                        } else {
+                            (uppercase, lowercase + 1)
+                        }
+                    });
+                let uppercase = uppercase > 0 && uppercase >= lowercase;
+                files = files
+                    .into_iter()
+                    .map(|file| file.with_uppercase_digits(uppercase))
+                    .collect();
+            }
+            let numbers = match start {
+                None => files.iter().map(FileInfo::number_group_value).collect(),
+                Some(start) => {
+                    let items = item_indices(&files, builder.group_sidecars);
+                    let count = items.iter().max().map_or(0, |max| max + 1);
+                    let numbers = renumbering_numbers(start, step, count, &builder.ignored_numbers)
+                        .ok_or(NFLZError::RenumberingOverflow(start, step, count))?;
+                    items
+                        .into_iter()
+                        .map(|index| numbers[index as usize])
+                        .collect::<Vec<_>>()
+                }
+            };
 
-        let max_digits = find_max_digits(&files);
+            // the reserve only applies to the last number group, i.e., the one that grows
+            let has_second_number_group = files
+                .iter()
+                .any(|file| file.second_number_group_value().is_some());
+            let (reserve, second_reserve) = if has_second_number_group {
+                (0, builder.reserve_additional)
+            } else {
+                (builder.reserve_additional, 0)
+            };
+            let radix = if builder.pad_all_number_groups {
+                Radix::Decimal
+            } else {
+                builder.radix
+            };
+            let expected_digits = expected_digits(builder.expected_count, start, step, radix);
+            let (expected_digits, second_expected_digits) = if has_second_number_group {
+                (0, expected_digits)
+            } else {
+                (expected_digits, 0)
+            };
+            let max_digits = find_max_digits(numbers.iter().copied(), reserve, radix)
+                .max(builder.fixed_width)
+                .max(scheme.min_width())
+                .max(expected_digits)
+                .max(archive_width);
+            let second_max_digits = find_max_digits(
+                files.iter().filter_map(FileInfo::second_number_group_value),
+                second_reserve,
+                radix,
+            )
+            .max(builder.fixed_width)
+            .max(second_expected_digits);
+            if let Some(width) = [max_digits, second_max_digits]
+                .into_iter()
+                .find(|&width| width > MAX_FILENAME_LEN as u64)
+            {
+                return Err(NFLZError::NumberGroupTooWide(width));
+            }
+
+            let widths = builder
+                .pad_all_number_groups
+                .then(|| digit_run_widths(&files, &numbers, max_digits, builder.fixed_width));
+            let prefixes = builder
+                .prefix_equivalence
+                .most_common_spellings(files.iter().map(FileInfo::filename_prefix));
+            let max_number = numbers.iter().copied().max().unwrap_or(0);
+            Ok(files
+                .into_iter()
+                .zip(numbers)
+                .map(|(info, number)| {
+                    let file = match &widths {
+                        Some(widths) => FileInfoWithRenameAdvice::new_with_padded_digit_runs(
+                            info,
+                            number,
+                            widths,
+                            &builder.transforms,
+                        ),
+                        None => {
+                            let prefix = prefixes
+                                .get(
+                                    builder
+                                        .prefix_equivalence
+                                        .key(info.filename_prefix())
+                                        .as_ref(),
+                                )
+                                .filter(|prefix| *prefix != info.filename_prefix())
+                                .cloned();
+                            let unified_prefix = prefix
+                                .as_deref()
+                                .map(|prefix| UnifiedPrefix { scheme, prefix });
+                            FileInfoWithRenameAdvice::new_with_max_digits(
+                                info,
+                                number,
+                                max_digits,
+                                second_max_digits,
+                                unified_prefix
+                                    .as_ref()
+                                    .map_or(scheme, |scheme| scheme as &dyn NumberingScheme),
+                                &builder.transforms,
+                            )
+                        }
+                    };
+                    let file = if builder.split_chunk_size == 0 {
+                        file
+                    } else {
+                        file.with_subdirectory(part_name(
+                            number,
+                            max_number,
+                            builder.split_chunk_size,
+                        ))
+                    };
+                    (file, number)
+                })
+                .collect::<Vec<_>>())
+        };
+        let files = if builder.sequence_per_extension {
+            group_by_extension(files, FileInfo::path)
+                .into_values()
+                .map(plan_sequence)
+                .collect::<Result<Vec<_>, _>>()
+                .map(|sequences| sequences.into_iter().flatten().collect())
+        } else {
+            plan_sequence(files)
+        };
+        let files = event_listener.emit_if_err(files)?;
+        // the files outside of the range keep their names but count for the padding
+        let (files, outside_range): (Vec<_>, Vec<_>) = files.into_iter().partition(
+            |(_, number)| !matches!(&builder.number_range, Some(range) if !range.contains(number)),
+        );
+        for (file, _) in outside_range {
+            let path = file.file_info().path().to_path_buf();
+            log::info!("Skipping '{}' because of its number", path.display());
+            event_listener.emit(Event::Skipped {
+                path: &path,
+                reason: SkipReason::OutsideRange,
+            });
+            skipped_files.push(
+                SkippedFile::new(path, SkipReason::OutsideRange)
+                    .with_number(file.file_info().number_group_value()),
+            );
+        }
+        let mut files = files.into_iter().map(|(file, _)| file).collect::<Vec<_>>();
 
-        let mut files = files
+        // sort by number, ascending, then by name. See `FileInfo::cmp`
+        files.sort();
+        if let Some(limit) = builder.limit {
+            // like the files outside of the range, the ones beyond the limit count for the
+            // padding. With `group_sidecars`, the files of an item are kept or skipped together
+            let mut count = 0;
+            let mut kept_items = HashMap::new();
+            let (kept, beyond_limit): (Vec<_>, Vec<_>) = files.into_iter().partition(|file| {
+                if !file.needs_rename() {
+                    return true;
+                }
+                let filename = file.file_info().original_filename();
+                let item = if builder.group_sidecars {
+                    filename_stem(filename)
+                } else {
+                    filename
+                };
+                *kept_items.entry(item.to_string()).or_insert_with(|| {
+                    count += 1;
+                    count <= limit
+                })
+            });
+            for file in beyond_limit {
+                let path = file.file_info().path().to_path_buf();
+                log::info!("Skipping '{}' because of the limit", path.display());
+                event_listener.emit(Event::Skipped {
+                    path: &path,
+                    reason: SkipReason::BeyondLimit,
+                });
+                skipped_files.push(
+                    SkippedFile::new(path, SkipReason::BeyondLimit)
+                        .with_number(file.file_info().number_group_value()),
+                );
+            }
+            files = kept;
+        }
+        // the directory listing has no defined order; like the numbered files, by number and
+        // then by name, with the files without a number last
+        skipped_files.sort_by(|a, b| {
+            (a.number().is_none(), a.number(), a.path()).cmp(&(
+                b.number().is_none(),
+                b.number(),
+                b.path(),
+            ))
+        });
+        let mut system_files = system_entries
             .into_iter()
-            .map(|info| FileInfoWithRenameAdvice::new(info, max_digits))
+            .map(|(path, _)| path)
             .collect::<Vec<_>>();
+        system_files.sort();
 
-        // sort by number, ascending
-        files.sort();
+        for file in &files {
+            event_listener.emit(Event::Planned {
+                path: file.file_info().path(),
+                new_path: file.path_with_new_filename(),
+            });
+        }
+
+        let mut warnings = find_warnings(&files, builder.illegal_character_replacement.is_some());
+        // with `OpenFilePolicy::Skip`, the open files are not part of the plan anymore
+        warnings.extend(
+            files
+                .iter()
+                .filter(|file| file.needs_rename() && open_files.contains(file.file_info().path()))
+                .map(|file| PlanWarning::OpenInOtherProcess {
+                    path: file.file_info().path().to_path_buf(),
+                }),
+        );
+        let timings = PhaseTimings {
+            scan: scan_duration,
+            plan: now.elapsed(),
+            ..Default::default()
+        };
 
         Ok(Self {
-            path: PathBuf::from(working_dir.as_ref()),
+            path: working_dir,
+            skipped_files,
+            hidden_files: hidden_entries.into_iter().map(|(path, _)| path).collect(),
+            system_files,
+            warnings,
+            unique_suffix: scheme.has_unique_suffix(),
+            unicode_normalization: builder.unicode_normalization,
+            prefix_equivalence: builder.prefix_equivalence,
+            reference_patterns: builder.reference_patterns,
+            update_manifests: builder.update_manifests,
+            retarget_symlinks: builder.retarget_symlinks,
+            checkpoint: builder.checkpoint,
+            sync_directories: builder.sync_directories,
+            throttle: builder.throttle,
+            rename_timeout: builder.rename_timeout,
+            interrupt_flag: builder.interrupt_flag,
+            sequence_per_extension: builder.sequence_per_extension,
+            group_sidecars: builder.group_sidecars,
+            pad_all_number_groups: builder.pad_all_number_groups,
+            #[cfg(feature = "xattr")]
+            record_original_filenames: builder.record_original_filenames,
             files_with_rename_info: files,
+            event_listener,
+            snapshot,
+            timings: Cell::new(timings),
         })
     }
 
@@ -77,40 +510,628 @@ impl NFLZAssistant {
     /// * `dir` Directory where all replacements happen. Needed to make some checks before the actual renaming starts.
     /// * `rn_map` Map with the mappings from old to new names.
     /// * `pf_list` List with parsed filenames. Needed to make some checks before the actual renaming starts.
+    ///
+    /// Fails with the error of the first failed check. See
+    /// [`Self::check_destinations_inside_directory`], [`Self::check_no_destination_conflicts`],
+    /// [`Self::check_unambiguous_prefixes`], and [`Self::check_unambiguous_suffixes`] to get
+    /// all violations instead.
     pub fn check_can_rename_all(&self) -> Result<(), NFLZError> {
-        check_no_destination_file_already_exists(&self.files_with_rename_info)?;
-        check_suffixes_and_prefixes_are_unambiguous(&self.files_with_rename_info)?;
-        Ok(())
+        let now = Instant::now();
+        let res = self
+            .check_destinations_inside_directory()
+            .and_then(|report| {
+                [
+                    report,
+                    self.check_no_destination_conflicts(),
+                    self.check_unambiguous_prefixes(),
+                    self.check_unambiguous_suffixes(),
+                ]
+                .iter()
+                .find_map(|report| self.violation_error(report.violations()))
+                .map_or(Ok(()), Err)
+            });
+        self.record_validation(now.elapsed());
+        res
+    }
+
+    /// Checks that all new paths are inside the canonicalized working directory. Thus, neither
+    /// a new filename with `..` or a path separator nor a file whose parent resolves to another
+    /// directory, e.g., via a symlink, can cause a rename outside of the directory. Fails only
+    /// if the working directory can't be canonicalized.
+    pub fn check_destinations_inside_directory(&self) -> Result<PlanReport, NFLZError> {
+        let dir = self
+            .path
+            .canonicalize()
+            .map_err(|err| NFLZError::CantReadDirectory(self.path.clone(), err))?;
+        Ok(PlanReport::new(destinations_outside_directory(
+            &dir,
+            &self.files_with_rename_info,
+        )))
+    }
+
+    /// Checks that no new path already exists inside the file system, unless the existing file
+    /// is renamed itself and thus moved away first, and that no two files get the same new
+    /// filename. The latter can only happen if a custom [`crate::FilenameTransform`] produces
+    /// the same name for different files, or if two names only differ in their Unicode
+    /// normalization form.
+    ///
+    /// The existing files are looked up in the directory listing of the scan instead of the
+    /// file system, which is much faster on network shares. Files that were added since then
+    /// are detected by [`Self::check_directory_unchanged`] before the renaming.
+    pub fn check_no_destination_conflicts(&self) -> PlanReport {
+        let mut violations = occupied_destinations(
+            &self.files_with_rename_info,
+            &self.snapshot,
+            self.unicode_normalization,
+            CASE_INSENSITIVE_FILE_SYSTEM,
+        );
+        violations.extend(duplicate_destinations(
+            &self.files_with_rename_info,
+            self.unicode_normalization,
+        ));
+        PlanReport::new(violations)
+    }
+
+    /// Checks that all files of a sequence share the same text in front of the number group.
+    /// With [`NFLZAssistantBuilder::sequence_per_extension`], each extension is checked on its
+    /// own.
+    pub fn check_unambiguous_prefixes(&self) -> PlanReport {
+        PlanReport::new(
+            self.sequences()
+                .iter()
+                .filter_map(|files| {
+                    ambiguous_prefixes(
+                        files,
+                        self.pad_all_number_groups,
+                        self.unicode_normalization,
+                        self.prefix_equivalence,
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Checks that all files of a sequence share the same text behind the number group, if the
+    /// pattern of the number group requires it. Suffixes that only differ in their case are
+    /// allowed. With [`NFLZAssistantBuilder::sequence_per_extension`], each extension is
+    /// checked on its own.
+    pub fn check_unambiguous_suffixes(&self) -> PlanReport {
+        if !self.unique_suffix {
+            return PlanReport::default();
+        }
+        PlanReport::new(
+            self.sequences()
+                .iter()
+                .filter_map(|files| {
+                    ambiguous_suffixes(
+                        files,
+                        self.group_sidecars,
+                        self.pad_all_number_groups,
+                        self.unicode_normalization,
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Reports all numbers that several files share, such as `paris (2).jpg` and
+    /// `paris (02).jpg`. Files with the same filename stem are no duplicates if
+    /// [`NFLZAssistantBuilder::group_sidecars`] is set. This is no requirement of
+    /// [`Self::check_can_rename_all`], as the files still get different new filenames if they
+    /// differ otherwise or are renumbered. See [`Self::analysis`].
+    pub fn check_duplicate_numbers(&self) -> PlanReport {
+        PlanReport::new(
+            self.analysis()
+                .duplicates()
+                .iter()
+                .map(|(number, sources)| PlanViolation::DuplicateNumber {
+                    number: *number,
+                    sources: sources.clone(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns all files that form an independent sequence. See
+    /// [`NFLZAssistantBuilder::sequence_per_extension`].
+    fn sequences(&self) -> Vec<Vec<&FileInfoWithRenameAdvice>> {
+        if self.sequence_per_extension {
+            group_by_extension(&self.files_with_rename_info, |file| file.file_info().path())
+                .into_values()
+                .collect()
+        } else {
+            vec![self.files_with_rename_info.iter().collect()]
+        }
+    }
+
+    /// Returns the files of the first sequence with ambiguous prefixes whose prefix differs
+    /// from the most common one.
+    fn prefix_outliers(&self) -> Vec<PathBuf> {
+        self.sequences()
+            .iter()
+            .find(|files| {
+                ambiguous_prefixes(
+                    files,
+                    self.pad_all_number_groups,
+                    self.unicode_normalization,
+                    self.prefix_equivalence,
+                )
+                .is_some()
+            })
+            .map_or_else(Vec::new, |files| {
+                let prefixes = files
+                    .iter()
+                    .map(|file| prefix_and_suffix(file.file_info(), self.pad_all_number_groups).0)
+                    .collect::<Vec<_>>();
+                // most files share the same prefix; computes the key of each distinct one once
+                let keys = prefixes
+                    .iter()
+                    .copied()
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .map(|prefix| {
+                        let normalized = normalize(self.unicode_normalization, prefix);
+                        (
+                            prefix,
+                            self.prefix_equivalence.key(&normalized).into_owned(),
+                        )
+                    })
+                    .collect::<HashMap<_, _>>();
+                outliers(
+                    files
+                        .iter()
+                        .zip(prefixes)
+                        .map(|(file, prefix)| (file.file_info().path(), keys[prefix].as_str())),
+                )
+            })
+    }
+
+    /// Returns the files of the first sequence with ambiguous suffixes whose suffix differs
+    /// from the most common one. Like the check, the case and, if the sidecars are grouped,
+    /// the extension are ignored.
+    fn suffix_outliers(&self) -> Vec<PathBuf> {
+        self.sequences()
+            .iter()
+            .find(|files| {
+                ambiguous_suffixes(
+                    files,
+                    self.group_sidecars,
+                    self.pad_all_number_groups,
+                    self.unicode_normalization,
+                )
+                .is_some()
+            })
+            .map_or_else(Vec::new, |files| {
+                let suffixes = files
+                    .iter()
+                    .map(|file| {
+                        let (_, suffix) =
+                            prefix_and_suffix(file.file_info(), self.pad_all_number_groups);
+                        if self.group_sidecars {
+                            filename_stem(suffix)
+                        } else {
+                            suffix
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                // most files share the same suffix; computes the key of each distinct one once
+                let keys = suffixes
+                    .iter()
+                    .copied()
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .map(|suffix| {
+                        let key = normalize(self.unicode_normalization, suffix).to_lowercase();
+                        (suffix, key)
+                    })
+                    .collect::<HashMap<_, _>>();
+                outliers(
+                    files
+                        .iter()
+                        .zip(suffixes)
+                        .map(|(file, suffix)| (file.file_info().path(), keys[suffix].as_str())),
+                )
+            })
+    }
+
+    /// Returns the error of [`Self::check_can_rename_all`] for the violations of a single
+    /// check, if there are any.
+    fn violation_error(&self, violations: &[PlanViolation]) -> Option<NFLZError> {
+        let conflicting_files = || {
+            violations
+                .iter()
+                .flat_map(|violation| match violation {
+                    PlanViolation::DestinationOccupied { destination } => self
+                        .files_with_rename_info
+                        .iter()
+                        .filter(|file| file.path_with_new_filename().as_ref() == Some(destination))
+                        .map(|file| PathBuf::from(file.file_info().path()))
+                        .collect(),
+                    // the first file gets the destination, all others conflict with it
+                    PlanViolation::DuplicateDestination { sources, .. } => sources[1..].to_vec(),
+                    _ => Vec::new(),
+                })
+                .collect()
+        };
+        match violations.first()? {
+            PlanViolation::DestinationOutsideDirectory {
+                source,
+                destination,
+            } => self
+                .files_with_rename_info
+                .iter()
+                .find(|file| file.file_info().path() == source)
+                .map(|file| {
+                    NFLZError::DestinationOutsideDirectory(
+                        Box::new(file.file_info().clone()),
+                        destination.clone(),
+                    )
+                }),
+            PlanViolation::AmbiguousPrefixes { prefixes } => Some(NFLZError::AmbiguousPrefixes(
+                prefixes.iter().cloned().collect(),
+                self.prefix_outliers(),
+            )),
+            PlanViolation::AmbiguousSuffixes { suffixes } => Some(NFLZError::AmbiguousSuffixes(
+                suffixes.iter().cloned().collect(),
+                self.suffix_outliers(),
+            )),
+            _ => Some(NFLZError::ConflictingFiles(conflicting_files())),
+        }
+    }
+
+    /// Verifies that the directory wasn't modified since the new filenames were computed.
+    /// Compares the names, inodes, sizes, and modification times of all entries. Fails
+    /// with [`NFLZError::DirectoryChanged`] otherwise.
+    pub fn check_directory_unchanged(&self) -> Result<(), NFLZError> {
+        let now = Instant::now();
+        let current = DirectorySnapshot::capture(&self.path)
+            .map_err(|err| NFLZError::CantReadDirectory(self.path.clone(), err))?;
+        let changes = self.snapshot.diff(&current);
+        self.record_validation(now.elapsed());
+        if changes.is_empty() {
+            Ok(())
+        } else {
+            Err(NFLZError::DirectoryChanged(self.path.clone(), changes))
+        }
     }
 
     /// Renames all files inside the file system if no possible conflicts are detected. Runs
-    /// [`Self::check_can_rename_all`] first. Note that there may be external changes to the file
-    /// system during that process.
+    /// [`Self::check_can_rename_all`] and [`Self::check_directory_unchanged`] first. Note that
+    /// there may still be external changes to the file system during the renaming itself.
     ///
     /// If the operation is successfully, it returns the same as [`Self::files_to_rename`].
     pub fn rename_all(self) -> Result<Vec<FileInfoWithRenameAdvice>, NFLZError> {
-        self.check_can_rename_all()?;
-        for file in self.files_to_rename() {
-            std::fs::rename(
-                file.file_info().path(),
-                file.path_with_new_filename()
-                    .expect("Must be present at this point! Programming error?!"),
-            )
-            .map_err(|io_err| {
-                NFLZError::RenameFailed(
-                    file.file_info().original_filename().to_string(),
-                    file.new_filename().unwrap().to_string(),
-                    io_err,
-                )
-            })?;
+        self.apply().map(|(files, _)| files)
+    }
+
+    /// Like [`Self::rename_all`] but returns the [`Stats`] of the whole run, including the
+    /// timings of all phases.
+    pub fn rename_all_with_stats(self) -> Result<Stats, NFLZError> {
+        self.apply().map(|(_, stats)| stats)
+    }
+
+    /// Implementation of [`Self::rename_all`] and [`Self::rename_all_with_stats`].
+    fn apply(self) -> Result<(Vec<FileInfoWithRenameAdvice>, Stats), NFLZError> {
+        // the validation of a previous call to check_can_rename_all() doesn't count
+        self.timings.set(PhaseTimings {
+            validation: None,
+            ..self.timings.get()
+        });
+        self.event_listener
+            .emit_if_err(self.check_can_rename_all())?;
+        self.event_listener
+            .emit_if_err(self.check_directory_unchanged())?;
+        let now = Instant::now();
+        let files = self.files_to_rename();
+        for subdirectory in files.iter().filter_map(|file| file.subdirectory()) {
+            let res = std::fs::create_dir_all(long_path(&self.path.join(subdirectory)))
+                .map_err(|err| NFLZError::CantCreateDirectory(self.path.join(subdirectory), err));
+            self.event_listener.emit_if_err(res)?;
+        }
+        let moves = self
+            .mappings()
+            .map(|(path, new_path)| (path.to_path_buf(), new_path))
+            .collect::<Vec<_>>();
+        // renames in an order that never overwrites a file that is renamed later
+        let steps = schedule(&moves);
+        let mut checkpoint = match &self.checkpoint {
+            Some((path, chunk_size)) => {
+                Some(self.event_listener.emit_if_err(CheckpointLog::create(
+                    path,
+                    &steps,
+                    chunk_size.get(),
+                ))?)
+            }
+            None => None,
+        };
+        let mut executed = Vec::new();
+        let mut renamed = 0;
+        // files with a temporary name that are not at their destination yet
+        let mut temporary_files = 0_usize;
+        // index of the executed step that started the current cycle
+        let mut cycle_start = 0;
+        // moves left and moves in total per group of files with the same stem, see
+        // `group_sidecars`
+        let mut group_moves = HashMap::<&str, (usize, usize)>::new();
+        if self.group_sidecars {
+            for (from, _) in &moves {
+                let (left, total) = group_moves
+                    .entry(filename_stem(path_to_filename(from)))
+                    .or_default();
+                *left += 1;
+                *total += 1;
+            }
+        }
+        // groups of which some but not all files are renamed
+        let mut partial_groups = 0_usize;
+        let mut last_rename: Option<Instant> = None;
+        for (index, step) in steps.into_iter().enumerate() {
+            if let (Some(interval), Some(last_rename)) = (self.throttle, last_rename) {
+                std::thread::sleep(interval.saturating_sub(last_rename.elapsed()));
+            }
+            last_rename = Some(Instant::now());
+            // every file keeps either its old or its new name, and groups are renamed completely
+            if temporary_files == 0 && partial_groups == 0 && self.is_interrupted() {
+                return self
+                    .event_listener
+                    .emit_if_err(Err(NFLZError::Interrupted(renamed, moves.len())));
+            }
+            let res =
+                rename_with_timeout(&step.from, &step.to, self.rename_timeout).map_err(|io_err| {
+                    if self.rename_timeout.is_some() && io_err.kind() == ErrorKind::TimedOut {
+                        NFLZError::RenameTimedOut(step.from.clone(), step.to.clone())
+                    } else {
+                        NFLZError::RenameFailed(step.from.clone(), step.to.clone(), io_err)
+                    }
+                });
+            if let Err(err @ NFLZError::RenameTimedOut(_, _)) = res {
+                // the rename may still be in flight, so the state of the files is unknown
+                return self.event_listener.emit_if_err(Err(err));
+            }
+            if let Err(err) = res {
+                let mut left = Vec::new();
+                // without a checkpoint to resume from, a file with a temporary name would drop
+                // out of the sequence, as hidden files are skipped
+                if temporary_files > 0 && checkpoint.is_none() {
+                    left = self.roll_back_cycle(&moves, &executed[cycle_start..]);
+                    executed.truncate(cycle_start);
+                }
+                if self.group_sidecars {
+                    self.roll_back_group(&moves, &executed, &step);
+                }
+                let err = if left.is_empty() {
+                    err
+                } else {
+                    NFLZError::TemporaryFilesLeft(Box::new(err), left)
+                };
+                return self.event_listener.emit_if_err(Err(err));
+            }
+            if let Some(checkpoint) = &mut checkpoint {
+                self.event_listener.emit_if_err(checkpoint.record(index))?;
+            }
+            match step.completes {
+                None => {
+                    if temporary_files == 0 {
+                        cycle_start = executed.len();
+                    }
+                    temporary_files += 1;
+                }
+                Some(index) if step.from != moves[index].0 => temporary_files -= 1,
+                Some(_) => {}
+            }
+            if let Some(index) = step.completes {
+                renamed += 1;
+                if let Some((left, total)) =
+                    group_moves.get_mut(filename_stem(path_to_filename(&moves[index].0)))
+                {
+                    if left == total {
+                        partial_groups += 1;
+                    }
+                    *left -= 1;
+                    if *left == 0 {
+                        partial_groups -= 1;
+                    }
+                }
+                #[cfg(feature = "xattr")]
+                if self.record_original_filenames {
+                    let original = path_to_filename(&moves[index].0);
+                    if let Err(err) =
+                        crate::provenance::record_original_filename(&step.to, original)
+                    {
+                        log::warn!(
+                            "Can't record the original filename of '{}': {}",
+                            step.to.display(),
+                            err
+                        );
+                    }
+                }
+                self.event_listener.emit(Event::Renamed {
+                    path: &moves[index].0,
+                    new_path: step.to.clone(),
+                });
+            }
+            executed.push(step);
+        }
+        if self.sync_directories {
+            // before the checkpoint is deleted, which must not outlive the renames
+            let directories = moves
+                .iter()
+                .flat_map(|(from, to)| [from.parent(), to.parent()])
+                .flatten()
+                .collect::<BTreeSet<_>>();
+            for directory in directories {
+                let res = sync_directory(directory)
+                    .map_err(|err| NFLZError::CantSyncDirectory(directory.to_path_buf(), err));
+                self.event_listener.emit_if_err(res)?;
+            }
+        }
+        if let Some(checkpoint) = checkpoint {
+            self.event_listener.emit_if_err(checkpoint.finish())?;
+        }
+        if !self.reference_patterns.is_empty() || self.update_manifests {
+            self.update_references(&moves)?;
+        }
+        if self.retarget_symlinks {
+            let retargeted = self
+                .event_listener
+                .emit_if_err(retarget_symlinks(&self.path, &moves))?;
+            for (path, target) in retargeted {
+                self.event_listener.emit(Event::SymlinkRetargeted {
+                    path: &path,
+                    target,
+                });
+            }
+        }
+        self.timings.set(PhaseTimings {
+            apply: Some(now.elapsed()),
+            ..self.timings.get()
+        });
+        let stats = self.stats();
+        Ok((self.files_with_rename_info, stats))
+    }
+
+    /// Returns whether [`NFLZAssistantBuilder::interrupt_flag`] is set.
+    fn is_interrupted(&self) -> bool {
+        self.interrupt_flag
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::SeqCst))
+    }
+
+    /// Renames the already renamed files of the group of the failed step back, so that files
+    /// with the same stem are not renamed partially. See
+    /// [`NFLZAssistantBuilder::group_sidecars`]. Files whose old name is taken by now, e.g.,
+    /// by another renamed file, stay renamed.
+    fn roll_back_group(
+        &self,
+        moves: &[(PathBuf, PathBuf)],
+        executed: &[RenameStep],
+        failed: &RenameStep,
+    ) {
+        // steps to temporary names don't complete a move but start at the original path
+        let group = |step: &RenameStep| {
+            let original = step.completes.map_or(&step.from, |index| &moves[index].0);
+            filename_stem(path_to_filename(original)).to_string()
+        };
+        let failed_group = group(failed);
+        for step in executed
+            .iter()
+            .rev()
+            .filter(|step| group(step) == failed_group)
+        {
+            if step.from.symlink_metadata().is_ok() {
+                log::warn!(
+                    "Can't roll back '{}' because '{}' exists",
+                    step.to.display(),
+                    step.from.display()
+                );
+                continue;
+            }
+            if let Err(err) = rename_with_timeout(&step.to, &step.from, self.rename_timeout) {
+                log::warn!("Can't roll back '{}': {}", step.to.display(), err);
+                continue;
+            }
+            match step.completes {
+                Some(index) if step.from == moves[index].0 => {
+                    self.event_listener.emit(Event::Renamed {
+                        path: &moves[index].1,
+                        new_path: step.from.clone(),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Undoes the executed steps of the current cycle in reverse order, so that the files with
+    /// a temporary name get their original name back. Returns the temporary and the original
+    /// path of the files that still have a temporary name because a step can't be undone.
+    fn roll_back_cycle(
+        &self,
+        moves: &[(PathBuf, PathBuf)],
+        cycle: &[RenameStep],
+    ) -> Vec<(PathBuf, PathBuf)> {
+        for (undone, step) in cycle.iter().enumerate().rev() {
+            let res = if step.from.symlink_metadata().is_ok() {
+                Err(std::io::Error::from(std::io::ErrorKind::AlreadyExists))
+            } else {
+                rename_with_timeout(&step.to, &step.from, self.rename_timeout)
+            };
+            if let Err(err) = res {
+                log::warn!("Can't roll back '{}': {}", step.to.display(), err);
+                // the earlier steps can't be undone either, as their old names are still taken
+                return cycle[..=undone]
+                    .iter()
+                    .filter(|step| step.completes.is_none())
+                    .map(|step| (step.to.clone(), step.from.clone()))
+                    .collect();
+            }
+            match step.completes {
+                Some(index) if step.from == moves[index].0 => {
+                    self.event_listener.emit(Event::Renamed {
+                        path: &moves[index].1,
+                        new_path: step.from.clone(),
+                    });
+                }
+                _ => {}
+            }
+        }
+        Vec::new()
+    }
+
+    /// Updates the references to the renamed files. See
+    /// [`NFLZAssistantBuilder::update_references_in`] and
+    /// [`NFLZAssistantBuilder::update_checksum_manifests`].
+    fn update_references(&self, moves: &[(PathBuf, PathBuf)]) -> Result<(), NFLZError> {
+        let renames = moves
+            .iter()
+            .map(|(from, to)| {
+                // the new path may be in a subdirectory, see `split_into_subdirectories`
+                let to = to
+                    .strip_prefix(&self.path)
+                    .unwrap_or(to)
+                    .iter()
+                    .map(|component| component.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                (path_to_filename(from).to_string(), to)
+            })
+            .collect::<Vec<_>>();
+        let mut updated = self.event_listener.emit_if_err(update_references(
+            &self.path,
+            &self.reference_patterns,
+            &renames,
+        ))?;
+        if self.update_manifests {
+            updated.extend(
+                self.event_listener
+                    .emit_if_err(update_manifests(&self.path, &renames))?,
+            );
         }
-        Ok(self.files_with_rename_info)
+        for (path, count) in &updated {
+            self.event_listener.emit(Event::ReferencesUpdated {
+                path,
+                count: *count,
+            });
+        }
+        Ok(())
+    }
+
+    /// Adds the duration to the validation phase of the timings.
+    fn record_validation(&self, duration: Duration) {
+        let timings = self.timings.get();
+        self.timings.set(PhaseTimings {
+            validation: Some(timings.validation.unwrap_or_default() + duration),
+            ..timings
+        });
     }
 
     // GETTERS
 
     /// Returns all files that need to be renamed. Getter can be used to print
     /// all files that the library is going to change in its final rename operation.
+    ///
+    /// The files are sorted by their number, then by their second number, their prefix, and
+    /// their filename, like [`FileInfo`]. The order is the same on every run and platform.
     pub fn files_to_rename(&self) -> Vec<&FileInfoWithRenameAdvice> {
         self.files_with_rename_info
             .iter()
@@ -121,6 +1142,8 @@ impl NFLZAssistant {
     /// Returns all files that need to be renamed because their file name already
     /// fits into the order of the other files. Getter can be used to print all files
     /// that the library will not change during its final rename operation.
+    ///
+    /// Sorted like [`Self::files_to_rename`].
     pub fn files_without_rename(&self) -> Vec<&FileInfoWithRenameAdvice> {
         self.files_with_rename_info
             .iter()
@@ -128,155 +1151,726 @@ impl NFLZAssistant {
             .collect()
     }
 
+    /// Returns whether the current filenames of the numbered files already sort alphabetically
+    /// in the order of their numbers, which is the goal of nflz. Thus, scripts can cheaply
+    /// decide whether a run is needed at all.
+    ///
+    /// The filenames are compared byte by byte, i.e., like a plain `ls` or a file listing
+    /// without natural sorting. Files with the same number, such as sidecars, may be in any
+    /// order among each other.
+    pub fn is_alphabetical_order_consistent(&self) -> bool {
+        let mut files = self
+            .files_with_rename_info
+            .iter()
+            .map(FileInfoWithRenameAdvice::file_info)
+            .collect::<Vec<_>>();
+        files.sort_by(|a, b| a.original_filename().cmp(b.original_filename()));
+        files.windows(2).all(|pair| {
+            let key =
+                |file: &FileInfo| (file.number_group_value(), file.second_number_group_value());
+            key(pair[0]) <= key(pair[1])
+        })
+    }
+
+    /// Returns the [`ArchiveAnalysis`] of all numbered files, e.g., to find gaps and
+    /// duplicates in an existing archive. Only uses the already scanned files.
+    pub fn analysis(&self) -> ArchiveAnalysis {
+        let files = self
+            .files_with_rename_info
+            .iter()
+            .map(FileInfoWithRenameAdvice::file_info);
+        if self.group_sidecars {
+            ArchiveAnalysis::new(files, |file| filename_stem(file.original_filename()))
+        } else {
+            ArchiveAnalysis::new(files, FileInfo::original_filename)
+        }
+    }
+
+    /// Returns the [`RenamePlan`] of all files that need to be renamed. See
+    /// [`RenamePlan::verify`] to check it independently of [`Self::check_can_rename_all`].
+    pub fn plan(&self) -> RenamePlan {
+        RenamePlan::new(
+            self.mappings()
+                .map(|(from, to)| PlannedRename::new(from, to))
+                .collect(),
+        )
+    }
+
+    /// Returns the current and the new path of all files that need to be renamed, in the
+    /// same order as [`Self::files_to_rename`].
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let assistant = NFLZAssistant::new("./test-resources").unwrap();
+    /// for (old, new) in assistant.mappings() {
+    ///     println!("{} => {}", old.display(), new.display());
+    /// }
+    /// ```
+    pub fn mappings(&self) -> impl Iterator<Item = (&Path, PathBuf)> {
+        self.files_with_rename_info
+            .iter()
+            .filter(|file| file.needs_rename())
+            .map(|file| {
+                let new_path = file
+                    .path_with_new_filename()
+                    .expect("must exist at that point");
+                (file.file_info().path(), new_path)
+            })
+    }
+
+    /// Returns an [`Explanation`] for every entry of the working directory, sorted by path:
+    /// whether it is renamed, left alone, or skipped, and why. For the numbered files, it
+    /// contains the chosen number group and the computed width. Helps to understand
+    /// unexpected outcomes.
+    ///
+    /// ```rust
+    /// use nflz::NFLZAssistant;
+    ///
+    /// let assistant = NFLZAssistant::new("./test-resources").unwrap();
+    /// for explanation in assistant.explain() {
+    ///     println!("{}", explanation);
+    /// }
+    /// ```
+    pub fn explain(&self) -> Vec<Explanation> {
+        let files = self.files_with_rename_info.iter().map(|file| {
+            let info = file.file_info();
+            let verdict = file
+                .path_with_new_filename()
+                .map_or(Verdict::Unchanged, |new_path| Verdict::Rename { new_path });
+            Explanation::with_number_group(
+                info.path().to_path_buf(),
+                verdict,
+                info.filename_prefix(),
+                info.number_group_str(),
+                file.width(),
+            )
+        });
+        let skipped_files = self.skipped_files.iter().map(|file| {
+            Explanation::new(
+                file.path().to_path_buf(),
+                Verdict::Skipped {
+                    reason: file.reason(),
+                },
+            )
+        });
+        let hidden_files = self
+            .hidden_files
+            .iter()
+            .map(|path| Explanation::new(path.clone(), Verdict::Hidden));
+        let system_files = self
+            .system_files
+            .iter()
+            .map(|path| Explanation::new(path.clone(), Verdict::SystemFile));
+        let mut explanations = files
+            .chain(skipped_files)
+            .chain(hidden_files)
+            .chain(system_files)
+            .collect::<Vec<_>>();
+        explanations.sort_by(|a, b| a.path().cmp(b.path()));
+        explanations
+    }
+
+    /// Returns all files of the working directory that are ignored, together with the
+    /// reason, such as `notes.txt` without a number group. Files with a number (see
+    /// [`SkippedFile::number`]) come first, sorted by the number and then by the path, i.e.,
+    /// `a (2).jpg` before `a (10).jpg`. The files without a number follow, sorted by path.
+    pub fn skipped_files(&self) -> &[SkippedFile] {
+        &self.skipped_files
+    }
+
+    /// Returns the amount of hidden files of the working directory that are ignored. They are
+    /// not part of [`Self::skipped_files`]. See
+    /// [`NFLZAssistantBuilder::include_hidden_files`].
+    // `Vec::len` is not const on the MSRV
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn hidden_files_ignored(&self) -> usize {
+        self.hidden_files.len()
+    }
+
+    /// Returns the junk files of the operating system or of file managers in the working
+    /// directory, such as `.DS_Store`, `Thumbs.db`, or `desktop.ini`. They are always ignored
+    /// silently and are neither part of [`Self::skipped_files`] nor of
+    /// [`Self::hidden_files_ignored`]. Sorted by path, i.e., by filename.
+    pub fn system_files(&self) -> &[PathBuf] {
+        &self.system_files
+    }
+
+    /// Returns the [`Stats`] of the files and the timings of all phases so far.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            files_to_rename: self.files_to_rename().len(),
+            files_without_rename: self.files_without_rename().len(),
+            timings: self.timings.get(),
+        }
+    }
+
+    /// Returns an owned copy of the whole state of the assistant, e.g., to send it to a GUI.
+    /// See [`AssistantSnapshot`].
+    pub fn snapshot(&self) -> AssistantSnapshot {
+        let entries = self
+            .files_with_rename_info
+            .iter()
+            .map(|file| {
+                SnapshotEntry::new(
+                    file.file_info().path().to_path_buf(),
+                    file.path_with_new_filename(),
+                    file.file_info().number_group_value(),
+                    file.width(),
+                )
+            })
+            .collect();
+        let violations = [
+            self.check_no_destination_conflicts(),
+            self.check_unambiguous_prefixes(),
+            self.check_unambiguous_suffixes(),
+        ]
+        .iter()
+        .flat_map(|report| report.violations().to_vec())
+        .collect();
+        AssistantSnapshot::new(
+            self.path.clone(),
+            entries,
+            self.skipped_files.clone(),
+            self.warnings.clone(),
+            violations,
+            self.stats(),
+        )
+    }
+
+    /// Returns all warnings about the new filenames, such as names with trailing dots that
+    /// are unusable on Windows. Warnings don't prevent the renaming.
+    pub fn warnings(&self) -> &[PlanWarning] {
+        &self.warnings
+    }
+
     /// Returns a copy of the original user input path.
     pub const fn path(&self) -> &PathBuf {
         &self.path
     }
 }
 
-/// Transforms all files by their path to a list of [`FileInfo`]. Files that can't be parsed
-/// to [`FileInfo`] are skipped. Thus, files such as `.gitignore` etc do not hinder the library.
-fn files_to_nflz_file_info_vec(paths: Vec<PathBuf>) -> Result<Vec<FileInfo>, NFLZError> {
-    let mut vec = Vec::with_capacity(paths.len());
-    for path in paths {
-        let file = FileInfo::new(path);
-        match file {
+/// Returns the entries with the given filenames inside the working directory, sorted by path
+/// like [`crate::fsutil::read_directory_flat`]. Fails if an entry can't be accessed or if a
+/// name isn't a plain filename.
+fn selected_entries(
+    working_dir: &Path,
+    names: &[OsString],
+) -> Result<Vec<(PathBuf, EntryKind)>, NFLZError> {
+    let mut entries = names
+        .iter()
+        .map(|name| {
+            let path = working_dir.join(name);
+            let mut components = Path::new(name).components();
+            if !matches!(
+                (components.next(), components.next()),
+                (Some(Component::Normal(_)), None)
+            ) {
+                return Err(NFLZError::CantReadSelectedFile(
+                    path,
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "not directly inside the working directory",
+                    ),
+                ));
+            }
+            crate::fsutil::entry_kind(&path)
+                .map(|kind| (path.clone(), kind))
+                .map_err(|err| NFLZError::CantReadSelectedFile(path, err))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    entries.dedup_by(|(a, _), (b, _)| a == b);
+    Ok(entries)
+}
+
+/// Transforms all files by their path to a list of [`FileInfo`]. Entries that are not a file,
+/// such as directories, and files that can't be parsed to [`FileInfo`] are skipped and
+/// returned as [`SkippedFile`]s. Thus, files such as `.gitignore` etc do not hinder the
+/// library. Uses the custom regex to find the number group, if one is provided.
+fn files_to_nflz_file_info_vec(
+    entries: Vec<(PathBuf, EntryKind)>,
+    scheme: &dyn NumberingScheme,
+    pad_all_number_groups: bool,
+    radix: Radix,
+    content_categories: &[ContentCategory],
+    ignored_numbers: &[RangeInclusive<u64>],
+    event_listener: &EventListener,
+) -> Result<(Vec<FileInfo>, Vec<SkippedFile>), NFLZError> {
+    let mut vec = Vec::with_capacity(entries.len());
+    let mut skipped_files = Vec::new();
+    for (path, kind) in entries {
+        if let Some(reason) = SkipReason::from_entry_kind(kind) {
+            log::info!("Skipping '{}' because of: {}", path.display(), reason);
+            event_listener.emit(Event::Skipped {
+                path: &path,
+                reason,
+            });
+            skipped_files.push(SkippedFile::new(path, reason));
+            continue;
+        }
+
+        let file = if pad_all_number_groups {
+            FileInfo::new_with_digit_runs(&path)
+        } else {
+            FileInfo::new_with_scheme_and_radix(&path, scheme, radix)
+        };
+        let (reason, number) = match file {
+            Ok(file)
+                if ignored_numbers
+                    .iter()
+                    .any(|range| range.contains(&file.number_group_value())) =>
+            {
+                log::info!("Skipping file '{}' because of its number", path.display());
+                (SkipReason::IgnoredNumber, Some(file.number_group_value()))
+            }
+            // only numbered files are inspected, as reading the magic bytes is not for free
+            Ok(file)
+                if !content_categories.is_empty()
+                    && !ContentCategory::detect(&path)
+                        .is_some_and(|category| content_categories.contains(&category)) =>
+            {
+                log::info!(
+                    "Skipping file '{}' because of its content type",
+                    path.display()
+                );
+                (
+                    SkipReason::ExcludedContentType,
+                    Some(file.number_group_value()),
+                )
+            }
             Ok(file) => {
                 vec.push(file);
+                continue;
             }
-            Err(err) => {
-                match err {
-                    // this is acceptable; skip irrelevant files
-                    NFLZError::FilenameMustIncludeExactlyOneNumberedGroup(filename) => {
-                        log::info!("Skipping file '{}'", filename);
-                        continue;
-                    }
-                    NFLZError::ValueInNumberedGroupNotANumber(filename) => {
-                        log::warn!(
-                            "Skipping file '{}' because of invalid number within number group.",
-                            filename
-                        );
-                        continue;
-                    }
-                    _ => (),
+            Err(err) => match SkipReason::from_parse_error(&err, scheme, radix) {
+                // this is acceptable; skip irrelevant files
+                Some(reason) => {
+                    log::info!("Skipping file '{}' because of: {}", path.display(), reason);
+                    (reason, None)
                 }
-                // if the previous match didn't execute the "continue", we have a hard error
-                // => return early from function
-                return Err(err);
-            }
-        }
+                // hard error => return early from function
+                None => return Err(err),
+            },
+        };
+        event_listener.emit(Event::Skipped {
+            path: &path,
+            reason,
+        });
+        let skipped_file = SkippedFile::new(path, reason);
+        skipped_files.push(match number {
+            Some(number) => skipped_file.with_number(number),
+            None => skipped_file,
+        });
+    }
+    Ok((vec, skipped_files))
+}
+
+/// Returns the name of the subdirectory for the number if the files are split into chunks of
+/// `chunk_size` numbers, such as `part-01` for the first chunk. The chunk index is padded to
+/// the same width for all subdirectories, but to at least two digits.
+fn part_name(number: u64, max_number: u64, chunk_size: u64) -> String {
+    let part = |number: u64| number.saturating_sub(1) / chunk_size + 1;
+    let width = count_digits_without_leading_zeroes(part(max_number)).max(2);
+    format!("part-{:0width$}", part(number), width = width as usize)
+}
+
+/// Returns the count of digits of the highest number of a sequence with `expected_count`
+/// files. The sequence starts at `start` with increments of `step` if the files are
+/// renumbered and at `1` otherwise. Returns `0` if `expected_count` is `0`.
+fn expected_digits(expected_count: u64, start: Option<u64>, step: u64, radix: Radix) -> u64 {
+    if expected_count == 0 {
+        return 0;
     }
-    Ok(vec)
+    let highest_number = start.map_or(expected_count, |start| {
+        start.saturating_add((expected_count - 1).saturating_mul(step))
+    });
+    radix.count_digits(highest_number)
+}
+
+/// Returns the highest count of digits of the numbers. `reserve_additional` is added to the
+/// highest number to reserve digits for files that will be added later. The highest number
+/// doesn't necessarily have the most digits, e.g., `VIII` and `X`.
+fn find_max_digits<I: Iterator<Item = u64>>(
+    numbers: I,
+    reserve_additional: u64,
+    radix: Radix,
+) -> u64 {
+    let (max_number, max_digits) = numbers.fold((0, 0), |(max_number, max_digits), number| {
+        (
+            max_number.max(number),
+            max_digits.max(radix.count_digits(number)),
+        )
+    });
+    max_digits.max(radix.count_digits(max_number.saturating_add(reserve_additional)))
 }
 
-/// Searches all files and returns the highest count of digits in a number in a number group.
-fn find_max_digits(files: &[FileInfo]) -> u64 {
+/// Returns the highest number and the widest number group, including leading zeroes, of all
+/// numbered files of the archive. See [`NFLZAssistantBuilder::continue_from`].
+fn scan_archive(
+    archive: &Path,
+    scheme: &dyn NumberingScheme,
+    radix: Radix,
+) -> Result<(u64, u64), NFLZError> {
+    let entries = crate::fsutil::read_directory_flat(archive)
+        .map_err(|err| NFLZError::CantReadDirectory(archive.to_path_buf(), err))?;
+    let files = entries
+        .iter()
+        .filter(|(_, kind)| *kind == EntryKind::File)
+        .filter_map(|(path, _)| FileInfo::new_with_scheme_and_radix(path, scheme, radix).ok())
+        .collect::<Vec<_>>();
     let max_number = files
         .iter()
-        .map(|pf| pf.number_group_value())
+        .map(FileInfo::number_group_value)
         .max()
         .unwrap_or(0);
-    count_digits_without_leading_zeroes(max_number)
+    let width = files
+        .iter()
+        .map(|file| file.existing_padding_width() as u64)
+        .max()
+        .unwrap_or(0);
+    Ok((max_number, width))
 }
 
-/// Checks that no file path after the renaming already exists inside the file system.
-/// Fails otherwise.
-fn check_no_destination_file_already_exists(
-    files: &[FileInfoWithRenameAdvice],
-) -> Result<(), NFLZError> {
-    let files = files
-        .iter()
-        .filter(|file| file.renamed_file_already_exists())
-        .collect::<Vec<_>>();
-    if files.is_empty() {
-        Ok(())
-    } else {
-        let paths = files
-            .iter()
-            .map(|info| PathBuf::from(info.file_info().path()))
-            .collect::<Vec<_>>();
-        Err(NFLZError::ConflictingFiles(paths))
+/// Returns the first `count` numbers of the sequence `start`, `start + step`, ... that are
+/// not ignored. See [`NFLZAssistantBuilder::ignore_numbers`]. `None` if the numbers run out
+/// before [`u64::MAX`].
+fn renumbering_numbers(
+    start: u64,
+    step: u64,
+    count: u64,
+    ignored_numbers: &[RangeInclusive<u64>],
+) -> Option<Vec<u64>> {
+    let mut numbers = Vec::with_capacity(count as usize);
+    let mut next = Some(start);
+    while let Some(number) = next.filter(|_| (numbers.len() as u64) < count) {
+        let ignored_range = ignored_numbers.iter().find(|range| range.contains(&number));
+        next = ignored_range.map_or_else(
+            || {
+                numbers.push(number);
+                number.checked_add(step)
+            },
+            // jumps to the first number of the sequence behind the range
+            |range| {
+                ((range.end() - number) / step)
+                    .checked_add(1)
+                    .and_then(|steps| steps.checked_mul(step))
+                    .and_then(|offset| number.checked_add(offset))
+            },
+        );
     }
+    (numbers.len() as u64 == count).then_some(numbers)
 }
 
-/// Checks if suffixes or prefixes are ambiguous. The only allowed exception for different suffixes
-/// is when there are two suffixes and they do only differ in their case. In this case, its probably
-/// a "Img (1).jpg" and "Img (2).JPG" situation. This might happen if you combine photos from
-/// different cameras.
-fn check_suffixes_and_prefixes_are_unambiguous(
-    pf_list: &[FileInfoWithRenameAdvice],
-) -> Result<(), NFLZError> {
-    let mut prefix_set = HashSet::new();
-    let mut suffix_set = HashSet::new();
-
-    for pf in pf_list {
-        prefix_set.insert(pf.file_info().filename_prefix());
-        suffix_set.insert(pf.file_info().filename_suffix());
-    }
-
-    let two_suffixes_only_differ_in_case = {
-        if suffix_set.len() == 2 {
-            let mut iter = suffix_set.iter();
-            let suffix1 = iter.next().unwrap();
-            let suffix2 = iter.next().unwrap();
-            suffix1.to_lowercase() == suffix2.to_lowercase()
-        } else {
-            false
+/// Returns the width of every position of the sequences of digits in the filenames, i.e., the
+/// most digits (including leading zeroes) of the first, second, ... sequence across all
+/// files, but at least `min_width`. The last sequence of each file, i.e., the number group,
+/// has the value of `numbers` and at least `max_digits` digits. See
+/// [`NFLZAssistantBuilder::pad_all_number_groups`].
+fn digit_run_widths(
+    files: &[FileInfo],
+    numbers: &[u64],
+    max_digits: u64,
+    min_width: u64,
+) -> Vec<u64> {
+    let mut widths = Vec::<u64>::new();
+    for (file, number) in files.iter().zip(numbers) {
+        let filename = file.original_filename();
+        let runs = digit_runs(filename);
+        if widths.len() < runs.len() {
+            widths.resize(runs.len(), min_width.max(1));
+        }
+        for (position, (from, to)) in runs.iter().enumerate() {
+            let digits = if position == runs.len() - 1 {
+                count_digits_without_leading_zeroes(*number).max(max_digits)
+            } else {
+                // never removes existing leading zeroes, such as the one of `S01`
+                u64::from(to - from)
+            };
+            widths[position] = widths[position].max(digits);
         }
-    };
-
-    if prefix_set.len() > 1 {
-        Err(NFLZError::AmbiguousPrefixes(
-            prefix_set
-                .into_iter()
-                .map(|s| s.to_string())
-                .collect::<HashSet<String>>(),
-        ))
-    } else if suffix_set.len() > 1 && !two_suffixes_only_differ_in_case {
-        Err(NFLZError::AmbiguousSuffixes(
-            suffix_set
-                .into_iter()
-                .map(|s| s.to_string())
-                .collect::<HashSet<String>>(),
-        ))
-    } else {
-        Ok(())
     }
+    widths
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::file_info::{FileInfo, FileInfoWithRenameAdvice};
-    use crate::nflz::check_suffixes_and_prefixes_are_unambiguous;
-    use crate::NFLZAssistant;
-    use std::path::Path;
+/// Returns the filename without its extension, e.g., `img (3)` for `img (3).jpg`.
+fn filename_stem(filename: &str) -> &str {
+    filename.rsplit_once('.').map_or(filename, |(stem, _)| stem)
+}
 
-    const TEST_DIR_SRC: &str = "./test-resources";
-    const TEST_DIR_RT: &str = "./.test-resources";
+/// Returns the index of the logical item of each file, in the order of the files. Without
+/// grouping, every file is an item of its own. With grouping, all files with the same stem
+/// share one item. See [`NFLZAssistantBuilder::group_sidecars`].
+fn item_indices(files: &[FileInfo], group: bool) -> Vec<u64> {
+    let mut items = HashMap::new();
+    files
+        .iter()
+        .enumerate()
+        .map(|(index, file)| {
+            if group {
+                let next = items.len() as u64;
+                *items
+                    .entry(filename_stem(file.original_filename()))
+                    .or_insert(next)
+            } else {
+                index as u64
+            }
+        })
+        .collect()
+}
 
-    /// Uses all test files from "test-resources", copies them to ".test-resources", and performs
-    /// the NFLZ action on them.
-    #[test]
-    fn test_nflz() {
-        if Path::is_dir(TEST_DIR_RT.as_ref()) {
-            fs_extra::dir::remove(TEST_DIR_RT).unwrap();
-        }
-        fs_extra::dir::copy(
-            TEST_DIR_SRC,
-            TEST_DIR_RT,
-            &fs_extra::dir::CopyOptions {
-                copy_inside: true,
-                ..Default::default()
-            },
-        )
-        .unwrap();
+/// Groups the files by their lowercase file extension, preserving their order. Files without
+/// an extension form a group of their own. See
+/// [`NFLZAssistantBuilder::sequence_per_extension`].
+fn group_by_extension<T, I: IntoIterator<Item = T>>(
+    files: I,
+    path: fn(&T) -> &Path,
+) -> BTreeMap<String, Vec<T>> {
+    let mut groups = BTreeMap::<String, Vec<T>>::new();
+    for file in files {
+        let extension = path(&file)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        groups.entry(extension).or_default().push(file);
+    }
+    groups
+}
 
-        let assistant = NFLZAssistant::new(TEST_DIR_RT).unwrap();
-        let files_to_rename = assistant.files_to_rename();
-        let files_without_rename = assistant.files_without_rename();
-        assert_eq!(
-            files_without_rename.len() + files_to_rename.len(),
-            11,
+/// Returns a violation for every file whose new path is not a plain filename inside `dir`,
+/// which must be canonicalized. See [`NFLZAssistant::check_destinations_inside_directory`].
+fn destinations_outside_directory(
+    dir: &Path,
+    files: &[FileInfoWithRenameAdvice],
+) -> Vec<PlanViolation> {
+    files
+        .iter()
+        .filter_map(|file| {
+            let new_filename = file.new_filename()?;
+            let new_path = file.path_with_new_filename()?;
+            let mut components = Path::new(new_filename).components();
+            let is_plain_filename = matches!(
+                (components.next(), components.next()),
+                (Some(Component::Normal(_)), None)
+            ) && !new_filename.contains(['/', '\\']);
+            let parent = file
+                .file_info()
+                .path()
+                .parent()
+                .and_then(|parent| parent.canonicalize().ok());
+            (!is_plain_filename || parent.as_deref() != Some(dir)).then(|| {
+                PlanViolation::DestinationOutsideDirectory {
+                    source: PathBuf::from(file.file_info().path()),
+                    destination: new_path,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Whether the file system usually compares filenames case-insensitively, as on Windows and
+/// macOS.
+const CASE_INSENSITIVE_FILE_SYSTEM: bool = cfg!(any(windows, target_os = "macos"));
+
+/// Returns a violation for every new path that already exists, unless the existing file is
+/// renamed itself and thus moved away first. The existing files of the working directory are
+/// looked up in the snapshot; only destinations in existing subdirectories access the file
+/// system. With `case_insensitive`, a file whose name only changes its case, such as
+/// `IMG (10).JPG` => `IMG (10).jpg`, doesn't occupy its own destination.
+fn occupied_destinations(
+    files: &[FileInfoWithRenameAdvice],
+    snapshot: &DirectorySnapshot,
+    normalization: Option<UnicodeNormalization>,
+    case_insensitive: bool,
+) -> Vec<PlanViolation> {
+    let comparable = |name: &str| comparable_name(name, normalization, case_insensitive);
+    // all sources are in the working directory
+    let sources = files
+        .iter()
+        .filter(|file| file.needs_rename())
+        .map(|file| comparable(file.file_info().original_filename()))
+        .collect::<HashSet<_>>();
+    let existing = snapshot.names().map(comparable).collect::<HashSet<_>>();
+    let occupied =
+        |file: &FileInfoWithRenameAdvice| match (file.subdirectory(), file.new_filename()) {
+            // only the working directory itself is in the listing
+            (Some(subdirectory), _) => {
+                existing.contains(&comparable(subdirectory)) && file.renamed_file_already_exists()
+            }
+            (None, Some(new_filename)) => {
+                let new_filename = comparable(new_filename);
+                existing.contains(&new_filename) && !sources.contains(&new_filename)
+            }
+            (None, None) => false,
+        };
+    files
+        .iter()
+        .filter(|file| occupied(file))
+        .filter_map(|file| file.path_with_new_filename())
+        .map(|destination| PlanViolation::DestinationOccupied { destination })
+        .collect()
+}
+
+/// Returns the filename in the form in that the file system compares it: in the Unicode
+/// normalization form and, with `case_insensitive`, in lowercase.
+fn comparable_name(
+    name: &str,
+    normalization: Option<UnicodeNormalization>,
+    case_insensitive: bool,
+) -> String {
+    let name = normalize(normalization, name);
+    if case_insensitive {
+        name.to_lowercase()
+    } else {
+        name.into_owned()
+    }
+}
+
+/// Returns a violation for every new filename that several files get. Files that keep their
+/// name also occupy their name. The filenames are compared in the given Unicode normalization
+/// form.
+fn duplicate_destinations(
+    files: &[FileInfoWithRenameAdvice],
+    normalization: Option<UnicodeNormalization>,
+) -> Vec<PlanViolation> {
+    let mut sources_by_destination = BTreeMap::<_, Vec<&FileInfoWithRenameAdvice>>::new();
+    for file in files {
+        let destination = file
+            .new_filename()
+            .unwrap_or_else(|| file.file_info().original_filename());
+        sources_by_destination
+            .entry(normalize(normalization, destination))
+            .or_default()
+            .push(file);
+    }
+    sources_by_destination
+        .into_values()
+        .filter(|sources| sources.len() > 1)
+        .map(|sources| PlanViolation::DuplicateDestination {
+            destination: sources[0]
+                .path_with_new_filename()
+                .unwrap_or_else(|| PathBuf::from(sources[0].file_info().path())),
+            sources: sources
+                .iter()
+                .map(|file| PathBuf::from(file.file_info().path()))
+                .collect(),
+        })
+        .collect()
+}
+
+/// Returns the text in front of and behind the number group. With `outside_digit_runs`, these
+/// are the texts before the first and behind the last sequence of digits.
+fn prefix_and_suffix(info: &FileInfo, outside_digit_runs: bool) -> (&str, &str) {
+    if outside_digit_runs {
+        let filename = info.original_filename();
+        let runs = digit_runs(filename);
+        let from = runs.first().map_or(0, |(from, _)| *from as usize);
+        let to = runs.last().map_or(0, |(_, to)| *to as usize);
+        (&filename[..from], &filename[to..])
+    } else {
+        (info.filename_prefix(), info.filename_suffix())
+    }
+}
+
+/// Returns a violation if the files don't share the same prefix. The prefixes are compared in
+/// the given Unicode normalization form and tolerate the differences of `equivalence`.
+fn ambiguous_prefixes(
+    files: &[&FileInfoWithRenameAdvice],
+    outside_digit_runs: bool,
+    normalization: Option<UnicodeNormalization>,
+    equivalence: PrefixEquivalence,
+) -> Option<PlanViolation> {
+    // most files share the same prefix; normalizes each distinct one only once
+    let prefixes = files
+        .iter()
+        .map(|file| prefix_and_suffix(file.file_info(), outside_digit_runs).0)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|prefix| normalize(normalization, prefix).into_owned())
+        .collect::<BTreeSet<_>>();
+    let keys = prefixes
+        .iter()
+        .map(|prefix| equivalence.key(prefix))
+        .collect::<BTreeSet<_>>();
+    (keys.len() > 1).then(|| PlanViolation::AmbiguousPrefixes {
+        prefixes: prefixes.into_iter().collect(),
+    })
+}
+
+/// Returns a violation if the files don't share the same suffix. The only allowed exception is
+/// when there are two suffixes and they do only differ in their case. In this case, its
+/// probably a "Img (1).jpg" and "Img (2).JPG" situation. This might happen if you combine
+/// photos from different cameras. The suffixes are compared in the given Unicode
+/// normalization form.
+fn ambiguous_suffixes(
+    files: &[&FileInfoWithRenameAdvice],
+    ignore_extension: bool,
+    outside_digit_runs: bool,
+    normalization: Option<UnicodeNormalization>,
+) -> Option<PlanViolation> {
+    // most files share the same suffix; normalizes each distinct one only once
+    let suffixes = files
+        .iter()
+        .map(|file| {
+            let (_, suffix) = prefix_and_suffix(file.file_info(), outside_digit_runs);
+            if ignore_extension {
+                filename_stem(suffix)
+            } else {
+                suffix
+            }
+        })
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|suffix| normalize(normalization, suffix).into_owned())
+        .collect::<BTreeSet<_>>();
+    let two_suffixes_only_differ_in_case = suffixes.len() == 2 && {
+        let mut iter = suffixes.iter();
+        let suffix1 = iter.next().unwrap();
+        let suffix2 = iter.next().unwrap();
+        suffix1.to_lowercase() == suffix2.to_lowercase()
+    };
+    (suffixes.len() > 1 && !two_suffixes_only_differ_in_case).then(|| {
+        PlanViolation::AmbiguousSuffixes {
+            suffixes: suffixes.into_iter().collect(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::file_info::{FileInfo, FileInfoWithRenameAdvice};
+    use crate::math::Radix;
+    use crate::nflz::{
+        ambiguous_prefixes, ambiguous_suffixes, duplicate_destinations, expected_digits,
+        occupied_destinations, part_name, renumbering_numbers,
+    };
+    use crate::prefix::PrefixEquivalence;
+    use crate::schedule::schedule;
+    use crate::testutil::FixtureBuilder;
+    use crate::transform::{FilenameTransform, FnTransform};
+    use crate::{
+        ContentCategory, Event, NFLZAssistant, NFLZError, PlanViolation, RomanNumerals, SkipReason,
+        UnicodeNormalization,
+    };
+    use std::num::NonZeroU64;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    /// Generates the same files as in "test-resources" and performs the NFLZ action on them.
+    #[test]
+    fn test_nflz() {
+        let fixture = FixtureBuilder::new("paris ")
+            .extra_file("paris (734).jpg")
+            .extra_file("invalid (100) (19231).jpg")
+            .create()
+            .unwrap();
+
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        let files_to_rename = assistant.files_to_rename();
+        let files_without_rename = assistant.files_without_rename();
+        assert_eq!(
+            files_without_rename.len() + files_to_rename.len(),
+            11,
             "must skip file invalid file that doesn't match the pattern!"
         );
 
@@ -306,11 +1900,18 @@ mod tests {
             .collect::<Vec<_>>();
         assert_eq!(["paris (734).jpg"], actual.as_slice());
 
+        let skipped_files = assistant.skipped_files();
+        assert_eq!(1, skipped_files.len());
+        assert_eq!(SkipReason::MultipleNumberGroups, skipped_files[0].reason());
+
         assert!(assistant.check_can_rename_all().is_ok());
 
         // do the renaming inside the file system
-        let renamed = assistant.rename_all().unwrap();
-        assert_eq!(renamed.len(), 11);
+        let stats = assistant.rename_all_with_stats().unwrap();
+        assert_eq!(stats.files_to_rename, 10);
+        assert_eq!(stats.files_without_rename, 1);
+        assert!(stats.timings.validation.is_some());
+        assert!(stats.timings.apply.is_some());
     }
 
     #[test]
@@ -322,8 +1923,16 @@ mod tests {
             FileInfoWithRenameAdvice::new(FileInfo::new("img (3).jpg").unwrap(), 1),
         ];
 
-        check_suffixes_and_prefixes_are_unambiguous(&input)
-            .expect("different font case for file type is allowed");
+        let input = input.iter().collect::<Vec<_>>();
+        assert_eq!(
+            None,
+            ambiguous_prefixes(&input, false, None, PrefixEquivalence::default())
+        );
+        assert_eq!(
+            None,
+            ambiguous_suffixes(&input, false, false, None),
+            "different font case for file type is allowed"
+        );
 
         let input = [
             FileInfoWithRenameAdvice::new(FileInfo::new("img (1).jpg").unwrap(), 1),
@@ -331,6 +1940,1336 @@ mod tests {
             FileInfoWithRenameAdvice::new(FileInfo::new("img (3).jpg").unwrap(), 1),
         ];
 
-        check_suffixes_and_prefixes_are_unambiguous(&input).expect_err("must fail because different prefixes are used (only different font casing is also an error)");
+        assert!(
+            ambiguous_prefixes(
+                &input.iter().collect::<Vec<_>>(),
+                false,
+                None,
+                PrefixEquivalence::default()
+            ).is_some(),
+            "must fail because different prefixes are used (only different font casing is also an error)"
+        );
+    }
+
+    #[test]
+    fn test_check_no_duplicate_destinations() {
+        let transforms: Vec<Box<dyn FilenameTransform>> =
+            vec![Box::new(FnTransform(|_: &FileInfo, _: &str| {
+                "img.jpg".to_string()
+            }))];
+        let input = [
+            FileInfoWithRenameAdvice::new_with_transforms(
+                FileInfo::new("img (1).jpg").unwrap(),
+                1,
+                &transforms,
+            ),
+            FileInfoWithRenameAdvice::new_with_transforms(
+                FileInfo::new("img (2).jpg").unwrap(),
+                1,
+                &transforms,
+            ),
+        ];
+        let violations = duplicate_destinations(&input, None);
+        assert!(matches!(
+            violations.as_slice(),
+            [PlanViolation::DuplicateDestination { sources, .. }] if sources.len() == 2
+        ));
+
+        let input = [
+            FileInfoWithRenameAdvice::new(FileInfo::new("img (1).jpg").unwrap(), 1),
+            FileInfoWithRenameAdvice::new(FileInfo::new("img (2).jpg").unwrap(), 1),
+        ];
+        assert!(duplicate_destinations(&input, None).is_empty());
+
+        // same visual filename in NFC and NFD
+        let input = [
+            FileInfoWithRenameAdvice::new(FileInfo::new("caf\u{e9} (1).jpg").unwrap(), 1),
+            FileInfoWithRenameAdvice::new(FileInfo::new("cafe\u{301} (2).jpg").unwrap(), 1),
+        ];
+        let input = input.iter().collect::<Vec<_>>();
+        assert!(ambiguous_prefixes(&input, false, None, PrefixEquivalence::default()).is_some());
+        assert_eq!(
+            None,
+            ambiguous_prefixes(
+                &input,
+                false,
+                Some(UnicodeNormalization::Nfc),
+                PrefixEquivalence::default()
+            )
+        );
+    }
+
+    #[test]
+    fn test_case_insensitive_prefixes() {
+        let fixture = FixtureBuilder::new("img ")
+            .count(9)
+            .extra_file("IMG (10).jpg")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        let err = assistant.check_can_rename_all().unwrap_err();
+        assert!(matches!(err, NFLZError::AmbiguousPrefixes(_, _)));
+
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .case_insensitive_prefixes(true)
+            .build()
+            .unwrap();
+        assistant.check_can_rename_all().unwrap();
+        assert_eq!(9, assistant.files_to_rename().len());
+
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .case_insensitive_prefixes(true)
+            .unify_prefixes(true)
+            .build()
+            .unwrap();
+        assert_eq!(10, assistant.files_to_rename().len());
+        assistant.rename_all().unwrap();
+        assert!(fixture.path().join("img (01).jpg").is_file());
+        assert!(fixture.path().join("img (10).jpg").is_file());
+    }
+
+    #[test]
+    fn test_whitespace_tolerant_prefixes() {
+        let fixture = FixtureBuilder::new("img ")
+            .count(2)
+            .extra_file("img  (3).jpg")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        let err = assistant.check_can_rename_all().unwrap_err();
+        assert!(matches!(
+            &err,
+            NFLZError::AmbiguousPrefixes(_, files) if files == &[fixture.path().join("img  (3).jpg")]
+        ));
+        assert_eq!(
+            Some("--ignore-prefix-whitespace"),
+            err.suggestions()[0].cli_option()
+        );
+
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .whitespace_tolerant_prefixes(true)
+            .unify_prefixes(true)
+            .build()
+            .unwrap();
+        assistant.check_can_rename_all().unwrap();
+        assistant.rename_all().unwrap();
+        assert!(fixture.path().join("img (3).jpg").is_file());
+        assert!(!fixture.path().join("img  (3).jpg").exists());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_open_file_policy() {
+        use crate::{OpenFilePolicy, PlanWarning};
+        use std::process::{Command, Stdio};
+
+        let fixture = FixtureBuilder::new("img ").count(10).create().unwrap();
+        let open_file = fixture.path().join("img (3).jpg");
+        // another process that keeps the file open as its stdin
+        let mut child = Command::new("sleep")
+            .arg("30")
+            .stdin(Stdio::from(std::fs::File::open(&open_file).unwrap()))
+            .spawn()
+            .unwrap();
+        let build = |policy| {
+            NFLZAssistant::builder(fixture.path())
+                .open_file_policy(policy)
+                .build()
+                .unwrap()
+        };
+        let ignoring = build(OpenFilePolicy::Ignore);
+        let warning = build(OpenFilePolicy::Warn);
+        let skipping = build(OpenFilePolicy::Skip);
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert!(ignoring.warnings().is_empty());
+        assert_eq!(9, ignoring.files_to_rename().len());
+        assert_eq!(
+            [PlanWarning::OpenInOtherProcess {
+                path: open_file.clone()
+            }],
+            warning.warnings()
+        );
+        assert_eq!(9, warning.files_to_rename().len());
+        assert_eq!(8, skipping.files_to_rename().len());
+        assert_eq!(
+            SkipReason::OpenInOtherProcess,
+            skipping.skipped_files()[0].reason()
+        );
+        assert_eq!(open_file, skipping.skipped_files()[0].path());
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn test_track_numbers() {
+        let fixture = FixtureBuilder::new("")
+            .count(0)
+            .extra_file("1 - Intro.mp3")
+            .extra_file("2 - Song.mp3")
+            .extra_file("10 - Outro.mp3")
+            .create()
+            .unwrap();
+
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .pattern(crate::BuiltinPattern::TrackNumber)
+            .build()
+            .unwrap();
+        assert_eq!(2, assistant.files_to_rename().len());
+        assistant.rename_all().unwrap();
+        assert!(fixture.path().join("01 - Intro.mp3").is_file());
+        assert!(fixture.path().join("02 - Song.mp3").is_file());
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn test_date_counters() {
+        let fixture = FixtureBuilder::new("")
+            .count(0)
+            .extra_file("IMG (20230101-3).jpg")
+            .extra_file("IMG (20230101-12).jpg")
+            .extra_file("IMG (20230102-1).jpg")
+            .create()
+            .unwrap();
+
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .pattern(crate::BuiltinPattern::DateCounter)
+            .build()
+            .unwrap();
+        assert_eq!(2, assistant.files_to_rename().len());
+        assistant.rename_all().unwrap();
+        assert!(fixture.path().join("IMG (20230101-03).jpg").is_file());
+        assert!(fixture.path().join("IMG (20230101-12).jpg").is_file());
+        assert!(fixture.path().join("IMG (20230102-01).jpg").is_file());
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn test_camera_presets() {
+        let fixture = FixtureBuilder::new("")
+            .count(0)
+            .extra_file("IMG_20230101_093000.jpg")
+            .extra_file("IMG_20230101_093000_1.jpg")
+            .extra_file("IMG_20230101_093000_10.jpg")
+            .extra_file("IMG_20230102_080000.jpg")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .pattern(crate::BuiltinPattern::Android)
+            .build()
+            .unwrap();
+        assistant.check_can_rename_all().unwrap();
+        assert_eq!(1, assistant.files_to_rename().len());
+        assistant.rename_all().unwrap();
+        assert!(fixture.path().join("IMG_20230101_093000_01.jpg").is_file());
+        assert!(fixture.path().join("IMG_20230102_080000.jpg").is_file());
+
+        let fixture = FixtureBuilder::new("DSC_")
+            .count(0)
+            .extra_file("DSC_0007.JPG")
+            .extra_file("DSC_0012.JPG")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .pattern(crate::BuiltinPattern::Dcf)
+            .start_at(1)
+            .build()
+            .unwrap();
+        assistant.rename_all().unwrap();
+        assert!(fixture.path().join("DSC_0001.JPG").is_file());
+        assert!(fixture.path().join("DSC_0002.JPG").is_file());
+    }
+
+    #[test]
+    fn test_check_destinations_inside_directory() {
+        let fixture = FixtureBuilder::new("a ").create().unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .transform_fn(|_, filename| format!("../{}", filename))
+            .build()
+            .unwrap();
+        let err = assistant.check_can_rename_all().unwrap_err();
+        assert!(matches!(err, NFLZError::DestinationOutsideDirectory(_, _)));
+
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        assistant.check_can_rename_all().unwrap();
+    }
+
+    #[test]
+    fn test_occupied_destinations_use_the_scan() {
+        let fixture = FixtureBuilder::new("a ")
+            .count(10)
+            .extra_file("a (02).jpg")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        let occupied = |assistant: &NFLZAssistant| {
+            assistant
+                .check_no_destination_conflicts()
+                .violations()
+                .iter()
+                .filter(|violation| matches!(violation, PlanViolation::DestinationOccupied { .. }))
+                .count()
+        };
+        assert_eq!(1, occupied(&assistant));
+
+        // files that are added after the scan are detected before the renaming
+        std::fs::write(fixture.path().join("a (03).jpg"), "").unwrap();
+        assert_eq!(1, occupied(&assistant));
+        assert!(matches!(
+            assistant.check_directory_unchanged(),
+            Err(NFLZError::DirectoryChanged(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_occupied_destinations_case_insensitive() {
+        let fixture = FixtureBuilder::new("IMG ")
+            .suffix(".JPG")
+            .extra_file("readme.txt")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .transform_fn(|info, filename| match info.number_group_value() {
+                1 => "README.TXT".to_string(),
+                _ => filename.replace(".JPG", ".jpg"),
+            })
+            .build()
+            .unwrap();
+        let occupied = |case_insensitive| {
+            occupied_destinations(
+                &assistant.files_with_rename_info,
+                &assistant.snapshot,
+                None,
+                case_insensitive,
+            )
+        };
+        // `IMG (10).JPG` => `IMG (10).jpg` doesn't occupy its own destination
+        assert_eq!(
+            vec![PlanViolation::DestinationOccupied {
+                destination: fixture.path().join("README.TXT")
+            }],
+            occupied(true)
+        );
+        assert!(occupied(false).is_empty());
+    }
+
+    #[test]
+    fn test_granular_checks() {
+        let fixture = FixtureBuilder::new("a ")
+            .count(10)
+            .extra_file("a (02).jpg")
+            .extra_file("a (4).png")
+            .extra_file("b (30).jpg")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        let err = assistant.check_can_rename_all().unwrap_err();
+        assert!(matches!(err, NFLZError::ConflictingFiles(_)));
+
+        // all violations at once
+        assert!(assistant
+            .check_destinations_inside_directory()
+            .unwrap()
+            .is_ok());
+        let report = assistant.check_no_destination_conflicts();
+        assert!(matches!(
+            report.violations(),
+            [
+                PlanViolation::DestinationOccupied { .. },
+                PlanViolation::DuplicateDestination { sources, .. }
+            ] if sources.len() == 2
+        ));
+        assert_eq!(
+            [PlanViolation::AmbiguousPrefixes {
+                prefixes: vec!["a (".to_string(), "b (".to_string()]
+            }],
+            assistant.check_unambiguous_prefixes().violations()
+        );
+        assert_eq!(
+            [PlanViolation::AmbiguousSuffixes {
+                suffixes: vec![").jpg".to_string(), ").png".to_string()]
+            }],
+            assistant.check_unambiguous_suffixes().violations()
+        );
+        let report = assistant.check_duplicate_numbers();
+        assert!(matches!(
+            report.violations(),
+            [
+                PlanViolation::DuplicateNumber { number: 2, .. },
+                PlanViolation::DuplicateNumber { number: 4, .. }
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_content_categories() {
+        let fixture = FixtureBuilder::new("a ")
+            .extra_file("a (11).log")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .content_categories([ContentCategory::Image])
+            .build()
+            .unwrap();
+        assert_eq!(9, assistant.files_to_rename().len());
+        let skipped_files = assistant.skipped_files();
+        assert_eq!(1, skipped_files.len());
+        assert_eq!(SkipReason::ExcludedContentType, skipped_files[0].reason());
+        assert!(skipped_files[0].path().ends_with("a (11).log"));
+    }
+
+    #[test]
+    fn test_hidden_files() {
+        let fixture = FixtureBuilder::new("a ")
+            .extra_file("._a (1).jpg")
+            .extra_file(".hidden")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        assert_eq!(2, assistant.hidden_files_ignored());
+        assert!(assistant.skipped_files().is_empty());
+        assistant.check_can_rename_all().unwrap();
+
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .include_hidden_files(true)
+            .build()
+            .unwrap();
+        assert_eq!(0, assistant.hidden_files_ignored());
+        assert_eq!(1, assistant.skipped_files().len());
+        let err = assistant.check_can_rename_all().unwrap_err();
+        assert!(matches!(err, NFLZError::AmbiguousPrefixes(_, _)));
+    }
+
+    #[test]
+    fn test_system_files() {
+        let fixture = FixtureBuilder::new("a ")
+            .extra_file(".DS_Store")
+            .extra_file("thumbs.db")
+            .extra_file("desktop.ini")
+            .create()
+            .unwrap();
+        for include_hidden_files in [false, true] {
+            let assistant = NFLZAssistant::builder(fixture.path())
+                .include_hidden_files(include_hidden_files)
+                .build()
+                .unwrap();
+            assert_eq!(3, assistant.system_files().len());
+            assert_eq!(0, assistant.hidden_files_ignored());
+            assert!(assistant.skipped_files().is_empty());
+            assistant.check_can_rename_all().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_ignore_file() {
+        let fixture = FixtureBuilder::new("a ")
+            .extra_file("b (1).jpg")
+            .extra_file("b (2).jpg")
+            .create()
+            .unwrap();
+        std::fs::write(fixture.path().join(".nflzignore"), "b *\n!b (2).jpg\n").unwrap();
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        let skipped_files = assistant.skipped_files();
+        assert_eq!(1, skipped_files.len());
+        assert_eq!(SkipReason::IgnoredByFile, skipped_files[0].reason());
+        assert!(skipped_files[0].path().ends_with("b (1).jpg"));
+
+        std::fs::write(fixture.path().join(".nflzignore"), "b *\n").unwrap();
+        NFLZAssistant::new(fixture.path())
+            .unwrap()
+            .check_can_rename_all()
+            .unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .respect_ignore_file(false)
+            .build()
+            .unwrap();
+        assert!(assistant.skipped_files().is_empty());
+        let err = assistant.check_can_rename_all().unwrap_err();
+        assert!(matches!(err, NFLZError::AmbiguousPrefixes(_, _)));
+    }
+
+    #[test]
+    fn test_is_alphabetical_order_consistent() {
+        let fixture = FixtureBuilder::new("a ").count(12).create().unwrap();
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        assert!(!assistant.is_alphabetical_order_consistent());
+        assistant.rename_all().unwrap();
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        assert!(assistant.is_alphabetical_order_consistent());
+
+        // sidecars with the same number may be in any order
+        let fixture = FixtureBuilder::new("a ")
+            .count(9)
+            .extra_file("a (1).xmp")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        assert!(assistant.is_alphabetical_order_consistent());
+    }
+
+    #[test]
+    fn test_select_files() {
+        let fixture = FixtureBuilder::new("a ")
+            .extra_file("b (1).jpg")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .select_files(["a (10).jpg", "a (1).jpg", "a (1).jpg"])
+            .build()
+            .unwrap();
+        assistant.check_can_rename_all().unwrap();
+        assert_eq!(1, assistant.files_to_rename().len());
+        assert_eq!(1, assistant.files_without_rename().len());
+        assert_eq!(
+            Some("a (01).jpg"),
+            assistant.files_to_rename()[0].new_filename()
+        );
+
+        for name in ["a (11).jpg", "../a (1).jpg"] {
+            let err = NFLZAssistant::builder(fixture.path())
+                .select_files([name])
+                .build()
+                .unwrap_err();
+            assert!(matches!(err, NFLZError::CantReadSelectedFile(_, _)));
+        }
+    }
+
+    #[test]
+    fn test_directories_are_skipped() {
+        let fixture = FixtureBuilder::new("b ").create().unwrap();
+        // sorts before all files
+        std::fs::create_dir(fixture.path().join("a (1)")).unwrap();
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        assert_eq!(9, assistant.files_to_rename().len());
+        assert_eq!(1, assistant.files_without_rename().len());
+        let skipped_files = assistant.skipped_files();
+        assert_eq!(1, skipped_files.len());
+        assert_eq!(SkipReason::Directory, skipped_files[0].reason());
+    }
+
+    #[test]
+    fn test_part_name() {
+        assert_eq!("part-01", part_name(1, 1000, 500));
+        assert_eq!("part-01", part_name(500, 1000, 500));
+        assert_eq!("part-02", part_name(501, 1000, 500));
+        assert_eq!("part-01", part_name(0, 1000, 500));
+        assert_eq!("part-100", part_name(1000, 1000, 10));
+        assert_eq!("part-001", part_name(1, 1000, 10));
+    }
+
+    #[test]
+    fn test_split_into_subdirectories() {
+        let fixture = FixtureBuilder::new("a ").count(10).create().unwrap();
+        let dir = fixture.path();
+        let assistant = NFLZAssistant::builder(dir)
+            .split_into_subdirectories(4)
+            .build()
+            .unwrap();
+        assert_eq!(10, assistant.files_to_rename().len());
+        assistant.rename_all().unwrap();
+
+        assert!(dir.join("part-01/a (01).jpg").exists());
+        assert!(dir.join("part-01/a (04).jpg").exists());
+        assert!(dir.join("part-02/a (05).jpg").exists());
+        assert!(dir.join("part-03/a (10).jpg").exists());
+        assert!(!dir.join("a (10).jpg").exists());
+    }
+
+    #[test]
+    fn test_continue_from() {
+        let archive = FixtureBuilder::new("a ")
+            .count(0)
+            .extra_file("a (0099).jpg")
+            .extra_file("a (0100).jpg")
+            .create()
+            .unwrap();
+        let fixture = FixtureBuilder::new("a ").count(3).create().unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .continue_from(archive.path())
+            .build()
+            .unwrap();
+        let actual = assistant
+            .files_to_rename()
+            .iter()
+            .map(|file| file.new_filename().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["a (0101).jpg", "a (0102).jpg", "a (0103).jpg"], actual);
+
+        let err = NFLZAssistant::builder(fixture.path())
+            .continue_from(fixture.path().join("foobar"))
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, NFLZError::CantReadDirectory(_, _)));
+    }
+
+    #[test]
+    fn test_start_at() {
+        let fixture = FixtureBuilder::new("a ")
+            .start(5)
+            .count(3)
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .start_at(9)
+            .build()
+            .unwrap();
+        let actual = assistant
+            .files_to_rename()
+            .iter()
+            .map(|file| file.new_filename().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["a (09).jpg", "a (10).jpg", "a (11).jpg"], actual);
+
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .start_at(100)
+            .renumber_step(NonZeroU64::new(20).unwrap())
+            .build()
+            .unwrap();
+        let actual = assistant
+            .files_to_rename()
+            .iter()
+            .map(|file| file.new_filename().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["a (100).jpg", "a (120).jpg", "a (140).jpg"], actual);
+    }
+
+    #[test]
+    fn test_renumber_into_occupied_names() {
+        // 1 => 2, 2 => 3, 3 => 4: every destination is occupied by the next file
+        let fixture = FixtureBuilder::new("a ").count(3).create().unwrap();
+        let dir = fixture.path();
+        for number in 1..=3 {
+            std::fs::write(dir.join(format!("a ({}).jpg", number)), number.to_string()).unwrap();
+        }
+        let assistant = NFLZAssistant::builder(dir).start_at(2).build().unwrap();
+        assistant.rename_all().unwrap();
+        for number in 1..=3 {
+            let content = std::fs::read_to_string(dir.join(format!("a ({}).jpg", number + 1)));
+            assert_eq!(number.to_string(), content.unwrap());
+        }
+        assert!(!dir.join("a (1).jpg").exists());
+    }
+
+    #[test]
+    fn test_plan_verify() {
+        let fixture = FixtureBuilder::new("a ").count(12).create().unwrap();
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        let plan = assistant.plan();
+        assert_eq!(9, plan.renames().len());
+        let (old, new) = assistant.mappings().next().unwrap();
+        assert!(old.ends_with("a (1).jpg"));
+        assert!(new.ends_with("a (01).jpg"));
+        assert!(plan.verify().is_ok());
+
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .transform_fn(|_, _| "same.jpg".to_string())
+            .build()
+            .unwrap();
+        assert!(!assistant.plan().verify().is_ok());
+    }
+
+    #[test]
+    fn test_sequence_per_extension() {
+        let fixture = FixtureBuilder::new("IMG ")
+            .count(10)
+            .extra_file("VID (1).mp4")
+            .extra_file("VID (2).MP4")
+            .create()
+            .unwrap();
+        let err = NFLZAssistant::new(fixture.path())
+            .unwrap()
+            .check_can_rename_all()
+            .unwrap_err();
+        assert!(matches!(err, NFLZError::AmbiguousPrefixes(_, _)));
+
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .sequence_per_extension(true)
+            .build()
+            .unwrap();
+        assistant.check_can_rename_all().unwrap();
+        let mut new_filenames = assistant
+            .files_with_rename_info
+            .iter()
+            .map(|file| {
+                file.new_filename()
+                    .unwrap_or_else(|| file.file_info().original_filename())
+            })
+            .collect::<Vec<_>>();
+        new_filenames.sort_unstable();
+        assert_eq!(
+            vec!["IMG (01).jpg", "IMG (10).jpg", "VID (1).mp4", "VID (2).MP4"],
+            [
+                new_filenames[0],
+                new_filenames[9],
+                new_filenames[10],
+                new_filenames[11]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_sidecars() {
+        let fixture = FixtureBuilder::new("img ")
+            .start(3)
+            .count(5)
+            .gaps([4, 5, 6])
+            .extra_file("img (3).xmp")
+            .extra_file("img (7).xmp")
+            .create()
+            .unwrap();
+        let err = NFLZAssistant::builder(fixture.path())
+            .start_at(1)
+            .build()
+            .unwrap()
+            .check_can_rename_all()
+            .unwrap_err();
+        assert!(matches!(err, NFLZError::AmbiguousSuffixes(_, _)));
+
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .start_at(1)
+            .group_sidecars(true)
+            .build()
+            .unwrap();
+        assistant.check_can_rename_all().unwrap();
+        let mut new_filenames = assistant
+            .files_to_rename()
+            .iter()
+            .map(|file| file.new_filename().unwrap())
+            .collect::<Vec<_>>();
+        new_filenames.sort_unstable();
+        assert_eq!(
+            vec!["img (1).jpg", "img (1).xmp", "img (2).jpg", "img (2).xmp"],
+            new_filenames
+        );
+
+        // the first file of the group was renamed, the second one failed
+        let dir = fixture.path();
+        let moves = vec![
+            (dir.join("img (3).jpg"), dir.join("img (1).jpg")),
+            (dir.join("img (3).xmp"), dir.join("img (1).xmp")),
+            (dir.join("img (7).jpg"), dir.join("img (2).jpg")),
+        ];
+        let steps = schedule(&moves);
+        let executed = [steps[0].clone(), steps[2].clone()];
+        for step in &executed {
+            std::fs::rename(&step.from, &step.to).unwrap();
+        }
+        assistant.roll_back_group(&moves, &executed, &steps[1]);
+        assert!(dir.join("img (3).jpg").exists(), "rolled back");
+        assert!(dir.join("img (3).xmp").exists());
+        assert!(dir.join("img (2).jpg").exists(), "other group");
+    }
+
+    #[test]
+    fn test_roll_back_cycle() {
+        let fixture = FixtureBuilder::new("a ").count(3).create().unwrap();
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        let dir = fixture.path();
+        let moves = vec![
+            (dir.join("a (1).jpg"), dir.join("a (2).jpg")),
+            (dir.join("a (2).jpg"), dir.join("a (3).jpg")),
+            (dir.join("a (3).jpg"), dir.join("a (1).jpg")),
+        ];
+        let steps = schedule(&moves);
+        let temporary_path = steps[0].to.clone();
+        assert_eq!(None, steps[0].completes);
+
+        // the third step failed
+        for step in &steps[..2] {
+            std::fs::rename(&step.from, &step.to).unwrap();
+        }
+        assert!(assistant.roll_back_cycle(&moves, &steps[..2]).is_empty());
+        assert!(!temporary_path.exists());
+        for name in ["a (1).jpg", "a (3).jpg"] {
+            assert_eq!(name, std::fs::read_to_string(dir.join(name)).unwrap());
+        }
+
+        // the old name of the second step is taken by someone else
+        for step in &steps[..2] {
+            std::fs::rename(&step.from, &step.to).unwrap();
+        }
+        std::fs::write(dir.join("a (3).jpg"), "other").unwrap();
+        assert_eq!(
+            vec![(temporary_path.clone(), dir.join("a (1).jpg"))],
+            assistant.roll_back_cycle(&moves, &steps[..2])
+        );
+        assert!(temporary_path.exists());
+        let err = NFLZError::TemporaryFilesLeft(
+            Box::new(NFLZError::RenameFailed(
+                steps[2].from.clone(),
+                steps[2].to.clone(),
+                std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+            )),
+            vec![(temporary_path, dir.join("a (1).jpg"))],
+        );
+        assert_eq!(42, err.code());
+        assert!(err.to_string().contains(".nflz-1.tmp"), "{}", err);
+    }
+
+    #[test]
+    fn test_renumbering_numbers() {
+        assert_eq!(Some(vec![1, 2, 3]), renumbering_numbers(1, 1, 3, &[]));
+        assert_eq!(
+            Some(vec![12, 14, 111, 112]),
+            renumbering_numbers(12, 1, 4, &[13..=13, 15..=110])
+        );
+        assert_eq!(
+            Some(vec![10, 30]),
+            renumbering_numbers(10, 10, 2, &[15..=25])
+        );
+        assert_eq!(
+            Some(vec![u64::MAX - 1, u64::MAX]),
+            renumbering_numbers(u64::MAX - 1, 1, 2, &[])
+        );
+        assert_eq!(None, renumbering_numbers(u64::MAX - 1, 1, 3, &[]));
+        assert_eq!(None, renumbering_numbers(0, 1, 1, &[0..=u64::MAX]));
+    }
+
+    #[test]
+    fn test_expected_digits() {
+        assert_eq!(0, expected_digits(0, None, 1, Radix::Decimal));
+        assert_eq!(5, expected_digits(25000, None, 1, Radix::Decimal));
+        assert_eq!(2, expected_digits(99, Some(1), 1, Radix::Decimal));
+        assert_eq!(3, expected_digits(99, Some(2), 1, Radix::Decimal));
+        assert_eq!(3, expected_digits(20, Some(10), 10, Radix::Decimal));
+        assert_eq!(3, expected_digits(256, None, 1, Radix::Hexadecimal));
+    }
+
+    #[test]
+    fn test_ignore_numbers() {
+        let fixture = FixtureBuilder::new("a ").count(15).create().unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .ignore_numbers([13..=13])
+            .start_at(12)
+            .build()
+            .unwrap();
+        let skipped = assistant.skipped_files();
+        assert_eq!(1, skipped.len());
+        assert_eq!(SkipReason::IgnoredNumber, skipped[0].reason());
+        assert!(skipped[0].path().ends_with("a (13).jpg"));
+        let new_filenames = assistant
+            .files_to_rename()
+            .iter()
+            .map(|file| file.new_filename().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(Some(&"a (12).jpg"), new_filenames.first());
+        assert_eq!(Some(&"a (14).jpg"), new_filenames.get(1));
+        assert_eq!(Some(&"a (26).jpg"), new_filenames.last());
+    }
+
+    #[test]
+    fn test_deterministic_order() {
+        let fixture = FixtureBuilder::new("a ")
+            .count(3)
+            .extra_file("a (2) b.jpg")
+            .extra_file("a (11).jpg")
+            .extra_file("notes.txt")
+            .extra_file("b (1) (2).jpg")
+            .extra_file("Thumbs.db")
+            .extra_file("cover.jpg")
+            .extra_file("desktop.ini")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+        let names = |files: Vec<&FileInfoWithRenameAdvice>| {
+            files
+                .iter()
+                .map(|file| file.file_info().original_filename().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec!["a (1).jpg", "a (2) b.jpg", "a (2).jpg", "a (3).jpg"],
+            names(assistant.files_to_rename())
+        );
+        assert_eq!(vec!["a (11).jpg"], names(assistant.files_without_rename()));
+        let skipped = assistant
+            .skipped_files()
+            .iter()
+            .map(|file| crate::file_info::path_to_filename(file.path()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["b (1) (2).jpg", "cover.jpg", "notes.txt"], skipped);
+        let system_files = assistant
+            .system_files()
+            .iter()
+            .map(|path| crate::file_info::path_to_filename(path))
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["Thumbs.db", "desktop.ini"], system_files);
+
+        // by number, not by path, and the files without a number last
+        let fixture = FixtureBuilder::new("a ")
+            .count(10)
+            .extra_file("b (2).jpg")
+            .extra_file("notes.txt")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .ignore_numbers([2..=2, 10..=10])
+            .build()
+            .unwrap();
+        let skipped = assistant
+            .skipped_files()
+            .iter()
+            .map(|file| crate::file_info::path_to_filename(file.path()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec!["a (2).jpg", "b (2).jpg", "a (10).jpg", "notes.txt"],
+            skipped
+        );
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let fixture = FixtureBuilder::new("a ").count(12).create().unwrap();
+        let checkpoint =
+            std::env::temp_dir().join(format!("nflz-test-checkpoint-{}", std::process::id()));
+        NFLZAssistant::builder(fixture.path())
+            .checkpoint(&checkpoint, std::num::NonZeroUsize::new(4).unwrap())
+            .build()
+            .unwrap()
+            .rename_all()
+            .unwrap();
+        assert!(!checkpoint.exists());
+        assert!(fixture.path().join("a (09).jpg").is_file());
+    }
+
+    #[test]
+    fn test_rename_timeout() {
+        let fixture = FixtureBuilder::new("a ").count(12).create().unwrap();
+        NFLZAssistant::builder(fixture.path())
+            .rename_timeout(std::time::Duration::from_secs(60))
+            .build()
+            .unwrap()
+            .rename_all()
+            .unwrap();
+        assert!(!fixture.path().join("a (1).jpg").exists());
+        assert!(fixture.path().join("a (09).jpg").is_file());
+    }
+
+    #[test]
+    fn test_sync_directories() {
+        let fixture = FixtureBuilder::new("a ").count(12).create().unwrap();
+        NFLZAssistant::builder(fixture.path())
+            .sync_directories(true)
+            .split_into_subdirectories(5)
+            .build()
+            .unwrap()
+            .rename_all()
+            .unwrap();
+        assert!(fixture.path().join("part-01/a (05).jpg").is_file());
+    }
+
+    #[test]
+    fn test_throttle() {
+        let fixture = FixtureBuilder::new("a ").count(12).create().unwrap();
+        let start = std::time::Instant::now();
+        NFLZAssistant::builder(fixture.path())
+            .throttle(std::time::Duration::from_millis(10))
+            .build()
+            .unwrap()
+            .rename_all()
+            .unwrap();
+        // 9 renames, 8 intervals
+        assert!(start.elapsed() >= std::time::Duration::from_millis(80));
+        assert!(fixture.path().join("a (09).jpg").is_file());
+    }
+
+    #[test]
+    fn test_interrupt_flag() {
+        let fixture = FixtureBuilder::new("a ").count(12).create().unwrap();
+        let checkpoint = std::env::temp_dir().join(format!(
+            "nflz-test-interrupt-checkpoint-{}",
+            std::process::id()
+        ));
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let flag = interrupted.clone();
+        let err = NFLZAssistant::builder(fixture.path())
+            .checkpoint(&checkpoint, std::num::NonZeroUsize::new(4).unwrap())
+            .interrupt_flag(interrupted)
+            .event_listener(move |event| {
+                if matches!(event, Event::Renamed { .. }) {
+                    flag.store(true, Ordering::SeqCst);
+                }
+            })
+            .build()
+            .unwrap()
+            .rename_all()
+            .unwrap_err();
+        assert!(matches!(err, NFLZError::Interrupted(1, 9)));
+        assert_eq!(41, err.code());
+        assert!(fixture.path().join("a (01).jpg").is_file());
+        assert!(fixture.path().join("a (2).jpg").is_file());
+
+        let renamed = crate::Checkpoint::read(&checkpoint)
+            .unwrap()
+            .resume()
+            .unwrap();
+        assert_eq!(8, renamed);
+        assert!(!checkpoint.exists());
+        assert!(fixture.path().join("a (09).jpg").is_file());
+    }
+
+    #[test]
+    fn test_interrupt_flag_group_sidecars() {
+        let fixture = FixtureBuilder::new("img ")
+            .start(3)
+            .count(5)
+            .gaps([4, 5, 6])
+            .extra_file("img (3).xmp")
+            .extra_file("img (7).xmp")
+            .create()
+            .unwrap();
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let flag = interrupted.clone();
+        let err = NFLZAssistant::builder(fixture.path())
+            .start_at(1)
+            .group_sidecars(true)
+            .interrupt_flag(interrupted)
+            .event_listener(move |event| {
+                if matches!(event, Event::Renamed { .. }) {
+                    flag.store(true, Ordering::SeqCst);
+                }
+            })
+            .build()
+            .unwrap()
+            .rename_all()
+            .unwrap_err();
+        // stops after the first group, not after its first file
+        assert!(matches!(err, NFLZError::Interrupted(2, 4)), "{:?}", err);
+        for number in [1, 2] {
+            let renamed = |extension| {
+                fixture
+                    .path()
+                    .join(format!("img ({}).{}", number, extension))
+                    .exists()
+            };
+            assert_eq!(renamed("jpg"), renamed("xmp"), "img ({})", number);
+        }
+    }
+
+    #[test]
+    fn test_reserve_additional_up_to_the_max() {
+        let fixture = FixtureBuilder::new("a ").count(3).create().unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .reserve_additional(u64::MAX)
+            .build()
+            .unwrap();
+        assert_eq!(
+            Some("a (00000000000000000001).jpg"),
+            assistant.files_to_rename()[0].new_filename()
+        );
+    }
+
+    #[test]
+    fn test_start_at_overflow() {
+        let fixture = FixtureBuilder::new("a ").count(3).create().unwrap();
+        let err = NFLZAssistant::builder(fixture.path())
+            .start_at(u64::MAX)
+            .build()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            NFLZError::RenumberingOverflow(u64::MAX, 1, 3)
+        ));
+
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .start_at(u64::MAX - 2)
+            .build()
+            .unwrap();
+        assert_eq!(
+            Some("a (18446744073709551615).jpg"),
+            assistant.files_to_rename()[2].new_filename()
+        );
+    }
+
+    #[test]
+    fn test_limit() {
+        let fixture = FixtureBuilder::new("a ").count(12).create().unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .limit(3)
+            .build()
+            .unwrap();
+        let new_filenames = assistant
+            .files_to_rename()
+            .iter()
+            .map(|file| file.new_filename().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec!["a (01).jpg", "a (02).jpg", "a (03).jpg"],
+            new_filenames
+        );
+        assert_eq!(3, assistant.files_without_rename().len());
+        let skipped = assistant.skipped_files();
+        assert_eq!(6, skipped.len());
+        assert!(skipped
+            .iter()
+            .all(|file| file.reason() == SkipReason::BeyondLimit));
+        assistant.rename_all().unwrap();
+        assert!(fixture.path().join("a (03).jpg").is_file());
+        assert!(fixture.path().join("a (4).jpg").is_file());
+    }
+
+    #[test]
+    fn test_limit_group_sidecars() {
+        let fixture = (1..=12)
+            .fold(FixtureBuilder::new("img ").count(12), |fixture, number| {
+                fixture.extra_file(&format!("img ({}).xmp", number))
+            })
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .group_sidecars(true)
+            .limit(3)
+            .build()
+            .unwrap();
+        let mut new_filenames = assistant
+            .files_to_rename()
+            .iter()
+            .map(|file| file.new_filename().unwrap())
+            .collect::<Vec<_>>();
+        new_filenames.sort_unstable();
+        assert_eq!(
+            vec![
+                "img (01).jpg",
+                "img (01).xmp",
+                "img (02).jpg",
+                "img (02).xmp",
+                "img (03).jpg",
+                "img (03).xmp"
+            ],
+            new_filenames
+        );
+        // both files of an item are beyond the limit
+        let skipped = assistant.skipped_files();
+        assert_eq!(12, skipped.len());
+        assert!(skipped
+            .iter()
+            .all(|file| file.reason() == SkipReason::BeyondLimit));
+        assert_eq!(fixture.path().join("img (4).jpg"), skipped[0].path());
+        assert_eq!(fixture.path().join("img (4).xmp"), skipped[1].path());
+    }
+
+    #[test]
+    fn test_only_numbers() {
+        let fixture = FixtureBuilder::new("a ").count(12).create().unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .only_numbers(5..=9)
+            .build()
+            .unwrap();
+        let skipped = assistant.skipped_files();
+        assert_eq!(7, skipped.len());
+        assert!(skipped
+            .iter()
+            .all(|file| file.reason() == SkipReason::OutsideRange));
+        let new_filenames = assistant
+            .files_to_rename()
+            .iter()
+            .map(|file| file.new_filename().unwrap())
+            .collect::<Vec<_>>();
+        // padded for all files, not only the ones in the range
+        assert_eq!(
+            vec![
+                "a (05).jpg",
+                "a (06).jpg",
+                "a (07).jpg",
+                "a (08).jpg",
+                "a (09).jpg"
+            ],
+            new_filenames
+        );
+        assistant.rename_all().unwrap();
+        assert!(fixture.path().join("a (1).jpg").is_file());
+        assert!(fixture.path().join("a (05).jpg").is_file());
+    }
+
+    #[test]
+    fn test_pad_all_number_groups() {
+        let fixture = FixtureBuilder::new("S01E3 - part ")
+            .count(2)
+            .extra_file("S01E12 - part (1).jpg")
+            .extra_file("S01E7 - part (10).jpg")
+            .extra_file("notes.txt")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .pad_all_number_groups(true)
+            .build()
+            .unwrap();
+        assistant.check_can_rename_all().unwrap();
+        assert_eq!(1, assistant.skipped_files().len());
+        let mut new_filenames = assistant
+            .files_to_rename()
+            .iter()
+            .map(|file| file.new_filename().unwrap())
+            .collect::<Vec<_>>();
+        new_filenames.sort_unstable();
+        assert_eq!(
+            vec![
+                "S01E03 - part (01).jpg",
+                "S01E03 - part (02).jpg",
+                "S01E07 - part (10).jpg",
+                "S01E12 - part (01).jpg"
+            ],
+            new_filenames
+        );
+    }
+
+    #[test]
+    fn test_hexadecimal() {
+        let fixture = FixtureBuilder::new("dump ")
+            .count(0)
+            .extra_file("dump (9).bin")
+            .extra_file("dump (a).bin")
+            .extra_file("dump (B).bin")
+            .extra_file("dump (1f).bin")
+            .extra_file("dump (1g).bin")
+            .create()
+            .unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .hexadecimal(true)
+            .build()
+            .unwrap();
+        assert_eq!(1, assistant.skipped_files().len());
+        let new_filenames = assistant
+            .files_to_rename()
+            .iter()
+            .map(|file| file.new_filename().unwrap())
+            .collect::<Vec<_>>();
+        // `0B` would sort before `0a`
+        assert_eq!(
+            vec!["dump (09).bin", "dump (0a).bin", "dump (0b).bin"],
+            new_filenames
+        );
+
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .hexadecimal(true)
+            .start_at(15)
+            .build()
+            .unwrap();
+        let new_filenames = assistant
+            .files_to_rename()
+            .iter()
+            .map(|file| file.new_filename().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "dump (0f).bin",
+                "dump (10).bin",
+                "dump (11).bin",
+                "dump (12).bin"
+            ],
+            new_filenames
+        );
+    }
+
+    #[test]
+    fn test_roman_numerals() {
+        let fixture = FixtureBuilder::new("Chapter ")
+            .count(0)
+            .extra_file("Chapter (iv).md")
+            .extra_file("Chapter (VIII).md")
+            .extra_file("Chapter (X).md")
+            .extra_file("Chapter (IIII).md")
+            .create()
+            .unwrap();
+        let new_filenames = |style| {
+            let assistant = NFLZAssistant::builder(fixture.path())
+                .roman_numerals(style)
+                .build()
+                .unwrap();
+            assert_eq!(1, assistant.skipped_files().len());
+            assistant
+                .files_to_rename()
+                .iter()
+                .map(|file| file.new_filename().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec!["Chapter (04).md", "Chapter (08).md", "Chapter (10).md"],
+            new_filenames(RomanNumerals::ToDecimal)
+        );
+        assert_eq!(
+            vec!["Chapter (__IV).md", "Chapter (___X).md"],
+            new_filenames(RomanNumerals::Padded)
+        );
+
+        // every thousand adds another `M`
+        let err = NFLZAssistant::builder(fixture.path())
+            .roman_numerals(RomanNumerals::Padded)
+            .reserve_additional(100_000_000_000)
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, NFLZError::NumberGroupTooWide(100_000_001)));
+    }
+
+    #[test]
+    fn test_update_references() {
+        let fixture = FixtureBuilder::new("a ").count(10).create().unwrap();
+        let playlist = fixture.path().join("list.m3u");
+        std::fs::write(&playlist, "a (1).jpg\na (10).jpg\n").unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .update_references_in(["*.m3u"])
+            .build()
+            .unwrap();
+        assistant.rename_all().unwrap();
+        assert_eq!(
+            "a (01).jpg\na (10).jpg\n",
+            std::fs::read_to_string(&playlist).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_update_checksum_manifests() {
+        let fixture = FixtureBuilder::new("a ").count(10).create().unwrap();
+        let manifest = fixture.path().join("SHA256SUMS");
+        std::fs::write(&manifest, "0f1e  a (1).jpg\n0f1e  a (10).jpg\n").unwrap();
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .update_checksum_manifests(true)
+            .build()
+            .unwrap();
+        assistant.rename_all().unwrap();
+        assert_eq!(
+            "0f1e  a (01).jpg\n0f1e  a (10).jpg\n",
+            std::fs::read_to_string(&manifest).unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_retarget_symlinks() {
+        let fixture = FixtureBuilder::new("a ").count(10).create().unwrap();
+        let dir = fixture.path();
+        std::os::unix::fs::symlink("a (1).jpg", dir.join("first")).unwrap();
+        std::os::unix::fs::symlink(dir.join("a (2).jpg"), dir.join("second")).unwrap();
+        std::os::unix::fs::symlink("a (10).jpg", dir.join("last")).unwrap();
+        let assistant = NFLZAssistant::builder(dir)
+            .retarget_symlinks(true)
+            .build()
+            .unwrap();
+        assistant.rename_all().unwrap();
+        assert_eq!(
+            std::path::Path::new("a (01).jpg"),
+            std::fs::read_link(dir.join("first")).unwrap()
+        );
+        assert_eq!(
+            dir.join("a (02).jpg"),
+            std::fs::read_link(dir.join("second")).unwrap()
+        );
+        assert_eq!(
+            std::path::Path::new("a (10).jpg"),
+            std::fs::read_link(dir.join("last")).unwrap()
+        );
+        assert!(dir.join("first").exists(), "the link must not dangle");
+    }
+
+    #[test]
+    fn test_rename_all_fails_if_directory_changed() {
+        let fixture = FixtureBuilder::new("a ").count(10).create().unwrap();
+        let dir = fixture.path();
+
+        let assistant = NFLZAssistant::new(dir).unwrap();
+        std::fs::write(dir.join("notes.txt"), "a").unwrap();
+        let err = assistant.rename_all().unwrap_err();
+        assert!(matches!(err, NFLZError::DirectoryChanged(_, changes) if changes.len() == 1));
+        assert!(dir.join("a (1).jpg").exists(), "no file must be renamed");
     }
 }
@@ -0,0 +1,150 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the detection of files that are opened by other processes. See
+//! [`OpenFilePolicy`].
+//!
+//! Renaming a file while an editor has it open leads to confusing situations, such as a "file
+//! not found" error when the editor saves the file. The detection is best effort:
+//! - On Linux, the open file descriptors of all processes in `/proc` that the user may inspect
+//!   are compared with the files.
+//! - On Windows, a file is open if it can't be opened exclusively.
+//! - On other platforms, no file is detected as open.
+//!
+//! Many programs, such as most image viewers, read the file and close it right away. They
+//! can't be detected.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// What happens to files that are opened by other processes. See
+/// [`crate::NFLZAssistantBuilder::open_file_policy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum OpenFilePolicy {
+    /// Open files are not detected.
+    #[default]
+    Ignore,
+    /// Open files that need a rename are reported as [`crate::PlanWarning::OpenInOtherProcess`]
+    /// but renamed anyway.
+    Warn,
+    /// Open files are skipped with [`crate::SkipReason::OpenInOtherProcess`] and keep their
+    /// name.
+    Skip,
+}
+
+/// Returns the files that are currently opened by another process. See the module
+/// documentation for the supported platforms.
+pub(crate) fn find_open_files<'a, I: IntoIterator<Item = &'a Path>>(files: I) -> HashSet<PathBuf> {
+    let files = files.into_iter();
+    #[cfg(target_os = "linux")]
+    {
+        // the file descriptors point to the canonical paths
+        let canonical_files = files
+            .filter_map(|path| Some((path.canonicalize().ok()?, path)))
+            .collect::<std::collections::HashMap<_, _>>();
+        if canonical_files.is_empty() {
+            return HashSet::new();
+        }
+        open_file_descriptors()
+            .filter_map(|target| canonical_files.get(&target))
+            .map(|path| path.to_path_buf())
+            .collect()
+    }
+    #[cfg(windows)]
+    {
+        files
+            .filter(|path| is_locked(path))
+            .map(Path::to_path_buf)
+            .collect()
+    }
+    #[cfg(not(any(target_os = "linux", windows)))]
+    {
+        let _ = files;
+        HashSet::new()
+    }
+}
+
+/// Returns the targets of the open file descriptors of all other processes that the user may
+/// inspect.
+#[cfg(target_os = "linux")]
+fn open_file_descriptors() -> impl Iterator<Item = PathBuf> {
+    let own_pid = std::process::id().to_string();
+    std::fs::read_dir("/proc")
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(move |entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name != own_pid && name.bytes().all(|byte| byte.is_ascii_digit())
+        })
+        // processes of other users can't be inspected and processes may exit meanwhile
+        .filter_map(|entry| std::fs::read_dir(entry.path().join("fd")).ok())
+        .flatten()
+        .filter_map(Result::ok)
+        .filter_map(|fd| std::fs::read_link(fd.path()).ok())
+}
+
+/// Returns true if the file can't be opened exclusively because another process has opened it.
+#[cfg(windows)]
+fn is_locked(path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    std::fs::OpenOptions::new()
+        .read(true)
+        .share_mode(0)
+        .open(crate::fsutil::long_path(path))
+        .is_err_and(|err| err.raw_os_error() == Some(ERROR_SHARING_VIOLATION))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::testutil::FixtureBuilder;
+    use std::fs::File;
+    use std::process::{Command, Stdio};
+
+    #[test]
+    fn test_find_open_files() {
+        let fixture = FixtureBuilder::new("img ").count(3).create().unwrap();
+        let open_file = fixture.path().join("img (2).jpg");
+        let files =
+            ["img (1).jpg", "img (2).jpg", "img (3).jpg"].map(|name| fixture.path().join(name));
+        let paths = || files.iter().map(PathBuf::as_path);
+        assert!(find_open_files(paths()).is_empty());
+
+        // another process that keeps the file open as its stdin
+        let mut child = Command::new("sleep")
+            .arg("30")
+            .stdin(Stdio::from(File::open(&open_file).unwrap()))
+            .spawn()
+            .unwrap();
+        let open_files = find_open_files(paths());
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert_eq!(HashSet::from([open_file]), open_files);
+    }
+}
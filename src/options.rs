@@ -0,0 +1,323 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the options that determine the plan, shared by the library, the CLI, and the
+//! configuration file. See [`Options`].
+
+use crate::builder::NFLZAssistantBuilder;
+use crate::content::ContentCategory;
+use crate::open_files::OpenFilePolicy;
+#[cfg(feature = "regex")]
+use crate::pattern::BuiltinPattern;
+use crate::roman::RomanNumerals;
+use crate::sort::RenumberOrder;
+use crate::transform::BuiltinTransform;
+use crate::unicode::UnicodeNormalization;
+use std::num::NonZeroU64;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+/// The options that determine the plan of a [`crate::NFLZAssistant`], as plain data.
+///
+/// The CLI and the `.nflz.toml` file ([`crate::DirectoryConfig`]) produce them, and
+/// [`NFLZAssistantBuilder::apply_options`] consumes them. Thus, a run of the CLI can be
+/// replayed exactly through the library and vice versa, e.g., with the output of
+/// `nflz --print-options`.
+///
+/// Options that are `None` or empty keep the value of the builder. The fields are named like
+/// the options of the CLI. With the `serde` feature, they are (de)serialized with these names
+/// in kebab case, such as `{"width": 3, "per-extension": true}`; missing fields are `None`.
+///
+/// ```rust
+/// use nflz::{NFLZAssistant, Options};
+///
+/// let mut options = Options::default();
+/// options.width = Some(3);
+/// let assistant = NFLZAssistant::builder("./test-resources")
+///     .apply_options(&options)
+///     .build()
+///     .unwrap();
+/// assert_eq!(
+///     assistant.files_to_rename()[0].new_filename(),
+///     Some("paris (001).jpg")
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(default, rename_all = "kebab-case", deny_unknown_fields)
+)]
+#[non_exhaustive]
+pub struct Options {
+    /// See [`NFLZAssistantBuilder::pattern`].
+    #[cfg(feature = "regex")]
+    pub pattern: Option<BuiltinPattern>,
+    /// Added to the transforms of the builder. See [`NFLZAssistantBuilder::transform`].
+    pub transform: Vec<BuiltinTransform>,
+    /// See [`NFLZAssistantBuilder::fixed_width`].
+    pub width: Option<u64>,
+    /// See [`NFLZAssistantBuilder::reserve_additional`].
+    pub reserve: Option<u64>,
+    /// See [`NFLZAssistantBuilder::expect_count`].
+    pub expect_count: Option<u64>,
+    /// See [`NFLZAssistantBuilder::sequence_per_extension`].
+    pub per_extension: Option<bool>,
+    /// See [`NFLZAssistantBuilder::group_sidecars`].
+    pub group_sidecars: Option<bool>,
+    /// See [`NFLZAssistantBuilder::pad_all_number_groups`].
+    pub all_groups: Option<bool>,
+    /// See [`NFLZAssistantBuilder::hexadecimal`].
+    pub hex: Option<bool>,
+    /// See [`NFLZAssistantBuilder::roman_numerals`].
+    pub roman: Option<RomanNumerals>,
+    /// See [`NFLZAssistantBuilder::include_hidden_files`].
+    pub include_hidden: Option<bool>,
+    /// See [`NFLZAssistantBuilder::content_categories`].
+    pub only: Vec<ContentCategory>,
+    /// The opposite of [`NFLZAssistantBuilder::respect_ignore_file`].
+    pub no_ignore_file: Option<bool>,
+    /// See [`NFLZAssistantBuilder::respect_gitignore`].
+    pub gitignore: Option<bool>,
+    /// See [`NFLZAssistantBuilder::ignore_numbers`].
+    pub ignore_numbers: Vec<RangeInclusive<u64>>,
+    /// See [`NFLZAssistantBuilder::only_numbers`].
+    pub range: Option<RangeInclusive<u64>>,
+    /// See [`NFLZAssistantBuilder::limit`].
+    pub limit: Option<usize>,
+    /// See [`NFLZAssistantBuilder::split_into_subdirectories`].
+    pub split: Option<u64>,
+    /// See [`NFLZAssistantBuilder::renumber_by`].
+    pub renumber_by: Option<RenumberOrder>,
+    /// See [`NFLZAssistantBuilder::start_at`].
+    pub start_at: Option<u64>,
+    /// See [`NFLZAssistantBuilder::renumber_step`].
+    pub step: Option<NonZeroU64>,
+    /// See [`NFLZAssistantBuilder::continue_from`].
+    pub continue_from: Option<PathBuf>,
+    /// See [`NFLZAssistantBuilder::update_references_in`].
+    pub update_refs: Vec<String>,
+    /// See [`NFLZAssistantBuilder::update_checksum_manifests`].
+    pub update_manifests: Option<bool>,
+    /// See [`NFLZAssistantBuilder::retarget_symlinks`].
+    pub retarget_symlinks: Option<bool>,
+    /// See [`NFLZAssistantBuilder::trim_trailing_dots_and_spaces`].
+    pub trim_trailing: Option<bool>,
+    /// Replaces illegal characters with `_`. See
+    /// [`NFLZAssistantBuilder::replace_illegal_characters`].
+    pub sanitize: Option<bool>,
+    /// See [`NFLZAssistantBuilder::open_file_policy`].
+    pub open_files: Option<OpenFilePolicy>,
+    /// See [`NFLZAssistantBuilder::case_insensitive_prefixes`].
+    pub ignore_prefix_case: Option<bool>,
+    /// See [`NFLZAssistantBuilder::whitespace_tolerant_prefixes`].
+    pub ignore_prefix_whitespace: Option<bool>,
+    /// See [`NFLZAssistantBuilder::unify_prefixes`].
+    pub unify_prefixes: Option<bool>,
+    /// See [`NFLZAssistantBuilder::unicode_normalization`]. `Some(None)` compares the
+    /// filenames byte by byte and is serialized as `null`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            deserialize_with = "deserialize_some",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    pub normalize: Option<Option<UnicodeNormalization>>,
+}
+
+impl Options {
+    /// Applies all options that are set on the builder. See
+    /// [`NFLZAssistantBuilder::apply_options`].
+    pub(crate) fn apply(&self, mut builder: NFLZAssistantBuilder) -> NFLZAssistantBuilder {
+        #[cfg(feature = "regex")]
+        if let Some(pattern) = self.pattern {
+            builder = builder.pattern(pattern);
+        }
+        for transform in &self.transform {
+            builder = builder.transform(*transform);
+        }
+        if let Some(width) = self.width {
+            builder = builder.fixed_width(width);
+        }
+        if let Some(reserve) = self.reserve {
+            builder = builder.reserve_additional(reserve);
+        }
+        if let Some(count) = self.expect_count {
+            builder = builder.expect_count(count);
+        }
+        if let Some(per_extension) = self.per_extension {
+            builder = builder.sequence_per_extension(per_extension);
+        }
+        if let Some(group) = self.group_sidecars {
+            builder = builder.group_sidecars(group);
+        }
+        if let Some(pad_all) = self.all_groups {
+            builder = builder.pad_all_number_groups(pad_all);
+        }
+        if let Some(hexadecimal) = self.hex {
+            builder = builder.hexadecimal(hexadecimal);
+        }
+        if let Some(style) = self.roman {
+            builder = builder.roman_numerals(style);
+        }
+        if let Some(include) = self.include_hidden {
+            builder = builder.include_hidden_files(include);
+        }
+        if !self.only.is_empty() {
+            builder = builder.content_categories(self.only.iter().copied());
+        }
+        if let Some(no_ignore_file) = self.no_ignore_file {
+            builder = builder.respect_ignore_file(!no_ignore_file);
+        }
+        if let Some(gitignore) = self.gitignore {
+            builder = builder.respect_gitignore(gitignore);
+        }
+        if !self.ignore_numbers.is_empty() {
+            builder = builder.ignore_numbers(self.ignore_numbers.iter().cloned());
+        }
+        if let Some(range) = &self.range {
+            builder = builder.only_numbers(range.clone());
+        }
+        if let Some(limit) = self.limit {
+            builder = builder.limit(limit);
+        }
+        if let Some(chunk_size) = self.split {
+            builder = builder.split_into_subdirectories(chunk_size);
+        }
+        if let Some(order) = self.renumber_by {
+            builder = builder.renumber_by(order);
+        }
+        if let Some(start) = self.start_at {
+            builder = builder.start_at(start);
+        }
+        if let Some(step) = self.step {
+            builder = builder.renumber_step(step);
+        }
+        if let Some(archive) = &self.continue_from {
+            builder = builder.continue_from(archive);
+        }
+        if !self.update_refs.is_empty() {
+            builder = builder.update_references_in(self.update_refs.iter().cloned());
+        }
+        if let Some(update) = self.update_manifests {
+            builder = builder.update_checksum_manifests(update);
+        }
+        if let Some(retarget) = self.retarget_symlinks {
+            builder = builder.retarget_symlinks(retarget);
+        }
+        if let Some(trim) = self.trim_trailing {
+            builder = builder.trim_trailing_dots_and_spaces(trim);
+        }
+        if self.sanitize == Some(true) {
+            builder = builder.replace_illegal_characters('_');
+        }
+        if let Some(policy) = self.open_files {
+            builder = builder.open_file_policy(policy);
+        }
+        if let Some(ignore_case) = self.ignore_prefix_case {
+            builder = builder.case_insensitive_prefixes(ignore_case);
+        }
+        if let Some(ignore_whitespace) = self.ignore_prefix_whitespace {
+            builder = builder.whitespace_tolerant_prefixes(ignore_whitespace);
+        }
+        if let Some(unify) = self.unify_prefixes {
+            builder = builder.unify_prefixes(unify);
+        }
+        if let Some(normalization) = self.normalize {
+            builder = builder.unicode_normalization(normalization);
+        }
+        builder
+    }
+}
+
+/// Deserializes a present value, including `null`, as `Some`. Missing fields are `None`
+/// because of `#[serde(default)]`.
+#[cfg(feature = "serde")]
+fn deserialize_some<'de, D: serde::Deserializer<'de>, T: serde::Deserialize<'de>>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::FixtureBuilder;
+    use crate::NFLZAssistant;
+
+    #[test]
+    fn test_apply() {
+        let fixture = FixtureBuilder::new("a ").count(3).create().unwrap();
+        let options = Options {
+            width: Some(3),
+            transform: vec![BuiltinTransform::SpacesToUnderscores],
+            start_at: Some(10),
+            ..Default::default()
+        };
+        let assistant = NFLZAssistant::builder(fixture.path())
+            .apply_options(&options)
+            .build()
+            .unwrap();
+        assert_eq!(
+            Some("a_(010).jpg"),
+            assistant.files_to_rename()[0].new_filename()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let options = Options {
+            width: Some(3),
+            per_extension: Some(true),
+            transform: vec![BuiltinTransform::LowercaseExtension],
+            only: vec![ContentCategory::Image],
+            roman: Some(RomanNumerals::ToDecimal),
+            renumber_by: Some(RenumberOrder::Mtime),
+            open_files: Some(OpenFilePolicy::Skip),
+            range: Some(100..=199),
+            normalize: Some(None),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&options).unwrap();
+        assert_eq!(3, json["width"]);
+        assert_eq!(true, json["per-extension"]);
+        assert_eq!("lowercase-ext", json["transform"][0]);
+        assert_eq!("image", json["only"][0]);
+        assert_eq!("decimal", json["roman"]);
+        assert_eq!("mtime", json["renumber-by"]);
+        assert_eq!("skip", json["open-files"]);
+        assert!(json["normalize"].is_null());
+        assert_eq!(options, serde_json::from_value::<Options>(json).unwrap());
+
+        // missing fields keep the builder's value
+        let options = serde_json::from_str::<Options>(r#"{"width": 3}"#).unwrap();
+        assert_eq!(Some(3), options.width);
+        assert_eq!(None, options.normalize);
+        let options = serde_json::from_str::<Options>(r#"{"normalize": "nfd"}"#).unwrap();
+        assert_eq!(Some(Some(UnicodeNormalization::Nfd)), options.normalize);
+        assert!(serde_json::from_str::<Options>(r#"{"foo": 1}"#).is_err());
+        assert!(serde_json::from_str::<Options>(r#"{"transform": ["foo"]}"#).is_err());
+    }
+}
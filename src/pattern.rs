@@ -0,0 +1,296 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for the built-in patterns that find the number groups inside a filename. See
+//! [`BuiltinPattern`]. Only available with the `regex` feature.
+
+use crate::error::NFLZError;
+use crate::scheme::{NumberGroups, NumberingScheme};
+use regex::Regex;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Regex of a date in the form `YYYYMMDD`.
+const DATE: &str = r"(?:19|20)[0-9]{2}(?:0[1-9]|1[0-2])(?:0[1-9]|[12][0-9]|3[01])";
+
+/// The built-in patterns that find the number group(s) inside the filenames.
+///
+/// They can be selected by their name, for example from the command line. Use
+/// [`crate::NFLZAssistantBuilder::number_group_regex`] for everything else.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BuiltinPattern {
+    /// `paris (1).jpg`: digits in parentheses. Name: `parentheses`.
+    #[default]
+    Parentheses,
+    /// `Show S1E3.mkv`: season and episode are padded independently of each other. Text
+    /// behind the episode, such as a title, is allowed to differ between the files. Name:
+    /// `season-episode`.
+    SeasonEpisode,
+    /// `3 - Song Title.mp3`: a bare number at the beginning of the filename, followed by
+    /// `-`, `. `, `_`, or a space. The title is allowed to differ between the files. Name:
+    /// `track`.
+    TrackNumber,
+    /// `frame3.png`: the digits directly before the file extension, without any separator.
+    /// Usually combined with [`crate::NFLZAssistantBuilder::fixed_width`], e.g., for
+    /// `ffmpeg -i frame%04d.png`. Name: `trailing`.
+    Trailing,
+    /// `IMG (20230101-3).jpg`: a date (`YYYYMMDD`) and a counter in parentheses, as produced
+    /// by some phone backup tools. Only the counter is padded; the date stays intact and
+    /// orders the files before the counter. As the date is the first number group,
+    /// renumbering options, such as [`crate::NFLZAssistantBuilder::start_at`], would replace
+    /// it. Name: `date-counter`.
+    DateCounter,
+    /// `IMG_20230101_123456_1.jpg`: the date and time of the camera app of most Android
+    /// phones, also with the prefix `VID_` or without a prefix (Samsung). Only the counter
+    /// behind the time, which the phone appends to photos taken within the same second
+    /// (`_1`, `(1)`, or `~1`), is padded; the date and the time stay intact. As the date is the
+    /// first number group, renumbering options would replace it. Name: `android`.
+    Android,
+    /// `PXL_20230101_123456789.jpg`: the date and time with milliseconds of Google Pixel
+    /// phones. Like [`Self::Android`], only a counter behind the time is padded. Name: `pixel`.
+    Pixel,
+    /// `IMG_0001.JPG`: the four-digit counter of cameras that follow the DCF standard for
+    /// `DCIM` directories, such as `DSC_0001.JPG`, `DSCF0001.JPG`, and the `IMG_` files of
+    /// iPhones. The counter is padded to at least four digits, so renumbered files look like
+    /// the ones of the camera. Name: `dcf`.
+    Dcf,
+}
+
+impl BuiltinPattern {
+    /// All built-in patterns.
+    pub const ALL: [Self; 8] = [
+        Self::Parentheses,
+        Self::SeasonEpisode,
+        Self::TrackNumber,
+        Self::Trailing,
+        Self::DateCounter,
+        Self::Android,
+        Self::Pixel,
+        Self::Dcf,
+    ];
+
+    /// Returns the name under that the pattern can be selected.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Parentheses => "parentheses",
+            Self::SeasonEpisode => "season-episode",
+            Self::TrackNumber => "track",
+            Self::Trailing => "trailing",
+            Self::DateCounter => "date-counter",
+            Self::Android => "android",
+            Self::Pixel => "pixel",
+            Self::Dcf => "dcf",
+        }
+    }
+
+    /// Returns the regex for [`crate::FileInfo::new_with_regex`]. `None` for
+    /// [`Self::Parentheses`], because the faster hand-written parser of [`crate::FileInfo::new`]
+    /// is used for it.
+    pub(crate) fn regex(self) -> Option<Regex> {
+        let regex = match self {
+            Self::Parentheses => return None,
+            Self::SeasonEpisode => r"(?i)\bS([0-9]+)E([0-9]+)".to_string(),
+            Self::TrackNumber => r"^([0-9]+)(?: ?- ?|\. |_| )".to_string(),
+            Self::Trailing => r"([0-9]+)(?:\.[^.]*)?$".to_string(),
+            Self::DateCounter => format!(r"\(({})-([0-9]+)\)", DATE),
+            Self::Android => format!(
+                r"^(?:[A-Za-z]+_)?({})_[0-9]{{6}}(?:[_(~]([0-9]+)\)?)?",
+                DATE
+            ),
+            Self::Pixel => format!(r"^PXL_({})_[0-9]{{9}}(?:[_~]([0-9]+))?", DATE),
+            Self::Dcf => r"^[A-Za-z]{3,4}_?([0-9]+)(?:\.[^.]*)?$".to_string(),
+        };
+        Some(Regex::new(&regex).unwrap())
+    }
+
+    /// Returns the [`NumberingScheme`] of the pattern. `None` for [`Self::Parentheses`], which
+    /// is the default scheme.
+    pub(crate) fn scheme(self) -> Option<Box<dyn NumberingScheme>> {
+        self.regex().map(|regex| {
+            Box::new(PatternScheme {
+                pattern: self,
+                regex,
+            }) as Box<dyn NumberingScheme>
+        })
+    }
+
+    /// Whether the text behind the number groups must be the same for all files. See
+    /// [`NFLZError::AmbiguousSuffixes`].
+    pub(crate) const fn has_unique_suffix(self) -> bool {
+        match self {
+            Self::Parentheses | Self::Trailing | Self::DateCounter | Self::Dcf => true,
+            // the time is part of the text behind the date
+            Self::SeasonEpisode | Self::TrackNumber | Self::Android | Self::Pixel => false,
+        }
+    }
+
+    /// Minimum amount of digits of the first number group, such as the four digits of the
+    /// counters of cameras. See [`crate::NFLZAssistantBuilder::fixed_width`].
+    pub(crate) const fn min_width(self) -> u64 {
+        match self {
+            Self::Dcf => 4,
+            _ => 0,
+        }
+    }
+}
+
+/// The [`NumberingScheme`] of a [`BuiltinPattern`] with its compiled regex.
+#[derive(Debug)]
+struct PatternScheme {
+    pattern: BuiltinPattern,
+    regex: Regex,
+}
+
+impl NumberingScheme for PatternScheme {
+    fn find_number_groups(&self, filename: &str) -> Vec<NumberGroups> {
+        self.regex.find_number_groups(filename)
+    }
+
+    fn has_unique_suffix(&self) -> bool {
+        self.pattern.has_unique_suffix()
+    }
+
+    fn min_width(&self) -> u64 {
+        self.pattern.min_width()
+    }
+}
+
+impl FromStr for BuiltinPattern {
+    type Err = NFLZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|pattern| pattern.name() == s)
+            .ok_or_else(|| NFLZError::UnknownPattern(s.to_string()))
+    }
+}
+
+impl Display for BuiltinPattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Serializes the pattern as its [`BuiltinPattern::name`].
+#[cfg(feature = "serde")]
+impl serde::Serialize for BuiltinPattern {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Deserializes the pattern from its [`BuiltinPattern::name`].
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BuiltinPattern {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_info::FileInfo;
+
+    #[test]
+    fn test_builtin_pattern() {
+        for pattern in BuiltinPattern::ALL {
+            assert_eq!(pattern, pattern.name().parse().unwrap());
+        }
+        assert!(matches!(
+            "foobar".parse::<BuiltinPattern>(),
+            Err(NFLZError::UnknownPattern(_))
+        ));
+
+        let regex = BuiltinPattern::SeasonEpisode.regex().unwrap();
+        let file_info = FileInfo::new_with_regex("show.s02e10.mkv", &regex).unwrap();
+        assert_eq!(2, file_info.number_group_value());
+        assert_eq!(Some(10), file_info.second_number_group_value());
+        assert!(FileInfo::new_with_regex("Show S1E3 S1E4.mkv", &regex).is_err());
+
+        let regex = BuiltinPattern::TrackNumber.regex().unwrap();
+        for (filename, number) in [
+            ("3 - Song Title.mp3", 3),
+            ("03. Song 2.mp3", 3),
+            ("12_Song.mp3", 12),
+            ("7 Song.mp3", 7),
+        ] {
+            let file_info = FileInfo::new_with_regex(filename, &regex).unwrap();
+            assert_eq!(number, file_info.number_group_value());
+            assert_eq!("", file_info.filename_prefix());
+        }
+        assert!(FileInfo::new_with_regex("Song 3.mp3", &regex).is_err());
+        assert!(FileInfo::new_with_regex("3.mp3", &regex).is_err());
+
+        let regex = BuiltinPattern::Trailing.regex().unwrap();
+        let file_info = FileInfo::new_with_regex("frame1432.png", &regex).unwrap();
+        assert_eq!(1432, file_info.number_group_value());
+        assert_eq!("frame", file_info.filename_prefix());
+        assert_eq!(".png", file_info.filename_suffix());
+        let file_info = FileInfo::new_with_regex("render_2_17", &regex).unwrap();
+        assert_eq!(17, file_info.number_group_value());
+        assert!(FileInfo::new_with_regex("frame.png", &regex).is_err());
+
+        let regex = BuiltinPattern::DateCounter.regex().unwrap();
+        let file_info = FileInfo::new_with_regex("IMG (20230101-3).jpg", &regex).unwrap();
+        assert_eq!(20230101, file_info.number_group_value());
+        assert_eq!(Some(3), file_info.second_number_group_value());
+        assert_eq!("IMG (", file_info.filename_prefix());
+        assert_eq!(").jpg", file_info.filename_suffix());
+        assert!(FileInfo::new_with_regex("IMG (20231301-3).jpg", &regex).is_err());
+        assert!(FileInfo::new_with_regex("IMG (3).jpg", &regex).is_err());
+
+        let regex = BuiltinPattern::Android.regex().unwrap();
+        for (filename, counter) in [
+            ("IMG_20230101_123456.jpg", None),
+            ("VID_20230101_123456_2.mp4", Some(2)),
+            ("20230101_123456(12).jpg", Some(12)),
+        ] {
+            let file_info = FileInfo::new_with_regex(filename, &regex).unwrap();
+            assert_eq!(20230101, file_info.number_group_value());
+            assert_eq!(counter, file_info.second_number_group_value());
+        }
+        assert!(FileInfo::new_with_regex("IMG_0001.jpg", &regex).is_err());
+
+        let regex = BuiltinPattern::Pixel.regex().unwrap();
+        let file_info = FileInfo::new_with_regex("PXL_20230101_123456789~3.jpg", &regex).unwrap();
+        assert_eq!(20230101, file_info.number_group_value());
+        assert_eq!(Some(3), file_info.second_number_group_value());
+        assert_eq!(Some("_123456789~"), file_info.filename_infix());
+        let file_info = FileInfo::new_with_regex("PXL_20230101_123456789.MP.jpg", &regex).unwrap();
+        assert_eq!(None, file_info.second_number_group_value());
+        assert!(FileInfo::new_with_regex("IMG_20230101_123456.jpg", &regex).is_err());
+
+        let regex = BuiltinPattern::Dcf.regex().unwrap();
+        for filename in ["IMG_0001.JPG", "DSC_0001.JPG", "DSCF0001.JPG", "IMG_1.HEIC"] {
+            let file_info = FileInfo::new_with_regex(filename, &regex).unwrap();
+            assert_eq!(1, file_info.number_group_value(), "{}", filename);
+        }
+        assert!(FileInfo::new_with_regex("IMG_20230101_123456.jpg", &regex).is_err());
+    }
+}
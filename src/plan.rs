@@ -0,0 +1,727 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for the verification of rename plans. See [`RenamePlan`].
+
+use crate::error::NFLZError;
+use crate::fsutil::long_path;
+use crate::schedule::schedule;
+use crate::script::ScriptFormat;
+use crate::transform::is_illegal_on_windows;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Filenames that are reserved on Windows, no matter the extension.
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// The maximum length of a filename: 255 bytes on most Unix file systems and 255 UTF-16 code
+/// units on Windows.
+pub(crate) const MAX_FILENAME_LEN: usize = 255;
+
+/// A single rename of a [`RenamePlan`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PlannedRename {
+    /// Current path of the file.
+    from: PathBuf,
+    /// Path of the file after the rename.
+    to: PathBuf,
+}
+
+impl PlannedRename {
+    /// Constructor.
+    pub fn new<F: Into<PathBuf>, T: Into<PathBuf>>(from: F, to: T) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    /// Returns the current path of the file.
+    pub fn from(&self) -> &Path {
+        &self.from
+    }
+
+    /// Returns the path of the file after the rename.
+    pub fn to(&self) -> &Path {
+        &self.to
+    }
+
+    /// Returns the filename of the file, or the whole path if it has none.
+    fn source_name(&self) -> Cow<'_, str> {
+        self.from.file_name().map_or_else(
+            || self.from.to_string_lossy(),
+            |filename| filename.to_string_lossy(),
+        )
+    }
+
+    /// Returns the path after the rename relative to the directory of the file, such as
+    /// `part-01/paris (01).jpg`, or the whole path if it is somewhere else.
+    fn destination_name(&self) -> Cow<'_, str> {
+        self.from
+            .parent()
+            .and_then(|dir| self.to.strip_prefix(dir).ok())
+            .unwrap_or(&self.to)
+            .to_string_lossy()
+    }
+}
+
+/// All renames of a run, such as the ones of [`crate::NFLZAssistant::plan`].
+///
+/// [`Self::verify`] checks the invariants that a plan must fulfill before it can be applied
+/// safely. Embedders can gate the renaming on it, and tests can use it as an oracle for
+/// arbitrary plans.
+///
+/// ```rust
+/// use nflz::{PlanViolation, PlannedRename, RenamePlan};
+///
+/// let plan = RenamePlan::new(vec![
+///     PlannedRename::new("./test-resources/paris (1).jpg", "./test-resources/paris (01).jpg"),
+///     PlannedRename::new("./test-resources/paris (2).jpg", "./test-resources/paris (01).jpg"),
+/// ]);
+/// let report = plan.verify();
+/// assert!(!report.is_ok());
+/// assert!(matches!(
+///     report.violations()[0],
+///     PlanViolation::DuplicateDestination { .. }
+/// ));
+/// ```
+///
+/// Before it is applied, single renames can be dropped, e.g., the files that the user
+/// unchecked in a list. The dropped files keep their names. Thus, the plan must be verified
+/// again, as a remaining rename may target the name of a dropped file. [`Self::apply`] does
+/// that.
+///
+/// ```rust,no_run
+/// use nflz::NFLZAssistant;
+/// use std::path::Path;
+///
+/// let mut plan = NFLZAssistant::new("./paris").unwrap().plan();
+/// plan.remove(Path::new("./paris/paris (7).jpg"));
+/// plan.set_destination("./paris/paris (8).jpg", "./paris/paris (08) cover.jpg");
+/// plan.apply().unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RenamePlan {
+    /// All renames.
+    renames: Vec<PlannedRename>,
+}
+
+impl RenamePlan {
+    /// Constructor.
+    pub const fn new(renames: Vec<PlannedRename>) -> Self {
+        Self { renames }
+    }
+
+    /// Returns all renames.
+    pub fn renames(&self) -> &[PlannedRename] {
+        &self.renames
+    }
+
+    /// Returns the number of renames.
+    // `Vec::len` is not const on the MSRV
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn len(&self) -> usize {
+        self.renames.len()
+    }
+
+    /// Returns true if the plan renames nothing.
+    // `Vec::is_empty` is not const on the MSRV
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn is_empty(&self) -> bool {
+        self.renames.is_empty()
+    }
+
+    /// Returns the renames with the indices of the range, e.g., a page of a virtualized list
+    /// in a GUI. The range is clamped to the renames, so the last page may be shorter and a
+    /// range behind the end is empty. The plan owns its renames, so it can be kept, e.g., by
+    /// the GUI, independently of the [`crate::NFLZAssistant`] that created it.
+    ///
+    /// ```rust
+    /// use nflz::{PlannedRename, RenamePlan};
+    ///
+    /// let plan = RenamePlan::new(
+    ///     (1..=250)
+    ///         .map(|n| PlannedRename::new(format!("a ({}).jpg", n), format!("a ({:03}).jpg", n)))
+    ///         .collect(),
+    /// );
+    /// assert_eq!(250, plan.len());
+    /// assert_eq!(100, plan.entries(100..200).len());
+    /// assert_eq!(50, plan.entries(200..300).len());
+    /// assert!(plan.entries(300..400).is_empty());
+    /// ```
+    pub fn entries(&self, range: Range<usize>) -> &[PlannedRename] {
+        let end = range.end.min(self.renames.len());
+        let start = range.start.min(end);
+        &self.renames[start..end]
+    }
+
+    /// Drops the rename of the file with the given current path, if there is one, and returns
+    /// it. The file keeps its name.
+    pub fn remove(&mut self, from: &Path) -> Option<PlannedRename> {
+        let index = self.renames.iter().position(|rename| rename.from == from)?;
+        Some(self.renames.remove(index))
+    }
+
+    /// Pins the path of the file after the rename, overriding the computed one, e.g., for a
+    /// single outlier of an otherwise automatic run. Adds a rename if the file isn't renamed
+    /// by the plan yet. Returns the previous destination, if there was one.
+    ///
+    /// The new destination may conflict with other renames or existing files, which
+    /// [`Self::verify`] and [`Self::apply`] detect.
+    pub fn set_destination<F: Into<PathBuf>, T: Into<PathBuf>>(
+        &mut self,
+        from: F,
+        to: T,
+    ) -> Option<PathBuf> {
+        let from = from.into();
+        let to = to.into();
+        match self.renames.iter_mut().find(|rename| rename.from == from) {
+            Some(rename) => Some(std::mem::replace(&mut rename.to, to)),
+            None => {
+                self.renames.push(PlannedRename { from, to });
+                None
+            }
+        }
+    }
+
+    /// Keeps only the renames for which the predicate returns true and returns the number of
+    /// dropped renames. The order of the remaining renames stays the same.
+    pub fn retain<F: FnMut(&PlannedRename) -> bool>(&mut self, predicate: F) -> usize {
+        let count = self.renames.len();
+        self.renames.retain(predicate);
+        count - self.renames.len()
+    }
+
+    /// Checks all invariants of the plan and returns every violation:
+    /// - no two renames have the same destination,
+    /// - no two renames have the same source,
+    /// - all sources exist,
+    /// - every destination is either free or the source of another rename of the plan, i.e.,
+    ///   vacated before (see the rename order of [`crate::NFLZAssistant::rename_all`]),
+    /// - all new filenames are valid on all platforms, including Windows.
+    ///
+    /// Only reads the file system.
+    pub fn verify(&self) -> PlanReport {
+        let mut violations = Vec::new();
+
+        let mut sources_by_destination = HashMap::<&Path, Vec<&Path>>::new();
+        for rename in &self.renames {
+            sources_by_destination
+                .entry(&rename.to)
+                .or_default()
+                .push(&rename.from);
+        }
+        let mut reported = HashSet::new();
+        for rename in &self.renames {
+            let sources = &sources_by_destination[rename.to.as_path()];
+            if sources.len() > 1 && reported.insert(rename.to.as_path()) {
+                violations.push(PlanViolation::DuplicateDestination {
+                    destination: rename.to.clone(),
+                    sources: sources.iter().map(|path| path.to_path_buf()).collect(),
+                });
+            }
+        }
+
+        let mut destinations_by_source = HashMap::<&Path, Vec<&Path>>::new();
+        for rename in &self.renames {
+            destinations_by_source
+                .entry(&rename.from)
+                .or_default()
+                .push(&rename.to);
+        }
+        let mut reported = HashSet::new();
+        for rename in &self.renames {
+            let destinations = &destinations_by_source[rename.from.as_path()];
+            if destinations.len() > 1 && reported.insert(rename.from.as_path()) {
+                violations.push(PlanViolation::DuplicateSource {
+                    source: rename.from.clone(),
+                    destinations: destinations.iter().map(|path| path.to_path_buf()).collect(),
+                });
+            }
+        }
+
+        let sources = destinations_by_source;
+        for rename in &self.renames {
+            if rename.from.symlink_metadata().is_err() {
+                violations.push(PlanViolation::MissingSource {
+                    source: rename.from.clone(),
+                });
+            }
+            if rename.to.symlink_metadata().is_ok() && !sources.contains_key(rename.to.as_path()) {
+                violations.push(PlanViolation::DestinationOccupied {
+                    destination: rename.to.clone(),
+                });
+            }
+            if let Some(reason) = invalid_filename_reason(&rename.to) {
+                violations.push(PlanViolation::InvalidFilename {
+                    destination: rename.to.clone(),
+                    reason,
+                });
+            }
+        }
+
+        PlanReport { violations }
+    }
+
+    /// Renames all files in an order that never overwrites a file that is renamed later.
+    /// Missing subdirectories of the destinations are created first. Runs [`Self::verify`]
+    /// first and fails with [`NFLZError::InvalidPlan`] if there is any violation.
+    pub fn apply(self) -> Result<Vec<PlannedRename>, NFLZError> {
+        let report = self.verify();
+        if !report.is_ok() {
+            return Err(NFLZError::InvalidPlan(report.violations));
+        }
+        for rename in &self.renames {
+            match rename.to.parent() {
+                Some(dir) if rename.from.parent() != Some(dir) => {
+                    std::fs::create_dir_all(long_path(dir))
+                        .map_err(|err| NFLZError::CantCreateDirectory(dir.to_path_buf(), err))?;
+                }
+                _ => {}
+            }
+        }
+        let moves = self
+            .renames
+            .iter()
+            .map(|rename| (rename.from.clone(), rename.to.clone()))
+            .collect::<Vec<_>>();
+        for step in schedule(&moves) {
+            std::fs::rename(long_path(&step.from), long_path(&step.to))
+                .map_err(|err| NFLZError::RenameFailed(step.from, step.to, err))?;
+        }
+        Ok(self.renames)
+    }
+
+    /// Returns a script that performs the renames without nflz, e.g., by someone else on
+    /// another machine.
+    ///
+    /// The renames are ordered like the ones of [`crate::NFLZAssistant::rename_all`], so that
+    /// no file is overwritten that is renamed later; cycles are broken with temporary names.
+    /// Missing subdirectories of the destinations are created first. The plan should be
+    /// verified before, see [`Self::verify`].
+    ///
+    /// ```rust
+    /// use nflz::{PlannedRename, RenamePlan, ScriptFormat};
+    ///
+    /// let plan = RenamePlan::new(vec![PlannedRename::new("paris (1).jpg", "paris (01).jpg")]);
+    /// assert!(plan
+    ///     .to_script(ScriptFormat::Posix)
+    ///     .ends_with("mv -- 'paris (1).jpg' 'paris (01).jpg'\n"));
+    /// ```
+    pub fn to_script(&self, format: ScriptFormat) -> String {
+        let moves = self
+            .renames
+            .iter()
+            .map(|rename| (rename.from.clone(), rename.to.clone()))
+            .collect::<Vec<_>>();
+        let directories = self
+            .renames
+            .iter()
+            .filter(|rename| rename.from.parent() != rename.to.parent())
+            .filter_map(|rename| rename.to.parent())
+            .collect::<BTreeSet<_>>();
+        format.render(&directories, &schedule(&moves))
+    }
+}
+
+/// Renders the plan as a table with one rename per line, such as
+/// `paris (1).jpg => paris (01).jpg`. The arrows are aligned. The files are shown by their
+/// filenames and the new paths relative to the directory of the file. There is no newline
+/// behind the last line; an empty plan renders as an empty string.
+impl Display for RenamePlan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let rows = self
+            .renames
+            .iter()
+            .map(|rename| (rename.source_name(), rename.destination_name()))
+            .collect::<Vec<_>>();
+        let width = rows
+            .iter()
+            .map(|(source, _)| source.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (index, (source, destination)) in rows.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{:width$} => {}", source, destination, width = width)?;
+        }
+        Ok(())
+    }
+}
+
+/// A violated invariant of a [`RenamePlan`]. See [`RenamePlan::verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "violation", rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum PlanViolation {
+    /// Several files would be renamed to the same destination.
+    DuplicateDestination {
+        /// The shared destination.
+        destination: PathBuf,
+        /// All files that would be renamed to it.
+        sources: Vec<PathBuf>,
+    },
+    /// The same file would be renamed several times.
+    DuplicateSource {
+        /// The shared file to rename.
+        source: PathBuf,
+        /// All destinations it would be renamed to.
+        destinations: Vec<PathBuf>,
+    },
+    /// The file to rename doesn't exist.
+    MissingSource {
+        /// The missing file.
+        source: PathBuf,
+    },
+    /// The destination exists and is not vacated by another rename of the plan.
+    DestinationOccupied {
+        /// The occupied destination.
+        destination: PathBuf,
+    },
+    /// The new filename is not valid on all platforms.
+    InvalidFilename {
+        /// The destination with the invalid filename.
+        destination: PathBuf,
+        /// Why the filename is invalid.
+        reason: &'static str,
+    },
+    /// The new path is not a plain filename inside the working directory. See
+    /// [`crate::NFLZAssistant::check_destinations_inside_directory`].
+    DestinationOutsideDirectory {
+        /// The file to rename.
+        source: PathBuf,
+        /// The new path of the file.
+        destination: PathBuf,
+    },
+    /// The files of a sequence don't share the same text in front of the number group. See
+    /// [`crate::NFLZAssistant::check_unambiguous_prefixes`].
+    AmbiguousPrefixes {
+        /// All different prefixes, sorted.
+        prefixes: Vec<String>,
+    },
+    /// The files of a sequence don't share the same text behind the number group. See
+    /// [`crate::NFLZAssistant::check_unambiguous_suffixes`].
+    AmbiguousSuffixes {
+        /// All different suffixes, sorted.
+        suffixes: Vec<String>,
+    },
+    /// Several files have the same number. See
+    /// [`crate::NFLZAssistant::check_duplicate_numbers`].
+    DuplicateNumber {
+        /// The shared number.
+        number: u64,
+        /// All files with the number.
+        sources: Vec<PathBuf>,
+    },
+}
+
+impl Display for PlanViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateDestination {
+                destination,
+                sources,
+            } => write!(
+                f,
+                "{} files would be renamed to '{}'.",
+                sources.len(),
+                destination.display()
+            ),
+            Self::DuplicateSource {
+                source,
+                destinations,
+            } => write!(
+                f,
+                "The file '{}' would be renamed {} times.",
+                source.display(),
+                destinations.len()
+            ),
+            Self::MissingSource { source } => {
+                write!(f, "The file '{}' doesn't exist.", source.display())
+            }
+            Self::DestinationOccupied { destination } => write!(
+                f,
+                "The destination '{}' already exists.",
+                destination.display()
+            ),
+            Self::InvalidFilename {
+                destination,
+                reason,
+            } => write!(
+                f,
+                "The destination '{}' is invalid: {}.",
+                destination.display(),
+                reason
+            ),
+            Self::DestinationOutsideDirectory {
+                source,
+                destination,
+            } => write!(
+                f,
+                "The file '{}' would be moved outside of its directory to '{}'.",
+                source.display(),
+                destination.display()
+            ),
+            Self::AmbiguousPrefixes { prefixes } => {
+                write!(f, "The files have different prefixes: {:?}.", prefixes)
+            }
+            Self::AmbiguousSuffixes { suffixes } => {
+                write!(f, "The files have different suffixes: {:?}.", suffixes)
+            }
+            Self::DuplicateNumber { number, sources } => {
+                write!(f, "{} files have the number {}.", sources.len(), number)
+            }
+        }
+    }
+}
+
+/// Result of [`RenamePlan::verify`] and of the individual checks of
+/// [`crate::NFLZAssistant`], such as [`crate::NFLZAssistant::check_no_destination_conflicts`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PlanReport {
+    /// All violations, in the order of the renames.
+    violations: Vec<PlanViolation>,
+}
+
+impl PlanReport {
+    /// Constructor.
+    pub(crate) const fn new(violations: Vec<PlanViolation>) -> Self {
+        Self { violations }
+    }
+
+    /// Returns true if the plan fulfills all invariants.
+    // `Vec::is_empty` is not const on the MSRV
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Returns all violations, in the order of the renames.
+    pub fn violations(&self) -> &[PlanViolation] {
+        &self.violations
+    }
+}
+
+/// Returns why the filename of the path is not valid on all platforms, if it isn't.
+fn invalid_filename_reason(path: &Path) -> Option<&'static str> {
+    let Some(filename) = path.file_name().and_then(|name| name.to_str()) else {
+        return Some("no valid UTF-8 filename");
+    };
+    let stem = filename.split('.').next().unwrap_or_default();
+    if filename.chars().any(is_illegal_on_windows) {
+        Some("contains characters that are not allowed on Windows")
+    } else if filename.len() > MAX_FILENAME_LEN {
+        // a name of at most 255 bytes also has at most 255 UTF-16 code units
+        Some("longer than 255 bytes")
+    } else if filename.ends_with(['.', ' ']) {
+        Some("ends with a dot or a space")
+    } else if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem.trim_end()))
+    {
+        Some("reserved name on Windows")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::FixtureBuilder;
+
+    #[test]
+    fn test_verify() {
+        let fixture = FixtureBuilder::new("a ").count(3).create().unwrap();
+        let dir = fixture.path();
+
+        // shifting into names of the plan is fine
+        let plan = RenamePlan::new(vec![
+            PlannedRename::new(dir.join("a (1).jpg"), dir.join("a (2).jpg")),
+            PlannedRename::new(dir.join("a (2).jpg"), dir.join("a (4).jpg")),
+        ]);
+        assert_eq!(PlanReport::default(), plan.verify());
+
+        let plan = RenamePlan::new(vec![
+            PlannedRename::new(dir.join("a (1).jpg"), dir.join("a (3).jpg")),
+            PlannedRename::new(dir.join("a (5).jpg"), dir.join("a?.jpg")),
+            PlannedRename::new(dir.join("a (2).jpg"), dir.join("con.jpg")),
+        ]);
+        let report = plan.verify();
+        assert_eq!(
+            vec![
+                PlanViolation::DestinationOccupied {
+                    destination: dir.join("a (3).jpg")
+                },
+                PlanViolation::MissingSource {
+                    source: dir.join("a (5).jpg")
+                },
+                PlanViolation::InvalidFilename {
+                    destination: dir.join("a?.jpg"),
+                    reason: "contains characters that are not allowed on Windows"
+                },
+                PlanViolation::InvalidFilename {
+                    destination: dir.join("con.jpg"),
+                    reason: "reserved name on Windows"
+                },
+            ],
+            report.violations()
+        );
+
+        let long_name = format!("{}.jpg", "ä".repeat(126));
+        let plan = RenamePlan::new(vec![
+            PlannedRename::new(dir.join("a (1).jpg"), dir.join("a (01).jpg")),
+            PlannedRename::new(dir.join("a (1).jpg"), dir.join("a (001).jpg")),
+            PlannedRename::new(dir.join("a (2).jpg"), dir.join(&long_name)),
+        ]);
+        assert_eq!(
+            vec![
+                PlanViolation::DuplicateSource {
+                    source: dir.join("a (1).jpg"),
+                    destinations: vec![dir.join("a (01).jpg"), dir.join("a (001).jpg")],
+                },
+                PlanViolation::InvalidFilename {
+                    destination: dir.join(&long_name),
+                    reason: "longer than 255 bytes"
+                },
+            ],
+            plan.verify().violations()
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let dir = Path::new("photos");
+        let plan = RenamePlan::new(vec![
+            PlannedRename::new(dir.join("a (1).jpg"), dir.join("a (01).jpg")),
+            PlannedRename::new(dir.join("a (10).jpg"), dir.join("part-01/a (10).jpg")),
+            PlannedRename::new(dir.join("é (2).jpg"), Path::new("other").join("é (02).jpg")),
+        ]);
+        let expected = [
+            "a (1).jpg  => a (01).jpg".to_string(),
+            format!(
+                "a (10).jpg => {}",
+                Path::new("part-01/a (10).jpg").display()
+            ),
+            format!(
+                "é (2).jpg  => {}",
+                Path::new("other").join("é (02).jpg").display()
+            ),
+        ];
+        assert_eq!(expected.join("\n"), plan.to_string());
+        assert_eq!("", RenamePlan::default().to_string());
+    }
+
+    #[test]
+    fn test_remove_and_apply() {
+        let fixture = FixtureBuilder::new("a ").count(3).create().unwrap();
+        let dir = fixture.path();
+
+        // the dropped file keeps its name, which another rename of the plan targets
+        let mut plan = RenamePlan::new(vec![
+            PlannedRename::new(dir.join("a (1).jpg"), dir.join("a (2).jpg")),
+            PlannedRename::new(dir.join("a (2).jpg"), dir.join("a (4).jpg")),
+        ]);
+        assert!(plan.remove(&dir.join("a (3).jpg")).is_none());
+        assert_eq!(
+            Some(PlannedRename::new(
+                dir.join("a (2).jpg"),
+                dir.join("a (4).jpg")
+            )),
+            plan.remove(&dir.join("a (2).jpg"))
+        );
+        let err = plan.apply().unwrap_err();
+        assert!(matches!(
+            &err,
+            NFLZError::InvalidPlan(violations) if violations == &[PlanViolation::DestinationOccupied {
+                destination: dir.join("a (2).jpg")
+            }]
+        ));
+        assert!(dir.join("a (1).jpg").exists());
+
+        let mut plan = RenamePlan::new(
+            (1..=3)
+                .map(|number| {
+                    PlannedRename::new(
+                        dir.join(format!("a ({}).jpg", number)),
+                        dir.join(format!("a ({:02}).jpg", number)),
+                    )
+                })
+                .collect(),
+        );
+        assert_eq!(
+            1,
+            plan.retain(|rename| rename.from() != dir.join("a (2).jpg"))
+        );
+        assert_eq!(2, plan.apply().unwrap().len());
+        assert!(dir.join("a (01).jpg").exists());
+        assert!(dir.join("a (2).jpg").exists());
+        assert!(dir.join("a (03).jpg").exists());
+    }
+
+    #[test]
+    fn test_set_destination() {
+        let fixture = FixtureBuilder::new("a ").count(3).create().unwrap();
+        let dir = fixture.path();
+        let mut plan = RenamePlan::new(vec![
+            PlannedRename::new(dir.join("a (1).jpg"), dir.join("a (01).jpg")),
+            PlannedRename::new(dir.join("a (2).jpg"), dir.join("a (02).jpg")),
+        ]);
+
+        // conflicts with the destination of another rename
+        assert_eq!(
+            Some(dir.join("a (01).jpg")),
+            plan.set_destination(dir.join("a (1).jpg"), dir.join("a (02).jpg"))
+        );
+        assert!(matches!(
+            plan.verify().violations(),
+            [PlanViolation::DuplicateDestination { .. }]
+        ));
+
+        assert_eq!(
+            Some(dir.join("a (02).jpg")),
+            plan.set_destination(dir.join("a (1).jpg"), dir.join("a (01) cover.jpg"))
+        );
+        assert_eq!(
+            None,
+            plan.set_destination(dir.join("a (3).jpg"), dir.join("a (03).jpg"))
+        );
+        assert_eq!(3, plan.apply().unwrap().len());
+        assert!(dir.join("a (01) cover.jpg").exists());
+        assert!(dir.join("a (02).jpg").exists());
+        assert!(dir.join("a (03).jpg").exists());
+    }
+}
@@ -0,0 +1,183 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the comparison of the prefixes of the files of a sequence. See
+//! [`PrefixEquivalence`].
+
+use crate::file_info::FileInfo;
+use crate::scheme::{NumberGroups, NumberingScheme};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+/// Which differences between the prefixes of the files of a sequence are tolerated instead of
+/// failing with [`crate::NFLZError::AmbiguousPrefixes`]. See
+/// [`crate::NFLZAssistantBuilder::case_insensitive_prefixes`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct PrefixEquivalence {
+    /// Whether prefixes that only differ in their case are equivalent.
+    pub(crate) ignore_case: bool,
+    /// Whether prefixes that only differ in leading, trailing, or repeated whitespace are
+    /// equivalent.
+    pub(crate) ignore_whitespace: bool,
+    /// Whether all files get the most common spelling of their equivalent prefixes.
+    pub(crate) unify: bool,
+}
+
+impl PrefixEquivalence {
+    /// Returns the key under that equivalent prefixes are equal.
+    pub(crate) fn key(self, prefix: &str) -> Cow<'_, str> {
+        let mut key = Cow::Borrowed(prefix);
+        if self.ignore_whitespace {
+            // `  img  (` => `img (`
+            key = Cow::Owned(key.split_whitespace().collect::<Vec<_>>().join(" "));
+        }
+        if self.ignore_case {
+            key = Cow::Owned(key.to_lowercase());
+        }
+        key
+    }
+
+    /// Returns the most common spelling of each group of equivalent prefixes by their
+    /// [`Self::key`]. On a tie, the spelling that sorts first wins. Empty unless the prefixes
+    /// are unified.
+    pub(crate) fn most_common_spellings<'a>(
+        self,
+        prefixes: impl Iterator<Item = &'a str>,
+    ) -> HashMap<String, String> {
+        if !self.unify {
+            return HashMap::new();
+        }
+        let mut counts = BTreeMap::<&str, usize>::new();
+        for prefix in prefixes {
+            *counts.entry(prefix).or_default() += 1;
+        }
+        let mut spellings = HashMap::<String, (&str, usize)>::new();
+        for (prefix, count) in counts {
+            let best = spellings
+                .entry(self.key(prefix).into_owned())
+                .or_insert((prefix, count));
+            // the spellings are visited in sort order, hence, only more common ones win
+            if count > best.1 {
+                *best = (prefix, count);
+            }
+        }
+        spellings
+            .into_iter()
+            .map(|(key, (prefix, _))| (key, prefix.to_string()))
+            .collect()
+    }
+}
+
+/// Wraps the [`NumberingScheme`] of a file to replace its prefix in the new filename with an
+/// equivalent spelling. See [`PrefixEquivalence::unify`].
+#[derive(Debug)]
+pub(crate) struct UnifiedPrefix<'a> {
+    /// The scheme that builds the new filename.
+    pub(crate) scheme: &'a dyn NumberingScheme,
+    /// The spelling of the prefix in the new filename.
+    pub(crate) prefix: &'a str,
+}
+
+impl NumberingScheme for UnifiedPrefix<'_> {
+    fn find_number_groups(&self, filename: &str) -> Vec<NumberGroups> {
+        self.scheme.find_number_groups(filename)
+    }
+
+    fn has_unique_suffix(&self) -> bool {
+        self.scheme.has_unique_suffix()
+    }
+
+    fn min_width(&self) -> u64 {
+        self.scheme.min_width()
+    }
+
+    fn format(&self, file_info: &FileInfo, number: &str, second_number: Option<&str>) -> String {
+        let filename = self.scheme.format(file_info, number, second_number);
+        // unchanged if the scheme changed the prefix on its own
+        filename
+            .strip_prefix(file_info.filename_prefix())
+            .map_or_else(
+                || filename.clone(),
+                |rest| format!("{}{}", self.prefix, rest),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheme::Parentheses;
+
+    #[test]
+    fn test_most_common_spellings() {
+        let equivalence = PrefixEquivalence {
+            ignore_case: true,
+            ignore_whitespace: false,
+            unify: true,
+        };
+        let spellings =
+            equivalence.most_common_spellings(["IMG (", "img (", "img (", "Vid ("].into_iter());
+        assert_eq!(Some("img ("), spellings.get("img (").map(String::as_str));
+        assert_eq!(Some("Vid ("), spellings.get("vid (").map(String::as_str));
+
+        // on a tie, the spelling that sorts first wins
+        let spellings = equivalence.most_common_spellings(["img (", "IMG ("].into_iter());
+        assert_eq!(Some("IMG ("), spellings.get("img (").map(String::as_str));
+
+        let equivalence = PrefixEquivalence {
+            unify: false,
+            ..equivalence
+        };
+        assert!(equivalence
+            .most_common_spellings(["IMG (", "img ("].into_iter())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_key() {
+        let equivalence = PrefixEquivalence {
+            ignore_whitespace: true,
+            ..PrefixEquivalence::default()
+        };
+        for prefix in ["img (", "img  (", " img (", "img\t("] {
+            assert_eq!("img (", equivalence.key(prefix), "{:?}", prefix);
+        }
+        assert_eq!("img(", equivalence.key("img("));
+        assert_eq!("IMG (", equivalence.key("IMG  ("));
+        let equivalence = PrefixEquivalence {
+            ignore_case: true,
+            ..equivalence
+        };
+        assert_eq!("img (", equivalence.key("IMG  ("));
+    }
+
+    #[test]
+    fn test_unified_prefix() {
+        let file_info = FileInfo::new("IMG (7).jpg").unwrap();
+        let scheme = UnifiedPrefix {
+            scheme: &Parentheses::default(),
+            prefix: "img (",
+        };
+        assert_eq!("img (07).jpg", scheme.format(&file_info, "07", None));
+    }
+}
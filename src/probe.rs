@@ -0,0 +1,111 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the fast check whether a directory needs to be processed at all. See
+//! [`needs_work`].
+
+use crate::error::NFLZError;
+use crate::file_info::FileInfo;
+use crate::fsutil::{is_hidden, read_directory_flat, EntryKind};
+use crate::ignore::IgnoreRules;
+use crate::math::count_digits_without_leading_zeroes;
+use std::path::{Path, PathBuf};
+
+/// Returns whether [`crate::NFLZAssistant::new`] would rename any file in the directory.
+///
+/// This is much cheaper than creating the assistant, as only the filenames are parsed: there
+/// are no rename advices, no snapshot of the directory, and no check whether a new filename
+/// already exists. Thus, it suits watchers and cron jobs that poll many directories. Like the
+/// assistant with its default configuration, it uses the default `(<digits>)` pattern and
+/// skips hidden files and the entries of the `.nflzignore` file.
+///
+/// It doesn't validate the renaming, i.e., [`crate::NFLZAssistant::check_can_rename_all`]
+/// may still fail.
+///
+/// ```rust
+/// assert!(nflz::needs_work("./test-resources").unwrap());
+/// ```
+pub fn needs_work<P: AsRef<Path>>(dir: P) -> Result<bool, NFLZError> {
+    let files = visible_files(dir.as_ref())?
+        .into_iter()
+        .filter_map(|path| FileInfo::new(path).ok())
+        .collect::<Vec<_>>();
+
+    let max_number = files.iter().map(FileInfo::number_group_value).max();
+    Ok(max_number.is_some_and(|max_number| {
+        let digits = count_digits_without_leading_zeroes(max_number);
+        files
+            .iter()
+            .any(|file| file.number_group_str().len() as u64 != digits)
+    }))
+}
+
+/// Returns the regular files of the directory that the assistant with its default
+/// configuration looks at, i.e., without hidden files and the entries of the `.nflzignore`
+/// file.
+pub(crate) fn visible_files(dir: &Path) -> Result<Vec<PathBuf>, NFLZError> {
+    let entries = read_directory_flat(dir)
+        .map_err(|err| NFLZError::CantReadDirectory(dir.to_path_buf(), err))?;
+    let ignore_rules = IgnoreRules::read(dir, true, false)?;
+    Ok(entries
+        .into_iter()
+        .filter(|(path, kind)| *kind == EntryKind::File && !is_hidden(path))
+        .filter(|(path, _)| {
+            path.file_name()
+                .is_some_and(|name| !ignore_rules.is_ignored(&name.to_string_lossy(), false))
+        })
+        .map(|(path, _)| path)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nflz::NFLZAssistant;
+    use crate::testutil::FixtureBuilder;
+
+    #[test]
+    fn test_needs_work() {
+        let fixtures = [
+            FixtureBuilder::new("a ").count(12),
+            FixtureBuilder::new("a ").count(12).padding(2),
+            FixtureBuilder::new("a ").count(9).padding(2),
+            FixtureBuilder::new("a ").count(0).extra_file("readme.txt"),
+            FixtureBuilder::new("a ")
+                .count(12)
+                .padding(2)
+                .extra_file(".a (3).jpg"),
+        ];
+        for fixture in fixtures {
+            let fixture = fixture.create().unwrap();
+            let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+            assert_eq!(
+                !assistant.files_to_rename().is_empty(),
+                needs_work(fixture.path()).unwrap(),
+                "{:?}",
+                fixture.path()
+            );
+        }
+        assert!(needs_work("./does-not-exist").is_err());
+    }
+}
@@ -0,0 +1,99 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for the original filenames that are recorded in an extended attribute of the
+//! renamed files. See [`original_filename`]. Only available with the `xattr` feature.
+
+use std::io;
+use std::path::Path;
+
+/// Name of the extended attribute with the original filename.
+pub const ORIGINAL_FILENAME_ATTRIBUTE: &str = "user.nflz.original";
+
+/// Records the original filename in the extended attribute of the renamed file, unless the
+/// file already has one from an earlier run. Thus, the attribute always holds the name
+/// before the first rename.
+pub(crate) fn record_original_filename(path: &Path, original: &str) -> io::Result<()> {
+    if xattr::get(path, ORIGINAL_FILENAME_ATTRIBUTE)?.is_none() {
+        xattr::set(path, ORIGINAL_FILENAME_ATTRIBUTE, original.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Returns the filename of the file before it was renamed by nflz for the first time, if it
+/// was recorded.
+///
+/// See [`crate::NFLZAssistantBuilder::record_original_filenames`]. Thus, the files can be
+/// renamed back without any other record of the renaming.
+///
+/// ```rust,no_run
+/// let original = nflz::original_filename("./photos/paris (01).jpg").unwrap();
+/// assert_eq!(Some("paris (1).jpg".to_string()), original);
+/// ```
+pub fn original_filename<P: AsRef<Path>>(path: P) -> io::Result<Option<String>> {
+    xattr::get(path, ORIGINAL_FILENAME_ATTRIBUTE)?
+        .map(|value| {
+            String::from_utf8(value).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::FixtureBuilder;
+    use crate::NFLZAssistant;
+
+    #[test]
+    fn test_record_original_filenames() {
+        let fixture = FixtureBuilder::new("a ").create().unwrap();
+        if !xattr::SUPPORTED_PLATFORM
+            || xattr::set(fixture.path().join("a (1).jpg"), "user.nflz.test", b"").is_err()
+        {
+            // e.g., tmpfs without user xattrs
+            return;
+        }
+        NFLZAssistant::builder(fixture.path())
+            .record_original_filenames(true)
+            .build()
+            .unwrap()
+            .rename_all()
+            .unwrap();
+        let path = fixture.path().join("a (01).jpg");
+        assert_eq!(
+            Some("a (1).jpg".to_string()),
+            original_filename(&path).unwrap()
+        );
+        let path = fixture.path().join("a (10).jpg");
+        assert_eq!(None, original_filename(path).unwrap());
+
+        // the first original name is kept
+        let path = fixture.path().join("a (01).jpg");
+        record_original_filename(&path, "b (1).jpg").unwrap();
+        assert_eq!(
+            Some("a (1).jpg".to_string()),
+            original_filename(&path).unwrap()
+        );
+    }
+}
@@ -0,0 +1,198 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Updates references to renamed files inside text files, such as `.m3u` playlists or `.cue`
+//! sheets, so that media players still find the files. See [`update_references`].
+
+use crate::error::NFLZError;
+use crate::fsutil::{long_path, read_directory_flat, EntryKind};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Prefixes of a path that resolves to the working directory, such as `./a (1).mp3`.
+const CURRENT_DIR_PREFIXES: [&str; 2] = ["./", ".\\"];
+
+/// Returns the reference with the new filename if the reference is an old filename, optionally
+/// with a prefix of [`CURRENT_DIR_PREFIXES`]. References with other paths, such as
+/// `other/a (1).mp3`, point at files in other directories and are not replaced.
+fn replace_reference(reference: &str, renames: &HashMap<&str, &str>) -> Option<String> {
+    let (current_dir, filename) = CURRENT_DIR_PREFIXES
+        .iter()
+        .find_map(|prefix| Some((*prefix, reference.strip_prefix(prefix)?)))
+        .unwrap_or(("", reference));
+    renames
+        .get(filename)
+        .map(|new| format!("{}{}", current_dir, new))
+}
+
+/// Replaces the references inside quotes, such as in `FILE "a (1).wav" WAVE`, and appends the
+/// result to `replaced`. Returns the number of replaced references.
+fn replace_quoted_references(
+    mut line: &str,
+    renames: &HashMap<&str, &str>,
+    replaced: &mut String,
+) -> usize {
+    let mut count = 0;
+    while let Some(start) = line.find(['"', '\'']) {
+        let quote = &line[start..=start];
+        replaced.push_str(&line[..=start]);
+        line = &line[start + 1..];
+        let Some(end) = line.find(quote) else {
+            continue;
+        };
+        match replace_reference(&line[..end], renames) {
+            Some(new) => {
+                replaced.push_str(&new);
+                count += 1;
+            }
+            None => replaced.push_str(&line[..end]),
+        }
+        replaced.push_str(quote);
+        line = &line[end + 1..];
+    }
+    replaced.push_str(line);
+    count
+}
+
+/// Replaces all references to the old filenames in the text with the new ones. A reference
+/// is a whole line, as in a playlist, or a quoted token, as in a cue sheet, that is an old
+/// filename, optionally with a prefix of [`CURRENT_DIR_PREFIXES`]. Thus, a filename that is
+/// only part of a line, such as `old a (1).mp3`, or that is inside another directory, such as
+/// `other/a (1).mp3`, is not a reference. The text is processed in a single pass, thus chains,
+/// such as `a (1).jpg => a (01).jpg` and `a (01).jpg => a (001).jpg`, are not applied twice.
+///
+/// Returns the new text and the number of replaced references.
+pub(crate) fn replace_references(text: &str, renames: &[(String, String)]) -> (String, usize) {
+    let renames = renames
+        .iter()
+        .map(|(old, new)| (old.as_str(), new.as_str()))
+        .collect::<HashMap<_, _>>();
+
+    let mut replaced = String::with_capacity(text.len());
+    let mut count = 0;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        match replace_reference(content, &renames) {
+            Some(new) => {
+                replaced.push_str(&new);
+                count += 1;
+            }
+            None => count += replace_quoted_references(content, &renames, &mut replaced),
+        }
+        replaced.push_str(&line[content.len()..]);
+    }
+    (replaced, count)
+}
+
+/// Updates the references to the renamed files in all files of the directory (not
+/// recursive) whose name matches one of the wildcard patterns, such as `*.m3u`. See
+/// [`replace_references`]. Files that are not valid UTF-8 are skipped with a warning.
+///
+/// # Parameters
+/// * `dir` The working directory.
+/// * `patterns` Wildcard patterns of the names of the files to update.
+/// * `renames` Old and new names of the renamed files, relative to the directory.
+///
+/// # Return Type
+/// All updated files with the number of replaced references.
+pub(crate) fn update_references(
+    dir: &Path,
+    patterns: &[String],
+    renames: &[(String, String)],
+) -> Result<Vec<(PathBuf, usize)>, NFLZError> {
+    let entries =
+        read_directory_flat(dir).map_err(|err| NFLZError::CantReadDirectory(dir.into(), err))?;
+    let mut updated = Vec::new();
+    for (path, kind) in entries {
+        let matches_pattern = path.file_name().is_some_and(|name| {
+            let name = name.to_string_lossy();
+            patterns
+                .iter()
+                .any(|pattern| crate::wildcard::matches(pattern, &name))
+        });
+        if kind != EntryKind::File || !matches_pattern {
+            continue;
+        }
+        let bytes = std::fs::read(long_path(&path))
+            .map_err(|err| NFLZError::CantUpdateReferences(path.clone(), err))?;
+        let Ok(text) = String::from_utf8(bytes) else {
+            log::warn!("Skipping '{}' because it is not UTF-8", path.display());
+            continue;
+        };
+        let (text, count) = replace_references(&text, renames);
+        if count > 0 {
+            std::fs::write(long_path(&path), text)
+                .map_err(|err| NFLZError::CantUpdateReferences(path.clone(), err))?;
+            updated.push((path, count));
+        }
+    }
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn renames(renames: &[(&str, &str)]) -> Vec<(String, String)> {
+        renames
+            .iter()
+            .map(|(old, new)| (old.to_string(), new.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_replace_references() {
+        let renames = renames(&[
+            ("a (1).mp3", "a (01).mp3"),
+            ("a (01).mp3", "a (001).mp3"),
+            ("a (10).mp3", "a (010).mp3"),
+        ]);
+        let playlist = "#EXTM3U\r\n#EXTINF:1,a (1).mp3\r\na (1).mp3\r\n./a (10).mp3\r\na (01).mp3";
+        assert_eq!(
+            (
+                "#EXTM3U\r\n#EXTINF:1,a (1).mp3\r\na (01).mp3\r\n./a (010).mp3\r\na (001).mp3"
+                    .to_string(),
+                3
+            ),
+            replace_references(playlist, &renames)
+        );
+
+        let cue = "FILE \"a (1).mp3\" MP3\nFILE \"xa (1).mp3\" MP3\nFILE \"a (1).mp3.bak\" MP3\nTITLE 'it\'s'";
+        assert_eq!(
+            (
+                "FILE \"a (01).mp3\" MP3\nFILE \"xa (1).mp3\" MP3\nFILE \"a (1).mp3.bak\" MP3\nTITLE 'it\'s'"
+                    .to_string(),
+                1
+            ),
+            replace_references(cue, &renames)
+        );
+    }
+
+    #[test]
+    fn test_replace_references_only_whole_references() {
+        let renames = renames(&[("a (1).mp3", "a (01).mp3")]);
+        // the filename is only the end of a reference to another file
+        let text = "old a (1).mp3\nother/a (1).mp3\n\"other/a (1).mp3\"\n\"old a (1).mp3\"\n";
+        assert_eq!((text.to_string(), 0), replace_references(text, &renames));
+    }
+}
@@ -0,0 +1,152 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for roman numerals inside the number groups. See [`RomanNumerals`].
+
+/// How number groups with roman numerals, such as `Chapter (IV).md`, are padded. See
+/// [`crate::NFLZAssistantBuilder::roman_numerals`].
+///
+/// Only canonical numerals, such as `IV` but not `IIII`, are recognized. Numbers above 3999
+/// repeat the `M`, and `0` is written as `N` (*nulla*).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RomanNumerals {
+    /// Converts the numerals to decimal numbers with leading zeroes: `Chapter (IV).md`
+    /// becomes `Chapter (04).md` next to `Chapter (XII).md`.
+    #[cfg_attr(feature = "serde", serde(rename = "decimal"))]
+    ToDecimal,
+    /// Keeps the roman numerals and pads them with leading underscores to the width of the
+    /// widest numeral: `Chapter (IV).md` becomes `Chapter (__IV).md` next to
+    /// `Chapter (VIII).md`. The underscores are recognized when the files are parsed again.
+    /// All numerals of a sequence get the case of the majority of them.
+    ///
+    /// Unlike [`Self::ToDecimal`], the padded numerals don't sort alphabetically in the order
+    /// of their values: `VIII` sorts before `__IV`. The padding only gives them the same width.
+    #[cfg_attr(feature = "serde", serde(rename = "padded"))]
+    Padded,
+}
+
+/// Character that pads the roman numerals with [`RomanNumerals::Padded`].
+pub(crate) const PADDING: u8 = b'_';
+
+/// Values of the numerals in descending order, including the subtractive forms.
+const NUMERALS: [(u64, &str); 13] = [
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+/// Returns whether the byte is a roman numeral or the padding, case-insensitive.
+pub(crate) const fn is_roman_digit(byte: u8) -> bool {
+    matches!(
+        byte.to_ascii_uppercase(),
+        b'I' | b'V' | b'X' | b'L' | b'C' | b'D' | b'M' | b'N'
+    ) || byte == PADDING
+}
+
+/// Parses a canonical roman numeral, case-insensitive. Leading padding is ignored. `None` if
+/// the numeral isn't canonical, such as `IIII` or `VX`.
+pub(crate) fn parse_roman(numeral: &str) -> Option<u64> {
+    let numeral = numeral.trim_start_matches(PADDING as char);
+    if numeral.eq_ignore_ascii_case("N") {
+        return Some(0);
+    }
+    if numeral.is_empty() {
+        return None;
+    }
+    let uppercase = numeral.to_ascii_uppercase();
+    let mut rest = uppercase.as_str();
+    let mut value = 0_u64;
+    for (numeral_value, symbol) in NUMERALS {
+        while let Some(remainder) = rest.strip_prefix(symbol) {
+            value = value.checked_add(numeral_value)?;
+            rest = remainder;
+        }
+    }
+    // the greedy parser accepts non-canonical forms, such as `IIII`
+    (rest.is_empty() && to_roman(value, true).eq_ignore_ascii_case(numeral)).then_some(value)
+}
+
+/// Returns the roman numeral of the number, e.g., `XIV` for `14`.
+pub(crate) fn to_roman(number: u64, uppercase: bool) -> String {
+    if number == 0 {
+        return if uppercase { "N" } else { "n" }.to_string();
+    }
+    let mut numeral = String::with_capacity(roman_len(number) as usize);
+    let mut rest = number;
+    for (value, symbol) in NUMERALS {
+        while rest >= value {
+            numeral.push_str(symbol);
+            rest -= value;
+        }
+    }
+    if !uppercase {
+        numeral.make_ascii_lowercase();
+    }
+    numeral
+}
+
+/// Returns the length of the roman numeral of the number without building it.
+pub(crate) const fn roman_len(number: u64) -> u64 {
+    // length of the numeral of a decimal digit, such as 4 for `VIII` or `DCCC`
+    const DIGIT_LEN: [u64; 10] = [0, 1, 2, 3, 2, 1, 2, 3, 4, 2];
+    if number == 0 {
+        return 1;
+    }
+    number / 1000
+        + DIGIT_LEN[(number / 100 % 10) as usize]
+        + DIGIT_LEN[(number / 10 % 10) as usize]
+        + DIGIT_LEN[(number % 10) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roman() {
+        for number in 0..=5000 {
+            let numeral = to_roman(number, true);
+            assert_eq!(roman_len(number), numeral.len() as u64);
+            assert_eq!(Some(number), parse_roman(&numeral));
+        }
+        assert_eq!("xiv", to_roman(14, false));
+        assert_eq!("MMMMCMXCIX", to_roman(4999, true));
+        assert_eq!(Some(4), parse_roman("__iv"));
+        assert_eq!(None, parse_roman("IIII"));
+        assert_eq!(None, parse_roman("VX"));
+        assert_eq!(None, parse_roman("IVI"));
+        assert_eq!(None, parse_roman("__"));
+    }
+}
@@ -0,0 +1,199 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for the order in that the files are renamed. See [`schedule`].
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A single rename of a schedule. See [`schedule`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RenameStep {
+    /// Current path of the file.
+    pub(crate) from: PathBuf,
+    /// New path of the file.
+    pub(crate) to: PathBuf,
+    /// Index of the planned move that this step completes. `None` if the step moves the file
+    /// to a temporary name to break a cycle.
+    pub(crate) completes: Option<usize>,
+}
+
+/// State of a planned move during the scheduling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Not scheduled yet.
+    Pending,
+    /// Part of the chain that is currently scheduled.
+    InChain,
+    /// Scheduled.
+    Done,
+}
+
+/// Orders the planned moves `(from, to)` such that no move overwrites a file that another
+/// move still has to move away.
+///
+/// A move depends on the move whose source is its destination. As all sources and all
+/// destinations are unique, the dependencies form chains and cycles. Chains are scheduled
+/// from their end; cycles, such as `a => b` and `b => a`, are broken by moving one file to a
+/// temporary name first. Runs in linear time.
+///
+/// The destinations must be unique and must not be occupied by files outside of the plan.
+pub(crate) fn schedule(moves: &[(PathBuf, PathBuf)]) -> Vec<RenameStep> {
+    let by_source = moves
+        .iter()
+        .enumerate()
+        .map(|(index, (from, _))| (from.as_path(), index))
+        .collect::<HashMap<_, _>>();
+    let mut states = vec![State::Pending; moves.len()];
+    let mut steps = Vec::with_capacity(moves.len());
+    let mut temporary_names = 0;
+
+    for start in 0..moves.len() {
+        // follow the dependencies until the destination is free or a cycle closes
+        let mut chain = Vec::new();
+        let mut next = Some(start);
+        let mut cycle_start = None;
+        while let Some(index) = next {
+            match states[index] {
+                State::Done => break,
+                State::InChain => {
+                    cycle_start = Some(index);
+                    break;
+                }
+                State::Pending => {
+                    states[index] = State::InChain;
+                    chain.push(index);
+                    next = by_source.get(moves[index].1.as_path()).copied();
+                }
+            }
+        }
+
+        // a cycle always closes at the start of the chain, as every file has at most one
+        // predecessor
+        let temporary_path = cycle_start.map(|index| {
+            debug_assert_eq!(index, start);
+            let temporary_path = temporary_path(&moves[index].0, &mut temporary_names);
+            steps.push(RenameStep {
+                from: moves[index].0.clone(),
+                to: temporary_path.clone(),
+                completes: None,
+            });
+            temporary_path
+        });
+        for &index in chain.iter().rev() {
+            let from = match &temporary_path {
+                Some(temporary_path) if index == start => temporary_path.clone(),
+                _ => moves[index].0.clone(),
+            };
+            steps.push(RenameStep {
+                from,
+                to: moves[index].1.clone(),
+                completes: Some(index),
+            });
+            states[index] = State::Done;
+        }
+    }
+    steps
+}
+
+/// Returns a path next to the file that doesn't exist yet.
+fn temporary_path(path: &Path, counter: &mut usize) -> PathBuf {
+    loop {
+        *counter += 1;
+        let temporary_path = path.with_file_name(format!(".nflz-{}.tmp", counter));
+        if !temporary_path.exists() {
+            return temporary_path;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Executes the steps on a set of virtual files and returns the final state.
+    fn simulate(files: &[&str], steps: &[RenameStep]) -> Vec<(String, String)> {
+        let mut state = files
+            .iter()
+            .map(|file| (PathBuf::from(file), file.to_string()))
+            .collect::<HashMap<_, _>>();
+        for step in steps {
+            let content = state.remove(&step.from).expect("source must exist");
+            assert!(
+                state.insert(step.to.clone(), content).is_none(),
+                "must not overwrite {}",
+                step.to.display()
+            );
+        }
+        let mut state = state
+            .into_iter()
+            .map(|(path, content)| (path.display().to_string(), content))
+            .collect::<Vec<_>>();
+        state.sort();
+        state
+    }
+
+    fn moves(moves: &[(&str, &str)]) -> Vec<(PathBuf, PathBuf)> {
+        moves
+            .iter()
+            .map(|(from, to)| (PathBuf::from(from), PathBuf::from(to)))
+            .collect()
+    }
+
+    #[test]
+    fn test_schedule_chain() {
+        // shift by one: 1 => 2, 2 => 3, 3 => 4
+        let plan = moves(&[("1", "2"), ("2", "3"), ("3", "4")]);
+        let steps = schedule(&plan);
+        assert_eq!(3, steps.len());
+        assert_eq!(
+            vec![
+                ("2".to_string(), "1".to_string()),
+                ("3".to_string(), "2".to_string()),
+                ("4".to_string(), "3".to_string()),
+            ],
+            simulate(&["1", "2", "3"], &steps)
+        );
+    }
+
+    #[test]
+    fn test_schedule_cycle() {
+        let plan = moves(&[("a", "b"), ("b", "c"), ("c", "a"), ("x", "y")]);
+        let steps = schedule(&plan);
+        assert_eq!(5, steps.len(), "one additional step for the cycle");
+        assert_eq!(
+            vec![
+                ("a".to_string(), "c".to_string()),
+                ("b".to_string(), "a".to_string()),
+                ("c".to_string(), "b".to_string()),
+                ("y".to_string(), "x".to_string()),
+            ],
+            simulate(&["a", "b", "c", "x"], &steps)
+        );
+        assert_eq!(
+            4,
+            steps.iter().filter(|step| step.completes.is_some()).count()
+        );
+    }
+}
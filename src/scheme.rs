@@ -0,0 +1,220 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the schemes that find the number groups inside a filename and build the new
+//! filename. See [`NumberingScheme`].
+
+use crate::file_info::{FileInfo, ParenthesesNumberGroups};
+use crate::math::Radix;
+#[cfg(feature = "regex")]
+use regex::Regex;
+use std::fmt::Debug;
+use std::ops::Range;
+
+/// Byte ranges of the number group and the optional second number group of a single match
+/// of a [`NumberingScheme`]. The ranges don't include delimiters, such as parentheses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberGroups {
+    /// Range of the number group.
+    first: Range<usize>,
+    /// Range of the second number group, such as the episode in `Show S1E3.mkv`.
+    second: Option<Range<usize>>,
+}
+
+impl NumberGroups {
+    /// Constructor for a match with a single number group.
+    pub const fn new(first: Range<usize>) -> Self {
+        Self {
+            first,
+            second: None,
+        }
+    }
+
+    /// Adds a second number group that is padded independently of the first one. It must be
+    /// behind the first one.
+    pub const fn with_second(mut self, second: Range<usize>) -> Self {
+        self.second = Some(second);
+        self
+    }
+
+    /// Returns the range of the number group.
+    pub fn first(&self) -> Range<usize> {
+        self.first.clone()
+    }
+
+    /// Returns the range of the second number group, if there is one.
+    pub fn second(&self) -> Option<Range<usize>> {
+        self.second.clone()
+    }
+}
+
+/// A scheme that finds the number group(s) inside a filename and builds the new filename.
+///
+/// All schemes go through the same planning and validation, so a new scheme only has to
+/// describe where the numbers are.
+///
+/// The default scheme is [`Parentheses`]. With the `regex` feature, the built-in patterns
+/// (`BuiltinPattern`) and every `regex::Regex` with one or two capture groups are schemes as
+/// well.
+///
+/// # Example
+/// ```rust
+/// use nflz::{NFLZAssistant, NumberGroups, NumberingScheme};
+///
+/// /// Finds `#` followed by digits, such as `photo #7.jpg`.
+/// #[derive(Debug)]
+/// struct Hash;
+///
+/// impl NumberingScheme for Hash {
+///     fn find_number_groups(&self, filename: &str) -> Vec<NumberGroups> {
+///         filename
+///             .match_indices('#')
+///             .map(|(index, _)| index + 1)
+///             .map(|begin| {
+///                 let len = filename[begin..]
+///                     .bytes()
+///                     .take_while(u8::is_ascii_digit)
+///                     .count();
+///                 NumberGroups::new(begin..begin + len)
+///             })
+///             .filter(|groups| !groups.first().is_empty())
+///             .collect()
+///     }
+/// }
+///
+/// let groups = Hash.find_number_groups("photo #7.jpg");
+/// assert_eq!(vec![NumberGroups::new(7..8)], groups);
+/// let assistant = NFLZAssistant::builder("./test-resources")
+///     .numbering_scheme(Hash)
+///     .build()
+///     .unwrap();
+/// assert!(assistant.files_to_rename().is_empty());
+/// ```
+pub trait NumberingScheme: Debug {
+    /// Returns all matches of the scheme inside the filename. Only filenames with exactly
+    /// one match are renamed; the other files are skipped.
+    fn find_number_groups(&self, filename: &str) -> Vec<NumberGroups>;
+
+    /// Whether the text behind the number groups must be the same for all files. See
+    /// [`crate::NFLZError::AmbiguousSuffixes`]. `true` by default.
+    fn has_unique_suffix(&self) -> bool {
+        true
+    }
+
+    /// Minimum amount of digits of the number group, such as the four digits of the counters
+    /// of cameras. `0` by default.
+    fn min_width(&self) -> u64 {
+        0
+    }
+
+    /// Builds the new filename from the number group(s) with the new amount of leading
+    /// zeroes. By default, the number groups are replaced and all other text stays the same.
+    fn format(&self, file_info: &FileInfo, number: &str, second_number: Option<&str>) -> String {
+        format!(
+            "{}{}{}{}",
+            file_info.filename_prefix(),
+            number,
+            file_info
+                .filename_infix()
+                .zip(second_number)
+                .map(|(infix, second_number)| format!("{}{}", infix, second_number))
+                .unwrap_or_default(),
+            file_info.filename_suffix(),
+        )
+    }
+}
+
+/// The default scheme: digits in parentheses, such as `paris (1).jpg`. Uses a hand-written
+/// parser, which is significantly faster than a regex for directories with lots of files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Parentheses {
+    /// Numeral system of the digits inside the parentheses.
+    radix: Radix,
+}
+
+impl Parentheses {
+    /// Like [`Self::default`] but the digits belong to the given numeral system.
+    pub(crate) const fn with_radix(radix: Radix) -> Self {
+        Self { radix }
+    }
+}
+
+impl NumberingScheme for Parentheses {
+    fn find_number_groups(&self, filename: &str) -> Vec<NumberGroups> {
+        ParenthesesNumberGroups::new(filename, self.radix)
+            .map(|(from, to)| NumberGroups::new(from as usize..to as usize))
+            .collect()
+    }
+}
+
+/// The first capture group of the regex is the number group, the optional second capture
+/// group is the second number group. See [`FileInfo::new_with_regex`].
+#[cfg(feature = "regex")]
+impl NumberingScheme for Regex {
+    fn find_number_groups(&self, filename: &str) -> Vec<NumberGroups> {
+        self.captures_iter(filename)
+            .filter_map(|captures| {
+                let groups = NumberGroups::new(captures.get(1)?.range());
+                Some(match captures.get(2) {
+                    Some(second) => groups.with_second(second.range()),
+                    None => groups,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parentheses() {
+        let scheme = Parentheses::default();
+        assert!(scheme.find_number_groups("img.jpg").is_empty());
+        assert_eq!(
+            vec![NumberGroups::new(5..6)],
+            scheme.find_number_groups("img (1).jpg")
+        );
+        assert_eq!(2, scheme.find_number_groups("img (1) (2).jpg").len());
+        assert_eq!(
+            1,
+            Parentheses::with_radix(Radix::Hexadecimal)
+                .find_number_groups("dump (1f).bin")
+                .len()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn test_regex() {
+        let regex = Regex::new(r"_([0-9]+)").unwrap();
+        assert_eq!(2, regex.find_number_groups("img_1_2.jpg").len());
+
+        let regex = Regex::new(r"S([0-9]+)E([0-9]+)").unwrap();
+        assert_eq!(
+            vec![NumberGroups::new(6..7).with_second(8..9)],
+            regex.find_number_groups("Show S1E3.mkv")
+        );
+    }
+}
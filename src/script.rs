@@ -0,0 +1,231 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for scripts that execute a rename plan elsewhere. See [`ScriptFormat`].
+
+use crate::error::NFLZError;
+use crate::schedule::RenameStep;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Format of the script of [`crate::RenamePlan::to_script`], which performs the renames of a
+/// plan without nflz, e.g., by someone else on another machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScriptFormat {
+    /// POSIX shell script with `mv`. Name: `sh`.
+    Posix,
+    /// PowerShell script with `Rename-Item`, and `Move-Item` for moves into
+    /// subdirectories. Name: `powershell`.
+    PowerShell,
+}
+
+impl ScriptFormat {
+    /// All script formats.
+    pub const ALL: [Self; 2] = [Self::Posix, Self::PowerShell];
+
+    /// Returns the name of the format, for example for the command line.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Posix => "sh",
+            Self::PowerShell => "powershell",
+        }
+    }
+
+    /// Returns the script that creates the directories and performs the steps in the given
+    /// order.
+    pub(crate) fn render(self, directories: &BTreeSet<&Path>, steps: &[RenameStep]) -> String {
+        let mut script = String::new();
+        // writing into a string can't fail
+        let _ = match self {
+            Self::Posix => write_posix(&mut script, directories, steps),
+            Self::PowerShell => write_powershell(&mut script, directories, steps),
+        };
+        script
+    }
+}
+
+impl FromStr for ScriptFormat {
+    type Err = NFLZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.name() == s)
+            .ok_or_else(|| NFLZError::UnknownScriptFormat(s.to_string()))
+    }
+}
+
+impl Display for ScriptFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Writes the POSIX shell script. Aborts on the first failure.
+fn write_posix(
+    script: &mut String,
+    directories: &BTreeSet<&Path>,
+    steps: &[RenameStep],
+) -> fmt::Result {
+    writeln!(script, "#!/bin/sh")?;
+    writeln!(
+        script,
+        "# Generated by nflz. Renames {} files.",
+        file_count(steps)
+    )?;
+    writeln!(script, "set -e")?;
+    for directory in directories {
+        writeln!(script, "mkdir -p -- {}", posix_quote(directory))?;
+    }
+    for step in steps {
+        writeln!(
+            script,
+            "mv -- {} {}",
+            posix_quote(&step.from),
+            posix_quote(&step.to)
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the PowerShell script. Aborts on the first failure. Paths are passed with
+/// `-LiteralPath`, so that brackets are no wildcards.
+fn write_powershell(
+    script: &mut String,
+    directories: &BTreeSet<&Path>,
+    steps: &[RenameStep],
+) -> fmt::Result {
+    writeln!(
+        script,
+        "# Generated by nflz. Renames {} files.",
+        file_count(steps)
+    )?;
+    writeln!(script, "$ErrorActionPreference = 'Stop'")?;
+    for directory in directories {
+        writeln!(
+            script,
+            "New-Item -ItemType Directory -Force -Path {} | Out-Null",
+            powershell_quote(directory)
+        )?;
+    }
+    for step in steps {
+        match step.to.file_name() {
+            Some(filename) if step.from.parent() == step.to.parent() => writeln!(
+                script,
+                "Rename-Item -LiteralPath {} -NewName {}",
+                powershell_quote(&step.from),
+                powershell_quote(Path::new(filename))
+            )?,
+            _ => writeln!(
+                script,
+                "Move-Item -LiteralPath {} -Destination {}",
+                powershell_quote(&step.from),
+                powershell_quote(&step.to)
+            )?,
+        }
+    }
+    Ok(())
+}
+
+/// Returns the amount of renamed files, without the steps to temporary names.
+fn file_count(steps: &[RenameStep]) -> usize {
+    steps.iter().filter(|step| step.completes.is_some()).count()
+}
+
+/// Quotes the path for a POSIX shell. Inside single quotes, only the single quote itself is
+/// special.
+fn posix_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "'\\''"))
+}
+
+/// Quotes the path for PowerShell. Inside single quotes, all kinds of single quotes,
+/// including the typographic ones, must be doubled.
+fn powershell_quote(path: &Path) -> String {
+    let mut quoted = String::from("'");
+    for c in path.to_string_lossy().chars() {
+        if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn step(from: &str, to: &str) -> RenameStep {
+        RenameStep {
+            from: PathBuf::from(from),
+            to: PathBuf::from(to),
+            completes: Some(0),
+        }
+    }
+
+    #[test]
+    fn test_posix() {
+        let steps = [
+            step("dir/it's (1).jpg", "dir/it's (01).jpg"),
+            step("dir/a (2).jpg", "dir/part-01/a (02).jpg"),
+        ];
+        let directories = BTreeSet::from([Path::new("dir/part-01")]);
+        assert_eq!(
+            "#!/bin/sh\n\
+             # Generated by nflz. Renames 2 files.\n\
+             set -e\n\
+             mkdir -p -- 'dir/part-01'\n\
+             mv -- 'dir/it'\\''s (1).jpg' 'dir/it'\\''s (01).jpg'\n\
+             mv -- 'dir/a (2).jpg' 'dir/part-01/a (02).jpg'\n",
+            ScriptFormat::Posix.render(&directories, &steps)
+        );
+    }
+
+    #[test]
+    fn test_powershell() {
+        let steps = [
+            step("dir/it's [1].jpg", "dir/it's [01].jpg"),
+            step("dir/\u{2019}$a (2).jpg", "dir/part-01/\u{2019}$a (02).jpg"),
+        ];
+        let directories = BTreeSet::from([Path::new("dir/part-01")]);
+        assert_eq!(
+            "# Generated by nflz. Renames 2 files.\n\
+             $ErrorActionPreference = 'Stop'\n\
+             New-Item -ItemType Directory -Force -Path 'dir/part-01' | Out-Null\n\
+             Rename-Item -LiteralPath 'dir/it''s [1].jpg' -NewName 'it''s [01].jpg'\n\
+             Move-Item -LiteralPath 'dir/\u{2019}\u{2019}$a (2).jpg' -Destination 'dir/part-01/\u{2019}\u{2019}$a (02).jpg'\n",
+            ScriptFormat::PowerShell.render(&directories, &steps)
+        );
+        assert_eq!(
+            ScriptFormat::PowerShell,
+            "powershell".parse::<ScriptFormat>().unwrap()
+        );
+        assert!("bat".parse::<ScriptFormat>().is_err());
+    }
+}
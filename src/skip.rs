@@ -0,0 +1,180 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for files that are ignored by the library. See [`SkippedFile`].
+
+use crate::error::NFLZError;
+use crate::file_info::path_to_filename;
+use crate::fsutil::EntryKind;
+use crate::math::Radix;
+use crate::scheme::NumberingScheme;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Why a file in the working directory is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// The filename doesn't contain a number group, such as `notes.txt`.
+    NoNumberGroup,
+    /// The filename contains more than one number group, such as `paris (1) (2).jpg`.
+    MultipleNumberGroups,
+    /// The number group has more digits than fit into a [`u64`].
+    NumberTooLarge,
+    /// The number group found by a custom regex doesn't consist of digits.
+    NotANumber,
+    /// The entry is a directory.
+    Directory,
+    /// The entry is a symbolic link. Symlinks are never renamed.
+    Symlink,
+    /// The entry is neither a file, nor a directory, nor a symlink, e.g., a socket or a FIFO.
+    SpecialFile,
+    /// The content of the file doesn't belong to one of the selected categories. See
+    /// [`crate::NFLZAssistantBuilder::content_categories`].
+    ExcludedContentType,
+    /// The number of the file is ignored. See [`crate::NFLZAssistantBuilder::ignore_numbers`].
+    IgnoredNumber,
+    /// The entry matches a rule of the `.nflzignore` file or a `.gitignore` file. See
+    /// [`crate::NFLZAssistantBuilder::respect_ignore_file`] and
+    /// [`crate::NFLZAssistantBuilder::respect_gitignore`].
+    IgnoredByFile,
+    /// The file is opened by another process. See
+    /// [`crate::NFLZAssistantBuilder::open_file_policy`].
+    OpenInOtherProcess,
+    /// The new number of the file is outside of the range that is applied. See
+    /// [`crate::NFLZAssistantBuilder::only_numbers`].
+    OutsideRange,
+    /// The file would be renamed, but more files than the limit would be renamed before it.
+    /// See [`crate::NFLZAssistantBuilder::limit`].
+    BeyondLimit,
+}
+
+impl SkipReason {
+    /// Returns the reason for an entry that is not a regular file. `None` for files.
+    pub(crate) const fn from_entry_kind(kind: EntryKind) -> Option<Self> {
+        match kind {
+            EntryKind::File => None,
+            EntryKind::Directory => Some(Self::Directory),
+            EntryKind::Symlink => Some(Self::Symlink),
+            EntryKind::Special => Some(Self::SpecialFile),
+        }
+    }
+
+    /// Returns the reason for an error of [`crate::FileInfo::new`] or
+    /// [`crate::FileInfo::new_with_scheme`]. `None` if the error isn't a parsing error.
+    pub(crate) fn from_parse_error(
+        err: &NFLZError,
+        scheme: &dyn NumberingScheme,
+        radix: Radix,
+    ) -> Option<Self> {
+        match err {
+            NFLZError::FilenameMustIncludeExactlyOneNumberedGroup(path) => {
+                if scheme.find_number_groups(path_to_filename(path)).is_empty() {
+                    Some(Self::NoNumberGroup)
+                } else {
+                    Some(Self::MultipleNumberGroups)
+                }
+            }
+            NFLZError::ValueInNumberedGroupNotANumber(_, value) => {
+                if radix.only_overflows() && value.bytes().all(|byte| radix.is_digit(byte)) {
+                    Some(Self::NumberTooLarge)
+                } else {
+                    Some(Self::NotANumber)
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for SkipReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NoNumberGroup => "no number group",
+            Self::MultipleNumberGroups => "multiple number groups",
+            Self::NumberTooLarge => "number too large",
+            Self::NotANumber => "number group is not a number",
+            Self::Directory => "directory",
+            Self::Symlink => "symlink",
+            Self::SpecialFile => "special file",
+            Self::ExcludedContentType => "excluded content type",
+            Self::IgnoredNumber => "ignored number",
+            Self::IgnoredByFile => "listed in an ignore file",
+            Self::OpenInOtherProcess => "opened by another process",
+            Self::OutsideRange => "outside of the applied range",
+            Self::BeyondLimit => "beyond the limit of renamed files",
+        })
+    }
+}
+
+/// A file in the working directory that is ignored by the library, together with the reason.
+/// See [`crate::NFLZAssistant::skipped_files`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SkippedFile {
+    /// Path to the file.
+    path: PathBuf,
+    /// Why the file is ignored.
+    reason: SkipReason,
+    /// Value of the number group, if the file was parsed before it was skipped.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    number: Option<u64>,
+}
+
+impl SkippedFile {
+    /// Constructor.
+    pub(crate) const fn new(path: PathBuf, reason: SkipReason) -> Self {
+        Self {
+            path,
+            reason,
+            number: None,
+        }
+    }
+
+    /// Sets the value of the number group of the file.
+    pub(crate) const fn with_number(mut self, number: u64) -> Self {
+        self.number = Some(number);
+        self
+    }
+
+    /// Returns the path to the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns why the file is ignored.
+    pub const fn reason(&self) -> SkipReason {
+        self.reason
+    }
+
+    /// Returns the value of the number group of the file. `None` if the file was skipped
+    /// before or because its filename was parsed, e.g., for [`SkipReason::NoNumberGroup`] or
+    /// [`SkipReason::IgnoredByFile`].
+    pub const fn number(&self) -> Option<u64> {
+        self.number
+    }
+}
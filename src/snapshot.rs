@@ -0,0 +1,211 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for an owned copy of the state of an assistant. See [`AssistantSnapshot`].
+
+use crate::plan::PlanViolation;
+use crate::skip::SkippedFile;
+use crate::stats::Stats;
+use crate::warning::PlanWarning;
+use std::path::{Path, PathBuf};
+
+/// A numbered file of an [`AssistantSnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SnapshotEntry {
+    /// Current path of the file.
+    path: PathBuf,
+    /// Path of the file after the renaming. `None` if the file already has the correct name.
+    new_path: Option<PathBuf>,
+    /// Value of the number group.
+    number: u64,
+    /// Number of digits the number group is padded to.
+    width: u64,
+}
+
+impl SnapshotEntry {
+    /// Constructor.
+    pub(crate) const fn new(
+        path: PathBuf,
+        new_path: Option<PathBuf>,
+        number: u64,
+        width: u64,
+    ) -> Self {
+        Self {
+            path,
+            new_path,
+            number,
+            width,
+        }
+    }
+
+    /// Returns the current path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path of the file after the renaming. `None` if the file already has the
+    /// correct name.
+    pub fn new_path(&self) -> Option<&Path> {
+        self.new_path.as_deref()
+    }
+
+    /// Returns the value of the number group.
+    pub const fn number(&self) -> u64 {
+        self.number
+    }
+
+    /// Returns the number of digits the number group is padded to.
+    pub const fn width(&self) -> u64 {
+        self.width
+    }
+}
+
+/// Owned copy of the whole state of a [`crate::NFLZAssistant`]. See
+/// [`crate::NFLZAssistant::snapshot`].
+///
+/// Contains all numbered files with their new paths, the skipped files, the warnings, the
+/// violations that prevent the renaming, and the stats.
+///
+/// Other than the getters of the assistant, the snapshot doesn't borrow from it. Thus, it can
+/// be kept or sent to another thread, and with the `serde` feature, it serializes to JSON,
+/// e.g., to send it over the IPC channel of a Tauri or Electron frontend.
+///
+/// ```rust
+/// use nflz::NFLZAssistant;
+///
+/// let snapshot = NFLZAssistant::new("./test-resources").unwrap().snapshot();
+/// for entry in snapshot.entries() {
+///     println!("{} => {:?}", entry.path().display(), entry.new_path());
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AssistantSnapshot {
+    /// The working directory.
+    directory: PathBuf,
+    /// All numbered files, in the order of [`crate::NFLZAssistant::files_to_rename`].
+    entries: Vec<SnapshotEntry>,
+    /// See [`crate::NFLZAssistant::skipped_files`].
+    skipped_files: Vec<SkippedFile>,
+    /// See [`crate::NFLZAssistant::warnings`].
+    warnings: Vec<PlanWarning>,
+    /// Violations that prevent the renaming.
+    violations: Vec<PlanViolation>,
+    /// See [`crate::NFLZAssistant::stats`].
+    stats: Stats,
+}
+
+impl AssistantSnapshot {
+    /// Constructor.
+    pub(crate) const fn new(
+        directory: PathBuf,
+        entries: Vec<SnapshotEntry>,
+        skipped_files: Vec<SkippedFile>,
+        warnings: Vec<PlanWarning>,
+        violations: Vec<PlanViolation>,
+        stats: Stats,
+    ) -> Self {
+        Self {
+            directory,
+            entries,
+            skipped_files,
+            warnings,
+            violations,
+            stats,
+        }
+    }
+
+    /// Returns the working directory.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Returns all numbered files, the ones that are renamed and the ones that already have
+    /// the correct name, sorted like [`crate::NFLZAssistant::files_to_rename`].
+    pub fn entries(&self) -> &[SnapshotEntry] {
+        &self.entries
+    }
+
+    /// Returns all files that are ignored. See [`crate::NFLZAssistant::skipped_files`].
+    pub fn skipped_files(&self) -> &[SkippedFile] {
+        &self.skipped_files
+    }
+
+    /// Returns all warnings about the new filenames. See [`crate::NFLZAssistant::warnings`].
+    pub fn warnings(&self) -> &[PlanWarning] {
+        &self.warnings
+    }
+
+    /// Returns the violations of [`crate::NFLZAssistant::check_no_destination_conflicts`],
+    /// [`crate::NFLZAssistant::check_unambiguous_prefixes`], and
+    /// [`crate::NFLZAssistant::check_unambiguous_suffixes`] at the time of the snapshot.
+    /// The renaming fails if there are any.
+    pub fn violations(&self) -> &[PlanViolation] {
+        &self.violations
+    }
+
+    /// Returns the stats. See [`crate::NFLZAssistant::stats`].
+    pub const fn stats(&self) -> &Stats {
+        &self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testutil::FixtureBuilder;
+    use crate::NFLZAssistant;
+
+    #[test]
+    fn test_snapshot() {
+        let fixture = FixtureBuilder::new("a ")
+            .count(10)
+            .extra_file("notes.txt")
+            .extra_file("b (3).jpg")
+            .create()
+            .unwrap();
+        let snapshot = NFLZAssistant::new(fixture.path()).unwrap().snapshot();
+        assert_eq!(fixture.path(), snapshot.directory());
+        assert_eq!(11, snapshot.entries().len());
+        let entry = &snapshot.entries()[0];
+        assert_eq!(fixture.path().join("a (1).jpg"), entry.path());
+        assert_eq!(
+            Some(fixture.path().join("a (01).jpg").as_path()),
+            entry.new_path()
+        );
+        assert_eq!((1, 2), (entry.number(), entry.width()));
+        assert_eq!(None, snapshot.entries()[10].new_path());
+        assert_eq!(1, snapshot.skipped_files().len());
+        assert_eq!(1, snapshot.violations().len());
+        assert_eq!(10, snapshot.stats().files_to_rename);
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_value(&snapshot).unwrap();
+            assert_eq!(11, json["entries"].as_array().unwrap().len());
+            assert_eq!(2, json["entries"][0]["width"]);
+            assert_eq!("ambiguous_prefixes", json["violations"][0]["violation"]);
+        }
+    }
+}
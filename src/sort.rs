@@ -0,0 +1,184 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for sorting files for the output and for the renumbering. See [`SortOrder`] and
+//! [`RenumberOrder`].
+
+use crate::file_info::{FileInfo, FileInfoWithRenameAdvice};
+use crate::fsutil::long_path;
+use std::fs::Metadata;
+use std::io;
+use std::time::SystemTime;
+
+/// Order in that files are presented to the user, for example in the tables of the CLI.
+/// Doesn't influence the new filenames.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    /// By the value of the number group, ascending. This is the order of all getters of
+    /// [`crate::NFLZAssistant`].
+    #[default]
+    Number,
+    /// By the original filename, ascending.
+    Name,
+    /// By the last modification time, oldest first. Files without a modification time come
+    /// first.
+    Mtime,
+    /// By the file size, smallest first.
+    Size,
+}
+
+impl SortOrder {
+    /// Sorts the files in this order. Ties are broken by the value of the number group. The
+    /// metadata for [`Self::Mtime`] and [`Self::Size`] is read from the file system once per file.
+    pub fn sort(self, files: &mut [&FileInfoWithRenameAdvice]) {
+        match self {
+            Self::Number => files.sort(),
+            Self::Name => files.sort_by(|a, b| {
+                a.file_info()
+                    .original_filename()
+                    .cmp(b.file_info().original_filename())
+                    .then_with(|| a.cmp(b))
+            }),
+            Self::Mtime => files.sort_by_cached_key(|file| {
+                let modified = std::fs::metadata(long_path(file.file_info().path()))
+                    .and_then(|meta| meta.modified())
+                    .ok();
+                (modified.unwrap_or(SystemTime::UNIX_EPOCH), *file)
+            }),
+            Self::Size => files.sort_by_cached_key(|file| {
+                let len = std::fs::metadata(long_path(file.file_info().path()))
+                    .map_or(0, |meta| meta.len());
+                (len, *file)
+            }),
+        }
+    }
+}
+
+/// Order in that files get their new numbers if they are renumbered. See
+/// [`crate::NFLZAssistantBuilder::renumber_by`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum RenumberOrder {
+    /// By the value of the existing number group, ascending.
+    #[default]
+    Number,
+    /// By the last modification time, oldest first. Useful if the counters of the camera
+    /// wrapped around or if two cameras restarted their counters.
+    Mtime,
+    /// By the creation time, oldest first. Falls back to the last modification time on
+    /// platforms and file systems that don't record the creation time.
+    Created,
+}
+
+impl RenumberOrder {
+    /// Sorts the files in this order. Ties are broken by the value of the number group. With
+    /// the `exif` feature, files with the same timestamp, such as the frames of a burst, are
+    /// first ordered by their EXIF image number. Files without a timestamp come first.
+    pub(crate) fn sort(self, files: &mut [FileInfo]) {
+        let timestamp = |metadata: &Metadata| -> io::Result<SystemTime> {
+            match self {
+                Self::Number | Self::Mtime => metadata.modified(),
+                Self::Created => metadata.created().or_else(|_| metadata.modified()),
+            }
+        };
+        match self {
+            Self::Number => files.sort(),
+            Self::Mtime | Self::Created => {
+                let mut timed_files = files
+                    .iter()
+                    .map(|file| {
+                        let time = std::fs::metadata(long_path(file.path()))
+                            .and_then(|metadata| timestamp(&metadata))
+                            .ok();
+                        (time.unwrap_or(SystemTime::UNIX_EPOCH), file.clone())
+                    })
+                    .collect::<Vec<_>>();
+                timed_files.sort();
+                #[cfg(feature = "exif")]
+                crate::burst::sort_bursts(&mut timed_files);
+                for (file, (_, sorted_file)) in files.iter_mut().zip(timed_files) {
+                    *file = sorted_file;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_info::FileInfo;
+
+    #[test]
+    fn test_sort() {
+        let files = [
+            FileInfoWithRenameAdvice::new(FileInfo::new("b (2).jpg").unwrap(), 2),
+            FileInfoWithRenameAdvice::new(FileInfo::new("a (10).jpg").unwrap(), 2),
+            FileInfoWithRenameAdvice::new(FileInfo::new("c (1).jpg").unwrap(), 2),
+        ];
+        let mut sorted = files.iter().collect::<Vec<_>>();
+        let names = |files: &[&FileInfoWithRenameAdvice]| {
+            files
+                .iter()
+                .map(|file| file.file_info().original_filename().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        SortOrder::Number.sort(&mut sorted);
+        assert_eq!(["c (1).jpg", "b (2).jpg", "a (10).jpg"], *names(&sorted));
+        SortOrder::Name.sort(&mut sorted);
+        assert_eq!(["a (10).jpg", "b (2).jpg", "c (1).jpg"], *names(&sorted));
+        // files don't exist => fallback to number
+        SortOrder::Size.sort(&mut sorted);
+        assert_eq!(["c (1).jpg", "b (2).jpg", "a (10).jpg"], *names(&sorted));
+    }
+
+    #[test]
+    fn test_renumber_order() {
+        let fixture = crate::testutil::FixtureBuilder::new("a ")
+            .count(3)
+            .create()
+            .unwrap();
+        let mut files = (1..=3)
+            .map(|number| FileInfo::new(fixture.path().join(format!("a ({}).jpg", number))))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        for (number, mtime) in [(1, 30), (2, 10), (3, 20)] {
+            filetime::set_file_mtime(
+                fixture.path().join(format!("a ({}).jpg", number)),
+                filetime::FileTime::from_unix_time(mtime, 0),
+            )
+            .unwrap();
+        }
+
+        RenumberOrder::Mtime.sort(&mut files);
+        let numbers = files
+            .iter()
+            .map(FileInfo::number_group_value)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![2, 3, 1], numbers);
+        RenumberOrder::Number.sort(&mut files);
+        assert_eq!(1, files[0].number_group_value());
+    }
+}
@@ -0,0 +1,121 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for statistics about a run of the library. See [`Stats`].
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ops::AddAssign;
+use std::time::Duration;
+
+/// How long the individual phases of the library took. Useful to see where time is spent,
+/// for example on slow network storage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PhaseTimings {
+    /// Reading the directory.
+    pub scan: Duration,
+    /// Parsing the filenames and computing the new filenames.
+    pub plan: Duration,
+    /// Checking that all files can be renamed. `None` if no validation happened yet.
+    pub validation: Option<Duration>,
+    /// Renaming the files. `None` if no files were renamed yet.
+    pub apply: Option<Duration>,
+}
+
+impl PhaseTimings {
+    /// Returns the sum of all phases.
+    pub fn total(&self) -> Duration {
+        self.scan + self.plan + self.validation.unwrap_or_default() + self.apply.unwrap_or_default()
+    }
+}
+
+impl AddAssign for PhaseTimings {
+    fn add_assign(&mut self, rhs: Self) {
+        let add = |a: Option<Duration>, b: Option<Duration>| match (a, b) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or_default() + b.unwrap_or_default()),
+        };
+        self.scan += rhs.scan;
+        self.plan += rhs.plan;
+        self.validation = add(self.validation, rhs.validation);
+        self.apply = add(self.apply, rhs.apply);
+    }
+}
+
+impl Display for PhaseTimings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let phase = |duration: Option<Duration>| {
+            duration.map_or_else(|| "-".to_string(), |duration| format!("{:.2?}", duration))
+        };
+        write!(
+            f,
+            "scan: {}, plan: {}, validation: {}, apply: {} (total: {:.2?})",
+            phase(Some(self.scan)),
+            phase(Some(self.plan)),
+            phase(self.validation),
+            phase(self.apply),
+            self.total()
+        )
+    }
+}
+
+/// Statistics about the files of a [`crate::NFLZAssistant`] and the run of the library.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Stats {
+    /// Amount of files that need a rename (or were renamed).
+    pub files_to_rename: usize,
+    /// Amount of files that already have the correct name.
+    pub files_without_rename: usize,
+    /// How long the individual phases took.
+    pub timings: PhaseTimings,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phase_timings() {
+        let mut timings = PhaseTimings {
+            scan: Duration::from_millis(1),
+            plan: Duration::from_millis(2),
+            validation: None,
+            apply: None,
+        };
+        timings += PhaseTimings {
+            scan: Duration::from_millis(1),
+            plan: Duration::from_millis(2),
+            validation: Some(Duration::from_millis(3)),
+            apply: None,
+        };
+        assert_eq!(Some(Duration::from_millis(3)), timings.validation);
+        assert_eq!(None, timings.apply);
+        assert_eq!(Duration::from_millis(9), timings.total());
+        assert_eq!(
+            "scan: 2.00ms, plan: 4.00ms, validation: 3.00ms, apply: - (total: 9.00ms)",
+            timings.to_string()
+        );
+    }
+}
@@ -0,0 +1,313 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for concrete ways to resolve an error. See [`Suggestion`].
+
+use crate::error::NFLZError;
+use crate::prefix::PrefixEquivalence;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Maximum number of files that are listed in the [`Display`] output of a [`Suggestion`].
+const MAX_DISPLAYED_FILES: usize = 5;
+
+/// A concrete way to resolve an [`NFLZError`], such as an option that tolerates the
+/// difference or the files that need attention. See [`NFLZError::suggestions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Suggestion {
+    /// What to do.
+    message: String,
+    /// The option of the CLI that does it, if any, such as `--ignore-prefix-case`.
+    cli_option: Option<&'static str>,
+    /// The files the suggestion is about, such as the files with an unusual prefix.
+    files: Vec<PathBuf>,
+}
+
+impl Suggestion {
+    /// Constructor for a suggestion without an option and without files.
+    fn new(message: &str) -> Self {
+        Self {
+            message: message.to_string(),
+            cli_option: None,
+            files: Vec::new(),
+        }
+    }
+
+    /// Sets the option of the CLI.
+    const fn with_cli_option(mut self, cli_option: &'static str) -> Self {
+        self.cli_option = Some(cli_option);
+        self
+    }
+
+    /// Sets the files.
+    fn with_files(mut self, files: &[PathBuf]) -> Self {
+        self.files = files.to_vec();
+        self
+    }
+
+    /// Returns what to do.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the option of the CLI that does it, if any, such as `--ignore-prefix-case`.
+    pub const fn cli_option(&self) -> Option<&'static str> {
+        self.cli_option
+    }
+
+    /// Returns the files the suggestion is about, such as the files with an unusual prefix.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+}
+
+impl Display for Suggestion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)?;
+        if let Some(option) = self.cli_option {
+            write!(f, " ({})", option)?;
+        }
+        if !self.files.is_empty() {
+            let names = self
+                .files
+                .iter()
+                .take(MAX_DISPLAYED_FILES)
+                .map(|path| {
+                    path.file_name()
+                        .map_or(path.as_path(), Path::new)
+                        .display()
+                        .to_string()
+                })
+                .collect::<Vec<_>>();
+            write!(f, ": {}", names.join(", "))?;
+            if self.files.len() > MAX_DISPLAYED_FILES {
+                write!(f, ", and {} more", self.files.len() - MAX_DISPLAYED_FILES)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the suggestions for the error. See [`NFLZError::suggestions`].
+pub(crate) fn suggest(err: &NFLZError) -> Vec<Suggestion> {
+    match err {
+        NFLZError::AmbiguousPrefixes(prefixes, files) => suggest_for_prefixes(prefixes, files),
+        NFLZError::AmbiguousSuffixes(suffixes, files) => suggest_for_suffixes(suffixes, files),
+        NFLZError::FilenameMustIncludeExactlyOneNumberedGroup(path) => {
+            let mut suggestions = vec![Suggestion::new("Pad every sequence of digits instead")
+                .with_cli_option("--all-groups")
+                .with_files(std::slice::from_ref(path))];
+            if cfg!(feature = "regex") {
+                suggestions.push(
+                    Suggestion::new("Select the pattern that finds the number group")
+                        .with_cli_option("--pattern auto"),
+                );
+            }
+            suggestions
+        }
+        NFLZError::TemporaryFilesLeft(_, files) => {
+            let temporary_paths = files
+                .iter()
+                .map(|(temporary, _)| temporary.clone())
+                .collect::<Vec<_>>();
+            vec![Suggestion::new(
+                "Rename the files with a temporary name back to their original name",
+            )
+            .with_files(&temporary_paths)]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Suggests the options that tolerate the differences between the prefixes, if they
+/// suffice, and lists the files with an unusual prefix.
+fn suggest_for_prefixes(prefixes: &HashSet<String>, files: &[PathBuf]) -> Vec<Suggestion> {
+    let are_equivalent = |ignore_case, ignore_whitespace| {
+        let equivalence = PrefixEquivalence {
+            ignore_case,
+            ignore_whitespace,
+            unify: false,
+        };
+        prefixes
+            .iter()
+            .map(|prefix| equivalence.key(prefix))
+            .collect::<BTreeSet<_>>()
+            .len()
+            == 1
+    };
+    let mut suggestions = Vec::new();
+    if are_equivalent(true, false) {
+        suggestions.push(
+            Suggestion::new("Treat prefixes that only differ in their case as the same sequence")
+                .with_cli_option("--ignore-prefix-case"),
+        );
+    } else if are_equivalent(false, true) {
+        suggestions.push(
+            Suggestion::new(
+                "Treat prefixes that only differ in their whitespace as the same sequence",
+            )
+            .with_cli_option("--ignore-prefix-whitespace"),
+        );
+    } else if are_equivalent(true, true) {
+        suggestions.push(
+            Suggestion::new(
+                "Treat prefixes that only differ in their case and whitespace as the same sequence",
+            )
+            .with_cli_option("--ignore-prefix-case --ignore-prefix-whitespace"),
+        );
+    }
+    if !files.is_empty() {
+        suggestions.push(
+            Suggestion::new(
+                "Move or rename the files with a different prefix, or list them in the .nflzignore file",
+            )
+            .with_files(files),
+        );
+    }
+    suggestions
+}
+
+/// Suggests the options that handle files whose suffixes only differ in the extension and
+/// lists the files with an unusual suffix.
+fn suggest_for_suffixes(suffixes: &HashSet<String>, files: &[PathBuf]) -> Vec<Suggestion> {
+    let stems = suffixes
+        .iter()
+        .map(|suffix| {
+            suffix
+                .rfind('.')
+                .map_or(suffix.as_str(), |dot| &suffix[..dot])
+        })
+        .collect::<BTreeSet<_>>();
+    let mut suggestions = Vec::new();
+    if stems.len() == 1 {
+        suggestions.push(
+            Suggestion::new("Rename files with the same name but another extension together")
+                .with_cli_option("--group-sidecars"),
+        );
+        suggestions.push(
+            Suggestion::new("Number each file extension as its own sequence")
+                .with_cli_option("--per-extension"),
+        );
+    }
+    if !files.is_empty() {
+        suggestions.push(
+            Suggestion::new(
+                "Move or rename the files with a different suffix, or list them in the .nflzignore file",
+            )
+            .with_files(files),
+        );
+    }
+    suggestions
+}
+
+/// Returns the files whose key differs from the most common key of all files, e.g., the files
+/// with an unusual prefix. On a tie, the key that sorts first is the common one.
+pub(crate) fn outliers<'a, K: Ord, I: IntoIterator<Item = (&'a Path, K)>>(
+    files: I,
+) -> Vec<PathBuf> {
+    let files = files.into_iter().collect::<Vec<_>>();
+    let mut counts = std::collections::BTreeMap::<&K, usize>::new();
+    for (_, key) in &files {
+        *counts.entry(key).or_default() += 1;
+    }
+    let most_common = counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, count)| **count)
+        .map(|(key, _)| *key);
+    files
+        .iter()
+        .filter(|(_, key)| Some(key) != most_common)
+        .map(|(path, _)| path.to_path_buf())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(elements: &[&str]) -> HashSet<String> {
+        elements.iter().map(|element| element.to_string()).collect()
+    }
+
+    #[test]
+    fn test_suggest_for_prefixes() {
+        let files = vec![PathBuf::from("dir/IMG (3).jpg")];
+        let err = NFLZError::AmbiguousPrefixes(set(&["img (", "IMG ("]), files.clone());
+        let suggestions = err.suggestions();
+        assert_eq!(2, suggestions.len());
+        assert_eq!(Some("--ignore-prefix-case"), suggestions[0].cli_option());
+        assert_eq!(files, suggestions[1].files());
+        assert!(suggestions[1].to_string().ends_with(": IMG (3).jpg"));
+
+        let err = NFLZError::AmbiguousPrefixes(set(&["img (", "img  ("]), Vec::new());
+        assert_eq!(
+            Some("--ignore-prefix-whitespace"),
+            err.suggestions()[0].cli_option()
+        );
+
+        let err = NFLZError::AmbiguousPrefixes(set(&["paris (", "london ("]), Vec::new());
+        assert!(err.suggestions().is_empty());
+    }
+
+    #[test]
+    fn test_suggest_for_suffixes() {
+        let err = NFLZError::AmbiguousSuffixes(set(&[").jpg", ").xmp"]), Vec::new());
+        let options = err
+            .suggestions()
+            .iter()
+            .filter_map(Suggestion::cli_option)
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["--group-sidecars", "--per-extension"], options);
+    }
+
+    #[test]
+    fn test_outliers() {
+        let files = ["a", "b", "c", "d"].map(PathBuf::from);
+        let keys = ["img (", "IMG (", "img (", "x ("];
+        let outliers = outliers(
+            files
+                .iter()
+                .zip(keys)
+                .map(|(path, key)| (path.as_path(), key.to_string())),
+        );
+        assert_eq!(vec![PathBuf::from("b"), PathBuf::from("d")], outliers);
+    }
+
+    #[test]
+    fn test_display() {
+        let files = (1..=7)
+            .map(|number| PathBuf::from(format!("dir/x ({}).jpg", number)))
+            .collect::<Vec<_>>();
+        let suggestion = Suggestion::new("Do something")
+            .with_cli_option("--something")
+            .with_files(&files);
+        assert_eq!(
+            "Do something (--something): x (1).jpg, x (2).jpg, x (3).jpg, x (4).jpg, x (5).jpg, and 2 more",
+            suggestion.to_string()
+        );
+    }
+}
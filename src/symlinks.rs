@@ -0,0 +1,228 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Retargets the symlinks of the working directory that point at renamed files, so that link
+//! farms don't break. See [`retarget_symlinks`].
+
+use crate::error::NFLZError;
+use crate::fsutil::{long_path, read_directory_flat, replace_symlink, EntryKind};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Normalizes the path lexically, i.e., removes `.` and resolves `..` without accessing the
+/// file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Resolves the target of a symlink in the directory to the path of the file it points at.
+/// The parent of an absolute target is canonicalized, as it may pass a symlink, e.g., a
+/// symlinked `/tmp`. Relative targets and targets whose parent doesn't exist are resolved
+/// lexically.
+fn resolve_target(canonical_dir: &Path, target: &Path) -> PathBuf {
+    let path = normalize(&canonical_dir.join(target));
+    if !target.is_absolute() {
+        return path;
+    }
+    let canonical_parent = path
+        .parent()
+        .and_then(|parent| std::fs::canonicalize(long_path(parent)).ok());
+    match (canonical_parent, path.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path,
+    }
+}
+
+/// Returns the new target of a symlink in `dir` whose target `target` points at the file
+/// that is moved from `from` to `to`. The target keeps its form, e.g., a relative target stays
+/// relative, unless the file moved to another directory, see
+/// [`crate::NFLZAssistantBuilder::split_into_subdirectories`].
+fn new_target(dir: &Path, canonical_dir: &Path, target: &Path, from: &Path, to: &Path) -> PathBuf {
+    if from.parent() == to.parent() {
+        return target.with_file_name(to.file_name().unwrap_or_default());
+    }
+    let relative = to.strip_prefix(dir).unwrap_or(to);
+    if target.is_absolute() {
+        canonical_dir.join(relative)
+    } else {
+        relative.to_path_buf()
+    }
+}
+
+/// Retargets all symlinks of the directory (not recursive) that point at a renamed file to
+/// the new path of the file. Symlinks that point at other files stay as they are.
+///
+/// Relative targets are resolved lexically, i.e., they only match if they lead to the file
+/// without passing another symlink. Absolute targets may pass symlinks in their parent
+/// directories, see [`resolve_target`].
+///
+/// # Parameters
+/// * `dir` The working directory.
+/// * `moves` Old and new paths of the renamed files.
+///
+/// # Return Type
+/// All retargeted symlinks with their new target.
+pub(crate) fn retarget_symlinks(
+    dir: &Path,
+    moves: &[(PathBuf, PathBuf)],
+) -> Result<Vec<(PathBuf, PathBuf)>, NFLZError> {
+    let canonical_dir = std::fs::canonicalize(long_path(dir))
+        .map_err(|err| NFLZError::CantReadDirectory(dir.into(), err))?;
+    // keyed by the path relative to the directory, as the files of subdirectories may have
+    // the same name
+    let by_source = moves
+        .iter()
+        .map(|(from, to)| {
+            let relative = from.strip_prefix(dir).unwrap_or(from);
+            (normalize(&canonical_dir.join(relative)), (from, to))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let entries =
+        read_directory_flat(dir).map_err(|err| NFLZError::CantReadDirectory(dir.into(), err))?;
+    let mut retargeted = Vec::new();
+    for (link, kind) in entries {
+        if kind != EntryKind::Symlink {
+            continue;
+        }
+        let target = std::fs::read_link(long_path(&link))
+            .map_err(|err| NFLZError::CantRetargetSymlink(link.clone(), err))?;
+        let Some((from, to)) = by_source.get(&resolve_target(&canonical_dir, &target)) else {
+            continue;
+        };
+        let new_target = new_target(dir, &canonical_dir, &target, from, to);
+        replace_symlink(&link, &new_target)
+            .map_err(|err| NFLZError::CantRetargetSymlink(link.clone(), err))?;
+        retargeted.push((link, new_target));
+    }
+    Ok(retargeted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            PathBuf::from("/photos/a (1).jpg"),
+            normalize(Path::new("/photos/./raw/../a (1).jpg"))
+        );
+        assert_eq!(PathBuf::from("../a"), normalize(Path::new("./../a")));
+    }
+
+    #[test]
+    fn test_new_target() {
+        let dir = Path::new("photos");
+        let canonical_dir = Path::new("/home/user/photos");
+        let from = dir.join("a (1).jpg");
+        assert_eq!(
+            PathBuf::from("./a (01).jpg"),
+            new_target(
+                dir,
+                canonical_dir,
+                Path::new("./a (1).jpg"),
+                &from,
+                &dir.join("a (01).jpg")
+            )
+        );
+        assert_eq!(
+            PathBuf::from("part-01/a (01).jpg"),
+            new_target(
+                dir,
+                canonical_dir,
+                Path::new("a (1).jpg"),
+                &from,
+                &dir.join("part-01/a (01).jpg")
+            )
+        );
+        assert_eq!(
+            PathBuf::from("/home/user/photos/part-01/a (01).jpg"),
+            new_target(
+                dir,
+                canonical_dir,
+                Path::new("/home/user/photos/a (1).jpg"),
+                &from,
+                &dir.join("part-01/a (01).jpg")
+            )
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_retarget_symlinks_in_subdirectory() {
+        let fixture = crate::testutil::FixtureBuilder::new("a ")
+            .count(1)
+            .create()
+            .unwrap();
+        let dir = fixture.path();
+        std::fs::create_dir(dir.join("raw")).unwrap();
+        std::fs::write(dir.join("raw/a (1).jpg"), "raw").unwrap();
+        std::os::unix::fs::symlink("raw/a (1).jpg", dir.join("link")).unwrap();
+        // same filename, but the link points at the file of the subdirectory
+        let moves = [
+            (dir.join("a (1).jpg"), dir.join("a (01).jpg")),
+            (dir.join("raw/a (1).jpg"), dir.join("raw/a (02).jpg")),
+        ];
+        let retargeted = retarget_symlinks(dir, &moves).unwrap();
+        assert_eq!(
+            vec![(dir.join("link"), PathBuf::from("raw/a (02).jpg"))],
+            retargeted
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_retarget_symlinks_with_symlinked_parent() {
+        let fixture = crate::testutil::FixtureBuilder::new("a ")
+            .count(1)
+            .create()
+            .unwrap();
+        let dir = fixture.path();
+        // e.g., a symlinked `/tmp`
+        let alias = dir.join("alias");
+        std::os::unix::fs::symlink(dir, &alias).unwrap();
+        std::os::unix::fs::symlink(alias.join("a (1).jpg"), dir.join("link")).unwrap();
+        let moves = [(dir.join("a (1).jpg"), dir.join("a (01).jpg"))];
+        let retargeted = retarget_symlinks(dir, &moves).unwrap();
+        assert_eq!(
+            vec![(dir.join("link"), alias.join("a (01).jpg"))],
+            retargeted
+        );
+    }
+}
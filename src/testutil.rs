@@ -0,0 +1,208 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Generator for synthetic sets of numbered files, e.g., for tests. Only available with the
+//! `testutil` feature.
+//!
+//! ```rust
+//! use nflz::testutil::FixtureBuilder;
+//! use nflz::NFLZAssistant;
+//!
+//! // creates "paris (1).jpg" to "paris (12).jpg" without "paris (5).jpg"
+//! let fixture = FixtureBuilder::new("paris ").count(12).gaps([5]).create().unwrap();
+//! let assistant = NFLZAssistant::new(fixture.path()).unwrap();
+//! assert_eq!(8, assistant.files_to_rename().len());
+//! // the directory is removed when `fixture` is dropped
+//! ```
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counter to give each fixture of the process its own directory.
+static FIXTURE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Builder for a [`Fixture`]. Generates the files `<prefix>(<number>)<suffix>` for the
+/// numbers `start..start + count` without the gaps, for every prefix.
+#[derive(Debug, Clone)]
+pub struct FixtureBuilder {
+    prefixes: Vec<String>,
+    suffix: String,
+    start: u64,
+    count: u64,
+    gaps: Vec<u64>,
+    padding: usize,
+    extra_files: Vec<String>,
+}
+
+impl FixtureBuilder {
+    /// Creates a builder for ten files with the given prefix and the suffix `.jpg`, i.e.,
+    /// `<prefix>(1).jpg` to `<prefix>(10).jpg`.
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefixes: vec![prefix.to_string()],
+            suffix: ".jpg".to_string(),
+            start: 1,
+            count: 10,
+            gaps: Vec::new(),
+            padding: 0,
+            extra_files: Vec::new(),
+        }
+    }
+
+    /// Adds another prefix. The same numbers are generated for every prefix, which results
+    /// in an ambiguous set of files.
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefixes.push(prefix.to_string());
+        self
+    }
+
+    /// Sets the suffix after the number group, e.g., `.jpg`.
+    pub fn suffix(mut self, suffix: &str) -> Self {
+        self.suffix = suffix.to_string();
+        self
+    }
+
+    /// Sets the first number. Default is `1`.
+    pub const fn start(mut self, start: u64) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Sets how many numbers, including the gaps, are generated. Default is `10`.
+    pub const fn count(mut self, count: u64) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Numbers for which no file is generated.
+    pub fn gaps<I: IntoIterator<Item = u64>>(mut self, gaps: I) -> Self {
+        self.gaps.extend(gaps);
+        self
+    }
+
+    /// Pads the numbers with leading zeros to at least this amount of digits. Default is `0`,
+    /// i.e., no padding.
+    pub const fn padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Adds a file with an arbitrary name, e.g., one that is not part of the numbered set.
+    pub fn extra_file(mut self, filename: &str) -> Self {
+        self.extra_files.push(filename.to_string());
+        self
+    }
+
+    /// Returns the names of all files that [`Self::create`] generates.
+    pub fn filenames(&self) -> Vec<String> {
+        let numbers = (self.start..self.start + self.count).filter(|n| !self.gaps.contains(n));
+        numbers
+            .flat_map(|number| {
+                self.prefixes.iter().map(move |prefix| {
+                    format!(
+                        "{}({:0padding$}){}",
+                        prefix,
+                        number,
+                        self.suffix,
+                        padding = self.padding
+                    )
+                })
+            })
+            .chain(self.extra_files.iter().cloned())
+            .collect()
+    }
+
+    /// Creates a new directory inside the temp directory of the system that contains
+    /// the files. Every file contains its own name.
+    pub fn create(&self) -> io::Result<Fixture> {
+        let dir = std::env::temp_dir().join(format!(
+            "nflz-fixture-{}-{}",
+            std::process::id(),
+            FIXTURE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)?;
+        }
+        std::fs::create_dir_all(&dir)?;
+        let fixture = Fixture { dir };
+        for filename in self.filenames() {
+            std::fs::write(fixture.path().join(&filename), &filename)?;
+        }
+        Ok(fixture)
+    }
+}
+
+/// Directory with generated files. See [`FixtureBuilder`]. The directory is removed on drop.
+#[derive(Debug)]
+pub struct Fixture {
+    dir: PathBuf,
+}
+
+impl Fixture {
+    /// Returns the path of the directory.
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture() {
+        let builder = FixtureBuilder::new("a ")
+            .prefix("b ")
+            .suffix(".png")
+            .start(9)
+            .count(3)
+            .gaps([10])
+            .padding(2)
+            .extra_file("notes.txt");
+        assert_eq!(
+            vec![
+                "a (09).png",
+                "b (09).png",
+                "a (11).png",
+                "b (11).png",
+                "notes.txt"
+            ],
+            builder.filenames()
+        );
+
+        let fixture = builder.create().unwrap();
+        let dir = fixture.path().to_path_buf();
+        assert_eq!(5, std::fs::read_dir(&dir).unwrap().count());
+        assert!(dir.join("a (09).png").is_file());
+        drop(fixture);
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,391 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for transformations of the new filename. See [`FilenameTransform`].
+
+use crate::error::NFLZError;
+use crate::file_info::FileInfo;
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+
+/// A single step of the transformation pipeline for new filenames.
+///
+/// Transforms are applied after the leading zeroes were added to the number group, in the
+/// order they were registered at the [`crate::NFLZAssistantBuilder`]. Each transform receives
+/// the output of the previous one.
+///
+/// If the final filename equals the original filename, the file doesn't need a rename.
+pub trait FilenameTransform: Debug {
+    /// Returns the transformed filename.
+    ///
+    /// # Parameters
+    /// - `file_info`: The file the new filename belongs to.
+    /// - `filename`: The new filename computed so far. Includes the padded number group and the
+    ///   changes of all previous transforms.
+    fn transform(&self, file_info: &FileInfo, filename: &str) -> String;
+}
+
+/// Transforms the file extension to lowercase. For example, `Img (01).JPG` => `Img (01).jpg`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LowercaseExtension;
+
+impl FilenameTransform for LowercaseExtension {
+    fn transform(&self, _file_info: &FileInfo, filename: &str) -> String {
+        map_extension(filename, str::to_lowercase)
+    }
+}
+
+/// Transforms the file extension to uppercase. For example, `Img (01).jpg` => `Img (01).JPG`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UppercaseExtension;
+
+impl FilenameTransform for UppercaseExtension {
+    fn transform(&self, _file_info: &FileInfo, filename: &str) -> String {
+        map_extension(filename, str::to_uppercase)
+    }
+}
+
+/// Replaces the beginning of the filename, if it starts with [`Self::from`]. For example,
+/// `paris (01).jpg` => `london (01).jpg`. Filenames that don't start with [`Self::from`] are
+/// left untouched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplacePrefix {
+    /// The prefix to replace.
+    pub from: String,
+    /// The new prefix.
+    pub to: String,
+}
+
+impl ReplacePrefix {
+    /// Constructor.
+    pub fn new(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+}
+
+impl FilenameTransform for ReplacePrefix {
+    fn transform(&self, _file_info: &FileInfo, filename: &str) -> String {
+        filename.strip_prefix(&self.from).map_or_else(
+            || filename.to_string(),
+            |remainder| format!("{}{}", self.to, remainder),
+        )
+    }
+}
+
+/// Replaces every occurrence of a separator character in the filename with another one.
+/// For example, `paris (01).jpg` => `paris_(01).jpg` for `' '` => `'_'`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplaceSeparator {
+    /// The separator to replace.
+    pub from: char,
+    /// The new separator.
+    pub to: char,
+}
+
+impl ReplaceSeparator {
+    /// Constructor.
+    pub const fn new(from: char, to: char) -> Self {
+        Self { from, to }
+    }
+}
+
+impl FilenameTransform for ReplaceSeparator {
+    fn transform(&self, _file_info: &FileInfo, filename: &str) -> String {
+        filename.replace(self.from, &self.to.to_string())
+    }
+}
+
+/// Removes all trailing dots and spaces from the filename. For example, `Img (01). ` =>
+/// `Img (01)`. Such names are unusable on Windows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrimTrailingDotsAndSpaces;
+
+impl FilenameTransform for TrimTrailingDotsAndSpaces {
+    fn transform(&self, _file_info: &FileInfo, filename: &str) -> String {
+        filename.trim_end_matches(['.', ' ']).to_string()
+    }
+}
+
+/// Characters that are not allowed in filenames on Windows, in addition to the control
+/// characters.
+pub const WINDOWS_ILLEGAL_CHARACTERS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Returns whether the character is not allowed in filenames on Windows.
+pub(crate) fn is_illegal_on_windows(c: char) -> bool {
+    c.is_ascii_control() || WINDOWS_ILLEGAL_CHARACTERS.contains(&c)
+}
+
+/// Replaces all characters that are not allowed in filenames on Windows with
+/// [`Self::replacement`].
+///
+/// These are the [`WINDOWS_ILLEGAL_CHARACTERS`] and control characters. For example,
+/// `What? (01).jpg` => `What_ (01).jpg`. This keeps archives usable on all platforms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplaceIllegalCharacters {
+    /// The character that replaces the illegal characters.
+    pub replacement: char,
+}
+
+impl ReplaceIllegalCharacters {
+    /// Constructor.
+    pub const fn new(replacement: char) -> Self {
+        Self { replacement }
+    }
+}
+
+impl Default for ReplaceIllegalCharacters {
+    fn default() -> Self {
+        Self::new('_')
+    }
+}
+
+impl FilenameTransform for ReplaceIllegalCharacters {
+    fn transform(&self, _file_info: &FileInfo, filename: &str) -> String {
+        filename
+            .chars()
+            .map(|c| {
+                if is_illegal_on_windows(c) {
+                    self.replacement
+                } else {
+                    c
+                }
+            })
+            .collect()
+    }
+}
+
+/// The built-in transforms that can be selected by their name, for example from the command
+/// line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuiltinTransform {
+    /// See [`LowercaseExtension`]. Name: `lowercase-ext`.
+    LowercaseExtension,
+    /// See [`UppercaseExtension`]. Name: `uppercase-ext`.
+    UppercaseExtension,
+    /// [`ReplaceSeparator`] from `' '` to `'_'`. Name: `spaces-to-underscores`.
+    SpacesToUnderscores,
+    /// [`ReplaceSeparator`] from `'_'` to `' '`. Name: `underscores-to-spaces`.
+    UnderscoresToSpaces,
+}
+
+impl BuiltinTransform {
+    /// All built-in transforms.
+    pub const ALL: [Self; 4] = [
+        Self::LowercaseExtension,
+        Self::UppercaseExtension,
+        Self::SpacesToUnderscores,
+        Self::UnderscoresToSpaces,
+    ];
+
+    /// Returns the name under that the transform can be selected.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::LowercaseExtension => "lowercase-ext",
+            Self::UppercaseExtension => "uppercase-ext",
+            Self::SpacesToUnderscores => "spaces-to-underscores",
+            Self::UnderscoresToSpaces => "underscores-to-spaces",
+        }
+    }
+}
+
+impl FromStr for BuiltinTransform {
+    type Err = NFLZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|transform| transform.name() == s)
+            .ok_or_else(|| NFLZError::UnknownTransform(s.to_string()))
+    }
+}
+
+impl Display for BuiltinTransform {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Serializes the transform as its [`BuiltinTransform::name`].
+#[cfg(feature = "serde")]
+impl serde::Serialize for BuiltinTransform {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Deserializes the transform from its [`BuiltinTransform::name`].
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BuiltinTransform {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl FilenameTransform for BuiltinTransform {
+    fn transform(&self, file_info: &FileInfo, filename: &str) -> String {
+        match self {
+            Self::LowercaseExtension => LowercaseExtension.transform(file_info, filename),
+            Self::UppercaseExtension => UppercaseExtension.transform(file_info, filename),
+            Self::SpacesToUnderscores => {
+                ReplaceSeparator::new(' ', '_').transform(file_info, filename)
+            }
+            Self::UnderscoresToSpaces => {
+                ReplaceSeparator::new('_', ' ').transform(file_info, filename)
+            }
+        }
+    }
+}
+
+/// Wraps a closure as [`FilenameTransform`]. This is useful for one-off custom naming schemes.
+/// Usually created via [`crate::NFLZAssistantBuilder::transform_fn`].
+pub struct FnTransform<F: Fn(&FileInfo, &str) -> String>(pub F);
+
+impl<F: Fn(&FileInfo, &str) -> String> Debug for FnTransform<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FnTransform").field(&"<closure>").finish()
+    }
+}
+
+impl<F: Fn(&FileInfo, &str) -> String> FilenameTransform for FnTransform<F> {
+    fn transform(&self, file_info: &FileInfo, filename: &str) -> String {
+        (self.0)(file_info, filename)
+    }
+}
+
+/// Applies all transforms in the given order on the filename. Also returns the illegal
+/// characters that a transform replaced with legal ones, such as `?` for `What? (1).jpg` =>
+/// `What_ (1).jpg`. Each step compares the filename before and after the transform character
+/// by character. Characters that a transform removed or that never reached a transform are
+/// not part of the result.
+pub(crate) fn apply_transforms(
+    transforms: &[Box<dyn FilenameTransform>],
+    file_info: &FileInfo,
+    filename: String,
+) -> (String, Vec<char>) {
+    let mut replaced = Vec::new();
+    let filename = transforms.iter().fold(filename, |filename, transform| {
+        let transformed = transform.transform(file_info, &filename);
+        if filename.chars().count() == transformed.chars().count() {
+            for (before, after) in filename.chars().zip(transformed.chars()) {
+                if is_illegal_on_windows(before)
+                    && !is_illegal_on_windows(after)
+                    && !replaced.contains(&before)
+                {
+                    replaced.push(before);
+                }
+            }
+        }
+        transformed
+    });
+    (filename, replaced)
+}
+
+/// Applies `f` on the file extension (everything behind the last dot) of the filename.
+/// Filenames without extension and hidden files without extension, such as `.foo`, are
+/// returned unchanged.
+fn map_extension(filename: &str, f: impl Fn(&str) -> String) -> String {
+    match filename.rfind('.') {
+        Some(index) if index > 0 => {
+            format!("{}.{}", &filename[..index], f(&filename[index + 1..]))
+        }
+        _ => filename.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_transforms() {
+        let file_info = FileInfo::new("paris (1).JPG").unwrap();
+        let filename = "paris (01).JPG".to_string();
+
+        assert_eq!(
+            "paris (01).jpg",
+            LowercaseExtension.transform(&file_info, &filename)
+        );
+        assert_eq!(
+            "paris (01).JPG",
+            UppercaseExtension.transform(&file_info, &filename)
+        );
+        assert_eq!(
+            "london (01).JPG",
+            ReplacePrefix::new("paris", "london").transform(&file_info, &filename)
+        );
+        assert_eq!(
+            "paris (01).JPG",
+            ReplacePrefix::new("london", "paris").transform(&file_info, &filename)
+        );
+        assert_eq!(
+            "paris_(01).JPG",
+            ReplaceSeparator::new(' ', '_').transform(&file_info, &filename)
+        );
+        assert_eq!(
+            "paris (01)",
+            TrimTrailingDotsAndSpaces.transform(&file_info, "paris (01). .")
+        );
+        assert_eq!(
+            "What_ _a_b_ (01).jpg",
+            ReplaceIllegalCharacters::default().transform(&file_info, "What? <a|b> (01).jpg")
+        );
+        assert_eq!(".foo", map_extension(".foo", str::to_uppercase));
+    }
+
+    #[test]
+    fn test_builtin_transform_by_name() {
+        for transform in BuiltinTransform::ALL {
+            assert_eq!(transform, transform.name().parse().unwrap());
+        }
+        assert!("foobar".parse::<BuiltinTransform>().is_err());
+
+        let file_info = FileInfo::new("my_paris (1).jpg").unwrap();
+        assert_eq!(
+            "my paris (1).jpg",
+            BuiltinTransform::UnderscoresToSpaces.transform(&file_info, "my_paris (1).jpg")
+        );
+    }
+
+    #[test]
+    fn test_apply_transforms_in_order() {
+        let file_info = FileInfo::new("paris (1).JPG").unwrap();
+        let transforms: Vec<Box<dyn FilenameTransform>> = vec![
+            Box::new(ReplaceSeparator::new(' ', '_')),
+            Box::new(ReplacePrefix::new("paris_", "london-")),
+            Box::new(LowercaseExtension),
+            Box::new(FnTransform(|info: &FileInfo, filename: &str| {
+                format!("{}-{}", info.number_group_value(), filename)
+            })),
+        ];
+        assert_eq!(
+            ("1-london-(01).jpg".to_string(), vec![]),
+            apply_transforms(&transforms, &file_info, "paris (01).JPG".to_string())
+        );
+    }
+}
@@ -0,0 +1,86 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for Unicode normalization of filenames. See [`UnicodeNormalization`].
+
+use std::borrow::Cow;
+use unicode_normalization::UnicodeNormalization as _;
+
+/// Unicode normalization form that is used to compare filenames.
+///
+/// The same visual filename, such as `café (1).jpg`, can be stored in the composed (NFC) or
+/// in the decomposed (NFD) form. macOS (APFS) treats both forms as the same file, thus,
+/// prefixes and new filenames must be compared in a normalized form there. Only the
+/// comparisons are affected; the filenames are never changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum UnicodeNormalization {
+    /// Normalization Form C (canonical composition).
+    Nfc,
+    /// Normalization Form D (canonical decomposition).
+    Nfd,
+}
+
+impl UnicodeNormalization {
+    /// The default of the platform: [`Self::Nfc`] on macOS, where the file system is
+    /// normalization-insensitive, and `None` everywhere else.
+    pub const fn platform_default() -> Option<Self> {
+        if cfg!(target_os = "macos") {
+            Some(Self::Nfc)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the string in this normalization form.
+    pub fn normalize(self, s: &str) -> String {
+        match self {
+            Self::Nfc => s.nfc().collect(),
+            Self::Nfd => s.nfd().collect(),
+        }
+    }
+}
+
+/// Returns the string in the normalization form, if there is one, or unchanged otherwise.
+pub(crate) fn normalize(normalization: Option<UnicodeNormalization>, s: &str) -> Cow<'_, str> {
+    normalization.map_or(Cow::Borrowed(s), |normalization| {
+        Cow::Owned(normalization.normalize(s))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let nfc = "caf\u{e9}";
+        let nfd = "cafe\u{301}";
+        assert_ne!(nfc, nfd);
+        assert_eq!(nfc, UnicodeNormalization::Nfc.normalize(nfd));
+        assert_eq!(nfd, UnicodeNormalization::Nfd.normalize(nfc));
+        assert_eq!(nfd, normalize(None, nfd));
+    }
+}
@@ -0,0 +1,194 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for warnings about the new filenames. See [`PlanWarning`].
+
+use crate::file_info::FileInfoWithRenameAdvice;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// A problem with a new filename that doesn't prevent the renaming but that the user should
+/// know about. See [`crate::NFLZAssistant::warnings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "warning", rename_all = "snake_case"))]
+pub enum PlanWarning {
+    /// The new filename ends with a dot or a space. Such files are unusable on Windows. Use
+    /// [`crate::NFLZAssistantBuilder::trim_trailing_dots_and_spaces`] to remove them.
+    TrailingDotOrSpace {
+        /// Path to the file.
+        path: PathBuf,
+        /// The new filename.
+        new_filename: String,
+    },
+    /// Characters that are not allowed on Windows were replaced in the new filename. See
+    /// [`crate::NFLZAssistantBuilder::replace_illegal_characters`].
+    IllegalCharactersReplaced {
+        /// Path to the file.
+        path: PathBuf,
+        /// The new filename.
+        new_filename: String,
+        /// The replaced characters, in the order of their first occurrence.
+        characters: Vec<char>,
+    },
+    /// The file is opened by another process, such as an editor, that may fail to save it
+    /// after the renaming. See [`crate::NFLZAssistantBuilder::open_file_policy`].
+    OpenInOtherProcess {
+        /// Path to the file.
+        path: PathBuf,
+    },
+}
+
+impl Display for PlanWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TrailingDotOrSpace { new_filename, .. } => write!(
+                f,
+                "The new filename '{}' ends with a dot or a space, which is not allowed on Windows.",
+                new_filename
+            ),
+            Self::IllegalCharactersReplaced {
+                new_filename,
+                characters,
+                ..
+            } => write!(
+                f,
+                "Replaced the illegal characters {:?} in the new filename '{}'.",
+                characters, new_filename
+            ),
+            Self::OpenInOtherProcess { path } => write!(
+                f,
+                "The file '{}' is opened by another process.",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// Returns all warnings for the new filenames of the files. If `illegal_characters_replaced`
+/// is set, the illegal characters that the transforms replaced in the new filenames are
+/// reported.
+pub(crate) fn find_warnings(
+    files: &[FileInfoWithRenameAdvice],
+    illegal_characters_replaced: bool,
+) -> Vec<PlanWarning> {
+    let trailing = files.iter().filter_map(|file| {
+        file.new_filename()
+            .filter(|name| name.ends_with('.') || name.ends_with(' '))
+            .map(|name| PlanWarning::TrailingDotOrSpace {
+                path: PathBuf::from(file.file_info().path()),
+                new_filename: name.to_string(),
+            })
+    });
+    let replaced = files
+        .iter()
+        .filter(|_| illegal_characters_replaced)
+        .filter(|file| !file.replaced_characters().is_empty())
+        .filter_map(|file| {
+            file.new_filename()
+                .map(|name| PlanWarning::IllegalCharactersReplaced {
+                    path: PathBuf::from(file.file_info().path()),
+                    new_filename: name.to_string(),
+                    characters: file.replaced_characters().to_vec(),
+                })
+        });
+    trailing.chain(replaced).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_info::FileInfo;
+    use crate::transform::{
+        FilenameTransform, FnTransform, ReplaceIllegalCharacters, ReplaceSeparator,
+    };
+
+    #[test]
+    fn test_find_warnings() {
+        let transforms: Vec<Box<dyn FilenameTransform>> =
+            vec![Box::new(ReplaceSeparator::new('_', ' '))];
+        let files = [
+            FileInfoWithRenameAdvice::new(FileInfo::new("img (1).jpg").unwrap(), 2),
+            FileInfoWithRenameAdvice::new(FileInfo::new("img (2).").unwrap(), 2),
+            FileInfoWithRenameAdvice::new_with_transforms(
+                FileInfo::new("img (3)_").unwrap(),
+                2,
+                &transforms,
+            ),
+        ];
+        let warnings = find_warnings(&files, false);
+        assert_eq!(2, warnings.len());
+        assert_eq!(
+            PlanWarning::TrailingDotOrSpace {
+                path: PathBuf::from("img (2)."),
+                new_filename: "img (02).".to_string()
+            },
+            warnings[0]
+        );
+
+        let transforms: Vec<Box<dyn FilenameTransform>> =
+            vec![Box::new(ReplaceIllegalCharacters::default())];
+        let files = [FileInfoWithRenameAdvice::new_with_transforms(
+            FileInfo::new("what? * (1).jpg").unwrap(),
+            1,
+            &transforms,
+        )];
+        assert_eq!(
+            vec![PlanWarning::IllegalCharactersReplaced {
+                path: PathBuf::from("what? * (1).jpg"),
+                new_filename: "what_ _ (1).jpg".to_string(),
+                characters: vec!['?', '*'],
+            }],
+            find_warnings(&files, true)
+        );
+
+        // `?` is removed by another transform before the replacement, `*` is never replaced
+        let transforms: Vec<Box<dyn FilenameTransform>> = vec![
+            Box::new(FnTransform(|_: &FileInfo, name: &str| {
+                name.replace('?', "")
+            })),
+            Box::new(ReplaceIllegalCharacters::default()),
+        ];
+        let files = [
+            FileInfoWithRenameAdvice::new_with_transforms(
+                FileInfo::new("what? * (1).jpg").unwrap(),
+                2,
+                &transforms,
+            ),
+            FileInfoWithRenameAdvice::new_with_transforms(
+                FileInfo::new("what? (2).jpg").unwrap(),
+                2,
+                &transforms,
+            ),
+        ];
+        assert_eq!(
+            vec![PlanWarning::IllegalCharactersReplaced {
+                path: PathBuf::from("what? * (1).jpg"),
+                new_filename: "what _ (01).jpg".to_string(),
+                characters: vec!['*'],
+            }],
+            find_warnings(&files, true)
+        );
+    }
+}
@@ -0,0 +1,211 @@
+/*
+MIT License
+
+Copyright (c) 2022 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Minimal wildcard matching for user-provided name patterns. See [`matches`].
+
+/// A single element of a wildcard pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// `*`: any sequence of characters except `/`, including none.
+    AnySequence,
+    /// `**` of the gitignore syntax: any sequence of characters, including none and `/`.
+    AnyPath,
+    /// `**/` of the gitignore syntax: none or any number of directories, such as `a/b/`.
+    AnyDirectories,
+    /// `?`: exactly one character except `/`.
+    AnyCharacter,
+    /// `[...]`: one character of the inclusive ranges, or one that is in none of them if
+    /// negated with `[!...]` or `[^...]`.
+    Class {
+        /// Whether the class matches the characters outside of the ranges.
+        negated: bool,
+        /// Inclusive ranges; single characters are ranges of length one.
+        ranges: Vec<(char, char)>,
+    },
+    /// A character that must match exactly.
+    Literal(char),
+}
+
+impl Token {
+    /// Returns true if the token matches exactly the single character `c`. The tokens for
+    /// sequences are handled by the matching itself.
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Self::AnySequence | Self::AnyPath | Self::AnyDirectories => false,
+            Self::AnyCharacter => c != '/',
+            Self::Class { negated, ranges } => {
+                c != '/' && ranges.iter().any(|(from, to)| (*from..=*to).contains(&c)) != *negated
+            }
+            Self::Literal(literal) => *literal == c,
+        }
+    }
+}
+
+/// Returns true if `name` matches the wildcard `pattern`. `*` matches any sequence of
+/// characters (including none) and `?` matches exactly one character. All other characters
+/// must match exactly. For example, `.git`, `thumb*`, or `IMG_????`.
+pub fn matches(pattern: &str, name: &str) -> bool {
+    matches_tokens(&tokenize(pattern, false), name)
+}
+
+/// Like [`matches`], but with the wildcards of the gitignore syntax for a `/`-separated
+/// `path`: `*` and `?` don't match a `/`, `**` matches across directories, character
+/// classes such as `[0-9]` or `[!a]` are supported, and a backslash escapes special
+/// characters, such as `\*`.
+pub fn matches_gitignore(pattern: &str, path: &str) -> bool {
+    matches_tokens(&tokenize(pattern, true), path)
+}
+
+/// Splits the pattern into its tokens. Without `gitignore`, only `*` and `?` are special. An
+/// unclosed `[` is a literal.
+fn tokenize(pattern: &str, gitignore: bool) -> Vec<Token> {
+    let chars = pattern.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::with_capacity(chars.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let token = match chars[i] {
+            '*' if gitignore && chars.get(i + 1) == Some(&'*') => {
+                i += 1;
+                if chars.get(i + 1) == Some(&'/') {
+                    i += 1;
+                    Token::AnyDirectories
+                } else {
+                    Token::AnyPath
+                }
+            }
+            '*' => Token::AnySequence,
+            '?' => Token::AnyCharacter,
+            '\\' if gitignore && i + 1 < chars.len() => {
+                i += 1;
+                Token::Literal(chars[i])
+            }
+            '[' if gitignore => match parse_class(&chars[i + 1..]) {
+                Some((token, len)) => {
+                    i += len;
+                    token
+                }
+                None => Token::Literal('['),
+            },
+            c => Token::Literal(c),
+        };
+        tokens.push(token);
+        i += 1;
+    }
+    tokens
+}
+
+/// Parses the character class behind a `[`. Returns the class and the amount of consumed
+/// characters, including the closing `]`, or `None` if the class isn't closed. A `]` directly
+/// behind the opening bracket is a literal.
+fn parse_class(chars: &[char]) -> Option<(Token, usize)> {
+    let negated = matches!(chars.first(), Some('!' | '^'));
+    let mut i = usize::from(negated);
+    let mut ranges = Vec::new();
+    loop {
+        let from = *chars.get(i)?;
+        if from == ']' && !ranges.is_empty() {
+            return Some((Token::Class { negated, ranges }, i + 1));
+        }
+        match (chars.get(i + 1), chars.get(i + 2)) {
+            (Some('-'), Some(&to)) if to != ']' => {
+                ranges.push((from, to));
+                i += 3;
+            }
+            _ => {
+                ranges.push((from, from));
+                i += 1;
+            }
+        }
+    }
+}
+
+/// Returns true if `name` matches the tokens of a pattern.
+fn matches_tokens(pattern: &[Token], name: &str) -> bool {
+    let name = name.chars().collect::<Vec<_>>();
+
+    // dynamic programming from the end of the pattern: `rest[n]` is true if the already
+    // processed tokens match `name[n..]`
+    let mut rest = vec![false; name.len() + 1];
+    rest[name.len()] = true;
+    for token in pattern.iter().rev() {
+        let mut current = vec![false; name.len() + 1];
+        for n in (0..=name.len()).rev() {
+            let next = name.get(n);
+            current[n] = match token {
+                Token::AnySequence => {
+                    rest[n] || (next.is_some_and(|&c| c != '/') && current[n + 1])
+                }
+                Token::AnyPath => rest[n] || (next.is_some() && current[n + 1]),
+                // either no directory or the longest match up to a `/`
+                Token::AnyDirectories => {
+                    rest[n] || (n + 1..=name.len()).any(|end| name[end - 1] == '/' && rest[end])
+                }
+                token => next.is_some_and(|&c| token.matches(c)) && rest[n + 1],
+            };
+        }
+        rest = current;
+    }
+    rest[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        assert!(matches(".git", ".git"));
+        assert!(!matches(".git", ".github"));
+        assert!(matches(".git*", ".github"));
+        assert!(matches("*thumb*", "my thumbnails"));
+        assert!(matches("IMG_????", "IMG_0001"));
+        assert!(!matches("IMG_????", "IMG_001"));
+        assert!(matches("*", ""));
+        assert!(matches("a*b*c", "aXbYbZc"));
+        assert!(!matches("a*b*c", "aXbYbZ"));
+        assert!(matches("[0-9]", "[0-9]"));
+    }
+
+    #[test]
+    fn test_matches_gitignore() {
+        assert!(matches_gitignore("IMG_[0-9][0-9]", "IMG_42"));
+        assert!(!matches_gitignore("IMG_[0-9][0-9]", "IMG_4a"));
+        assert!(matches_gitignore("[!a]*", "b (1).jpg"));
+        assert!(!matches_gitignore("[^a]*", "a (1).jpg"));
+        assert!(matches_gitignore("[]x]", "]"));
+        assert!(matches_gitignore("a[-]b", "a-b"));
+        assert!(matches_gitignore("\\*.jpg", "*.jpg"));
+        assert!(!matches_gitignore("\\*.jpg", "a.jpg"));
+        assert!(matches_gitignore("a[b", "a[b"));
+
+        assert!(matches_gitignore("photos/*.tmp", "photos/a (1).tmp"));
+        assert!(!matches_gitignore("*.tmp", "photos/a (1).tmp"));
+        assert!(!matches_gitignore("photos/?", "photos//"));
+        assert!(matches_gitignore("photos/**", "photos/2023/a (1).jpg"));
+        assert!(matches_gitignore("**/a (1).jpg", "a (1).jpg"));
+        assert!(matches_gitignore("**/a (1).jpg", "photos/2023/a (1).jpg"));
+        assert!(matches_gitignore("photos/**/a*", "photos/a (1).jpg"));
+        assert!(matches_gitignore("photos/**/a*", "photos/2023/a (1).jpg"));
+        assert!(!matches_gitignore("photos/**/a*", "photos2023/a (1).jpg"));
+    }
+}